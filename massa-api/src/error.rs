@@ -17,6 +17,8 @@ pub enum ApiError {
     PoolError(#[from] PoolError),
     /// too many arguments error: {0}
     TooManyArguments(String),
+    /// bad request: {0}
+    BadRequest(String),
     /// send channel error: {0}
     SendChannelError(String),
     /// receive channel error: {0}
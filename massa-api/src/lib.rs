@@ -8,12 +8,15 @@ use error::ApiError;
 use jsonrpc_core::{BoxFuture, IoHandler, Value};
 use jsonrpc_derive::rpc;
 use jsonrpc_http_server::{CloseHandle, ServerBuilder};
+use jsonrpc_pubsub::{manager::RandomStringIdProvider, typed::Subscriber, SubscriptionId};
 use massa_consensus_exports::{ConsensusCommandSender, ConsensusConfig};
 use massa_execution_exports::ExecutionController;
 use massa_models::api::{
-    AddressInfo, BlockInfo, BlockSummary, DatastoreEntryInput, DatastoreEntryOutput,
-    EndorsementInfo, EventFilter, NodeStatus, OperationInfo, ReadOnlyBytecodeExecution,
-    ReadOnlyCall, TimeInterval,
+    AddressInfo, BlockInfo, BlockNotification, BlockSummary, DatastoreEntryInput,
+    DatastoreEntryOutput, DatastoreKeysInput, DatastoreKeysOutput, EndorsementInfo, EventFilter,
+    GasEstimation, GasEstimationInput, HistoricalLedgerInput, HistoricalLedgerOutput, NodeStatus,
+    OperationInfo, PendingOperationInfo, ReadOnlyBytecodeExecution, ReadOnlyCall,
+    SCOutputEventNotification, StakerReport, TimeInterval,
 };
 use massa_models::clique::Clique;
 use massa_models::composite::PubkeySig;
@@ -22,10 +25,13 @@ use massa_models::node::NodeId;
 use massa_models::operation::OperationId;
 use massa_models::output_event::SCOutputEvent;
 use massa_models::prehash::Set;
-use massa_models::{Address, BlockId, EndorsementId, SignedOperation, Version};
+use massa_models::stats::PoolThreadFeeStats;
+use massa_models::{Address, BlockId, EndorsementId, SignedOperation, Slot, Version};
 use massa_network_exports::{NetworkCommandSender, NetworkSettings};
 use massa_pool::PoolCommandSender;
 use massa_signature::PrivateKey;
+use massa_storage::Storage;
+use massa_time::ClockCompensation;
 use std::net::{IpAddr, SocketAddr};
 use std::thread;
 use std::thread::JoinHandle;
@@ -35,8 +41,11 @@ use tracing::{info, warn};
 mod error;
 mod private;
 mod public;
+mod pubsub;
 mod settings;
+mod web;
 pub use settings::APISettings;
+pub use web::{start_web_gateway, WebGatewayStopHandle};
 
 /// Public API component
 pub struct Public {
@@ -56,10 +65,12 @@ pub struct Public {
     pub version: Version,
     /// link to the network component
     pub network_command_sender: NetworkCommandSender,
-    /// compensation milliseconds (used to sync time with bootstrap server)
-    pub compensation_millis: i64,
+    /// clock compensation (used to sync time with bootstrap servers), updated live by the clock synchronization
+    pub clock_compensation: ClockCompensation,
     /// our node id
     pub node_id: NodeId,
+    /// shared storage, giving access to the block archive
+    pub storage: Storage,
 }
 
 /// Private API content
@@ -78,6 +89,20 @@ pub struct Private {
     pub stop_node_channel: mpsc::Sender<()>,
 }
 
+/// Publish/subscribe API content
+pub struct PubSub {
+    /// link to the consensus component
+    pub consensus_command_sender: ConsensusCommandSender,
+    /// link to the execution component
+    pub execution_controller: Box<dyn ExecutionController>,
+    /// max active subscriptions per WebSocket session
+    max_subscriptions_per_session: u64,
+    /// active subscriptions
+    active_subscriptions: pubsub::ActiveSubscriptions,
+    /// generates the random ids of the subscriptions
+    subscription_id_provider: RandomStringIdProvider,
+}
+
 /// The API wrapper
 pub struct API<T>(T);
 
@@ -97,7 +122,7 @@ fn serve(api: impl Endpoints, url: &SocketAddr) -> StopHandle {
         .start_http(url)
         .expect("Unable to start RPC server");
 
-    let close_handle = server.close_handle();
+    let close_handle = ServerCloseHandle::Http(server.close_handle());
     let join_handle = thread::spawn(|| server.wait());
 
     StopHandle {
@@ -106,16 +131,27 @@ fn serve(api: impl Endpoints, url: &SocketAddr) -> StopHandle {
     }
 }
 
+/// Close handle of the server running an API
+enum ServerCloseHandle {
+    /// HTTP JSON-RPC server
+    Http(CloseHandle),
+    /// WebSocket JSON-RPC server
+    Ws(jsonrpc_ws_server::CloseHandle),
+}
+
 /// Used to be able to stop the API
 pub struct StopHandle {
-    close_handle: CloseHandle,
+    close_handle: ServerCloseHandle,
     join_handle: JoinHandle<()>,
 }
 
 impl StopHandle {
     /// stop the API gracefully
     pub fn stop(self) {
-        self.close_handle.close();
+        match self.close_handle {
+            ServerCloseHandle::Http(close_handle) => close_handle.close(),
+            ServerCloseHandle::Ws(close_handle) => close_handle.close(),
+        }
         if let Err(err) = self.join_handle.join() {
             warn!("API thread panicked: {:?}", err);
         } else {
@@ -137,7 +173,7 @@ pub trait Endpoints {
     fn node_sign_message(&self, _: Vec<u8>) -> BoxFuture<Result<PubkeySig, ApiError>>;
 
     /// Add a vector of new private keys for the node to use to stake.
-    /// No confirmation to expect.
+    /// Fails if the staking keys are locked.
     #[rpc(name = "add_staking_private_keys")]
     fn add_staking_private_keys(&self, _: Vec<PrivateKey>) -> BoxFuture<Result<(), ApiError>>;
 
@@ -155,8 +191,16 @@ pub trait Endpoints {
         _: Vec<ReadOnlyCall>,
     ) -> BoxFuture<Result<Vec<ExecuteReadOnlyResponse>, ApiError>>;
 
+    /// Estimate the gas needed by `ExecuteSC` or `CallSC` operations
+    /// by executing them in read-only mode on top of the active state.
+    #[rpc(name = "estimate_gas")]
+    fn estimate_gas(
+        &self,
+        _: Vec<GasEstimationInput>,
+    ) -> BoxFuture<Result<Vec<GasEstimation>, ApiError>>;
+
     /// Remove a vector of addresses used to stake.
-    /// No confirmation to expect.
+    /// Fails if the staking keys are locked.
     #[rpc(name = "remove_staking_addresses")]
     fn remove_staking_addresses(&self, _: Vec<Address>) -> BoxFuture<Result<(), ApiError>>;
 
@@ -164,6 +208,15 @@ pub trait Endpoints {
     #[rpc(name = "get_staking_addresses")]
     fn get_staking_addresses(&self) -> BoxFuture<Result<Set<Address>, ApiError>>;
 
+    /// Unlock the staking keys with their password and start staking with them.
+    /// Returns the unlocked staking addresses.
+    #[rpc(name = "unlock_staking_keys")]
+    fn unlock_staking_keys(&self, _: String) -> BoxFuture<Result<Set<Address>, ApiError>>;
+
+    /// Lock the staking keys: the node stops staking until they are unlocked again.
+    #[rpc(name = "lock_staking_keys")]
+    fn lock_staking_keys(&self) -> BoxFuture<Result<(), ApiError>>;
+
     /// Bans given IP address(es).
     /// No confirmation to expect.
     #[rpc(name = "node_ban_by_ip")]
@@ -208,6 +261,12 @@ pub trait Endpoints {
     #[rpc(name = "get_stakers")]
     fn get_stakers(&self) -> BoxFuture<Result<Vec<(Address, u64)>, ApiError>>;
 
+    /// Returns, for each given staking address, its draws for the current and next cycles,
+    /// its block and endorsement production per cycle, its roll deactivation risk and its deferred roll sale credits.
+    #[rpc(name = "get_staker_reports")]
+    fn get_staker_reports(&self, _: Vec<Address>)
+        -> BoxFuture<Result<Vec<StakerReport>, ApiError>>;
+
     /// Returns operations information associated to a given list of operations' IDs.
     #[rpc(name = "get_operations")]
     fn get_operations(
@@ -215,6 +274,18 @@ pub trait Endpoints {
         _: Vec<OperationId>,
     ) -> BoxFuture<Result<Vec<OperationInfo>, ApiError>>;
 
+    /// Returns the operations involving an address that are waiting in the pool.
+    #[rpc(name = "get_pending_operations")]
+    fn get_pending_operations(
+        &self,
+        _: Address,
+    ) -> BoxFuture<Result<Vec<PendingOperationInfo>, ApiError>>;
+
+    /// Returns the fee statistics of the pool for each thread.
+    /// The suggested fee is the fee an operation needs to be included within the given number of slots of its thread.
+    #[rpc(name = "get_pool_fee_stats")]
+    fn get_pool_fee_stats(&self, _: u64) -> BoxFuture<Result<Vec<PoolThreadFeeStats>, ApiError>>;
+
     /// Get endorsements (not yet implemented).
     #[rpc(name = "get_endorsements")]
     fn get_endorsements(
@@ -226,6 +297,10 @@ pub trait Endpoints {
     #[rpc(name = "get_block")]
     fn get_block(&self, _: BlockId) -> BoxFuture<Result<BlockInfo, ApiError>>;
 
+    /// Get information on the final block of a slot, if any.
+    #[rpc(name = "get_block_by_slot")]
+    fn get_block_by_slot(&self, _: Slot) -> BoxFuture<Result<Option<BlockInfo>, ApiError>>;
+
     /// Get the block graph within the specified time interval.
     /// Optional parameters: from `<time_start>` (included) and to `<time_end>` (excluded) millisecond timestamp
     #[rpc(name = "get_graph_interval")]
@@ -239,6 +314,20 @@ pub trait Endpoints {
         _: DatastoreEntryInput,
     ) -> BoxFuture<Result<DatastoreEntryOutput, ApiError>>;
 
+    /// Get a page of the active datastore keys of an address starting with a prefix
+    #[rpc(name = "get_datastore_keys")]
+    fn get_datastore_keys(
+        &self,
+        _: DatastoreKeysInput,
+    ) -> BoxFuture<Result<DatastoreKeysOutput, ApiError>>;
+
+    /// Get the balance, bytecode and some datastore entries of an address at the output of a final slot
+    #[rpc(name = "get_historical_ledger_info")]
+    fn get_historical_ledger_info(
+        &self,
+        _: HistoricalLedgerInput,
+    ) -> BoxFuture<Result<HistoricalLedgerOutput, ApiError>>;
+
     /// Get addresses.
     #[rpc(name = "get_addresses")]
     fn get_addresses(&self, _: Vec<Address>) -> BoxFuture<Result<Vec<AddressInfo>, ApiError>>;
@@ -263,6 +352,51 @@ pub trait Endpoints {
     ) -> BoxFuture<Result<Vec<SCOutputEvent>, ApiError>>;
 }
 
+/// Exposed publish/subscribe endpoints
+#[rpc(server)]
+pub trait PubSubEndpoints {
+    /// Session metadata
+    type Metadata;
+
+    /// Subscribe to the SC output events matching a filter, as they are emitted.
+    /// Each notification tells whether the event is final or comes from a speculative execution.
+    #[pubsub(
+        subscription = "sc_output_events",
+        subscribe,
+        name = "subscribe_sc_output_events"
+    )]
+    fn subscribe_sc_output_events(
+        &self,
+        _: Self::Metadata,
+        _: Subscriber<SCOutputEventNotification>,
+        _: EventFilter,
+    );
+
+    /// Cancel a subscription to SC output events.
+    #[pubsub(
+        subscription = "sc_output_events",
+        unsubscribe,
+        name = "unsubscribe_sc_output_events"
+    )]
+    fn unsubscribe_sc_output_events(
+        &self,
+        _: Option<Self::Metadata>,
+        _: SubscriptionId,
+    ) -> jsonrpc_core::Result<bool>;
+
+    /// Subscribe to notifications of blocks becoming final or stale.
+    #[pubsub(subscription = "blocks", subscribe, name = "subscribe_blocks")]
+    fn subscribe_blocks(&self, _: Self::Metadata, _: Subscriber<BlockNotification>);
+
+    /// Cancel a subscription to block notifications.
+    #[pubsub(subscription = "blocks", unsubscribe, name = "unsubscribe_blocks")]
+    fn unsubscribe_blocks(
+        &self,
+        _: Option<Self::Metadata>,
+        _: SubscriptionId,
+    ) -> jsonrpc_core::Result<bool>;
+}
+
 fn wrong_api<T>() -> BoxFuture<Result<T, ApiError>> {
     let closure = async move || Err(WrongAPI);
     Box::pin(closure())
@@ -9,8 +9,9 @@ use massa_consensus_exports::{ConsensusCommandSender, ConsensusConfig};
 use massa_execution_exports::ExecutionController;
 use massa_models::api::{
     AddressInfo, BlockInfo, BlockSummary, DatastoreEntryInput, DatastoreEntryOutput,
-    EndorsementInfo, EventFilter, NodeStatus, OperationInfo, ReadOnlyBytecodeExecution,
-    ReadOnlyCall, TimeInterval,
+    DatastoreKeysInput, DatastoreKeysOutput, EndorsementInfo, EventFilter, GasEstimation,
+    GasEstimationInput, HistoricalLedgerInput, HistoricalLedgerOutput, NodeStatus, OperationInfo,
+    PendingOperationInfo, ReadOnlyBytecodeExecution, ReadOnlyCall, StakerReport, TimeInterval,
 };
 use massa_models::clique::Clique;
 use massa_models::composite::PubkeySig;
@@ -18,7 +19,8 @@ use massa_models::execution::ExecuteReadOnlyResponse;
 use massa_models::node::NodeId;
 use massa_models::output_event::SCOutputEvent;
 use massa_models::prehash::Set;
-use massa_models::{Address, BlockId, EndorsementId, OperationId, SignedOperation};
+use massa_models::stats::PoolThreadFeeStats;
+use massa_models::{Address, BlockId, EndorsementId, OperationId, SignedOperation, Slot};
 use massa_network_exports::NetworkCommandSender;
 use massa_signature::PrivateKey;
 use std::net::{IpAddr, SocketAddr};
@@ -92,6 +94,13 @@ impl Endpoints for API<Private> {
         crate::wrong_api::<_>()
     }
 
+    fn estimate_gas(
+        &self,
+        _reqs: Vec<GasEstimationInput>,
+    ) -> BoxFuture<Result<Vec<GasEstimation>, ApiError>> {
+        crate::wrong_api::<_>()
+    }
+
     fn remove_staking_addresses(&self, keys: Vec<Address>) -> BoxFuture<Result<(), ApiError>> {
         let cmd_sender = self.0.consensus_command_sender.clone();
         let closure = async move || {
@@ -108,6 +117,18 @@ impl Endpoints for API<Private> {
         Box::pin(closure())
     }
 
+    fn unlock_staking_keys(&self, password: String) -> BoxFuture<Result<Set<Address>, ApiError>> {
+        let cmd_sender = self.0.consensus_command_sender.clone();
+        let closure = async move || Ok(cmd_sender.unlock_staking_keys(password).await?);
+        Box::pin(closure())
+    }
+
+    fn lock_staking_keys(&self) -> BoxFuture<Result<(), ApiError>> {
+        let cmd_sender = self.0.consensus_command_sender.clone();
+        let closure = async move || Ok(cmd_sender.lock_staking_keys().await?);
+        Box::pin(closure())
+    }
+
     fn node_ban_by_ip(&self, ips: Vec<IpAddr>) -> BoxFuture<Result<(), ApiError>> {
         let network_command_sender = self.0.network_command_sender.clone();
         let closure = async move || Ok(network_command_sender.node_ban_by_ips(ips).await?);
@@ -144,6 +165,13 @@ impl Endpoints for API<Private> {
         crate::wrong_api::<Vec<(Address, u64)>>()
     }
 
+    fn get_staker_reports(
+        &self,
+        _: Vec<Address>,
+    ) -> BoxFuture<Result<Vec<StakerReport>, ApiError>> {
+        crate::wrong_api::<Vec<StakerReport>>()
+    }
+
     fn get_operations(
         &self,
         _: Vec<OperationId>,
@@ -151,6 +179,17 @@ impl Endpoints for API<Private> {
         crate::wrong_api::<Vec<OperationInfo>>()
     }
 
+    fn get_pending_operations(
+        &self,
+        _: Address,
+    ) -> BoxFuture<Result<Vec<PendingOperationInfo>, ApiError>> {
+        crate::wrong_api::<Vec<PendingOperationInfo>>()
+    }
+
+    fn get_pool_fee_stats(&self, _: u64) -> BoxFuture<Result<Vec<PoolThreadFeeStats>, ApiError>> {
+        crate::wrong_api::<Vec<PoolThreadFeeStats>>()
+    }
+
     fn get_endorsements(
         &self,
         _: Vec<EndorsementId>,
@@ -162,6 +201,10 @@ impl Endpoints for API<Private> {
         crate::wrong_api::<BlockInfo>()
     }
 
+    fn get_block_by_slot(&self, _: Slot) -> BoxFuture<Result<Option<BlockInfo>, ApiError>> {
+        crate::wrong_api::<Option<BlockInfo>>()
+    }
+
     fn get_graph_interval(
         &self,
         _: TimeInterval,
@@ -176,6 +219,20 @@ impl Endpoints for API<Private> {
         crate::wrong_api()
     }
 
+    fn get_datastore_keys(
+        &self,
+        _: DatastoreKeysInput,
+    ) -> BoxFuture<Result<DatastoreKeysOutput, ApiError>> {
+        crate::wrong_api()
+    }
+
+    fn get_historical_ledger_info(
+        &self,
+        _: HistoricalLedgerInput,
+    ) -> BoxFuture<Result<HistoricalLedgerOutput, ApiError>> {
+        crate::wrong_api()
+    }
+
     fn get_addresses(&self, _: Vec<Address>) -> BoxFuture<Result<Vec<AddressInfo>, ApiError>> {
         crate::wrong_api::<Vec<AddressInfo>>()
     }
@@ -5,22 +5,30 @@ use crate::settings::APISettings;
 use crate::{Endpoints, Public, RpcServer, StopHandle, API};
 use futures::{stream::FuturesUnordered, StreamExt};
 use jsonrpc_core::BoxFuture;
+use massa_async_pool::Change;
 use massa_consensus_exports::{ConsensusCommandSender, ConsensusConfig};
 use massa_execution_exports::{
-    ExecutionController, ExecutionStackElement, ReadOnlyExecutionRequest, ReadOnlyExecutionTarget,
+    ExecutionCallTrace, ExecutionController, ExecutionError, ExecutionStackElement,
+    ReadOnlyExecutionOutput, ReadOnlyExecutionRequest, ReadOnlyExecutionTarget,
 };
 use massa_graph::{DiscardReason, ExportBlockStatus};
+use massa_ledger_exports::{LedgerEntry, SetOrDelete, SetOrKeep, SetUpdateOrDelete};
 use massa_models::api::{
-    DatastoreEntryInput, DatastoreEntryOutput, ReadOnlyBytecodeExecution, ReadOnlyCall,
-    SCELedgerInfo,
+    DatastoreEntryInput, DatastoreEntryOutput, DatastoreKeysInput, DatastoreKeysOutput,
+    GasEstimation, GasEstimationInput, HistoricalDatastoreEntry, HistoricalLedgerInput,
+    HistoricalLedgerOutput, ReadOnlyBytecodeExecution, ReadOnlyCall, SCELedgerInfo,
 };
-use massa_models::execution::ReadOnlyResult;
-use massa_models::SignedOperation;
+use massa_models::constants::{DATASTORE_KEYS_PAGE_SIZE, MAX_GAS_PER_BLOCK};
+use massa_models::execution::{
+    BalanceChange, ReadOnlyAsyncMessage, ReadOnlyCallTrace, ReadOnlyLedgerChange, ReadOnlyResult,
+    ReadOnlyStateChanges,
+};
+use massa_models::{OperationType, SignedOperation};
 
 use massa_models::{
     api::{
         AddressInfo, BlockInfo, BlockInfoContent, BlockSummary, EndorsementInfo, EventFilter,
-        IndexedSlot, NodeStatus, OperationInfo, TimeInterval,
+        IndexedSlot, NodeStatus, OperationInfo, PendingOperationInfo, StakerReport, TimeInterval,
     },
     clique::Clique,
     composite::PubkeySig,
@@ -28,13 +36,15 @@ use massa_models::{
     node::NodeId,
     output_event::SCOutputEvent,
     prehash::{BuildMap, Map, Set},
+    stats::PoolThreadFeeStats,
     timeslots::{get_latest_block_slot_at_timestamp, time_range_to_slot_range},
-    Address, BlockId, CompactConfig, EndorsementId, OperationId, Slot, Version,
+    Address, Amount, BlockId, CompactConfig, EndorsementId, OperationId, Slot, Version,
 };
 use massa_network_exports::{NetworkCommandSender, NetworkSettings};
 use massa_pool::PoolCommandSender;
 use massa_signature::{derive_public_key, generate_random_private_key, PrivateKey};
-use massa_time::MassaTime;
+use massa_storage::Storage;
+use massa_time::ClockCompensation;
 use std::net::{IpAddr, SocketAddr};
 
 impl API<Public> {
@@ -48,8 +58,9 @@ impl API<Public> {
         network_settings: &'static NetworkSettings,
         version: Version,
         network_command_sender: NetworkCommandSender,
-        compensation_millis: i64,
+        clock_compensation: ClockCompensation,
         node_id: NodeId,
+        storage: Storage,
     ) -> Self {
         API(Public {
             consensus_command_sender,
@@ -59,9 +70,10 @@ impl API<Public> {
             network_settings,
             version,
             network_command_sender,
-            compensation_millis,
+            clock_compensation,
             node_id,
             execution_controller,
+            storage,
         })
     }
 }
@@ -72,6 +84,128 @@ impl RpcServer for API<Public> {
     }
 }
 
+/// Map a call traced during a read-only execution to the response of the API
+fn map_call_trace(trace: ExecutionCallTrace) -> ReadOnlyCallTrace {
+    ReadOnlyCallTrace {
+        address: trace.address,
+        coins: trace.coins,
+        gas_cost: trace.gas_cost,
+        sub_calls: trace.sub_calls.into_iter().map(map_call_trace).collect(),
+    }
+}
+
+/// Map the output of a read-only execution to the response of the API
+fn read_only_response(
+    result: Result<ReadOnlyExecutionOutput, ExecutionError>,
+) -> ExecuteReadOnlyResponse {
+    let ReadOnlyExecutionOutput {
+        mut out,
+        gas_cost,
+        parallel_balances_before,
+        call_trace,
+    } = match result {
+        Ok(output) => output,
+        Err(err) => {
+            return ExecuteReadOnlyResponse {
+                executed_at: Slot::new(0, 0),
+                result: ReadOnlyResult::Error(format!("readonly call failed: {}", err)),
+                output_events: Default::default(),
+                gas_cost: 0,
+                state_changes: Default::default(),
+                call_trace: None,
+            }
+        }
+    };
+
+    let balance_change = |address: &Address, after: Amount| BalanceChange {
+        before: parallel_balances_before
+            .get(address)
+            .copied()
+            .unwrap_or_default(),
+        after,
+    };
+    let ledger_changes = out
+        .state_changes
+        .ledger_changes
+        .0
+        .into_iter()
+        .map(|(address, change)| match change {
+            SetUpdateOrDelete::Set(entry) => ReadOnlyLedgerChange {
+                address,
+                deleted: false,
+                parallel_balance: Some(balance_change(&address, entry.parallel_balance)),
+                bytecode: Some(entry.bytecode),
+                datastore_writes: entry.datastore.into_iter().collect(),
+                datastore_deletes: Vec::new(),
+            },
+            SetUpdateOrDelete::Update(update) => {
+                let mut datastore_writes = Vec::new();
+                let mut datastore_deletes = Vec::new();
+                for (key, value) in update.datastore {
+                    match value {
+                        SetOrDelete::Set(value) => datastore_writes.push((key, value)),
+                        SetOrDelete::Delete => datastore_deletes.push(key),
+                    }
+                }
+                ReadOnlyLedgerChange {
+                    address,
+                    deleted: false,
+                    parallel_balance: match update.parallel_balance {
+                        SetOrKeep::Set(balance) => Some(balance_change(&address, balance)),
+                        SetOrKeep::Keep => None,
+                    },
+                    bytecode: match update.bytecode {
+                        SetOrKeep::Set(bytecode) => Some(bytecode),
+                        SetOrKeep::Keep => None,
+                    },
+                    datastore_writes,
+                    datastore_deletes,
+                }
+            }
+            SetUpdateOrDelete::Delete => ReadOnlyLedgerChange {
+                address,
+                deleted: true,
+                parallel_balance: Some(balance_change(&address, Amount::default())),
+                bytecode: None,
+                datastore_writes: Vec::new(),
+                datastore_deletes: Vec::new(),
+            },
+        })
+        .collect();
+    let emitted_messages = out
+        .state_changes
+        .async_pool_changes
+        .0
+        .into_iter()
+        .filter_map(|change| match change {
+            Change::Add(_id, msg) => Some(ReadOnlyAsyncMessage {
+                sender: msg.sender,
+                destination: msg.destination,
+                handler: msg.handler,
+                max_gas: msg.max_gas,
+                gas_price: msg.gas_price,
+                coins: msg.coins,
+                validity_start: msg.validity_start,
+                validity_end: msg.validity_end,
+                data: msg.data,
+            }),
+            Change::Delete(_id) => None,
+        })
+        .collect();
+
+    ExecuteReadOnlyResponse {
+        executed_at: out.slot,
+        result: ReadOnlyResult::Ok,
+        output_events: out.events.take(),
+        gas_cost,
+        state_changes: ReadOnlyStateChanges {
+            ledger_changes,
+            emitted_messages,
+        },
+        call_trace: call_trace.map(map_call_trace),
+    }
+}
+
 #[doc(hidden)]
 impl Endpoints for API<Public> {
     fn stop_node(&self) -> BoxFuture<Result<(), ApiError>> {
@@ -130,16 +264,7 @@ impl Endpoints for API<Public> {
             let result = self.0.execution_controller.execute_readonly_request(req);
 
             // map result
-            let result = ExecuteReadOnlyResponse {
-                executed_at: result.as_ref().map_or_else(|_| Slot::new(0, 0), |v| v.slot),
-                result: result.as_ref().map_or_else(
-                    |err| ReadOnlyResult::Error(format!("readonly call failed: {}", err)),
-                    |_| ReadOnlyResult::Ok,
-                ),
-                output_events: result.map_or_else(|_| Default::default(), |mut v| v.events.take()),
-            };
-
-            res.push(result);
+            res.push(read_only_response(result));
         }
 
         // return result
@@ -204,19 +329,113 @@ impl Endpoints for API<Public> {
             let result = self.0.execution_controller.execute_readonly_request(req);
 
             // map result
-            let result = ExecuteReadOnlyResponse {
-                executed_at: result.as_ref().map_or_else(|_| Slot::new(0, 0), |v| v.slot),
-                result: result.as_ref().map_or_else(
-                    |err| ReadOnlyResult::Error(format!("readonly call failed: {}", err)),
-                    |_| ReadOnlyResult::Ok,
+            res.push(read_only_response(result));
+        }
+
+        // return result
+        let closure = async move || Ok(res);
+        Box::pin(closure())
+    }
+
+    fn estimate_gas(
+        &self,
+        reqs: Vec<GasEstimationInput>,
+    ) -> BoxFuture<Result<Vec<GasEstimation>, ApiError>> {
+        if reqs.len() as u64 > self.0.api_settings.max_arguments {
+            let closure =
+                async move || Err(ApiError::TooManyArguments("too many arguments".into()));
+            return Box::pin(closure());
+        }
+
+        let mut res: Vec<GasEstimation> = Vec::with_capacity(reqs.len());
+        for GasEstimationInput { sender, op } in reqs {
+            // translate the operation into a read-only request,
+            // allowing it to consume up to the gas of a whole block
+            let (target, call_stack, simulated_gas_price) = match op {
+                OperationType::ExecuteSC {
+                    data,
+                    coins,
+                    gas_price,
+                    ..
+                } => (
+                    ReadOnlyExecutionTarget::BytecodeExecution(data),
+                    vec![ExecutionStackElement {
+                        address: sender,
+                        coins,
+                        owned_addresses: vec![sender],
+                    }],
+                    gas_price,
                 ),
-                output_events: result.map_or_else(|_| Default::default(), |mut v| v.events.take()),
+                // no function is called, so no gas is consumed
+                OperationType::CallSC { target_func, .. } if target_func.is_empty() => {
+                    res.push(GasEstimation {
+                        gas_cost: 0,
+                        max_gas: 0,
+                    });
+                    continue;
+                }
+                OperationType::CallSC {
+                    target_addr,
+                    target_func,
+                    param,
+                    sequential_coins,
+                    parallel_coins,
+                    gas_price,
+                    ..
+                } => {
+                    let coins = sequential_coins.saturating_add(parallel_coins);
+                    (
+                        ReadOnlyExecutionTarget::FunctionCall {
+                            target_addr,
+                            target_func,
+                            parameter: param,
+                        },
+                        vec![
+                            ExecutionStackElement {
+                                address: sender,
+                                coins,
+                                owned_addresses: vec![sender],
+                            },
+                            ExecutionStackElement {
+                                address: target_addr,
+                                coins,
+                                owned_addresses: vec![target_addr],
+                            },
+                        ],
+                        gas_price,
+                    )
+                }
+                _ => {
+                    let closure = async move || {
+                        Err(ApiError::BadRequest(
+                            "gas can only be estimated for ExecuteSC and CallSC operations".into(),
+                        ))
+                    };
+                    return Box::pin(closure());
+                }
+            };
+            let req = ReadOnlyExecutionRequest {
+                max_gas: MAX_GAS_PER_BLOCK,
+                simulated_gas_price,
+                call_stack,
+                target,
+            };
+
+            // run
+            let gas_cost = match self.0.execution_controller.execute_readonly_request(req) {
+                Ok(output) => output.gas_cost,
+                Err(err) => {
+                    let closure = async move || Err(ApiError::ExecutionError(err));
+                    return Box::pin(closure());
+                }
             };
 
-            res.push(result);
+            res.push(gas_estimation(
+                gas_cost,
+                self.0.api_settings.gas_estimation_margin_percent,
+            ));
         }
 
-        // return result
         let closure = async move || Ok(res);
         Box::pin(closure())
     }
@@ -229,6 +448,14 @@ impl Endpoints for API<Public> {
         crate::wrong_api::<Set<Address>>()
     }
 
+    fn unlock_staking_keys(&self, _: String) -> BoxFuture<Result<Set<Address>, ApiError>> {
+        crate::wrong_api::<Set<Address>>()
+    }
+
+    fn lock_staking_keys(&self) -> BoxFuture<Result<(), ApiError>> {
+        crate::wrong_api::<()>()
+    }
+
     fn node_ban_by_ip(&self, _: Vec<IpAddr>) -> BoxFuture<Result<(), ApiError>> {
         crate::wrong_api::<()>()
     }
@@ -251,12 +478,12 @@ impl Endpoints for API<Public> {
         let network_config = self.0.network_settings.clone();
         let version = self.0.version;
         let consensus_settings = self.0.consensus_config.clone();
-        let compensation_millis = self.0.compensation_millis;
+        let clock_compensation = self.0.clock_compensation.clone();
         let mut pool_command_sender = self.0.pool_command_sender.clone();
         let node_id = self.0.node_id;
         let config = CompactConfig::default();
         let closure = async move || {
-            let now = MassaTime::compensated_now(compensation_millis)?;
+            let now = clock_compensation.now()?;
             let last_slot = get_latest_block_slot_at_timestamp(
                 consensus_settings.thread_count,
                 consensus_settings.t0,
@@ -275,6 +502,8 @@ impl Endpoints for API<Public> {
                 node_ip: network_config.routable_ip,
                 version,
                 current_time: now,
+                clock_compensation: clock_compensation.get(),
+                clock_drift: clock_compensation.get_measured_drift(),
                 connected_nodes: peers?
                     .peers
                     .iter()
@@ -318,6 +547,23 @@ impl Endpoints for API<Public> {
         Box::pin(closure())
     }
 
+    fn get_staker_reports(
+        &self,
+        addresses: Vec<Address>,
+    ) -> BoxFuture<Result<Vec<StakerReport>, ApiError>> {
+        let api_cfg = self.0.api_settings;
+        let consensus_command_sender = self.0.consensus_command_sender.clone();
+        let closure = async move || {
+            if addresses.len() as u64 > api_cfg.max_staker_report_addresses {
+                return Err(ApiError::TooManyArguments("too many arguments".into()));
+            }
+            Ok(consensus_command_sender
+                .get_staker_reports(addresses)
+                .await?)
+        };
+        Box::pin(closure())
+    }
+
     fn get_operations(
         &self,
         ops: Vec<OperationId>,
@@ -325,6 +571,7 @@ impl Endpoints for API<Public> {
         let api_cfg = self.0.api_settings;
         let consensus_command_sender = self.0.consensus_command_sender.clone();
         let mut pool_command_sender = self.0.pool_command_sender.clone();
+        let storage = self.0.storage.clone();
         let closure = async move || {
             if ops.len() as u64 > api_cfg.max_arguments {
                 return Err(ApiError::TooManyArguments("too many arguments".into()));
@@ -374,6 +621,25 @@ impl Endpoints for API<Public> {
                     .or_insert(search_new);
             });
 
+            // add operations of final blocks that were pruned from consensus
+            for op_id in ops.iter() {
+                if res.contains_key(op_id) {
+                    continue;
+                }
+                if let Some((operation, block_id)) = storage.get_archived_operation(op_id) {
+                    res.insert(
+                        *op_id,
+                        OperationInfo {
+                            id: *op_id,
+                            in_pool: false,
+                            in_blocks: vec![block_id],
+                            is_final: true,
+                            operation,
+                        },
+                    );
+                }
+            }
+
             // return values in the right order
             Ok(ops
                 .into_iter()
@@ -383,6 +649,38 @@ impl Endpoints for API<Public> {
         Box::pin(closure())
     }
 
+    fn get_pending_operations(
+        &self,
+        address: Address,
+    ) -> BoxFuture<Result<Vec<PendingOperationInfo>, ApiError>> {
+        let mut pool_command_sender = self.0.pool_command_sender.clone();
+        let closure = async move || {
+            let mut operations = pool_command_sender.get_pending_operations(address).await?;
+            operations.sort_by_key(|op| (op.expire_period, op.validity_start_period));
+            Ok(operations)
+        };
+        Box::pin(closure())
+    }
+
+    fn get_pool_fee_stats(
+        &self,
+        slot_count: u64,
+    ) -> BoxFuture<Result<Vec<PoolThreadFeeStats>, ApiError>> {
+        let cfg = self.0.consensus_config.clone();
+        let mut pool_command_sender = self.0.pool_command_sender.clone();
+        let closure = async move || {
+            Ok(pool_command_sender
+                .get_fee_stats(
+                    slot_count,
+                    cfg.max_block_size as u64,
+                    cfg.max_operations_per_block as u64,
+                    cfg.max_gas_per_block,
+                )
+                .await?)
+        };
+        Box::pin(closure())
+    }
+
     fn get_endorsements(
         &self,
         eds: Vec<EndorsementId>,
@@ -415,9 +713,10 @@ impl Endpoints for API<Public> {
     }
 
     /// gets a block. Returns None if not found
-    /// only active blocks are returned
+    /// only active blocks and archived final blocks are returned
     fn get_block(&self, id: BlockId) -> BoxFuture<Result<BlockInfo, ApiError>> {
         let consensus_command_sender = self.0.consensus_command_sender.clone();
+        let storage = self.0.storage.clone();
         let closure = async move || {
             let cliques = consensus_command_sender.get_cliques().await?;
             let blockclique = cliques
@@ -445,6 +744,16 @@ impl Endpoints for API<Public> {
                         block,
                     }),
                 })
+            } else if let Some(block) = storage.get_archived_block(&id) {
+                Ok(BlockInfo {
+                    id,
+                    content: Some(BlockInfoContent {
+                        is_final: true,
+                        is_stale: false,
+                        is_in_blockclique: false,
+                        block,
+                    }),
+                })
             } else {
                 Ok(BlockInfo { id, content: None })
             }
@@ -452,6 +761,56 @@ impl Endpoints for API<Public> {
         Box::pin(closure())
     }
 
+    /// gets the final block of a slot. Returns None if there is none
+    /// the block is looked for in consensus first, then in the block archive
+    fn get_block_by_slot(&self, slot: Slot) -> BoxFuture<Result<Option<BlockInfo>, ApiError>> {
+        let consensus_command_sender = self.0.consensus_command_sender.clone();
+        let consensus_settings = self.0.consensus_config.clone();
+        let storage = self.0.storage.clone();
+        let closure = async move || {
+            let graph = consensus_command_sender
+                .get_block_graph_status(
+                    Some(slot),
+                    Some(slot.get_next_slot(consensus_settings.thread_count)?),
+                )
+                .await?;
+            let final_block_id = graph
+                .active_blocks
+                .iter()
+                .find(|(_, block)| block.is_final)
+                .map(|(id, _)| *id);
+            if let Some(id) = final_block_id {
+                if let Some(ExportBlockStatus::Active(block) | ExportBlockStatus::Final(block)) =
+                    consensus_command_sender.get_block_status(id).await?
+                {
+                    return Ok(Some(BlockInfo {
+                        id,
+                        content: Some(BlockInfoContent {
+                            is_final: true,
+                            is_stale: false,
+                            is_in_blockclique: graph.max_cliques.iter().any(|clique| {
+                                clique.is_blockclique && clique.block_ids.contains(&id)
+                            }),
+                            block,
+                        }),
+                    }));
+                }
+            }
+            Ok(storage.get_archived_block_id_by_slot(&slot).and_then(|id| {
+                storage.get_archived_block(&id).map(|block| BlockInfo {
+                    id,
+                    content: Some(BlockInfoContent {
+                        is_final: true,
+                        is_stale: false,
+                        is_in_blockclique: false,
+                        block,
+                    }),
+                })
+            }))
+        };
+        Box::pin(closure())
+    }
+
     /// gets an interval of the block graph from consensus, with time filtering
     /// time filtering is done consensus-side to prevent communication overhead
     fn get_graph_interval(
@@ -523,6 +882,59 @@ impl Endpoints for API<Public> {
         Box::pin(closure())
     }
 
+    fn get_datastore_keys(
+        &self,
+        input: DatastoreKeysInput,
+    ) -> BoxFuture<Result<DatastoreKeysOutput, ApiError>> {
+        let execution_controller = self.0.execution_controller.clone();
+        let closure = async move || {
+            let (_, active_keys) = execution_controller.get_final_and_active_datastore_keys(
+                &input.address,
+                &input.prefix,
+                input.cursor.as_deref(),
+                DATASTORE_KEYS_PAGE_SIZE + 1,
+            );
+            let mut keys: Vec<Vec<u8>> = active_keys.into_iter().collect();
+            // a key beyond the page size means that there is a next page
+            let next_cursor = if keys.len() > DATASTORE_KEYS_PAGE_SIZE {
+                keys.truncate(DATASTORE_KEYS_PAGE_SIZE);
+                keys.last().cloned()
+            } else {
+                None
+            };
+            Ok(DatastoreKeysOutput { keys, next_cursor })
+        };
+        Box::pin(closure())
+    }
+
+    fn get_historical_ledger_info(
+        &self,
+        input: HistoricalLedgerInput,
+    ) -> BoxFuture<Result<HistoricalLedgerOutput, ApiError>> {
+        let execution_controller = self.0.execution_controller.clone();
+        let closure = async move || {
+            let entry = execution_controller.get_historical_ledger_entry(
+                &input.address,
+                &input.datastore_keys,
+                input.slot,
+            )?;
+            let datastore = input
+                .datastore_keys
+                .into_iter()
+                .map(|key| HistoricalDatastoreEntry {
+                    value: entry.datastore.get(&key).cloned().flatten(),
+                    key,
+                })
+                .collect();
+            Ok(HistoricalLedgerOutput {
+                parallel_balance: entry.parallel_balance,
+                bytecode: entry.bytecode,
+                datastore,
+            })
+        };
+        Box::pin(closure())
+    }
+
     fn get_addresses(
         &self,
         addresses: Vec<Address>,
@@ -532,7 +944,7 @@ impl Endpoints for API<Public> {
         let api_cfg = self.0.api_settings;
         let pool_command_sender = self.0.pool_command_sender.clone();
         let execution_controller = self.0.execution_controller.clone();
-        let compensation_millis = self.0.compensation_millis;
+        let clock_compensation = self.0.clock_compensation.clone();
 
         let closure = async move || {
             let mut res = Vec::with_capacity(addresses.len());
@@ -542,7 +954,7 @@ impl Endpoints for API<Public> {
                 return Err(ApiError::TooManyArguments("too many arguments".into()));
             }
             // next draws info
-            let now = MassaTime::compensated_now(compensation_millis)?;
+            let now = clock_compensation.now()?;
             let current_slot = get_latest_block_slot_at_timestamp(
                 cfg.thread_count,
                 cfg.t0,
@@ -607,38 +1019,17 @@ impl Endpoints for API<Public> {
                         .chain(get_consensus_eds?.into_keys())
                         .collect();
 
-                    let (final_sce, candidate_sce) =
-                        match exec_snd.get_final_and_active_ledger_entry(&address) {
-                            (None, None) => (SCELedgerInfo::default(), SCELedgerInfo::default()),
-                            (None, Some(candidate)) => (
-                                SCELedgerInfo::default(),
-                                SCELedgerInfo {
-                                    balance: candidate.parallel_balance,
-                                    module: candidate.bytecode,
-                                    datastore: candidate.datastore.into_iter().collect(),
-                                },
-                            ),
-                            (Some(final_entry), None) => (
-                                SCELedgerInfo {
-                                    balance: final_entry.parallel_balance,
-                                    module: final_entry.bytecode,
-                                    datastore: final_entry.datastore.into_iter().collect(),
-                                },
-                                SCELedgerInfo::default(),
-                            ),
-                            (Some(final_entry), Some(candidate)) => (
-                                SCELedgerInfo {
-                                    balance: final_entry.parallel_balance,
-                                    module: final_entry.bytecode,
-                                    datastore: final_entry.datastore.into_iter().collect(),
-                                },
-                                SCELedgerInfo {
-                                    balance: candidate.parallel_balance,
-                                    module: candidate.bytecode,
-                                    datastore: candidate.datastore.into_iter().collect(),
-                                },
-                            ),
-                        };
+                    let to_sce_info = |entry: LedgerEntry| {
+                        SCELedgerInfo::new(
+                            entry.parallel_balance,
+                            entry.bytecode,
+                            entry.datastore.into_iter().collect(),
+                        )
+                    };
+                    let (final_entry, candidate) =
+                        exec_snd.get_final_and_active_ledger_entry(&address);
+                    let final_sce = final_entry.map(to_sce_info).unwrap_or_default();
+                    let candidate_sce = candidate.map(to_sce_info).unwrap_or_default();
 
                     Result::<
                         (
@@ -764,3 +1155,42 @@ impl Endpoints for API<Public> {
         crate::wrong_api::<()>()
     }
 }
+
+/// Suggests a `max_gas` covering an estimated gas cost with a safety margin
+///
+/// # Arguments
+/// * `gas_cost`: gas consumed by the estimated execution
+/// * `margin_percent`: safety margin, in percent of the gas cost, rounded up
+///
+/// # Returns
+/// The estimation, whose `max_gas` never exceeds the gas of a block
+fn gas_estimation(gas_cost: u64, margin_percent: u64) -> GasEstimation {
+    let margin = gas_cost.saturating_mul(margin_percent).saturating_add(99) / 100;
+    GasEstimation {
+        gas_cost,
+        max_gas: gas_cost.saturating_add(margin).min(MAX_GAS_PER_BLOCK),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gas_estimation_margin() {
+        // the margin is rounded up so that any gas cost is covered
+        let estimation = gas_estimation(1001, 10);
+        assert_eq!(estimation.gas_cost, 1001);
+        assert_eq!(estimation.max_gas, 1102);
+        assert_eq!(gas_estimation(1, 10).max_gas, 2);
+        assert_eq!(gas_estimation(1000, 0).max_gas, 1000);
+        assert_eq!(gas_estimation(0, 10).max_gas, 0);
+
+        // the suggested gas cannot exceed the gas of a block
+        assert_eq!(
+            gas_estimation(MAX_GAS_PER_BLOCK - 1, 10).max_gas,
+            MAX_GAS_PER_BLOCK
+        );
+        assert_eq!(gas_estimation(u64::MAX, 10).max_gas, MAX_GAS_PER_BLOCK);
+    }
+}
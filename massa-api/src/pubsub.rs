@@ -0,0 +1,440 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use crate::error::ApiError;
+use crate::{PubSub, PubSubEndpoints, ServerCloseHandle, StopHandle, API};
+use jsonrpc_core::MetaIoHandler;
+use jsonrpc_pubsub::manager::IdProvider;
+use jsonrpc_pubsub::typed::{Sink, Subscriber};
+use jsonrpc_pubsub::{PubSubHandler, Session, SubscriptionId};
+use jsonrpc_ws_server::{RequestContext, ServerBuilder};
+use massa_consensus_exports::ConsensusCommandSender;
+use massa_execution_exports::ExecutionController;
+use massa_models::api::{BlockNotification, EventFilter, SCOutputEventNotification};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, Weak};
+use std::thread;
+use tokio::sync::{broadcast, oneshot};
+use tracing::{debug, warn};
+
+/// Kind of notifications a subscription receives
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SubscriptionKind {
+    /// smart contract output events
+    ScOutputEvents,
+    /// blocks
+    Blocks,
+}
+
+/// Active subscription
+pub(crate) struct ActiveSubscription {
+    /// kind of notifications
+    kind: SubscriptionKind,
+    /// session that subscribed, the only one allowed to unsubscribe
+    session: Weak<Session>,
+    /// channel used to cancel the subscription
+    stop_tx: oneshot::Sender<()>,
+}
+
+/// Active subscriptions, by id
+pub(crate) type ActiveSubscriptions = Arc<Mutex<HashMap<SubscriptionId, ActiveSubscription>>>;
+
+impl API<PubSub> {
+    /// generate a new publish/subscribe API
+    pub fn new(
+        consensus_command_sender: ConsensusCommandSender,
+        execution_controller: Box<dyn ExecutionController>,
+        max_subscriptions_per_session: u64,
+    ) -> Self {
+        API(PubSub {
+            consensus_command_sender,
+            execution_controller,
+            max_subscriptions_per_session,
+            active_subscriptions: Default::default(),
+            subscription_id_provider: Default::default(),
+        })
+    }
+
+    /// Start the WebSocket server of the publish/subscribe API
+    pub fn serve(self, url: &SocketAddr) -> StopHandle {
+        let mut io = PubSubHandler::new(MetaIoHandler::default());
+        io.extend_with(self.to_delegate());
+
+        let server = ServerBuilder::with_meta_extractor(io, |context: &RequestContext| {
+            Arc::new(Session::new(context.sender()))
+        })
+        .event_loop_executor(tokio::runtime::Handle::current())
+        .start(url)
+        .expect("Unable to start PubSub server");
+
+        let close_handle = ServerCloseHandle::Ws(server.close_handle());
+        let join_handle = thread::spawn(|| {
+            if let Err(err) = server.wait() {
+                warn!("PubSub server error: {}", err);
+            }
+        });
+
+        StopHandle {
+            close_handle,
+            join_handle,
+        }
+    }
+
+    /// Assign a random id to a new subscription of a session and register it as active
+    ///
+    /// # Returns
+    /// The id and the sink of the subscription, and the channel signaling its cancellation.
+    /// `None` if the client is already gone, or if the subscription was rejected
+    /// because the session reached `max_subscriptions_per_session`.
+    fn register<T>(
+        &self,
+        session: &Arc<Session>,
+        kind: SubscriptionKind,
+        subscriber: Subscriber<T>,
+    ) -> Option<(SubscriptionId, Sink<T>, oneshot::Receiver<()>)> {
+        let mut active_subscriptions = self
+            .0
+            .active_subscriptions
+            .lock()
+            .expect("poisoned subscriptions lock");
+        let session_subscriptions = active_subscriptions
+            .values()
+            .filter(|subscription| {
+                std::ptr::eq(subscription.session.as_ptr(), Arc::as_ptr(session))
+            })
+            .count();
+        if session_subscriptions as u64 >= self.0.max_subscriptions_per_session {
+            let _ = subscriber.reject(jsonrpc_core::Error {
+                code: jsonrpc_core::ErrorCode::InvalidRequest,
+                message: "too many subscriptions in this session".into(),
+                data: None,
+            });
+            return None;
+        }
+        let id = loop {
+            let id = SubscriptionId::from(self.0.subscription_id_provider.next_id());
+            if !active_subscriptions.contains_key(&id) {
+                break id;
+            }
+        };
+        let sink = subscriber.assign_id(id.clone()).ok()?;
+        let (stop_tx, stop_rx) = oneshot::channel();
+        active_subscriptions.insert(
+            id.clone(),
+            ActiveSubscription {
+                kind,
+                session: Arc::downgrade(session),
+                stop_tx,
+            },
+        );
+        Some((id, sink, stop_rx))
+    }
+
+    /// Cancel an active subscription of the given kind, made by the same session
+    fn unregister(
+        &self,
+        session: Option<Arc<Session>>,
+        kind: SubscriptionKind,
+        id: SubscriptionId,
+    ) -> jsonrpc_core::Result<bool> {
+        let mut active_subscriptions = self
+            .0
+            .active_subscriptions
+            .lock()
+            .expect("poisoned subscriptions lock");
+        // subscriptions of other sessions or of another kind are reported as unknown
+        let owned = match (active_subscriptions.get(&id), session) {
+            (Some(subscription), Some(session)) => {
+                subscription.kind == kind
+                    && std::ptr::eq(subscription.session.as_ptr(), Arc::as_ptr(&session))
+            }
+            _ => false,
+        };
+        if !owned {
+            return Err(jsonrpc_core::Error::invalid_params(
+                "unknown subscription id",
+            ));
+        }
+        if let Some(subscription) = active_subscriptions.remove(&id) {
+            let _ = subscription.stop_tx.send(());
+        }
+        Ok(true)
+    }
+}
+
+/// Forward the notifications accepted by `filter` to a subscriber
+/// until the subscription is cancelled or the client is gone
+async fn forward<T, F>(
+    id: SubscriptionId,
+    active_subscriptions: ActiveSubscriptions,
+    mut receiver: broadcast::Receiver<T>,
+    sink: Sink<T>,
+    mut stop_rx: oneshot::Receiver<()>,
+    filter: F,
+) where
+    T: Clone + Serialize + Send + 'static,
+    F: Fn(&T) -> bool + Send + 'static,
+{
+    loop {
+        tokio::select! {
+            _ = &mut stop_rx => break,
+            notification = receiver.recv() => match notification {
+                Ok(notification) => {
+                    if filter(&notification) && sink.notify(Ok(notification)).is_err() {
+                        debug!("subscriber {:?} is gone", id);
+                        break;
+                    }
+                }
+                Err(broadcast::error::RecvError::Lagged(count)) => {
+                    warn!("subscriber {:?} is too slow: {} notifications were dropped", id, count);
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    }
+    active_subscriptions
+        .lock()
+        .expect("poisoned subscriptions lock")
+        .remove(&id);
+}
+
+#[doc(hidden)]
+impl PubSubEndpoints for API<PubSub> {
+    type Metadata = Arc<Session>;
+
+    fn subscribe_sc_output_events(
+        &self,
+        session: Self::Metadata,
+        subscriber: Subscriber<SCOutputEventNotification>,
+        filter: EventFilter,
+    ) {
+        let receiver = self.0.execution_controller.subscribe_sc_output_events();
+        if let Some((id, sink, stop_rx)) =
+            self.register(&session, SubscriptionKind::ScOutputEvents, subscriber)
+        {
+            tokio::spawn(forward(
+                id,
+                self.0.active_subscriptions.clone(),
+                receiver,
+                sink,
+                stop_rx,
+                move |notification: &SCOutputEventNotification| filter.matches(&notification.event),
+            ));
+        }
+    }
+
+    fn unsubscribe_sc_output_events(
+        &self,
+        session: Option<Self::Metadata>,
+        id: SubscriptionId,
+    ) -> jsonrpc_core::Result<bool> {
+        self.unregister(session, SubscriptionKind::ScOutputEvents, id)
+    }
+
+    fn subscribe_blocks(&self, session: Self::Metadata, subscriber: Subscriber<BlockNotification>) {
+        let cmd_sender = self.0.consensus_command_sender.clone();
+        let active_subscriptions = self.0.active_subscriptions.clone();
+        if let Some((id, sink, stop_rx)) =
+            self.register(&session, SubscriptionKind::Blocks, subscriber)
+        {
+            tokio::spawn(async move {
+                match cmd_sender.subscribe_block_notifications().await {
+                    Ok(receiver) => {
+                        forward(id, active_subscriptions, receiver, sink, stop_rx, |_| true).await
+                    }
+                    Err(err) => {
+                        let _ = sink.notify(Err(ApiError::from(err).into()));
+                        active_subscriptions
+                            .lock()
+                            .expect("poisoned subscriptions lock")
+                            .remove(&id);
+                    }
+                }
+            });
+        }
+    }
+
+    fn unsubscribe_blocks(
+        &self,
+        session: Option<Self::Metadata>,
+        id: SubscriptionId,
+    ) -> jsonrpc_core::Result<bool> {
+        self.unregister(session, SubscriptionKind::Blocks, id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::{channel::mpsc, StreamExt};
+    use massa_consensus_exports::commands::ConsensusCommand;
+    use massa_execution_exports::test_exports::MockExecutionController;
+    use massa_hash::Hash;
+    use massa_models::{BlockId, Slot};
+    use serde_json::Value;
+
+    #[tokio::test]
+    async fn test_subscribe_blocks() {
+        let (consensus_tx, mut consensus_rx) = tokio::sync::mpsc::channel(10);
+        let (execution_controller, _execution_rx) = MockExecutionController::new_with_receiver();
+        let api = API::<PubSub>::new(
+            ConsensusCommandSender(consensus_tx),
+            execution_controller,
+            10,
+        );
+        let mut io = PubSubHandler::new(MetaIoHandler::default());
+        io.extend_with(api.to_delegate());
+
+        // subscribe through a session whose transport is a channel
+        let (transport_tx, mut transport_rx) = mpsc::unbounded();
+        let session = Arc::new(Session::new(transport_tx));
+        let response = io
+            .handle_request(
+                r#"{"jsonrpc":"2.0","id":1,"method":"subscribe_blocks","params":[]}"#,
+                session.clone(),
+            )
+            .await
+            .expect("no response to the subscription");
+        let response: Value = serde_json::from_str(&response).unwrap();
+        let subscription_id = response["result"].clone();
+        assert!(subscription_id.is_string(), "{}", response);
+
+        // answer the subscription of the API to consensus
+        let (notification_tx, _) = broadcast::channel(10);
+        match consensus_rx.recv().await {
+            Some(ConsensusCommand::SubscribeBlockNotifications(response_tx)) => response_tx
+                .send(notification_tx.subscribe())
+                .expect("could not answer the subscription"),
+            _ => panic!("the API did not subscribe to block notifications"),
+        }
+
+        // a final block is notified to the subscriber
+        let block_id = BlockId(Hash::compute_from(b"block"));
+        notification_tx
+            .send(BlockNotification::Final {
+                block_id,
+                slot: Slot::new(1, 0),
+            })
+            .unwrap();
+        let notification =
+            tokio::time::timeout(std::time::Duration::from_secs(5), transport_rx.next())
+                .await
+                .expect("no notification received")
+                .unwrap();
+        let notification: Value = serde_json::from_str(&notification).unwrap();
+        assert_eq!(notification["method"], "blocks");
+        assert_eq!(notification["params"]["subscription"], subscription_id);
+        assert_eq!(
+            notification["params"]["result"],
+            serde_json::to_value(BlockNotification::Final {
+                block_id,
+                slot: Slot::new(1, 0),
+            })
+            .unwrap()
+        );
+
+        // another session, or the unsubscription from another kind, cannot cancel the subscription
+        let (other_transport_tx, _other_transport_rx) = mpsc::unbounded();
+        let other_session = Arc::new(Session::new(other_transport_tx));
+        for (method, session) in [
+            ("unsubscribe_blocks", other_session),
+            ("unsubscribe_sc_output_events", session.clone()),
+        ] {
+            let response = io
+                .handle_request(
+                    &format!(
+                        r#"{{"jsonrpc":"2.0","id":2,"method":"{}","params":[{}]}}"#,
+                        method, subscription_id
+                    ),
+                    session,
+                )
+                .await
+                .expect("no response to the unsubscription");
+            let response: Value = serde_json::from_str(&response).unwrap();
+            assert!(response["error"].is_object(), "{}", response);
+        }
+
+        // the subscriber still receives notifications
+        notification_tx
+            .send(BlockNotification::Final {
+                block_id,
+                slot: Slot::new(2, 0),
+            })
+            .unwrap();
+        tokio::time::timeout(std::time::Duration::from_secs(5), transport_rx.next())
+            .await
+            .expect("no notification received after the rejected unsubscriptions")
+            .unwrap();
+
+        // unsubscribe
+        let response = io
+            .handle_request(
+                &format!(
+                    r#"{{"jsonrpc":"2.0","id":2,"method":"unsubscribe_blocks","params":[{}]}}"#,
+                    subscription_id
+                ),
+                session.clone(),
+            )
+            .await
+            .expect("no response to the unsubscription");
+        let response: Value = serde_json::from_str(&response).unwrap();
+        assert_eq!(response["result"], true);
+    }
+
+    #[tokio::test]
+    async fn test_max_subscriptions_per_session() {
+        let (consensus_tx, _consensus_rx) = tokio::sync::mpsc::channel(10);
+        let (execution_controller, _execution_rx) = MockExecutionController::new_with_receiver();
+        let api = API::<PubSub>::new(
+            ConsensusCommandSender(consensus_tx),
+            execution_controller,
+            2,
+        );
+        let mut io = PubSubHandler::new(MetaIoHandler::default());
+        io.extend_with(api.to_delegate());
+        let subscribe = |session: Arc<Session>| {
+            let response = io.handle_request(
+                r#"{"jsonrpc":"2.0","id":1,"method":"subscribe_blocks","params":[]}"#,
+                session,
+            );
+            async move {
+                let response = response.await.expect("no response to the subscription");
+                serde_json::from_str::<Value>(&response).unwrap()
+            }
+        };
+
+        // consensus is never answered: the subscriptions stay active until cancelled
+        // a session can hold up to `max_subscriptions_per_session` subscriptions
+        let (transport_tx, _transport_rx) = mpsc::unbounded();
+        let session = Arc::new(Session::new(transport_tx));
+        let first = subscribe(session.clone()).await;
+        assert!(first["result"].is_string(), "{}", first);
+        let second = subscribe(session.clone()).await;
+        assert!(second["result"].is_string(), "{}", second);
+        let rejected = subscribe(session.clone()).await;
+        assert!(rejected["error"].is_object(), "{}", rejected);
+
+        // other sessions are not affected
+        let (other_transport_tx, _other_transport_rx) = mpsc::unbounded();
+        let other_session = Arc::new(Session::new(other_transport_tx));
+        let other = subscribe(other_session).await;
+        assert!(other["result"].is_string(), "{}", other);
+
+        // cancelling a subscription frees a place
+        let response = io
+            .handle_request(
+                &format!(
+                    r#"{{"jsonrpc":"2.0","id":2,"method":"unsubscribe_blocks","params":[{}]}}"#,
+                    first["result"]
+                ),
+                session.clone(),
+            )
+            .await
+            .expect("no response to the unsubscription");
+        let response: Value = serde_json::from_str(&response).unwrap();
+        assert_eq!(response["result"], true);
+        let replacement = subscribe(session).await;
+        assert!(replacement["result"].is_string(), "{}", replacement);
+    }
+}
@@ -1,6 +1,7 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 use jsonrpc_core::serde::Deserialize;
+use massa_models::Address;
 use std::net::SocketAddr;
 
 /// API settings.
@@ -13,6 +14,21 @@ pub struct APISettings {
     pub bind_private: SocketAddr,
     /// bind for the public API
     pub bind_public: SocketAddr,
+    /// optional bind for the WebSocket publish/subscribe API, disabled if not set
+    #[serde(default)]
+    pub bind_pubsub: Option<SocketAddr>,
+    /// max active subscriptions per WebSocket session of the publish/subscribe API
+    pub max_subscriptions_per_session: u64,
     /// max argument count
     pub max_arguments: u64,
+    /// max address count per staker report request
+    pub max_staker_report_addresses: u64,
+    /// safety margin, in percent of the estimated gas cost, added to the `max_gas` suggested by gas estimations
+    pub gas_estimation_margin_percent: u64,
+    /// optional bind for the HTTP gateway serving the websites stored on chain, disabled if not set
+    #[serde(default)]
+    pub bind_web: Option<SocketAddr>,
+    /// optional address of the DNS smart contract resolving website names for the HTTP gateway
+    #[serde(default)]
+    pub web_dns_address: Option<Address>,
 }
@@ -0,0 +1,467 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! HTTP gateway serving the websites stored on chain.
+//!
+//! A website is a zip archive stored, raw or base64 encoded, in the `massa_web` datastore entry of an address.
+//! It is served at `/<address>/<path>`, or at `/<name>/<path>` where `name` is resolved to an address
+//! through the datastore of the DNS smart contract.
+//! Only final values are read, and unzipped websites are cached until the final value of their entry changes.
+//!
+//! All the websites share the origin of the gateway. To keep a website from reading the pages,
+//! cookies or storage of another one, their files are served with a `sandbox` content security policy:
+//! browsers give each page a unique opaque origin, and only allow it to run scripts, submit forms and open popups.
+
+use hyper::header::{
+    CACHE_CONTROL, CONTENT_SECURITY_POLICY, CONTENT_TYPE, ETAG, IF_NONE_MATCH, LOCATION,
+    X_CONTENT_TYPE_OPTIONS,
+};
+use hyper::{Body, Method, Request, Response, StatusCode};
+use massa_execution_exports::ExecutionController;
+use massa_hash::Hash;
+use massa_metrics::{start_http_server, HttpServerStopHandle};
+use massa_models::{Address, Slot};
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::io::{Cursor, Read};
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use tracing::warn;
+use zip::ZipArchive;
+
+/// datastore key of the website archive of an address
+const WEBSITE_DATASTORE_KEY: &[u8] = b"massa_web";
+
+/// maximum total uncompressed size of the files of a website
+const MAX_WEBSITE_SIZE: u64 = 64 * 1024 * 1024;
+
+/// maximum number of unzipped websites kept in the cache
+const MAX_CACHED_WEBSITES: usize = 32;
+
+/// content security policy of the website files: each page gets an opaque origin,
+/// isolating it from the other websites served by the gateway
+const WEBSITE_CONTENT_SECURITY_POLICY: &str =
+    "sandbox allow-scripts allow-forms allow-popups allow-modals allow-downloads; frame-ancestors 'none'";
+
+/// files of a website, by path in the archive
+type WebsiteFiles = HashMap<String, Vec<u8>>;
+
+/// unzipped website
+struct CachedWebsite {
+    /// final slot at which the website entry was last read
+    final_slot: Slot,
+    /// hash of the website entry
+    hash: Hash,
+    /// files of the website
+    files: Arc<WebsiteFiles>,
+}
+
+/// state shared by the requests to the gateway
+struct WebGateway {
+    /// link to the execution component
+    execution_controller: Box<dyn ExecutionController>,
+    /// address of the DNS smart contract resolving website names
+    dns_address: Option<Address>,
+    /// unzipped websites, by address
+    cache: Mutex<HashMap<Address, CachedWebsite>>,
+}
+
+impl WebGateway {
+    /// Resolves an address or a name registered in the DNS smart contract.
+    /// The DNS datastore maps each name to the address of its website, as a string.
+    fn resolve(&self, name: &str) -> Option<Address> {
+        if let Ok(address) = Address::from_str(name) {
+            return Some(address);
+        }
+        let value = self
+            .execution_controller
+            .get_final_and_active_data_entry(&self.dns_address?, name.as_bytes())
+            .0?;
+        Address::from_str(std::str::from_utf8(&value).ok()?.trim()).ok()
+    }
+
+    /// Gets the final version of the website of an address, along with the hash of its entry
+    fn get_website(&self, address: &Address) -> Result<Option<(Hash, Arc<WebsiteFiles>)>, String> {
+        let final_slot = self.execution_controller.get_final_slot();
+        let cached_hash = {
+            let cache = self.cache.lock().expect("web gateway cache poisoned");
+            match cache.get(address) {
+                Some(cached) if cached.final_slot == final_slot => {
+                    return Ok(Some((cached.hash, cached.files.clone())))
+                }
+                Some(cached) => Some((cached.hash, cached.files.clone())),
+                None => None,
+            }
+        };
+
+        // the final state moved on: read the entry again, and only unzip it if it changed
+        let archive = match self
+            .execution_controller
+            .get_final_and_active_data_entry(address, WEBSITE_DATASTORE_KEY)
+            .0
+        {
+            Some(archive) => archive,
+            None => {
+                self.cache
+                    .lock()
+                    .expect("web gateway cache poisoned")
+                    .remove(address);
+                return Ok(None);
+            }
+        };
+        let hash = Hash::compute_from(&archive);
+        let files = match cached_hash {
+            Some((cached_hash, files)) if cached_hash == hash => files,
+            _ => Arc::new(unzip_website(&archive)?),
+        };
+
+        let mut cache = self.cache.lock().expect("web gateway cache poisoned");
+        cache.insert(
+            *address,
+            CachedWebsite {
+                final_slot,
+                hash,
+                files: files.clone(),
+            },
+        );
+        if cache.len() > MAX_CACHED_WEBSITES {
+            // evict the website that was read the longest time ago
+            if let Some(oldest) = cache
+                .iter()
+                .min_by_key(|(_, cached)| cached.final_slot)
+                .map(|(address, _)| *address)
+            {
+                cache.remove(&oldest);
+            }
+        }
+        Ok(Some((hash, files)))
+    }
+
+    /// Answers a request for `/<address or name>/<path>`
+    fn serve(&self, path: &str, if_none_match: Option<&[u8]>) -> Response<Body> {
+        let (name, file_path) = match path.trim_start_matches('/').split_once('/') {
+            Some(parts) => parts,
+            None if path.trim_start_matches('/').is_empty() => {
+                return text_response(StatusCode::NOT_FOUND, "usage: /<address or name>/<path>")
+            }
+            // redirect so that the relative links of the index page resolve inside the website
+            None => {
+                return Response::builder()
+                    .status(StatusCode::MOVED_PERMANENTLY)
+                    .header(LOCATION, format!("{}/", path))
+                    .body(Body::empty())
+                    .unwrap()
+            }
+        };
+        let mut file_path = match percent_decode(file_path) {
+            Some(file_path) => file_path,
+            None => return text_response(StatusCode::BAD_REQUEST, "invalid path encoding"),
+        };
+        if file_path.is_empty() || file_path.ends_with('/') {
+            file_path.push_str("index.html");
+        }
+
+        let address = match self.resolve(name) {
+            Some(address) => address,
+            None => return text_response(StatusCode::NOT_FOUND, "unknown address or name"),
+        };
+        let (hash, files) = match self.get_website(&address) {
+            Ok(Some(website)) => website,
+            Ok(None) => return text_response(StatusCode::NOT_FOUND, "no website at this address"),
+            Err(err) => {
+                return text_response(
+                    StatusCode::BAD_GATEWAY,
+                    &format!("invalid website archive: {}", err),
+                )
+            }
+        };
+
+        // all the files of a website share the hash of its archive as entity tag
+        let etag = format!("\"{}\"", hash);
+        if if_none_match == Some(etag.as_bytes()) {
+            return Response::builder()
+                .status(StatusCode::NOT_MODIFIED)
+                .header(ETAG, etag)
+                .body(Body::empty())
+                .unwrap();
+        }
+        match files.get(&file_path) {
+            Some(content) => Response::builder()
+                .header(CONTENT_TYPE, content_type(&file_path))
+                .header(CONTENT_SECURITY_POLICY, WEBSITE_CONTENT_SECURITY_POLICY)
+                .header(X_CONTENT_TYPE_OPTIONS, "nosniff")
+                .header(CACHE_CONTROL, "no-cache")
+                .header(ETAG, etag)
+                .body(Body::from(content.clone()))
+                .unwrap(),
+            None => text_response(StatusCode::NOT_FOUND, "file not found in the website"),
+        }
+    }
+}
+
+/// Builds a plain text response
+fn text_response(status: StatusCode, message: &str) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header(CONTENT_TYPE, "text/plain; charset=utf-8")
+        .body(Body::from(message.to_string()))
+        .unwrap()
+}
+
+/// Decodes the `%XX` sequences of a path
+fn percent_decode(path: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(path.len());
+    let mut iter = path.bytes();
+    while let Some(byte) = iter.next() {
+        if byte == b'%' {
+            let hex = [iter.next()?, iter.next()?];
+            bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+        } else {
+            bytes.push(byte);
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+/// Gets the MIME type of a file from its extension
+fn content_type(path: &str) -> &'static str {
+    let file_name = path.rsplit('/').next().unwrap_or_default();
+    let extension = file_name
+        .rsplit_once('.')
+        .map(|(_, extension)| extension.to_ascii_lowercase());
+    match extension.as_deref() {
+        Some("html") | Some("htm") => "text/html; charset=utf-8",
+        Some("css") => "text/css; charset=utf-8",
+        Some("js") | Some("mjs") => "text/javascript; charset=utf-8",
+        Some("json") | Some("map") => "application/json",
+        Some("txt") => "text/plain; charset=utf-8",
+        Some("xml") => "application/xml",
+        Some("svg") => "image/svg+xml",
+        Some("png") => "image/png",
+        Some("jpg") | Some("jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("webp") => "image/webp",
+        Some("ico") => "image/x-icon",
+        Some("woff") => "font/woff",
+        Some("woff2") => "font/woff2",
+        Some("ttf") => "font/ttf",
+        Some("otf") => "font/otf",
+        Some("wasm") => "application/wasm",
+        Some("pdf") => "application/pdf",
+        Some("mp3") => "audio/mpeg",
+        Some("wav") => "audio/wav",
+        Some("mp4") => "video/mp4",
+        Some("webm") => "video/webm",
+        _ => "application/octet-stream",
+    }
+}
+
+/// Unzips a website archive, raw or base64 encoded
+fn unzip_website(archive: &[u8]) -> Result<WebsiteFiles, String> {
+    // websites uploaded with `include_base64` are stored as base64 text
+    let decoded;
+    let archive = if archive.starts_with(b"PK") {
+        archive
+    } else {
+        decoded = base64::decode(archive).map_err(|err| err.to_string())?;
+        &decoded
+    };
+    let mut zip = ZipArchive::new(Cursor::new(archive)).map_err(|err| err.to_string())?;
+    let mut files = WebsiteFiles::new();
+    let mut total_size = 0u64;
+    for index in 0..zip.len() {
+        let mut file = zip.by_index(index).map_err(|err| err.to_string())?;
+        // skip directories and paths escaping the website
+        if file.is_dir() || file.enclosed_name().is_none() {
+            continue;
+        }
+        let name = file.name().to_string();
+        let mut content = Vec::new();
+        // the declared sizes are not trusted
+        (&mut file)
+            .take(MAX_WEBSITE_SIZE - total_size + 1)
+            .read_to_end(&mut content)
+            .map_err(|err| err.to_string())?;
+        total_size += content.len() as u64;
+        if total_size > MAX_WEBSITE_SIZE {
+            return Err(format!(
+                "uncompressed size exceeds {} bytes",
+                MAX_WEBSITE_SIZE
+            ));
+        }
+        files.insert(name, content);
+    }
+    Ok(files)
+}
+
+/// Answers an HTTP request to the web gateway
+async fn handle_request(
+    request: Request<Body>,
+    gateway: Arc<WebGateway>,
+) -> Result<Response<Body>, Infallible> {
+    if request.method() != Method::GET && request.method() != Method::HEAD {
+        return Ok(text_response(
+            StatusCode::METHOD_NOT_ALLOWED,
+            "only GET and HEAD are allowed",
+        ));
+    }
+    let path = request.uri().path().to_string();
+    let if_none_match = request
+        .headers()
+        .get(IF_NONE_MATCH)
+        .map(|value| value.as_bytes().to_vec());
+    // reading the ledger and unzipping are blocking
+    let response =
+        tokio::task::spawn_blocking(move || gateway.serve(&path, if_none_match.as_deref()))
+            .await
+            .unwrap_or_else(|err| {
+                warn!("web gateway request panicked: {}", err);
+                text_response(StatusCode::INTERNAL_SERVER_ERROR, "internal error")
+            });
+    Ok(response)
+}
+
+/// Used to be able to stop the web gateway
+pub type WebGatewayStopHandle = HttpServerStopHandle;
+
+/// Starts an HTTP server serving the websites stored on chain at `/<address or name>/<path>`
+///
+/// # Arguments
+/// * `bind`: address the server listens on
+/// * `execution_controller`: link to the execution component, to read the final ledger
+/// * `dns_address`: address of the DNS smart contract resolving website names, if any
+pub fn start_web_gateway(
+    bind: &SocketAddr,
+    execution_controller: Box<dyn ExecutionController>,
+    dns_address: Option<Address>,
+) -> WebGatewayStopHandle {
+    let gateway = Arc::new(WebGateway {
+        execution_controller,
+        dns_address,
+        cache: Default::default(),
+    });
+    start_http_server("web gateway", bind, move |request| {
+        handle_request(request, gateway.clone())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_execution_exports::test_exports::{
+        MockExecutionController, MockExecutionControllerMessage,
+    };
+    use massa_signature::{derive_public_key, generate_random_private_key};
+    use std::io::Write;
+    use zip::write::FileOptions;
+    use zip::ZipWriter;
+
+    fn random_address() -> Address {
+        Address::from_public_key(&derive_public_key(&generate_random_private_key()))
+    }
+
+    fn zip_files(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, content) in files {
+            zip.start_file(*name, FileOptions::default()).unwrap();
+            zip.write_all(content).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_unzip_website() {
+        let archive = zip_files(&[
+            ("index.html", b"index"),
+            ("css/style.css", b"style"),
+            ("../escaped.html", b"escaped"),
+            ("/absolute.html", b"absolute"),
+        ]);
+        let files = unzip_website(&archive).unwrap();
+        assert_eq!(files.len(), 2);
+        assert_eq!(files["index.html"], b"index");
+        assert_eq!(files["css/style.css"], b"style");
+
+        // websites can also be stored base64 encoded
+        let files = unzip_website(base64::encode(&archive).as_bytes()).unwrap();
+        assert_eq!(files.len(), 2);
+
+        assert!(unzip_website(b"not a zip archive").is_err());
+    }
+
+    #[test]
+    fn test_percent_decode() {
+        assert_eq!(percent_decode("index.html").unwrap(), "index.html");
+        assert_eq!(
+            percent_decode("a%20b/%C3%A9.html").unwrap(),
+            "a b/\u{e9}.html"
+        );
+        // truncated or non hexadecimal escapes
+        assert_eq!(percent_decode("a%2"), None);
+        assert_eq!(percent_decode("a%"), None);
+        assert_eq!(percent_decode("a%zz"), None);
+        // escapes decoding to invalid UTF-8
+        assert_eq!(percent_decode("a%ff"), None);
+    }
+
+    #[test]
+    fn test_content_type() {
+        assert_eq!(content_type("index.html"), "text/html; charset=utf-8");
+        assert_eq!(content_type("img/LOGO.PNG"), "image/png");
+        assert_eq!(content_type("app.min.js"), "text/javascript; charset=utf-8");
+        assert_eq!(content_type("dir.css/file"), "application/octet-stream");
+        assert_eq!(content_type("archive.unknown"), "application/octet-stream");
+    }
+
+    #[test]
+    fn test_resolve() {
+        let dns_address = random_address();
+        let website_address = random_address();
+        let (execution_controller, execution_rx) = MockExecutionController::new_with_receiver();
+
+        // the DNS smart contract only knows the name `flappy`
+        let responder = std::thread::spawn(move || {
+            while let Ok(message) = execution_rx.recv() {
+                if let MockExecutionControllerMessage::GetDataEntry {
+                    addr,
+                    key,
+                    response_tx,
+                } = message
+                {
+                    let value = (addr == dns_address && key == b"flappy")
+                        .then(|| format!(" {} ", website_address).into_bytes());
+                    response_tx.send((value, None)).unwrap();
+                }
+            }
+        });
+
+        let gateway = WebGateway {
+            execution_controller: execution_controller.clone(),
+            dns_address: Some(dns_address),
+            cache: Default::default(),
+        };
+        assert_eq!(
+            gateway.resolve(&website_address.to_string()),
+            Some(website_address)
+        );
+        assert_eq!(gateway.resolve("flappy"), Some(website_address));
+        assert_eq!(gateway.resolve("unknown"), None);
+
+        // without DNS, only addresses are resolved
+        let gateway_without_dns = WebGateway {
+            execution_controller,
+            dns_address: None,
+            cache: Default::default(),
+        };
+        assert_eq!(gateway_without_dns.resolve("flappy"), None);
+        assert_eq!(
+            gateway_without_dns.resolve(&website_address.to_string()),
+            Some(website_address)
+        );
+
+        // stop the responder once the mock is dropped
+        drop(gateway);
+        drop(gateway_without_dns);
+        responder.join().unwrap();
+    }
+}
@@ -35,6 +35,19 @@ impl AsyncPool {
         }
     }
 
+    /// Creates an `AsyncPool` containing the given messages, for example messages loaded from disk
+    pub fn from_messages(
+        config: AsyncPoolConfig,
+        messages: BTreeMap<AsyncMessageId, AsyncMessage>,
+    ) -> AsyncPool {
+        AsyncPool { config, messages }
+    }
+
+    /// Gets the ID of the message with the lowest priority, `None` if the pool is empty
+    pub fn get_last_id(&self) -> Option<AsyncMessageId> {
+        self.messages.last_key_value().map(|(id, _)| *id)
+    }
+
     /// Applies pre-compiled `AsyncPoolChanges` to the pool without checking for overflows.
     /// This function is used when applying pre-compiled `AsyncPoolChanges` to an `AsyncPool`.
     ///
@@ -166,11 +179,13 @@ impl AsyncPool {
     /// * data: must be the serialized version provided by `get_pool_part`
     ///
     /// # Returns
-    /// The last id of the inserted entry (this is an optimization to easily keep a reference to the last id)
+    /// A tuple containing:
+    /// * The last id of the inserted entry (this is an optimization to easily keep a reference to the last id)
+    /// * The insertions of the part as `AsyncPoolChanges`, so that they can be persisted
     pub fn set_pool_part<'a>(
         &mut self,
         part: &'a [u8],
-    ) -> Result<Option<AsyncMessageId>, ModelsError> {
+    ) -> Result<(Option<AsyncMessageId>, AsyncPoolChanges), ModelsError> {
         let async_message_id_deserializer = AsyncMessageIdDeserializer::new();
         let async_message_deserializer = AsyncMessageDeserializer::new();
         let (rest, messages) = many0(|input: &'a [u8]| {
@@ -186,8 +201,14 @@ impl AsyncPool {
             ))(input)
         })(part)?;
         if rest.is_empty() {
+            let changes = AsyncPoolChanges(
+                messages
+                    .iter()
+                    .map(|(id, message)| Change::Add(*id, message.clone()))
+                    .collect(),
+            );
             self.messages.extend(messages);
-            Ok(self.messages.last_key_value().map(|(id, _)| *id))
+            Ok((self.get_last_id(), changes))
         } else {
             Err(ModelsError::SerializeError(
                 "pool part deserialization has data left".to_string(),
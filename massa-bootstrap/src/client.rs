@@ -67,15 +67,22 @@ async fn stream_final_state(
                 } => {
                     let mut write_final_state = global_bootstrap_state.final_state.write();
                     let last_key = write_final_state.ledger.set_ledger_part(ledger_data)?;
-                    let last_last_async_id = write_final_state
+                    let (last_last_async_id, mut async_pool_changes) = write_final_state
                         .async_pool
                         .set_pool_part(async_pool_part.as_bytes())?;
-                    write_final_state
-                        .ledger
-                        .apply_changes(final_state_changes.ledger_changes.clone(), slot);
                     write_final_state
                         .async_pool
                         .apply_changes_unchecked(final_state_changes.async_pool_changes.clone());
+                    // persist the received messages and changes, but keep the disk state detached
+                    // from any slot until the stream is finished so that a partial state is not resumed
+                    async_pool_changes
+                        .0
+                        .extend(final_state_changes.async_pool_changes.0.clone());
+                    write_final_state.ledger.apply_changes(
+                        final_state_changes.ledger_changes.clone(),
+                        async_pool_changes,
+                        None,
+                    );
                     write_final_state.slot = slot;
                     if let Some(BootstrapClientMessage::AskFinalStatePart {
                         last_key: old_key,
@@ -94,11 +101,20 @@ async fn stream_final_state(
                     });
                 }
                 BootstrapServerMessage::FinalStateFinished => {
+                    // the persisted final state is now complete: attach it to its slot
+                    let mut write_final_state = global_bootstrap_state.final_state.write();
+                    let slot = write_final_state.slot;
+                    write_final_state.ledger.apply_changes(
+                        Default::default(),
+                        Default::default(),
+                        Some(slot),
+                    );
                     *next_bootstrap_message = Some(BootstrapClientMessage::AskBootstrapPeers);
                     return Ok(());
                 }
                 BootstrapServerMessage::SlotTooOld => {
                     info!("Slot is too old retry bootstrap from scratch");
+                    global_bootstrap_state.final_state.write().reset();
                     *next_bootstrap_message = Some(BootstrapClientMessage::AskFinalStatePart {
                         last_key: None,
                         slot: None,
@@ -121,17 +137,16 @@ async fn stream_final_state(
     }
 }
 
-/// Gets the state from a bootstrap server (internal private function)
+/// Performs the handshake with a bootstrap server and receives its time
 /// needs to be CANCELLABLE
-async fn bootstrap_from_server(
+///
+/// # Returns
+/// The offset in milliseconds to add to the local clock to match the clock of the server
+pub(crate) async fn handshake_and_get_clock_offset(
     cfg: &BootstrapSettings,
     client: &mut BootstrapClientBinder,
-    next_bootstrap_message: &mut Option<BootstrapClientMessage>,
-    global_bootstrap_state: &mut GlobalBootstrapState,
     our_version: Version,
-) -> Result<(), BootstrapError> {
-    massa_trace!("bootstrap.lib.bootstrap_from_server", {});
-
+) -> Result<i64, BootstrapError> {
     // read error (if sent by the server)
     // client.next() is not cancel-safe but we drop the whole client object if cancelled => it's OK
     match tokio::time::timeout(cfg.read_error_timeout.into(), client.next()).await {
@@ -209,30 +224,47 @@ async fn bootstrap_from_server(
         ));
     }
 
-    // compute compensation
-    let compensation_millis = if cfg.enable_clock_synchronization {
-        let local_time_uncompensated =
-            recv_time_uncompensated.checked_sub(ping.checked_div_u64(2)?)?;
-        let compensation_millis = if server_time >= local_time_uncompensated {
+    // compute the offset of the local clock, taking the server time as received at half the ping
+    let local_time_uncompensated = recv_time_uncompensated.checked_sub(ping.checked_div_u64(2)?)?;
+    let offset_millis = if server_time >= local_time_uncompensated {
+        i64::try_from(
             server_time
                 .saturating_sub(local_time_uncompensated)
-                .to_millis()
-        } else {
+                .to_millis(),
+        )
+    } else {
+        i64::try_from(
             local_time_uncompensated
                 .saturating_sub(server_time)
-                .to_millis()
-        };
-        let compensation_millis: i64 = compensation_millis.try_into().map_err(|_| {
-            BootstrapError::GeneralError("Failed to convert compensation time into i64".into())
-        })?;
-        debug!("Server clock compensation set to: {}", compensation_millis);
-        compensation_millis
+                .to_millis(),
+        )
+        .map(|offset| -offset)
+    };
+    offset_millis
+        .map_err(|_| BootstrapError::GeneralError("Failed to convert clock offset into i64".into()))
+}
+
+/// Gets the state from a bootstrap server (internal private function)
+/// needs to be CANCELLABLE
+async fn bootstrap_from_server(
+    cfg: &BootstrapSettings,
+    client: &mut BootstrapClientBinder,
+    next_bootstrap_message: &mut Option<BootstrapClientMessage>,
+    global_bootstrap_state: &mut GlobalBootstrapState,
+    our_version: Version,
+) -> Result<(), BootstrapError> {
+    massa_trace!("bootstrap.lib.bootstrap_from_server", {});
+
+    let clock_offset = handshake_and_get_clock_offset(cfg, client, our_version).await?;
+
+    // compute compensation
+    global_bootstrap_state.compensation_millis = if cfg.enable_clock_synchronization {
+        debug!("Server clock compensation set to: {}", clock_offset);
+        clock_offset
     } else {
         0
     };
 
-    global_bootstrap_state.compensation_millis = compensation_millis;
-
     let write_timeout: std::time::Duration = cfg.write_timeout.into();
     // Loop to ask data to the server depending on the last message we sent
     loop {
@@ -319,7 +351,7 @@ async fn send_client_message(
     }
 }
 
-async fn connect_to_server(
+pub(crate) async fn connect_to_server(
     establisher: &mut Establisher,
     bootstrap_settings: &BootstrapSettings,
     addr: &SocketAddr,
@@ -363,13 +395,28 @@ pub async fn get_state(
     }
     let mut shuffled_list = bootstrap_settings.bootstrap_list.clone();
     shuffled_list.shuffle(&mut StdRng::from_entropy());
+    // resume from the persisted final state if any, so that only the missing changes are streamed
+    let bootstrap_cursor = final_state.read().get_bootstrap_cursor();
+    let final_state_part_request = match bootstrap_cursor {
+        Some((last_key, slot, last_async_message_id)) => {
+            info!("Resuming the final state persisted at slot {}", slot);
+            BootstrapClientMessage::AskFinalStatePart {
+                last_key: Some(last_key),
+                slot: Some(slot),
+                last_async_message_id: Some(last_async_message_id),
+            }
+        }
+        None => {
+            final_state.write().reset();
+            BootstrapClientMessage::AskFinalStatePart {
+                last_key: None,
+                slot: None,
+                last_async_message_id: None,
+            }
+        }
+    };
     // Will be none when bootstrap is over
-    let mut next_bootstrap_message: Option<BootstrapClientMessage> =
-        Some(BootstrapClientMessage::AskFinalStatePart {
-            last_key: None,
-            slot: None,
-            last_async_message_id: None,
-        });
+    let mut next_bootstrap_message: Option<BootstrapClientMessage> = Some(final_state_part_request);
     let mut global_bootstrap_state = GlobalBootstrapState::new(final_state.clone());
     loop {
         for (addr, pub_key) in shuffled_list.iter() {
@@ -0,0 +1,178 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Clock synchronization with the bootstrap servers while the node runs.
+//!
+//! The offset between the local clock and the clock of a random bootstrap server is measured periodically,
+//! and the median of the latest measurements is taken as the reference offset, to smooth out the network jitter
+//! and a single server with a wrong clock.
+//! When clock synchronization is enabled, the reference offset is applied live to the shared clock compensation.
+//! In any case, the drift of the compensated clock is recorded and a warning is logged beyond `max_clock_drift`.
+
+use crate::{
+    client::{connect_to_server, handshake_and_get_clock_offset},
+    error::BootstrapError,
+    BootstrapSettings, Establisher,
+};
+use massa_logging::massa_trace;
+use massa_models::Version;
+use massa_signature::PublicKey;
+use massa_time::ClockCompensation;
+use rand::{
+    prelude::{SliceRandom, StdRng},
+    SeedableRng,
+};
+use std::{collections::VecDeque, net::SocketAddr};
+use tokio::{
+    sync::mpsc,
+    task::JoinHandle,
+    time::{interval_at, Instant, MissedTickBehavior},
+};
+use tracing::{debug, info, warn};
+
+/// Number of latest measurements the reference offset is the median of
+pub(crate) const CLOCK_SYNC_SAMPLES: usize = 5;
+
+/// handle used to stop the clock synchronization
+pub struct ClockSyncManager {
+    join_handle: JoinHandle<()>,
+    manager_tx: mpsc::Sender<()>,
+}
+
+impl ClockSyncManager {
+    /// stop the clock synchronization
+    pub async fn stop(self) {
+        massa_trace!("bootstrap.clock_sync.stop", {});
+        if self.manager_tx.send(()).await.is_err() {
+            warn!("clock synchronization already dropped");
+        }
+        if let Err(err) = self.join_handle.await {
+            warn!("clock synchronization task panicked: {}", err);
+        }
+    }
+}
+
+/// Starts measuring the clock drift against the bootstrap servers every `clock_sync_period`.
+/// Returns `None` if the period is zero or if there are no bootstrap servers.
+///
+/// # Arguments
+/// * `bootstrap_settings`: bootstrap settings, holding the bootstrap servers and the synchronization parameters
+/// * `establisher`: used to connect to the bootstrap servers
+/// * `clock_compensation`: compensation shared with the other components, updated live if clock synchronization is enabled
+/// * `version`: version of the node
+pub fn start_clock_sync(
+    bootstrap_settings: &'static BootstrapSettings,
+    establisher: Establisher,
+    clock_compensation: ClockCompensation,
+    version: Version,
+) -> Option<ClockSyncManager> {
+    if bootstrap_settings.clock_sync_period == 0.into()
+        || bootstrap_settings.bootstrap_list.is_empty()
+    {
+        return None;
+    }
+    let (manager_tx, manager_rx) = mpsc::channel::<()>(1);
+    let join_handle = tokio::spawn(run_clock_sync(
+        bootstrap_settings,
+        establisher,
+        clock_compensation,
+        version,
+        manager_rx,
+    ));
+    Some(ClockSyncManager {
+        join_handle,
+        manager_tx,
+    })
+}
+
+/// Measures the offset of the local clock against a bootstrap server
+pub(crate) async fn measure_clock_offset(
+    bootstrap_settings: &BootstrapSettings,
+    establisher: &mut Establisher,
+    addr: &SocketAddr,
+    pub_key: &PublicKey,
+    version: Version,
+) -> Result<i64, BootstrapError> {
+    let mut client = connect_to_server(establisher, bootstrap_settings, addr, pub_key).await?;
+    handshake_and_get_clock_offset(bootstrap_settings, &mut client, version).await
+}
+
+/// Gets the median of the latest offset measurements
+pub(crate) fn get_reference_offset(samples: &VecDeque<i64>) -> Option<i64> {
+    let mut sorted: Vec<i64> = samples.iter().copied().collect();
+    sorted.sort_unstable();
+    sorted.get(sorted.len() / 2).copied()
+}
+
+/// Records the drift of the compensated clock from a reference offset,
+/// and applies the offset to the compensation if clock synchronization is enabled
+pub(crate) fn apply_reference_offset(
+    bootstrap_settings: &BootstrapSettings,
+    clock_compensation: &ClockCompensation,
+    offset_millis: i64,
+) {
+    let drift = offset_millis.saturating_sub(clock_compensation.get());
+    clock_compensation.set_measured_drift(drift);
+    if bootstrap_settings.enable_clock_synchronization {
+        if drift.unsigned_abs() > bootstrap_settings.max_clock_drift.to_millis() {
+            warn!(
+                "the local clock drifted by {} ms from the bootstrap servers since the last synchronization, compensating",
+                drift
+            );
+        }
+        clock_compensation.set(offset_millis);
+        debug!("clock compensation set to {} ms", offset_millis);
+    } else if drift.unsigned_abs() > bootstrap_settings.max_clock_drift.to_millis() {
+        warn!(
+            "the clock of the bootstrap servers is {} ms {} the local clock: slots may be missed. Synchronize the system clock or enable clock synchronization",
+            drift.unsigned_abs(),
+            if drift > 0 { "ahead of" } else { "behind" }
+        );
+    }
+}
+
+/// Clock synchronization loop
+async fn run_clock_sync(
+    bootstrap_settings: &'static BootstrapSettings,
+    mut establisher: Establisher,
+    clock_compensation: ClockCompensation,
+    version: Version,
+    mut manager_rx: mpsc::Receiver<()>,
+) {
+    debug!("starting clock synchronization");
+    let period = bootstrap_settings.clock_sync_period.to_duration();
+    // the clock was just measured during bootstrap
+    let mut timer = interval_at(Instant::now() + period, period);
+    timer.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    let mut rng = StdRng::from_entropy();
+    let mut samples = VecDeque::with_capacity(CLOCK_SYNC_SAMPLES + 1);
+    loop {
+        tokio::select! {
+            _ = manager_rx.recv() => break,
+            _ = timer.tick() => {
+                let (addr, pub_key) = bootstrap_settings
+                    .bootstrap_list
+                    .choose(&mut rng)
+                    .expect("bootstrap list checked as not empty");
+                massa_trace!("bootstrap.clock_sync.measure", { "addr": addr });
+                let offset = tokio::select! {
+                    _ = manager_rx.recv() => break,
+                    offset = measure_clock_offset(bootstrap_settings, &mut establisher, addr, pub_key, version) => offset,
+                };
+                match offset {
+                    Ok(offset) => {
+                        debug!("clock offset measured against bootstrap server {}: {} ms", addr, offset);
+                        samples.push_back(offset);
+                        if samples.len() > CLOCK_SYNC_SAMPLES {
+                            samples.pop_front();
+                        }
+                        if let Some(reference_offset) = get_reference_offset(&samples) {
+                            apply_reference_offset(bootstrap_settings, &clock_compensation, reference_offset);
+                        }
+                    }
+                    Err(err) => debug!("could not measure the clock offset against bootstrap server {}: {}", addr, err),
+                }
+            }
+        }
+    }
+    info!("clock synchronization finished cleanly");
+}
@@ -47,4 +47,6 @@ pub enum BootstrapError {
     IncompatibleVersionError(String),
     /// Received error: {0}
     ReceivedError(String),
+    /// snapshot error: {0}
+    SnapshotError(String),
 }
@@ -23,16 +23,20 @@ use std::sync::Arc;
 
 mod client;
 mod client_binder;
+mod clock_sync;
 mod error;
 mod establisher;
 mod messages;
 mod server;
 mod server_binder;
 mod settings;
+mod snapshot;
 pub use client::get_state;
+pub use clock_sync::{start_clock_sync, ClockSyncManager};
 pub use establisher::types;
 pub use server::{start_bootstrap_server, BootstrapManager};
 pub use settings::BootstrapSettings;
+pub use snapshot::{read_snapshot, write_snapshot};
 
 #[cfg(test)]
 pub mod tests;
@@ -13,13 +13,18 @@ use massa_final_state::{FinalState, StateChanges};
 use massa_graph::BootstrapableGraph;
 use massa_ledger_exports::get_address_from_key;
 use massa_logging::massa_trace;
+use massa_metrics::BOOTSTRAP_SESSIONS;
 use massa_models::{Slot, Version};
 use massa_network_exports::{BootstrapPeers, NetworkCommandSender};
 use massa_proof_of_stake_exports::ExportProofOfStake;
 use massa_signature::PrivateKey;
-use massa_time::MassaTime;
+use massa_time::ClockCompensation;
 use parking_lot::RwLock;
-use tokio::{sync::mpsc, task::JoinHandle, time::sleep};
+use tokio::{
+    sync::{mpsc, oneshot},
+    task::JoinHandle,
+    time::sleep,
+};
 use tracing::{debug, info, warn};
 
 use crate::{
@@ -59,7 +64,7 @@ pub async fn start_bootstrap_server(
     bootstrap_settings: &'static BootstrapSettings,
     establisher: Establisher,
     private_key: PrivateKey,
-    compensation_millis: i64,
+    clock_compensation: ClockCompensation,
     version: Version,
 ) -> Result<Option<BootstrapManager>, BootstrapError> {
     massa_trace!("bootstrap.lib.start_bootstrap_server", {});
@@ -74,7 +79,7 @@ pub async fn start_bootstrap_server(
                 manager_rx,
                 bind,
                 private_key,
-                compensation_millis,
+                clock_compensation,
                 version,
                 ip_hist_map: HashMap::with_capacity(bootstrap_settings.ip_list_max_size),
                 bootstrap_settings,
@@ -91,6 +96,28 @@ pub async fn start_bootstrap_server(
     }
 }
 
+/// data served by the bootstrap sessions, cached by the server
+type BootstrapData = (
+    ExportProofOfStake,
+    BootstrapableGraph,
+    BootstrapPeers,
+    Arc<RwLock<FinalState>>,
+);
+
+/// request of a bootstrap session for the served data,
+/// answered with an error message if the peer bootstrapped too recently
+type BootstrapDataRequest = (IpAddr, oneshot::Sender<Result<BootstrapData, String>>);
+
+/// how a bootstrap session ended
+enum BootstrapSessionOutcome {
+    /// the peer was served bootstrap data
+    Bootstrapped,
+    /// the peer only synchronized its clock
+    ClockSynced,
+    /// the peer asked for data but bootstrapped too recently
+    Refused,
+}
+
 struct BootstrapServer {
     consensus_command_sender: ConsensusCommandSender,
     network_command_sender: NetworkCommandSender,
@@ -100,7 +127,7 @@ struct BootstrapServer {
     bind: SocketAddr,
     private_key: PrivateKey,
     bootstrap_settings: &'static BootstrapSettings,
-    compensation_millis: i64,
+    clock_compensation: ClockCompensation,
     version: Version,
     ip_hist_map: HashMap<IpAddr, Instant>,
 }
@@ -112,12 +139,15 @@ impl BootstrapServer {
         let mut listener = self.establisher.get_listener(self.bind).await?;
         let mut bootstrap_sessions = FuturesUnordered::new();
         let cache_timeout = self.bootstrap_settings.cache_duration.to_duration();
-        let mut bootstrap_data: Option<(
-            ExportProofOfStake,
-            BootstrapableGraph,
-            BootstrapPeers,
-            Arc<RwLock<FinalState>>,
-        )> = None;
+        let mut bootstrap_data: Option<BootstrapData> = None;
+        let (data_request_tx, mut data_request_rx) = mpsc::channel::<BootstrapDataRequest>(
+            self.bootstrap_settings
+                .max_simultaneous_bootstraps
+                .try_into()
+                .map_err(|_| {
+                    BootstrapError::GeneralError("Fail to convert u32 to usize".to_string())
+                })?,
+        );
         let cache_timer = sleep(cache_timeout);
         let per_ip_min_interval = self.bootstrap_settings.per_ip_min_interval.to_duration();
         tokio::pin!(cache_timer);
@@ -128,6 +158,7 @@ impl BootstrapServer {
                 * manager commands to avoid waiting too long to stop in case of contention
                 * cache timeout to avoid skipping timeouts cleanup tasks (they are relatively rare)
                 * bootstrap sessions (rare)
+                * data requests of the bootstrap sessions
                 * listener: most frequent => last
         */
         loop {
@@ -150,9 +181,10 @@ impl BootstrapServer {
                     massa_trace!("bootstrap.session.finished", {"active_count": bootstrap_sessions.len()});
                 }
 
-                // listener
-                Ok((dplx, remote_addr)) = listener.accept() => if bootstrap_sessions.len() < self.bootstrap_settings.max_simultaneous_bootstraps.try_into().map_err(|_| BootstrapError::GeneralError("Fail to convert u32 to usize".to_string()))? {
-                    massa_trace!("bootstrap.lib.run.select.accept", {"remote_addr": remote_addr});
+                // bootstrap data requested by a session: only the sessions asking for data count in the IP history,
+                // so that the peers only synchronizing their clock can do it at any time
+                Some((remote_ip, response_tx)) = data_request_rx.recv() => {
+                    massa_trace!("bootstrap.lib.run.select.data_request", {"remote_ip": remote_ip});
                     let now = Instant::now();
 
                     // clear IP history if necessary
@@ -166,20 +198,12 @@ impl BootstrapServer {
                     }
 
                     // check IP's bootstrap attempt history
-                    match self.ip_hist_map.entry(remote_addr.ip()) {
+                    match self.ip_hist_map.entry(remote_ip) {
                         hash_map::Entry::Occupied(mut occ) => {
                             if now.duration_since(*occ.get()) <= per_ip_min_interval {
-                                let mut server = BootstrapServerBinder::new(dplx, self.private_key, self.bootstrap_settings.max_bytes_read_write);
-                                let _ = match tokio::time::timeout(self.bootstrap_settings.write_error_timeout.into(), server.send(BootstrapServerMessage::BootstrapError {
-                                    error:
-                                    format!("Your last bootstrap on this server was {:#?} ago and you have to wait {:#?} before retrying.", occ.get().elapsed(), per_ip_min_interval.saturating_sub(occ.get().elapsed()))
-                                })).await {
-                                    Err(_) => Err(std::io::Error::new(std::io::ErrorKind::TimedOut, "bootstrap error no available slots send timed out").into()),
-                                    Ok(Err(e)) => Err(e),
-                                    Ok(Ok(_)) => Ok(()),
-                                };
                                 // in list, non-expired => refuse
-                                massa_trace!("bootstrap.lib.run.select.accept.refuse_limit", {"remote_addr": remote_addr});
+                                let _ = response_tx.send(Err(format!("Your last bootstrap on this server was {:#?} ago and you have to wait {:#?} before retrying.", occ.get().elapsed(), per_ip_min_interval.saturating_sub(occ.get().elapsed()))));
+                                massa_trace!("bootstrap.lib.run.select.data_request.refuse_limit", {"remote_ip": remote_ip});
                                 continue;
                             } else {
                             // in list, expired
@@ -193,7 +217,7 @@ impl BootstrapServer {
 
                     // load cache if absent
                     if bootstrap_data.is_none() {
-                        massa_trace!("bootstrap.lib.run.select.accept.cache_load.start", {});
+                        massa_trace!("bootstrap.lib.run.select.data_request.cache_load.start", {});
 
                         // Note that all requests are done simultaneously except for the consensus graph that is done after the others.
                         // This is done to ensure that the execution bootstrap state is older than the consensus state.
@@ -204,21 +228,42 @@ impl BootstrapServer {
                         bootstrap_data = Some((pos_boot, graph_boot, peer_boot, self.final_state.clone()));
                         cache_timer.set(sleep(cache_timeout));
                     }
-                    massa_trace!("bootstrap.lib.run.select.accept.cache_available", {});
+                    massa_trace!("bootstrap.lib.run.select.data_request.cache_available", {});
+                    let _ = response_tx.send(Ok(bootstrap_data.clone().unwrap())); // will not panic (checked above)
+                }
+
+                // listener
+                Ok((dplx, remote_addr)) = listener.accept() => if bootstrap_sessions.len() < self.bootstrap_settings.max_simultaneous_bootstraps.try_into().map_err(|_| BootstrapError::GeneralError("Fail to convert u32 to usize".to_string()))? {
+                    massa_trace!("bootstrap.lib.run.select.accept", {"remote_addr": remote_addr});
 
                     // launch bootstrap
                     let private_key = self.private_key;
-                    let compensation_millis = self.compensation_millis;
+                    let clock_compensation = self.clock_compensation.clone();
                     let version = self.version;
-                    let (data_pos, data_graph, data_peers, data_execution) = bootstrap_data.clone().unwrap(); // will not panic (checked above)
+                    let data_request_tx = data_request_tx.clone();
                     bootstrap_sessions.push(async move {
                         //Socket lifetime
                         {
                             let mut server = BootstrapServerBinder::new(dplx, private_key, self.bootstrap_settings.max_bytes_read_write);
-                            match manage_bootstrap(self.bootstrap_settings, &mut server, data_pos, data_graph, data_peers, data_execution, compensation_millis, version).await {
-                                Ok(_) => info!("bootstrapped peer {}", remote_addr),
-                                Err(BootstrapError::ReceivedError(error)) => debug!("bootstrap serving error received from peer {}: {}", remote_addr, error),
+                            match manage_bootstrap(self.bootstrap_settings, &mut server, remote_addr.ip(), data_request_tx, clock_compensation, version).await {
+                                Ok(BootstrapSessionOutcome::Bootstrapped) => {
+                                    BOOTSTRAP_SESSIONS.with_label_values(&["success"]).inc();
+                                    info!("bootstrapped peer {}", remote_addr)
+                                },
+                                Ok(BootstrapSessionOutcome::ClockSynced) => {
+                                    BOOTSTRAP_SESSIONS.with_label_values(&["clock_sync"]).inc();
+                                    debug!("synchronized the clock of peer {}", remote_addr)
+                                },
+                                Ok(BootstrapSessionOutcome::Refused) => {
+                                    BOOTSTRAP_SESSIONS.with_label_values(&["refused"]).inc();
+                                    debug!("did not bootstrap {}: last bootstrap too recent", remote_addr)
+                                },
+                                Err(BootstrapError::ReceivedError(error)) => {
+                                    BOOTSTRAP_SESSIONS.with_label_values(&["error"]).inc();
+                                    debug!("bootstrap serving error received from peer {}: {}", remote_addr, error)
+                                },
                                 Err(err) => {
+                                    BOOTSTRAP_SESSIONS.with_label_values(&["error"]).inc();
                                     debug!("bootstrap serving error for peer {}: {}", remote_addr, err);
                                     // We allow unused result because we don't care if an error is thrown when sending the error message to the server we will close the socket anyway.
                                     let _ = tokio::time::timeout(self.bootstrap_settings.write_error_timeout.into(), server.send(BootstrapServerMessage::BootstrapError { error: err.to_string() })).await;
@@ -228,6 +273,7 @@ impl BootstrapServer {
                     });
                     massa_trace!("bootstrap.session.started", {"active_count": bootstrap_sessions.len()});
                 } else {
+                    BOOTSTRAP_SESSIONS.with_label_values(&["refused"]).inc();
                     let mut server = BootstrapServerBinder::new(dplx, self.private_key, self.bootstrap_settings.max_bytes_read_write);
                     let _ = match tokio::time::timeout(self.bootstrap_settings.write_error_timeout.into(), server.send(BootstrapServerMessage::BootstrapError {
                         error: "Bootstrap failed because the bootstrap server currently has no slots available.".to_string()
@@ -266,6 +312,7 @@ pub async fn send_final_state_stream(
         let async_pool_data;
         let final_state_changes;
         let current_slot;
+        let slot_changed;
         {
             // Get all data for the next message
             let final_state_read = final_state.read();
@@ -302,11 +349,14 @@ pub async fn send_final_state_stream(
             if new_last_key.is_some() || !ledger_data.is_empty() {
                 old_key = new_last_key;
             }
+            slot_changed = old_slot != Some(final_state_read.slot);
             old_slot = Some(final_state_read.slot);
             current_slot = final_state_read.slot;
         }
 
-        if !ledger_data.is_empty() || !async_pool_data.is_empty() {
+        // a part is also sent when only the slot changed, so that a client resuming
+        // its persisted final state receives the changes it missed
+        if !ledger_data.is_empty() || !async_pool_data.is_empty() || slot_changed {
             if let Ok(final_state_changes) = final_state_changes {
                 match tokio::time::timeout(
                     write_timeout,
@@ -366,17 +416,34 @@ pub async fn send_final_state_stream(
     Ok(())
 }
 
-#[allow(clippy::too_many_arguments)]
+/// Asks the server for the bootstrap data, on the first data request of a session
+///
+/// # Returns
+/// The data, or the error message to send to a peer that bootstrapped too recently
+async fn request_bootstrap_data(
+    data_request_tx: &mpsc::Sender<BootstrapDataRequest>,
+    remote_ip: IpAddr,
+) -> Result<Result<BootstrapData, String>, BootstrapError> {
+    let (response_tx, response_rx) = oneshot::channel();
+    data_request_tx
+        .send((remote_ip, response_tx))
+        .await
+        .map_err(|_| BootstrapError::GeneralError("bootstrap server stopped".to_string()))?;
+    response_rx
+        .await
+        .map_err(|_| BootstrapError::GeneralError("bootstrap server stopped".to_string()))
+}
+
+/// Serves a bootstrap session. The bootstrap data is only requested when the peer asks for it,
+/// so that a session only synchronizing the clock of the peer is not a bootstrap.
 async fn manage_bootstrap(
     bootstrap_settings: &'static BootstrapSettings,
     server: &mut BootstrapServerBinder,
-    data_pos: ExportProofOfStake,
-    data_graph: BootstrapableGraph,
-    data_peers: BootstrapPeers,
-    final_state: Arc<RwLock<FinalState>>,
-    compensation_millis: i64,
+    remote_ip: IpAddr,
+    data_request_tx: mpsc::Sender<BootstrapDataRequest>,
+    clock_compensation: ClockCompensation,
     version: Version,
-) -> Result<(), BootstrapError> {
+) -> Result<BootstrapSessionOutcome, BootstrapError> {
     massa_trace!("bootstrap.lib.manage_bootstrap", {});
     let read_error_timeout: std::time::Duration = bootstrap_settings.read_error_timeout.into();
 
@@ -409,7 +476,7 @@ async fn manage_bootstrap(
     let write_timeout: std::time::Duration = bootstrap_settings.write_timeout.into();
 
     // Sync clocks.
-    let server_time = MassaTime::compensated_now(compensation_millis)?;
+    let server_time = clock_compensation.now()?;
 
     match tokio::time::timeout(
         write_timeout,
@@ -429,67 +496,95 @@ async fn manage_bootstrap(
         Ok(Ok(_)) => Ok(()),
     }?;
 
+    let mut data: Option<BootstrapData> = None;
     loop {
-        match tokio::time::timeout(bootstrap_settings.read_timeout.into(), server.next()).await {
-            Err(_) => return Ok(()),
+        let msg = match tokio::time::timeout(bootstrap_settings.read_timeout.into(), server.next())
+            .await
+        {
+            Err(_) if data.is_none() => return Ok(BootstrapSessionOutcome::ClockSynced),
+            Err(_) => return Ok(BootstrapSessionOutcome::Bootstrapped),
+            // the peer disconnects once it received the time when it only synchronizes its clock
+            Ok(Err(BootstrapError::IoError(e)))
+                if data.is_none() && e.kind() == std::io::ErrorKind::UnexpectedEof =>
+            {
+                return Ok(BootstrapSessionOutcome::ClockSynced)
+            }
             Ok(Err(e)) => return Err(e),
-            Ok(Ok(msg)) => match msg {
-                BootstrapClientMessage::AskBootstrapPeers => {
-                    match tokio::time::timeout(
-                        write_timeout,
-                        server.send(BootstrapServerMessage::BootstrapPeers {
-                            peers: data_peers.clone(),
-                        }),
+            Ok(Ok(msg)) => msg,
+        };
+        if let BootstrapClientMessage::BootstrapError { error } = msg {
+            return Err(BootstrapError::ReceivedError(error));
+        }
+        let (data_pos, data_graph, data_peers, final_state) = match data {
+            Some(ref data) => data,
+            None => match request_bootstrap_data(&data_request_tx, remote_ip).await? {
+                Ok(bootstrap_data) => data.insert(bootstrap_data),
+                Err(error) => {
+                    // We allow unused result because we close the socket anyway.
+                    let _ = tokio::time::timeout(
+                        bootstrap_settings.write_error_timeout.into(),
+                        server.send(BootstrapServerMessage::BootstrapError { error }),
                     )
-                    .await
-                    {
-                        Err(_) => Err(std::io::Error::new(
-                            std::io::ErrorKind::TimedOut,
-                            "bootstrap peers send timed out",
-                        )
-                        .into()),
-                        Ok(Err(e)) => Err(e),
-                        Ok(Ok(_)) => Ok(()),
-                    }?;
+                    .await;
+                    return Ok(BootstrapSessionOutcome::Refused);
                 }
-                BootstrapClientMessage::AskFinalStatePart {
+            },
+        };
+        match msg {
+            BootstrapClientMessage::AskBootstrapPeers => {
+                match tokio::time::timeout(
+                    write_timeout,
+                    server.send(BootstrapServerMessage::BootstrapPeers {
+                        peers: data_peers.clone(),
+                    }),
+                )
+                .await
+                {
+                    Err(_) => Err(std::io::Error::new(
+                        std::io::ErrorKind::TimedOut,
+                        "bootstrap peers send timed out",
+                    )
+                    .into()),
+                    Ok(Err(e)) => Err(e),
+                    Ok(Ok(_)) => Ok(()),
+                }?;
+            }
+            BootstrapClientMessage::AskFinalStatePart {
+                last_key,
+                slot,
+                last_async_message_id,
+            } => {
+                send_final_state_stream(
+                    server,
                     last_key,
+                    final_state.clone(),
                     slot,
                     last_async_message_id,
-                } => {
-                    send_final_state_stream(
-                        server,
-                        last_key,
-                        final_state.clone(),
-                        slot,
-                        last_async_message_id,
-                        write_timeout,
-                    )
-                    .await?;
-                }
-                BootstrapClientMessage::AskConsensusState => {
-                    match tokio::time::timeout(
-                        write_timeout,
-                        server.send(BootstrapServerMessage::ConsensusState {
-                            pos: data_pos.clone(),
-                            graph: data_graph.clone(),
-                        }),
+                    write_timeout,
+                )
+                .await?;
+            }
+            BootstrapClientMessage::AskConsensusState => {
+                match tokio::time::timeout(
+                    write_timeout,
+                    server.send(BootstrapServerMessage::ConsensusState {
+                        pos: data_pos.clone(),
+                        graph: data_graph.clone(),
+                    }),
+                )
+                .await
+                {
+                    Err(_) => Err(std::io::Error::new(
+                        std::io::ErrorKind::TimedOut,
+                        "bootstrap consensus state send timed out",
                     )
-                    .await
-                    {
-                        Err(_) => Err(std::io::Error::new(
-                            std::io::ErrorKind::TimedOut,
-                            "bootstrap consensus state send timed out",
-                        )
-                        .into()),
-                        Ok(Err(e)) => Err(e),
-                        Ok(Ok(_)) => Ok(()),
-                    }?;
-                }
-                BootstrapClientMessage::BootstrapError { error } => {
-                    return Err(BootstrapError::ReceivedError(error));
-                }
-            },
-        };
+                    .into()),
+                    Ok(Err(e)) => Err(e),
+                    Ok(Ok(_)) => Ok(()),
+                }?;
+            }
+            // handled above
+            BootstrapClientMessage::BootstrapError { .. } => {}
+        }
     }
 }
@@ -29,6 +29,10 @@ pub struct BootstrapSettings {
     pub max_ping: MassaTime,
     /// Enable clock synchronization
     pub enable_clock_synchronization: bool,
+    /// Period of the clock drift measurements against the bootstrap servers while the node runs, disabled if zero
+    pub clock_sync_period: MassaTime,
+    /// Drift of the compensated clock from the bootstrap servers beyond which a warning is logged
+    pub max_clock_drift: MassaTime,
     /// Cache duration
     pub cache_duration: MassaTime,
     /// Max simultaneous bootstraps
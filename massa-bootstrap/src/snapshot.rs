@@ -0,0 +1,205 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Bootstrap snapshot files.
+//!
+//! A snapshot holds the final ledger, the async pool, the proof of stake state and the block graph
+//! at a given final slot. A node can boot from it and then only stream the changes it missed from its peers.
+//!
+//! File layout: `magic | hash(body) | body` where the body is the node version followed by
+//! the bootstrap server messages that would have streamed the same state
+//! (`FinalStatePart`s, `FinalStateFinished` and `ConsensusState`).
+
+use crate::{
+    error::BootstrapError,
+    messages::{
+        BootstrapServerMessage, BootstrapServerMessageDeserializer,
+        BootstrapServerMessageSerializer,
+    },
+    GlobalBootstrapState,
+};
+use massa_final_state::FinalState;
+use massa_graph::BootstrapableGraph;
+use massa_hash::{Hash, HASH_SIZE_BYTES};
+use massa_models::{Slot, Version, VersionDeserializer, VersionSerializer};
+use massa_proof_of_stake_exports::ExportProofOfStake;
+use massa_serialization::{DeserializeError, Deserializer, Serializer};
+use parking_lot::RwLock;
+use std::{path::Path, sync::Arc};
+use tracing::info;
+
+/// Magic bytes at the start of a snapshot file
+const SNAPSHOT_MAGIC: &[u8] = b"MASSA_SNAPSHOT";
+
+/// Writes a snapshot of the final state and of the consensus state to a file
+///
+/// # Arguments
+/// * `path`: file to write, overwritten if it exists
+/// * `final_state`: final state to export, its slot is the slot of the snapshot
+/// * `pos`: proof of stake state
+/// * `graph`: block graph
+/// * `version`: version of the node writing the snapshot
+///
+/// # Returns
+/// The final slot of the snapshot
+pub fn write_snapshot(
+    path: &Path,
+    final_state: &FinalState,
+    pos: &ExportProofOfStake,
+    graph: &BootstrapableGraph,
+    version: Version,
+) -> Result<Slot, BootstrapError> {
+    let message_serializer = BootstrapServerMessageSerializer::new();
+    let mut body = Vec::new();
+    VersionSerializer::new().serialize(&version, &mut body)?;
+
+    // final state parts, following the same cursors as a bootstrap stream
+    let mut last_key = None;
+    let mut last_async_message_id = None;
+    let mut part_written = false;
+    loop {
+        let (ledger_data, new_last_key) = final_state.ledger.get_ledger_part(&last_key)?;
+        let (async_pool_part, new_last_async_message_id) = final_state
+            .async_pool
+            .get_pool_part(last_async_message_id)?;
+        // at least one part is written so that the slot is known even if the state is empty
+        if ledger_data.is_empty() && async_pool_part.is_empty() && part_written {
+            break;
+        }
+        part_written = true;
+        if new_last_key.is_some() {
+            last_key = new_last_key;
+        }
+        if new_last_async_message_id.is_some() {
+            last_async_message_id = new_last_async_message_id;
+        }
+        message_serializer.serialize(
+            &BootstrapServerMessage::FinalStatePart {
+                ledger_data,
+                async_pool_part,
+                slot: final_state.slot,
+                final_state_changes: Default::default(),
+            },
+            &mut body,
+        )?;
+    }
+    message_serializer.serialize(&BootstrapServerMessage::FinalStateFinished, &mut body)?;
+
+    // consensus state
+    message_serializer.serialize(
+        &BootstrapServerMessage::ConsensusState {
+            pos: pos.clone(),
+            graph: graph.clone(),
+        },
+        &mut body,
+    )?;
+
+    let mut content = Vec::with_capacity(SNAPSHOT_MAGIC.len() + HASH_SIZE_BYTES + body.len());
+    content.extend(SNAPSHOT_MAGIC);
+    content.extend(Hash::compute_from(&body).to_bytes());
+    content.extend(body);
+    std::fs::write(path, content)?;
+    Ok(final_state.slot)
+}
+
+/// Loads a snapshot file into the final state, replacing its content.
+/// The loaded final state is persisted and attached to the slot of the snapshot,
+/// so that a following bootstrap only streams the changes that happened after it.
+///
+/// # Arguments
+/// * `path`: snapshot file
+/// * `final_state`: final state to fill
+/// * `version`: version of the node, the snapshot must have been written by a compatible version
+///
+/// # Returns
+/// A bootstrap state holding the proof of stake state and the block graph of the snapshot,
+/// without peers nor clock compensation
+pub fn read_snapshot(
+    path: &Path,
+    final_state: Arc<RwLock<FinalState>>,
+    version: Version,
+) -> Result<GlobalBootstrapState, BootstrapError> {
+    let content = std::fs::read(path)?;
+
+    // check the integrity of the file
+    let header_len = SNAPSHOT_MAGIC.len() + HASH_SIZE_BYTES;
+    if content.len() < header_len || !content.starts_with(SNAPSHOT_MAGIC) {
+        return Err(BootstrapError::SnapshotError(
+            "not a snapshot file".to_string(),
+        ));
+    }
+    let body = &content[header_len..];
+    if Hash::compute_from(body).to_bytes()[..] != content[SNAPSHOT_MAGIC.len()..header_len] {
+        return Err(BootstrapError::SnapshotError(
+            "checksum mismatch, the file is corrupted".to_string(),
+        ));
+    }
+
+    let (mut rest, snapshot_version) = VersionDeserializer::new()
+        .deserialize::<DeserializeError>(body)
+        .map_err(|err| BootstrapError::SnapshotError(format!("{}", err)))?;
+    if !version.is_compatible(&snapshot_version) {
+        return Err(BootstrapError::IncompatibleVersionError(format!(
+            "snapshot written by incompatible version: {} (local node version: {})",
+            snapshot_version, version
+        )));
+    }
+
+    let message_deserializer = BootstrapServerMessageDeserializer::new();
+    let mut next_message = || {
+        let (new_rest, message) = message_deserializer
+            .deserialize::<DeserializeError>(rest)
+            .map_err(|err| BootstrapError::SnapshotError(format!("{}", err)))?;
+        rest = new_rest;
+        Ok::<_, BootstrapError>(message)
+    };
+
+    // final state
+    {
+        let mut write_final_state = final_state.write();
+        write_final_state.reset();
+        loop {
+            match next_message()? {
+                BootstrapServerMessage::FinalStatePart {
+                    ledger_data,
+                    async_pool_part,
+                    slot,
+                    ..
+                } => {
+                    write_final_state.ledger.set_ledger_part(ledger_data)?;
+                    let (_, async_pool_changes) = write_final_state
+                        .async_pool
+                        .set_pool_part(&async_pool_part)?;
+                    write_final_state.ledger.apply_changes(
+                        Default::default(),
+                        async_pool_changes,
+                        None,
+                    );
+                    write_final_state.slot = slot;
+                }
+                BootstrapServerMessage::FinalStateFinished => break,
+                other => return Err(BootstrapError::UnexpectedServerMessage(other)),
+            }
+        }
+        let slot = write_final_state.slot;
+        write_final_state
+            .ledger
+            .apply_changes(Default::default(), Default::default(), Some(slot));
+        info!("Final state loaded from snapshot at slot {}", slot);
+    }
+
+    // consensus state
+    let mut global_bootstrap_state = GlobalBootstrapState::new(final_state);
+    match next_message()? {
+        BootstrapServerMessage::ConsensusState { pos, graph } => {
+            global_bootstrap_state.pos = Some(pos);
+            global_bootstrap_state.graph = Some(graph);
+        }
+        other => return Err(BootstrapError::UnexpectedServerMessage(other)),
+    }
+    if !rest.is_empty() {
+        return Err(BootstrapError::SnapshotError(
+            "unexpected data at the end of the snapshot".to_string(),
+        ));
+    }
+    Ok(global_bootstrap_state)
+}
@@ -7,22 +7,24 @@ use super::{
         wait_consensus_command, wait_network_command,
     },
 };
+use crate::clock_sync::{apply_reference_offset, get_reference_offset, measure_clock_offset};
 use crate::BootstrapSettings;
 use crate::{
-    get_state, start_bootstrap_server,
+    get_state, read_snapshot, start_bootstrap_server,
     tests::tools::{
         assert_eq_bootstrap_graph, assert_eq_thread_cycle_states, get_bootstrap_config,
     },
+    write_snapshot,
 };
 use massa_consensus_exports::{commands::ConsensusCommand, ConsensusCommandSender};
 use massa_final_state::{test_exports::assert_eq_final_state, FinalState};
 use massa_models::Version;
 use massa_network_exports::{NetworkCommand, NetworkCommandSender};
 use massa_signature::PrivateKey;
-use massa_time::MassaTime;
+use massa_time::{ClockCompensation, MassaTime};
 use parking_lot::RwLock;
 use serial_test::serial;
-use std::{str::FromStr, sync::Arc};
+use std::{collections::VecDeque, net::SocketAddr, str::FromStr, sync::Arc};
 use tokio::sync::mpsc;
 
 lazy_static::lazy_static! {
@@ -51,18 +53,58 @@ async fn test_bootstrap_server() {
         bootstrap_settings,
         bootstrap_establisher,
         *private_key,
-        0,
+        Default::default(),
         Version::from_str("TEST.1.2").unwrap(),
     )
     .await
     .unwrap()
     .unwrap();
 
+    // synchronize the clock of the remote that bootstraps afterwards
+    let (mut remote_establisher, mut remote_interface) = mock_establisher::new();
+    let remote_addr = SocketAddr::from_str("82.245.72.98:10000").unwrap(); // not checked
+    let (clock_offset, _) = tokio::join!(
+        measure_clock_offset(
+            bootstrap_settings,
+            &mut remote_establisher,
+            &bootstrap_settings.bootstrap_list[0].0,
+            &bootstrap_settings.bootstrap_list[0].1,
+            Version::from_str("TEST.1.2").unwrap(),
+        ),
+        async {
+            let (remote_rw, _, resp) = remote_interface
+                .wait_connection_attempt_from_controller()
+                .await
+                .expect("error receiving clock sync connection attempt from remote");
+            resp.send(true)
+                .expect("could not send clock sync connection accept to remote");
+            let bootstrap_rw = bootstrap_interface
+                .connect_to_controller(&remote_addr)
+                .await
+                .expect("could not connect to bootstrap for clock sync");
+            bridge_mock_streams(remote_rw, bootstrap_rw).await;
+        }
+    );
+    clock_offset.expect("clock sync failed");
+
+    // a clock sync does not load the bootstrap data
+    assert!(
+        wait_network_command(&mut network_cmd_rx, 200.into(), Some)
+            .await
+            .is_none(),
+        "clock sync asked network for bootstrap peers"
+    );
+    assert!(
+        wait_consensus_command(&mut consensus_cmd_rx, 200.into(), Some)
+            .await
+            .is_none(),
+        "clock sync asked consensus for bootstrap state"
+    );
+
     let final_state_client = Arc::new(RwLock::new(FinalState::default()));
     let final_state_client_thread = final_state_client.clone();
 
-    // launch the get_state process
-    let (remote_establisher, mut remote_interface) = mock_establisher::new();
+    // launch the get_state process, from the same IP as the clock sync
     let get_state_h = tokio::spawn(async move {
         get_state(
             bootstrap_settings,
@@ -93,7 +135,6 @@ async fn test_bootstrap_server() {
         .expect("could not send connection accept to remote");
 
     // connect to bootstrap
-    let remote_addr = std::net::SocketAddr::from_str("82.245.72.98:10000").unwrap(); // not checked
     let bootstrap_rw = tokio::time::timeout(
         std::time::Duration::from_millis(1000),
         bootstrap_interface.connect_to_controller(&remote_addr),
@@ -174,3 +215,55 @@ async fn test_bootstrap_server() {
         .await
         .expect("could not stop bootstrap server");
 }
+
+#[test]
+#[serial]
+fn test_snapshot() {
+    // initializes the serialization context
+    let _ = &*BOOTSTRAP_SETTINGS_PRIVATE_KEY;
+    let version = Version::from_str("TEST.1.2").unwrap();
+    let path = std::env::temp_dir().join(format!("massa_snapshot_{}", rand::random::<u64>()));
+
+    let final_state = get_random_final_state_bootstrap(2);
+    let (pos, graph) = get_boot_state();
+    let slot = write_snapshot(&path, &final_state, &pos, &graph, version).unwrap();
+    assert_eq!(slot, final_state.slot);
+
+    let loaded_final_state = Arc::new(RwLock::new(FinalState::default()));
+    let state = read_snapshot(&path, loaded_final_state.clone(), version).unwrap();
+    assert_eq_final_state(&final_state, &loaded_final_state.read());
+    assert_eq!(loaded_final_state.read().ledger.get_slot(), Some(slot));
+    assert_eq_thread_cycle_states(&pos, &state.pos.unwrap());
+    assert_eq_bootstrap_graph(&graph, &state.graph.unwrap());
+
+    // a corrupted snapshot is rejected
+    let mut content = std::fs::read(&path).unwrap();
+    let last = content.len() - 1;
+    content[last] ^= 1;
+    std::fs::write(&path, content).unwrap();
+    assert!(read_snapshot(&path, loaded_final_state, version).is_err());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn test_clock_sync_offset() {
+    let (mut bootstrap_settings, _) = BOOTSTRAP_SETTINGS_PRIVATE_KEY.clone();
+
+    // the median of the latest measurements ignores an outlier
+    let samples: VecDeque<i64> = vec![120, -4000, 100, 110, 95000].into();
+    assert_eq!(get_reference_offset(&samples), Some(110));
+    assert_eq!(get_reference_offset(&VecDeque::new()), None);
+
+    // with clock synchronization, the drift is recorded and compensated
+    let clock_compensation = ClockCompensation::new(100);
+    apply_reference_offset(&bootstrap_settings, &clock_compensation, 1600);
+    assert_eq!(clock_compensation.get_measured_drift(), Some(1500));
+    assert_eq!(clock_compensation.get(), 1600);
+
+    // without clock synchronization, the drift is only recorded
+    bootstrap_settings.enable_clock_synchronization = false;
+    let clock_compensation = ClockCompensation::default();
+    apply_reference_offset(&bootstrap_settings, &clock_compensation, -300);
+    assert_eq!(clock_compensation.get_measured_drift(), Some(-300));
+    assert_eq!(clock_compensation.get(), 0);
+}
@@ -60,7 +60,7 @@ fn get_random_ledger_entry() -> LedgerEntry {
     let bytecode: Vec<u8> = get_some_random_bytes();
     let mut datastore = BTreeMap::new();
     for _ in 0usize..rng.gen_range(0..10) {
-        let key = Hash::compute_from(&get_some_random_bytes());
+        let key = get_some_random_bytes();
         let value = get_some_random_bytes();
         datastore.insert(key, value);
     }
@@ -150,6 +150,8 @@ pub fn get_bootstrap_config(bootstrap_public_key: PublicKey) -> BootstrapSetting
         write_error_timeout: 200.into(),
         bootstrap_list: vec![(SocketAddr::new(BASE_BOOTSTRAP_IP, 16), bootstrap_public_key)],
         enable_clock_synchronization: true,
+        clock_sync_period: 0.into(),
+        max_clock_drift: 1000.into(),
         cache_duration: 10000.into(),
         max_simultaneous_bootstraps: 2,
         ip_list_max_size: 10,
@@ -396,6 +398,7 @@ pub fn get_boot_state() -> (ExportProofOfStake, BootstrapableGraph) {
         ]
         .into_iter()
         .collect(),
+        endorsement_stats: Default::default(),
     };
     let boot_pos = ExportProofOfStake {
         cycle_states: vec![
@@ -450,6 +453,8 @@ pub fn get_boot_state() -> (ExportProofOfStake, BootstrapableGraph) {
                         recipient_address: get_random_address(),
                         amount: Amount::from_str("1259787").unwrap(),
                     },
+                    multisig: None,
+                    replaces: None,
                 },
                 &generate_random_private_key(),
             )
@@ -461,6 +466,8 @@ pub fn get_boot_state() -> (ExportProofOfStake, BootstrapableGraph) {
                     fee: Amount::from_str("878763222").unwrap(),
                     expire_period: 4557887,
                     op: massa_models::OperationType::RollBuy { roll_count: 45544 },
+                    multisig: None,
+                    replaces: None,
                 },
                 &generate_random_private_key(),
             )
@@ -474,6 +481,8 @@ pub fn get_boot_state() -> (ExportProofOfStake, BootstrapableGraph) {
                     op: massa_models::OperationType::RollSell {
                         roll_count: 4888787,
                     },
+                    multisig: None,
+                    replaces: None,
                 },
                 &generate_random_private_key(),
             )
@@ -4,3 +4,44 @@
 ///
 /// Read `lib.rs` module documentation for more information.
 pub const NONCE_SIZE: usize = 12;
+
+/// Salt size
+///
+/// A fresh random salt is generated every time some data is encrypted.
+pub const SALT_SIZE: usize = 16;
+
+/// Size of the derived AES-256 key
+pub const KEY_SIZE: usize = 32;
+
+/// Magic bytes prefixing every versioned envelope
+///
+/// Data that does not start with these bytes is read as the legacy `nonce || ciphertext` layout.
+pub const ENVELOPE_MAGIC: &[u8; 4] = b"MCPH";
+
+/// Current envelope version
+pub const CIPHER_VERSION: u8 = 1;
+
+/// Flags written in new envelopes
+///
+/// Reserved for future use: they are authenticated with the rest of the header but not interpreted.
+pub const ENVELOPE_FLAGS: u8 = 0;
+
+/// Argon2id memory cost in KiB used for new envelopes
+pub const ARGON2_MEMORY_COST: u32 = 19 * 1024;
+
+/// Argon2id number of iterations used for new envelopes
+pub const ARGON2_TIME_COST: u32 = 2;
+
+/// Argon2id degree of parallelism used for new envelopes
+pub const ARGON2_PARALLELISM: u32 = 1;
+
+/// Maximum Argon2id memory cost in KiB accepted when reading an envelope
+///
+/// Prevents a crafted file from making the decryption allocate an arbitrary amount of memory.
+pub const MAX_ARGON2_MEMORY_COST: u32 = 1024 * 1024;
+
+/// Maximum Argon2id number of iterations accepted when reading an envelope
+pub const MAX_ARGON2_TIME_COST: u32 = 64;
+
+/// Maximum Argon2id degree of parallelism accepted when reading an envelope
+pub const MAX_ARGON2_PARALLELISM: u32 = 16;
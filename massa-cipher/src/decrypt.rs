@@ -1,19 +1,88 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
-use aes_gcm_siv::aead::{Aead, NewAead};
+use aes_gcm_siv::aead::{Aead, NewAead, Payload};
 use aes_gcm_siv::{Aes256GcmSiv, Key, Nonce};
-use massa_hash::Hash;
 
-use crate::constants::NONCE_SIZE;
+use crate::constants::{CIPHER_VERSION, ENVELOPE_MAGIC, NONCE_SIZE, SALT_SIZE};
 use crate::error::CipherError;
+use crate::kdf::KdfParams;
 
 /// Decryption function using AES-GCM-SIV cipher.
 ///
+/// Reads both the versioned envelope and the legacy `nonce || ciphertext` layout.
 /// Read `lib.rs` module documentation for more information.
 pub fn decrypt(password: &str, data: &[u8]) -> Result<Vec<u8>, CipherError> {
-    let cipher = Aes256GcmSiv::new(Key::from_slice(
-        Hash::compute_from(password.as_bytes()).to_bytes(),
-    ));
+    if data.starts_with(ENVELOPE_MAGIC) {
+        match decrypt_envelope(password, data) {
+            Ok(decrypted_bytes) => return Ok(decrypted_bytes),
+            // a legacy random nonce may start with the magic bytes by chance
+            Err(err) => return decrypt_legacy(password, data).map_err(|_| err),
+        }
+    }
+    decrypt_legacy(password, data)
+}
+
+/// Returns true if the data is not stored in a valid current version envelope
+/// and should be encrypted again with `encrypt`.
+pub fn needs_upgrade(data: &[u8]) -> bool {
+    read_envelope_header(data).is_err()
+}
+
+/// Reads the version, the flags and the key derivation parameters of an envelope,
+/// returns the parameters along with the data following them
+fn read_envelope_header(data: &[u8]) -> Result<(KdfParams, &[u8]), CipherError> {
+    let rest = data
+        .strip_prefix(ENVELOPE_MAGIC)
+        .ok_or_else(|| CipherError::DecryptionError("envelope magic bytes missing".to_string()))?;
+    let (version, rest) = rest.split_first().ok_or_else(|| {
+        CipherError::DecryptionError("envelope truncated: version missing".to_string())
+    })?;
+    if *version != CIPHER_VERSION {
+        return Err(CipherError::DecryptionError(format!(
+            "unsupported envelope version: {}",
+            version
+        )));
+    }
+    // the flags are reserved: authenticated with the header but not interpreted
+    let (_flags, rest) = rest.split_first().ok_or_else(|| {
+        CipherError::DecryptionError("envelope truncated: flags missing".to_string())
+    })?;
+    let (kdf_params, rest) = KdfParams::from_bytes(rest)?;
+    if kdf_params == KdfParams::Blake3 {
+        return Err(CipherError::DecryptionError(
+            "blake3 key derivation is only supported in the legacy layout".to_string(),
+        ));
+    }
+    Ok((kdf_params, rest))
+}
+
+fn decrypt_envelope(password: &str, data: &[u8]) -> Result<Vec<u8>, CipherError> {
+    let (kdf_params, rest) = read_envelope_header(data)?;
+    let salt = rest.get(..SALT_SIZE).ok_or_else(|| {
+        CipherError::DecryptionError("envelope truncated: salt missing or incomplete".to_string())
+    })?;
+    let nonce_bytes = rest.get(SALT_SIZE..SALT_SIZE + NONCE_SIZE).ok_or_else(|| {
+        CipherError::DecryptionError("envelope truncated: nonce missing or incomplete".to_string())
+    })?;
+    let encrypted_bytes = &rest[SALT_SIZE + NONCE_SIZE..];
+    let header = &data[..data.len() - encrypted_bytes.len()];
+
+    let key = kdf_params.derive_key(password, salt)?;
+    let cipher = Aes256GcmSiv::new(Key::from_slice(&key));
+    cipher
+        .decrypt(
+            Nonce::from_slice(nonce_bytes),
+            Payload {
+                msg: encrypted_bytes,
+                aad: header,
+            },
+        )
+        .map_err(|_| CipherError::DecryptionError("wrong password or corrupted data".to_string()))
+}
+
+fn decrypt_legacy(password: &str, data: &[u8]) -> Result<Vec<u8>, CipherError> {
+    let key = KdfParams::Blake3.derive_key(password, &[])?;
+    let cipher = Aes256GcmSiv::new(Key::from_slice(&key));
     let nonce = Nonce::from_slice(data.get(..NONCE_SIZE).ok_or_else(|| {
         CipherError::DecryptionError(
             "wallet file truncated: nonce missing or incomplete".to_string(),
@@ -1,27 +1,57 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
-use aes_gcm_siv::aead::{Aead, NewAead};
+use aes_gcm_siv::aead::{Aead, NewAead, Payload};
 use aes_gcm_siv::{Aes256GcmSiv, Key, Nonce};
-use massa_hash::Hash;
 use rand::{thread_rng, RngCore};
 
-use crate::constants::NONCE_SIZE;
+use crate::constants::{CIPHER_VERSION, ENVELOPE_FLAGS, ENVELOPE_MAGIC, NONCE_SIZE, SALT_SIZE};
 use crate::error::CipherError;
+use crate::kdf::KdfParams;
 
-/// Encryption function using AES-GCM-SIV cipher.
+/// Encryption function using AES-GCM-SIV cipher, with a key derived from the password using Argon2id.
 ///
 /// Read `lib.rs` module documentation for more information.
 pub fn encrypt(password: &str, data: &[u8]) -> Result<Vec<u8>, CipherError> {
-    let cipher = Aes256GcmSiv::new(Key::from_slice(
-        Hash::compute_from(password.as_bytes()).to_bytes(),
-    ));
+    encrypt_with_params(password, data, KdfParams::default())
+}
+
+/// Encryption function using AES-GCM-SIV cipher, with a key derived using the given KDF parameters.
+///
+/// Read `lib.rs` module documentation for more information.
+pub fn encrypt_with_params(
+    password: &str,
+    data: &[u8],
+    kdf_params: KdfParams,
+) -> Result<Vec<u8>, CipherError> {
+    if kdf_params == KdfParams::Blake3 {
+        return Err(CipherError::EncryptionError(
+            "blake3 key derivation is only supported for decryption".to_string(),
+        ));
+    }
+    let mut salt = [0u8; SALT_SIZE];
+    thread_rng().fill_bytes(&mut salt);
     let mut nonce_bytes = [0u8; NONCE_SIZE];
     thread_rng().fill_bytes(&mut nonce_bytes);
-    let nonce = Nonce::from_slice(&nonce_bytes);
+
+    let mut header = ENVELOPE_MAGIC.to_vec();
+    header.push(CIPHER_VERSION);
+    header.push(ENVELOPE_FLAGS);
+    header.extend(kdf_params.to_bytes());
+    header.extend(salt);
+    header.extend(nonce_bytes);
+
+    let key = kdf_params.derive_key(password, &salt)?;
+    let cipher = Aes256GcmSiv::new(Key::from_slice(&key));
     let encrypted_bytes = cipher
-        .encrypt(nonce, data.as_ref())
+        .encrypt(
+            Nonce::from_slice(&nonce_bytes),
+            Payload {
+                msg: data,
+                aad: &header,
+            },
+        )
         .map_err(|e| CipherError::EncryptionError(e.to_string()))?;
-    let mut content = nonce_bytes.to_vec();
+    let mut content = header;
     content.extend(encrypted_bytes);
     Ok(content)
 }
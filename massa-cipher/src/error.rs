@@ -10,4 +10,6 @@ pub enum CipherError {
     EncryptionError(String),
     /// Decryption error: {0}
     DecryptionError(String),
+    /// Key derivation error: {0}
+    KdfError(String),
 }
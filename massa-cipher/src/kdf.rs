@@ -0,0 +1,153 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use argon2::{Algorithm, Argon2, Params, Version};
+use massa_hash::Hash;
+
+use crate::constants::{
+    ARGON2_MEMORY_COST, ARGON2_PARALLELISM, ARGON2_TIME_COST, KEY_SIZE, MAX_ARGON2_MEMORY_COST,
+    MAX_ARGON2_PARALLELISM, MAX_ARGON2_TIME_COST,
+};
+use crate::error::CipherError;
+
+/// Identifier of the key derivation function, as written in the envelope
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum KdfId {
+    /// Single blake3 hash of the password, only used by the legacy layout
+    Blake3 = 0,
+    /// Argon2id
+    Argon2id = 1,
+}
+
+impl TryFrom<u8> for KdfId {
+    type Error = CipherError;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(KdfId::Blake3),
+            1 => Ok(KdfId::Argon2id),
+            _ => Err(CipherError::DecryptionError(format!(
+                "unknown key derivation function id: {}",
+                value
+            ))),
+        }
+    }
+}
+
+/// Key derivation function and its parameters
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KdfParams {
+    /// Single blake3 hash of the password, only used by the legacy layout
+    Blake3,
+    /// Argon2id with the given costs
+    Argon2id {
+        /// memory cost in KiB
+        memory_cost: u32,
+        /// number of iterations
+        time_cost: u32,
+        /// degree of parallelism
+        parallelism: u32,
+    },
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        KdfParams::Argon2id {
+            memory_cost: ARGON2_MEMORY_COST,
+            time_cost: ARGON2_TIME_COST,
+            parallelism: ARGON2_PARALLELISM,
+        }
+    }
+}
+
+impl KdfParams {
+    /// Identifier of the function
+    pub fn id(&self) -> KdfId {
+        match self {
+            KdfParams::Blake3 => KdfId::Blake3,
+            KdfParams::Argon2id { .. } => KdfId::Argon2id,
+        }
+    }
+
+    /// Serialize the identifier followed by the parameters
+    pub(crate) fn to_bytes(self) -> Vec<u8> {
+        let mut bytes = vec![self.id() as u8];
+        if let KdfParams::Argon2id {
+            memory_cost,
+            time_cost,
+            parallelism,
+        } = self
+        {
+            bytes.extend(memory_cost.to_le_bytes());
+            bytes.extend(time_cost.to_le_bytes());
+            bytes.extend(parallelism.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Deserialize the identifier and the parameters,
+    /// returns them along with the remaining data
+    pub(crate) fn from_bytes(data: &[u8]) -> Result<(KdfParams, &[u8]), CipherError> {
+        let (id, rest) = data.split_first().ok_or_else(|| {
+            CipherError::DecryptionError("envelope truncated: kdf id missing".to_string())
+        })?;
+        match KdfId::try_from(*id)? {
+            KdfId::Blake3 => Ok((KdfParams::Blake3, rest)),
+            KdfId::Argon2id => {
+                let (memory_cost, rest) = read_u32(rest)?;
+                let (time_cost, rest) = read_u32(rest)?;
+                let (parallelism, rest) = read_u32(rest)?;
+                if memory_cost > MAX_ARGON2_MEMORY_COST
+                    || time_cost > MAX_ARGON2_TIME_COST
+                    || parallelism > MAX_ARGON2_PARALLELISM
+                {
+                    return Err(CipherError::DecryptionError(
+                        "argon2id parameters out of the accepted range".to_string(),
+                    ));
+                }
+                Ok((
+                    KdfParams::Argon2id {
+                        memory_cost,
+                        time_cost,
+                        parallelism,
+                    },
+                    rest,
+                ))
+            }
+        }
+    }
+
+    /// Derive an AES-256 key from the password and the salt
+    pub(crate) fn derive_key(
+        &self,
+        password: &str,
+        salt: &[u8],
+    ) -> Result<[u8; KEY_SIZE], CipherError> {
+        match self {
+            KdfParams::Blake3 => Ok(*Hash::compute_from(password.as_bytes()).to_bytes()),
+            KdfParams::Argon2id {
+                memory_cost,
+                time_cost,
+                parallelism,
+            } => {
+                let params = Params::new(*memory_cost, *time_cost, *parallelism, Some(KEY_SIZE))
+                    .map_err(|e| CipherError::KdfError(e.to_string()))?;
+                let mut key = [0u8; KEY_SIZE];
+                Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+                    .hash_password_into(password.as_bytes(), salt, &mut key)
+                    .map_err(|e| CipherError::KdfError(e.to_string()))?;
+                Ok(key)
+            }
+        }
+    }
+}
+
+fn read_u32(data: &[u8]) -> Result<(u32, &[u8]), CipherError> {
+    let bytes = data.get(..4).ok_or_else(|| {
+        CipherError::DecryptionError("envelope truncated: kdf parameters missing".to_string())
+    })?;
+    Ok((
+        u32::from_le_bytes(bytes.try_into().expect("slice of length 4")),
+        &data[4..],
+    ))
+}
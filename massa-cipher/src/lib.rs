@@ -13,12 +13,102 @@
 //!
 //! No complete security audits of the crate we use has been performed.
 //! But some of this crate's dependencies were audited by by NCC Group as part of an audit of the AES-GCM crate
+//!
+//! The encryption key is derived from the password with Argon2id, a memory-hard function,
+//! using a random salt generated for every encryption.
+//!
+//! Encrypted data is stored in a versioned envelope:
+//!
+//! `magic (4 bytes) || version (1 byte) || flags (1 byte) || kdf id (1 byte) || kdf parameters || salt (16 bytes) || nonce (12 bytes) || ciphertext`
+//!
+//! The header is authenticated as associated data. The flags are reserved and currently zero.
+//! Envelopes always use Argon2id, whose kdf parameters are the memory cost,
+//! the number of iterations and the degree of parallelism, each as a little endian `u32`.
+//!
+//! Data produced by earlier versions, laid out as `nonce || ciphertext` with a key equal to the blake3 hash
+//! of the password, can still be decrypted. Use `needs_upgrade` to detect it and `encrypt` it again.
 
 mod constants;
 mod decrypt;
 mod encrypt;
 mod error;
+mod kdf;
 
-pub use decrypt::decrypt;
-pub use encrypt::encrypt;
+pub use decrypt::{decrypt, needs_upgrade};
+pub use encrypt::{encrypt, encrypt_with_params};
 pub use error::CipherError;
+pub use kdf::{KdfId, KdfParams};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aes_gcm_siv::aead::{Aead, NewAead, Payload};
+    use aes_gcm_siv::{Aes256GcmSiv, Key, Nonce};
+    use massa_hash::Hash;
+
+    // cheap parameters to keep the tests fast
+    const TEST_PARAMS: KdfParams = KdfParams::Argon2id {
+        memory_cost: 64,
+        time_cost: 1,
+        parallelism: 1,
+    };
+
+    #[test]
+    fn test_encrypt_decrypt() {
+        let data = b"massa wallet content";
+        let encrypted = encrypt_with_params("password", data, TEST_PARAMS).unwrap();
+        assert!(!needs_upgrade(&encrypted));
+        assert_eq!(decrypt("password", &encrypted).unwrap(), data);
+        assert!(decrypt("wrong password", &encrypted).is_err());
+    }
+
+    #[test]
+    fn test_tampered_header() {
+        let mut encrypted = encrypt_with_params("password", b"data", TEST_PARAMS).unwrap();
+        // flip the reserved flags, which only the authentication of the header covers
+        encrypted[5] ^= 1;
+        assert!(decrypt("password", &encrypted).is_err());
+    }
+
+    #[test]
+    fn test_blake3_envelope() {
+        // a current version envelope declaring the legacy key derivation
+        let cipher = Aes256GcmSiv::new(Key::from_slice(
+            Hash::compute_from("password".as_bytes()).to_bytes(),
+        ));
+        let nonce_bytes = [7u8; 12];
+        let mut envelope = b"MCPH".to_vec();
+        envelope.extend([1, 0, KdfId::Blake3 as u8]);
+        envelope.extend([3u8; 16]);
+        envelope.extend(nonce_bytes);
+        let encrypted = cipher
+            .encrypt(
+                Nonce::from_slice(&nonce_bytes),
+                Payload {
+                    msg: b"data",
+                    aad: &envelope,
+                },
+            )
+            .unwrap();
+        envelope.extend(encrypted);
+        assert!(needs_upgrade(&envelope));
+        assert!(decrypt("password", &envelope).is_err());
+    }
+
+    #[test]
+    fn test_decrypt_legacy() {
+        let data = b"massa wallet content";
+        let cipher = Aes256GcmSiv::new(Key::from_slice(
+            Hash::compute_from("password".as_bytes()).to_bytes(),
+        ));
+        let nonce_bytes = [7u8; 12];
+        let mut legacy = nonce_bytes.to_vec();
+        legacy.extend(
+            cipher
+                .encrypt(Nonce::from_slice(&nonce_bytes), data.as_ref())
+                .unwrap(),
+        );
+        assert!(needs_upgrade(&legacy));
+        assert_eq!(decrypt("password", &legacy).unwrap(), data);
+    }
+}
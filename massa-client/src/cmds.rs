@@ -3,13 +3,18 @@
 use crate::repl::Output;
 use anyhow::{anyhow, bail, Result};
 use console::style;
-use massa_models::api::{AddressInfo, CompactAddressInfo, EventFilter};
-use massa_models::api::{ReadOnlyBytecodeExecution, ReadOnlyCall};
+use dialoguer::Password;
+use massa_models::api::{AddressInfo, CompactAddressInfo, DatastoreEntryInput, EventFilter};
+use massa_models::api::{GasEstimationInput, ReadOnlyBytecodeExecution, ReadOnlyCall};
+use massa_models::multisig::MultisigPolicy;
 use massa_models::node::NodeId;
+use massa_models::operation::MAX_BATCH_TRANSFERS;
 use massa_models::prehash::Map;
+use massa_models::sc_args::{ContractAbi, TypedCall, ABI_DATASTORE_KEY};
 use massa_models::timeslots::get_current_latest_block_slot;
 use massa_models::{
-    Address, Amount, BlockId, EndorsementId, Operation, OperationId, OperationType, Slot,
+    Address, Amount, BlockId, CompactConfig, EndorsementId, Operation, OperationId, OperationType,
+    SignedOperation, Slot,
 };
 use massa_sdk::Client;
 use massa_signature::{generate_random_private_key, PrivateKey, PublicKey};
@@ -85,6 +90,19 @@ pub enum Command {
     )]
     node_add_staking_private_keys,
 
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "[Password]"),
+        message = "unlock the staking keys of the node (the password is asked if not given)"
+    )]
+    node_unlock_staking_keys,
+
+    #[strum(
+        ascii_case_insensitive,
+        message = "lock the staking keys of the node (stops staking until they are unlocked)"
+    )]
+    node_lock_staking_keys,
+
     #[strum(
         ascii_case_insensitive,
         props(args = "Address discord_id"),
@@ -119,6 +137,13 @@ pub enum Command {
     )]
     get_addresses,
 
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "Address1 Address2 ..."),
+        message = "get staking info about a list of addresses (draws of the current and next cycles, produced and missed blocks and endorsements, roll deactivation risk, deferred credits)"
+    )]
+    staker_report,
+
     #[strum(
         ascii_case_insensitive,
         props(args = "BlockId"),
@@ -161,6 +186,31 @@ pub enum Command {
     )]
     wallet_generate_private_key,
 
+    #[strum(
+        ascii_case_insensitive,
+        message = "generate a mnemonic seed phrase and use it as the seed of the wallet (write it down and keep it safe)"
+    )]
+    wallet_generate_mnemonic,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "Word1 Word2 ..."),
+        message = "use an existing mnemonic seed phrase as the seed of the wallet"
+    )]
+    wallet_import_mnemonic,
+
+    #[strum(
+        ascii_case_insensitive,
+        message = "derive the next address from the seed of the wallet and add it into the wallet"
+    )]
+    wallet_derive_address,
+
+    #[strum(
+        ascii_case_insensitive,
+        message = "add every used address derived from the seed of the wallet, stopping after 20 consecutive unused addresses"
+    )]
+    wallet_restore_addresses,
+
     #[strum(
         ascii_case_insensitive,
         props(args = "PrivateKey1 PrivateKey2 ..."),
@@ -198,15 +248,22 @@ pub enum Command {
 
     #[strum(
         ascii_case_insensitive,
-        props(args = "SenderAddress ReceiverAddress Amount Fee"),
-        message = "send coins from a wallet address"
+        props(args = "SenderAddress ReceiverAddress Amount Fee|auto"),
+        message = "send coins from a wallet address, `auto` paying the fee suggested by the node"
     )]
     send_transaction,
 
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "SenderAddress PathToCsv Fee|auto"),
+        message = "send coins from a wallet address to the recipients of a CSV file, one ReceiverAddress,Amount line per transfer, `auto` paying the fee suggested by the node"
+    )]
+    send_batch_transaction,
+
     #[strum(
         ascii_case_insensitive,
         props(args = "SenderAddress PathToBytecode MaxGas GasPrice Coins Fee",),
-        message = "create and send an operation containing byte code"
+        message = "create and send an operation containing byte code (MaxGas can be set to auto to estimate it)"
     )]
     send_smart_contract,
 
@@ -215,10 +272,68 @@ pub enum Command {
         props(
             args = "SenderAddress TargetAddress FunctionName Parameter MaxGas GasPrice Coins Fee",
         ),
-        message = "create and send an operation to call a function of a smart contract"
+        message = "create and send an operation to call a function of a smart contract (MaxGas can be set to auto to estimate it). With typed arguments, like transfer(A12...,1.5), FunctionName holds the arguments, checked against the ABI of the contract, and Parameter is omitted"
     )]
     call_smart_contract,
 
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "OperationId NewFee"),
+        message = "replace a pending operation of the wallet by the same operation paying a strictly higher fee and declaring the operation it replaces"
+    )]
+    bump_fee,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "Threshold PublicKey1 PublicKey2 ..."),
+        message = "get the multisig address of a set of public keys, Threshold of them being required to sign"
+    )]
+    multisig_get_address,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "Threshold PublicKey1,PublicKey2,... ReceiverAddress Amount Fee PathToFile"),
+        message = "create a transaction from a multisig address, signed by a wallet key of the multisig, and write it to a file"
+    )]
+    multisig_create_transaction,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "PathToFile"),
+        message = "co-sign the multisig operation of a file with the wallet keys of the multisig"
+    )]
+    multisig_sign,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "PathToFile"),
+        message = "send the multisig operation of a file once it is signed by enough keys"
+    )]
+    multisig_send,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(
+            args = "SenderPublicKey Period,Thread Fee transaction ReceiverAddress Amount|roll_buy RollCount|roll_sell RollCount PathToFile",
+        ),
+        message = "create an unsigned operation without node, expiring relatively to the given latest slot, and print it (PathToFile is optional)"
+    )]
+    offline_create_operation,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "UnsignedOperation|PathToFile PathToFile"),
+        message = "sign an unsigned operation with the wallet without node, and print it (the last PathToFile is optional)"
+    )]
+    offline_sign_operation,
+
+    #[strum(
+        ascii_case_insensitive,
+        props(args = "SignedOperation|PathToFile"),
+        message = "send an operation signed offline"
+    )]
+    offline_send_operation,
+
     #[strum(
         ascii_case_insensitive,
         props(args = "PathToBytecode MaxGas GasPrice Address",),
@@ -229,7 +344,7 @@ pub enum Command {
     #[strum(
         ascii_case_insensitive,
         props(args = "TargetAddress TargetFunction Parameter MaxGas GasPrice SenderAddress",),
-        message = "call a smart contract function, sender address is optional. Nothing is really executed on chain"
+        message = "call a smart contract function, sender address is optional. Nothing is really executed on chain. With typed arguments, like balance_of(A12...), TargetFunction holds the arguments and Parameter is omitted"
     )]
     read_only_call,
 
@@ -324,6 +439,23 @@ impl Display for ExtendedWallet {
     }
 }
 
+/// Keys of the local wallet, printed when the address information cannot be fetched from the node.
+/// Unlike `Wallet`, its json output contains neither the mnemonic nor the password of the wallet.
+#[derive(Debug, Serialize)]
+pub struct WalletKeys(Map<Address, (PublicKey, PrivateKey)>);
+
+impl Display for WalletKeys {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f)?;
+        for (addr, (public_key, private_key)) in &self.0 {
+            writeln!(f, "Private key: {}", private_key)?;
+            writeln!(f, "Public key: {}", public_key)?;
+            writeln!(f, "Address: {}", addr)?;
+        }
+        Ok(())
+    }
+}
+
 impl Command {
     /// Display the help of the command
     /// with fancy colors and so on
@@ -478,6 +610,37 @@ impl Command {
                 Ok(Box::new(()))
             }
 
+            Command::node_unlock_staking_keys => {
+                let password = match parameters.len() {
+                    0 => Password::new()
+                        .with_prompt("Enter staking keys password")
+                        .interact()?,
+                    1 => parameters[0].clone(),
+                    _ => bail!("wrong number of parameters"),
+                };
+                match client.private.unlock_staking_keys(password).await {
+                    Ok(staking_addresses) => {
+                        if !json {
+                            println!("Staking keys successfully unlocked!")
+                        }
+                        Ok(Box::new(staking_addresses))
+                    }
+                    Err(e) => rpc_error!(e),
+                }
+            }
+
+            Command::node_lock_staking_keys => {
+                match client.private.lock_staking_keys().await {
+                    Ok(()) => {
+                        if !json {
+                            println!("Staking keys successfully locked!")
+                        }
+                    }
+                    Err(e) => rpc_error!(e),
+                };
+                Ok(Box::new(()))
+            }
+
             Command::node_testnet_rewards_program_ownership_proof => {
                 if parameters.len() != 2 {
                     bail!("wrong number of parameters");
@@ -522,6 +685,14 @@ impl Command {
                 }
             }
 
+            Command::staker_report => {
+                let addresses = parse_vec::<Address>(parameters)?;
+                match client.public.get_staker_reports(addresses).await {
+                    Ok(staker_reports) => Ok(Box::new(staker_reports)),
+                    Err(e) => rpc_error!(e),
+                }
+            }
+
             Command::get_block => {
                 if parameters.len() != 1 {
                     bail!("wrong param numbers")
@@ -591,7 +762,7 @@ impl Command {
                     Ok(addresses_info) => {
                         Ok(Box::new(ExtendedWallet::new(wallet, &addresses_info)?))
                     }
-                    Err(_) => Ok(Box::new(wallet.clone())), // FIXME
+                    Err(_) => Ok(Box::new(WalletKeys(wallet.keys.clone()))),
                 }
             }
 
@@ -607,6 +778,76 @@ impl Command {
                 }
             }
 
+            Command::wallet_generate_mnemonic => {
+                let mnemonic = wallet.generate_mnemonic()?;
+                if json {
+                    Ok(Box::new(mnemonic))
+                } else {
+                    client_warning!(
+                        "do not share your mnemonic, anyone knowing it can spend your coins"
+                    );
+                    println!("Write down this mnemonic and keep it safe, it allows restoring the derived addresses of the wallet:");
+                    println!("{}\n", mnemonic);
+                    println!("Type `wallet_derive_address` to derive a new address from it.");
+                    Ok(Box::new(()))
+                }
+            }
+
+            Command::wallet_import_mnemonic => {
+                if parameters.is_empty() {
+                    bail!("wrong number of parameters");
+                }
+                wallet.set_mnemonic(&parameters.join(" "))?;
+                if !json {
+                    println!("Mnemonic imported.");
+                    println!("Type `wallet_restore_addresses` to restore the addresses that were already used.");
+                }
+                Ok(Box::new(()))
+            }
+
+            Command::wallet_derive_address => {
+                let address = wallet.derive_next_address()?;
+                if json {
+                    Ok(Box::new(address.to_string()))
+                } else {
+                    println!("Derived {} address and added it to the wallet", address);
+                    Ok(Box::new(()))
+                }
+            }
+
+            Command::wallet_restore_addresses => {
+                // number of accounts up to the last used one
+                let mut used_count = 0;
+                let mut account = 0;
+                while account < used_count + ADDRESS_GAP_LIMIT {
+                    let derived = (account..account + ADDRESS_GAP_LIMIT)
+                        .map(|index| Ok((wallet.derive_account(index)?.0, index)))
+                        .collect::<Result<HashMap<Address, u32>>>()?;
+                    let addresses_info = match client
+                        .public
+                        .get_addresses(derived.keys().copied().collect())
+                        .await
+                    {
+                        Ok(addresses_info) => addresses_info,
+                        Err(e) => rpc_error!(e),
+                    };
+                    for info in addresses_info.iter().filter(|info| is_address_used(info)) {
+                        if let Some(index) = derived.get(&info.address) {
+                            used_count = used_count.max(index + 1);
+                        }
+                    }
+                    account += ADDRESS_GAP_LIMIT;
+                }
+                let addresses = wallet.restore_accounts(used_count)?;
+                if !json {
+                    println!(
+                        "Restored {} address(es) derived from the seed of the wallet",
+                        addresses.len()
+                    );
+                }
+                Ok(Box::new(addresses))
+            }
+
             Command::wallet_add_private_keys => {
                 let addresses = parse_vec::<PrivateKey>(parameters)?
                     .into_iter()
@@ -735,30 +976,10 @@ impl Command {
                 let addr = parameters[0].parse::<Address>()?;
                 let recipient_address = parameters[1].parse::<Address>()?;
                 let amount = parameters[2].parse::<Amount>()?;
-                let fee = parameters[3].parse::<Amount>()?;
+                let fee = parse_fee(client, addr, &parameters[3], json).await?;
 
                 if !json {
-                    match amount.checked_add(fee) {
-                        Some(total) => {
-                            if let Ok(addresses_info) =
-                                client.public.get_addresses(vec![addr]).await
-                            {
-                                match addresses_info.get(0) {
-                                    Some(info) => {
-                                        if info.ledger_info.candidate_ledger_info.balance < total {
-                                            client_warning!("this operation may be rejected due to insufficient balance");
-                                        }
-                                    }
-                                    None => {
-                                        client_warning!(format!("address {} not found", addr))
-                                    }
-                                }
-                            }
-                        }
-                        None => {
-                            client_warning!("the total amount hit the limit overflow, operation will certainly be rejected");
-                        }
-                    }
+                    warn_if_balance_too_low(client, addr, amount.checked_add(fee)).await;
                 }
 
                 send_operation(
@@ -774,6 +995,285 @@ impl Command {
                 )
                 .await
             }
+            Command::send_batch_transaction => {
+                if parameters.len() != 3 {
+                    bail!("wrong number of parameters");
+                }
+                let addr = parameters[0].parse::<Address>()?;
+                let path = parameters[1].parse::<PathBuf>()?;
+                let fee = parse_fee(client, addr, &parameters[2], json).await?;
+                let transfers = parse_transfers_csv(&tokio::fs::read_to_string(&path).await?)?;
+                if transfers.is_empty() || transfers.len() > MAX_BATCH_TRANSFERS {
+                    bail!(
+                        "a batch transaction needs 1 to {} transfers, {} found",
+                        MAX_BATCH_TRANSFERS,
+                        transfers.len()
+                    );
+                }
+
+                if !json {
+                    let total = transfers
+                        .iter()
+                        .try_fold(fee, |total, (_, amount)| total.checked_add(*amount));
+                    warn_if_balance_too_low(client, addr, total).await;
+                }
+
+                send_operation(
+                    client,
+                    wallet,
+                    OperationType::BatchTransaction { transfers },
+                    fee,
+                    addr,
+                    json,
+                )
+                .await
+            }
+            Command::bump_fee => {
+                if parameters.len() != 2 {
+                    bail!("wrong number of parameters");
+                }
+                let operation_id = parameters[0].parse::<OperationId>()?;
+                let fee = parameters[1].parse::<Amount>()?;
+
+                let operation = match client.public.get_operations(vec![operation_id]).await {
+                    Ok(operations_info) => match operations_info.into_iter().next() {
+                        Some(info) if info.in_pool && info.in_blocks.is_empty() => {
+                            info.operation.content
+                        }
+                        Some(_) => bail!("operation {} is not pending anymore", operation_id),
+                        None => bail!("operation {} not found", operation_id),
+                    },
+                    Err(e) => rpc_error!(e),
+                };
+                if fee <= operation.fee {
+                    bail!(
+                        "the new fee must be strictly higher than the current fee ({})",
+                        operation.fee
+                    );
+                }
+                let addr = operation.get_sender_address();
+                if wallet.find_associated_public_key(addr).is_none() {
+                    bail!("the sender {} of the operation is not in the wallet", addr);
+                }
+
+                let op = wallet.create_operation(
+                    Operation {
+                        fee,
+                        replaces: Some(operation_id),
+                        ..operation
+                    },
+                    addr,
+                )?;
+                match client.public.send_operations(vec![op]).await {
+                    Ok(operation_ids) => {
+                        if !json {
+                            println!("Sent replacement operation IDs:");
+                        }
+                        Ok(Box::new(operation_ids))
+                    }
+                    Err(e) => rpc_error!(e),
+                }
+            }
+
+            Command::multisig_get_address => {
+                if parameters.len() < 2 {
+                    bail!("wrong number of parameters");
+                }
+                let policy = parse_multisig_policy(&parameters[0], &parameters[1..])?;
+                let address = policy.get_address();
+                if !json {
+                    println!("Multisig address requiring {}", policy);
+                }
+                Ok(Box::new(address.to_string()))
+            }
+            Command::multisig_create_transaction => {
+                if parameters.len() != 6 {
+                    bail!("wrong number of parameters");
+                }
+                let public_keys: Vec<String> = parameters[1]
+                    .split(',')
+                    .map(|key| key.to_string())
+                    .collect();
+                let policy = parse_multisig_policy(&parameters[0], &public_keys)?;
+                let recipient_address = parameters[2].parse::<Address>()?;
+                let amount = parameters[3].parse::<Amount>()?;
+                let fee = parameters[4].parse::<Amount>()?;
+                let path = parameters[5].parse::<PathBuf>()?;
+
+                let (proposer_address, sender_public_key) = match policy
+                    .public_keys
+                    .iter()
+                    .map(|key| (Address::from_public_key(key), *key))
+                    .find(|(addr, _)| wallet.find_associated_public_key(*addr).is_some())
+                {
+                    Some(proposer) => proposer,
+                    None => bail!("no key of the multisig is in the wallet"),
+                };
+                let addr = policy.get_address();
+                let expire_period = get_expire_period(client, addr).await?;
+                let op = wallet.create_operation(
+                    Operation {
+                        sender_public_key,
+                        fee,
+                        expire_period,
+                        op: OperationType::Transaction {
+                            recipient_address,
+                            amount,
+                        },
+                        multisig: Some(policy),
+                        replaces: None,
+                    },
+                    proposer_address,
+                )?;
+                tokio::fs::write(&path, serde_json::to_string_pretty(&op)?).await?;
+                if !json {
+                    println!(
+                        "Operation from {} written to {}, signed by 1 of the {} required keys",
+                        addr,
+                        path.display(),
+                        op.content
+                            .multisig
+                            .as_ref()
+                            .map_or(0, |policy| policy.threshold)
+                    );
+                }
+                Ok(Box::new(()))
+            }
+            Command::multisig_sign => {
+                if parameters.len() != 1 {
+                    bail!("wrong number of parameters");
+                }
+                let path = parameters[0].parse::<PathBuf>()?;
+                let mut op: SignedOperation =
+                    serde_json::from_slice(&get_file_as_byte_vec(&path).await?)?;
+                let added = wallet.co_sign_operation(&mut op)?;
+                if added == 0 {
+                    bail!("no key of the wallet can add a signature to this operation");
+                }
+                tokio::fs::write(&path, serde_json::to_string_pretty(&op)?).await?;
+                if !json {
+                    println!(
+                        "Added {} signature(s), the operation is signed by {} of the {} required keys",
+                        added,
+                        op.co_signatures.len() + 1,
+                        op.content.multisig.as_ref().map_or(0, |policy| policy.threshold)
+                    );
+                }
+                Ok(Box::new(()))
+            }
+            Command::multisig_send => {
+                if parameters.len() != 1 {
+                    bail!("wrong number of parameters");
+                }
+                let path = parameters[0].parse::<PathBuf>()?;
+                let op: SignedOperation =
+                    serde_json::from_slice(&get_file_as_byte_vec(&path).await?)?;
+                if op.content.multisig.is_none() {
+                    bail!("the operation is not sent from a multisig address");
+                }
+                op.verify_integrity()?;
+                match client.public.send_operations(vec![op]).await {
+                    Ok(operation_ids) => {
+                        if !json {
+                            println!("Sent operation IDs:");
+                        }
+                        Ok(Box::new(operation_ids))
+                    }
+                    Err(e) => rpc_error!(e),
+                }
+            }
+
+            Command::offline_create_operation => {
+                if parameters.len() < 5 {
+                    bail!("wrong number of parameters");
+                }
+                let sender_public_key = parameters[0].parse::<PublicKey>()?;
+                let slot = parameters[1].parse::<Slot>()?;
+                let fee = parameters[2].parse::<Amount>()?;
+                let (op, type_parameter_count) = match parameters[3].as_str() {
+                    "transaction" if parameters.len() >= 6 => (
+                        OperationType::Transaction {
+                            recipient_address: parameters[4].parse::<Address>()?,
+                            amount: parameters[5].parse::<Amount>()?,
+                        },
+                        2,
+                    ),
+                    "roll_buy" => (
+                        OperationType::RollBuy {
+                            roll_count: parameters[4].parse::<u64>()?,
+                        },
+                        1,
+                    ),
+                    "roll_sell" => (
+                        OperationType::RollSell {
+                            roll_count: parameters[4].parse::<u64>()?,
+                        },
+                        1,
+                    ),
+                    _ => bail!("invalid operation type or wrong number of parameters"),
+                };
+                let path = match &parameters[4 + type_parameter_count..] {
+                    [] => None,
+                    [path] => Some(path.parse::<PathBuf>()?),
+                    _ => bail!("wrong number of parameters"),
+                };
+
+                let cfg = CompactConfig::default();
+                let op = Operation {
+                    sender_public_key,
+                    fee,
+                    expire_period: get_expire_period_from_slot(
+                        slot,
+                        cfg.thread_count,
+                        cfg.operation_validity_periods,
+                        Address::from_public_key(&sender_public_key),
+                    ),
+                    op,
+                    multisig: None,
+                    replaces: None,
+                };
+                let encoded = op.to_bs58_check()?;
+                write_offline_operation(&encoded, path, json).await?;
+                Ok(Box::new(encoded))
+            }
+            Command::offline_sign_operation => {
+                if parameters.is_empty() || parameters.len() > 2 {
+                    bail!("wrong number of parameters");
+                }
+                let content =
+                    Operation::from_bs58_check(&read_offline_operation(&parameters[0]).await?)?;
+                let addr = Address::from_public_key(&content.sender_public_key);
+                if !json {
+                    println!("Signing with {}:\n{}", addr, content);
+                }
+                let op = wallet.create_operation(content, addr)?;
+                let path = match parameters.get(1) {
+                    Some(path) => Some(path.parse::<PathBuf>()?),
+                    None => None,
+                };
+                let encoded = op.to_bs58_check()?;
+                write_offline_operation(&encoded, path, json).await?;
+                Ok(Box::new(encoded))
+            }
+            Command::offline_send_operation => {
+                if parameters.len() != 1 {
+                    bail!("wrong number of parameters");
+                }
+                let op = SignedOperation::from_bs58_check(
+                    &read_offline_operation(&parameters[0]).await?,
+                )?;
+                op.verify_integrity()?;
+                match client.public.send_operations(vec![op]).await {
+                    Ok(operation_ids) => {
+                        if !json {
+                            println!("Sent operation IDs:");
+                        }
+                        Ok(Box::new(operation_ids))
+                    }
+                    Err(e) => rpc_error!(e),
+                }
+            }
+
             Command::when_episode_ends => {
                 let end = match client.public.get_status().await {
                     Ok(node_status) => node_status.config.end_timestamp,
@@ -806,10 +1306,28 @@ impl Command {
                 }
                 let addr = parameters[0].parse::<Address>()?;
                 let path = parameters[1].parse::<PathBuf>()?;
-                let max_gas = parameters[2].parse::<u64>()?;
+                let max_gas = parse_max_gas(&parameters[2])?;
                 let gas_price = parameters[3].parse::<Amount>()?;
                 let coins = parameters[4].parse::<Amount>()?;
                 let fee = parameters[5].parse::<Amount>()?;
+                let data = get_file_as_byte_vec(&path).await?;
+                let max_gas = match max_gas {
+                    Some(max_gas) => max_gas,
+                    None => {
+                        estimate_max_gas(
+                            client,
+                            addr,
+                            OperationType::ExecuteSC {
+                                data: data.clone(),
+                                max_gas: 0,
+                                coins,
+                                gas_price,
+                            },
+                            json,
+                        )
+                        .await?
+                    }
+                };
 
                 if !json {
                     match gas_price
@@ -838,7 +1356,6 @@ impl Command {
                         }
                     }
                 };
-                let data = get_file_as_byte_vec(&path).await?;
                 if !json {
                     let max_block_size = match client.public.get_status().await {
                         Ok(node_status) => node_status.config.max_block_size,
@@ -865,17 +1382,40 @@ impl Command {
                 .await
             }
             Command::call_smart_contract => {
-                if parameters.len() != 8 {
+                if parameters.len() < 7 {
                     bail!("wrong number of parameters");
                 }
                 let addr = parameters[0].parse::<Address>()?;
                 let target_addr = parameters[1].parse::<Address>()?;
-                let target_func = parameters[2].clone();
-                let param = parameters[3].clone();
-                let max_gas = parameters[4].parse::<u64>()?;
-                let gas_price = parameters[5].parse::<Amount>()?;
-                let coins = parameters[6].parse::<Amount>()?;
-                let fee = parameters[7].parse::<Amount>()?;
+                let (target_func, param, parameters) =
+                    parse_function_call(client, target_addr, &parameters[2..]).await?;
+                if parameters.len() != 4 {
+                    bail!("wrong number of parameters");
+                }
+                let max_gas = parse_max_gas(&parameters[0])?;
+                let gas_price = parameters[1].parse::<Amount>()?;
+                let coins = parameters[2].parse::<Amount>()?;
+                let fee = parameters[3].parse::<Amount>()?;
+                let max_gas = match max_gas {
+                    Some(max_gas) => max_gas,
+                    None => {
+                        estimate_max_gas(
+                            client,
+                            addr,
+                            OperationType::CallSC {
+                                target_addr,
+                                target_func: target_func.clone(),
+                                param: param.clone(),
+                                max_gas: 0,
+                                sequential_coins: Amount::from_raw(0),
+                                parallel_coins: coins,
+                                gas_price,
+                            },
+                            json,
+                        )
+                        .await?
+                    }
+                };
                 if !json {
                     match gas_price
                         .checked_mul_u64(max_gas)
@@ -966,16 +1506,19 @@ impl Command {
                 }
             }
             Command::read_only_call => {
-                if parameters.len() != 5 && parameters.len() != 6 {
+                if parameters.len() < 4 {
                     bail!("wrong number of parameters");
                 }
 
                 let target_address = parameters[0].parse::<Address>()?;
-                let target_function = parameters[1].parse::<String>()?;
-                let parameter = parameters[2].parse::<String>()?;
-                let max_gas = parameters[3].parse::<u64>()?;
-                let simulated_gas_price = parameters[4].parse::<Amount>()?;
-                let caller_address = if let Some(addr) = parameters.get(5) {
+                let (target_function, parameter, parameters) =
+                    parse_function_call(client, target_address, &parameters[1..]).await?;
+                if parameters.len() != 2 && parameters.len() != 3 {
+                    bail!("wrong number of parameters");
+                }
+                let max_gas = parameters[0].parse::<u64>()?;
+                let simulated_gas_price = parameters[1].parse::<Amount>()?;
+                let caller_address = if let Some(addr) = parameters.get(2) {
                     Some(addr.parse::<Address>()?)
                 } else {
                     None
@@ -1033,18 +1576,7 @@ async fn send_operation(
     addr: Address,
     json: bool,
 ) -> Result<Box<dyn Output>> {
-    let cfg = match client.public.get_status().await {
-        Ok(node_status) => node_status,
-        Err(e) => rpc_error!(e),
-    }
-    .config;
-
-    let slot = get_current_latest_block_slot(cfg.thread_count, cfg.t0, cfg.genesis_timestamp, 0)? // clock compensation is zero
-        .unwrap_or_else(|| Slot::new(0, 0));
-    let mut expire_period = slot.period + cfg.operation_validity_periods;
-    if slot.thread >= addr.get_thread(cfg.thread_count) {
-        expire_period += 1;
-    };
+    let expire_period = get_expire_period(client, addr).await?;
     let sender_public_key = match wallet.find_associated_public_key(addr) {
         Some(pk) => *pk,
         None => bail!("Missing public key"),
@@ -1056,6 +1588,8 @@ async fn send_operation(
             fee,
             expire_period,
             op,
+            multisig: None,
+            replaces: None,
         },
         addr,
     )?;
@@ -1071,6 +1605,279 @@ async fn send_operation(
     }
 }
 
+/// computes the expire period of an operation sent now from an address
+async fn get_expire_period(client: &Client, addr: Address) -> Result<u64> {
+    let cfg = match client.public.get_status().await {
+        Ok(node_status) => node_status,
+        Err(e) => rpc_error!(e),
+    }
+    .config;
+
+    let slot = get_current_latest_block_slot(cfg.thread_count, cfg.t0, cfg.genesis_timestamp, 0)? // clock compensation is zero
+        .unwrap_or_else(|| Slot::new(0, 0));
+    Ok(get_expire_period_from_slot(
+        slot,
+        cfg.thread_count,
+        cfg.operation_validity_periods,
+        addr,
+    ))
+}
+
+/// computes the expire period of an operation sent from an address when the given slot is the latest one
+fn get_expire_period_from_slot(
+    slot: Slot,
+    thread_count: u8,
+    operation_validity_periods: u64,
+    addr: Address,
+) -> u64 {
+    let mut expire_period = slot.period + operation_validity_periods;
+    if slot.thread >= addr.get_thread(thread_count) {
+        expire_period += 1;
+    };
+    expire_period
+}
+
+/// reads an operation encoded for offline signing, either given directly or as the path of the file containing it
+async fn read_offline_operation(parameter: &str) -> Result<String> {
+    let path = std::path::Path::new(parameter);
+    if path.is_file() {
+        Ok(tokio::fs::read_to_string(path).await?)
+    } else {
+        Ok(parameter.to_string())
+    }
+}
+
+/// prints an operation encoded for offline signing and writes it to a file if a path is given
+async fn write_offline_operation(encoded: &str, path: Option<PathBuf>, json: bool) -> Result<()> {
+    if let Some(path) = &path {
+        tokio::fs::write(path, encoded).await?;
+    }
+    if !json {
+        match path {
+            Some(path) => println!("Operation written to {}:", path.display()),
+            None => println!("Operation:"),
+        }
+    }
+    Ok(())
+}
+
+/// parses the transfers of a batch transaction from CSV lines `ReceiverAddress,Amount`
+/// empty lines and lines starting with `#` are ignored
+fn parse_transfers_csv(content: &str) -> Result<Vec<(Address, Amount)>> {
+    content
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+        .map(
+            |(index, line)| match line.split(',').map(str::trim).collect::<Vec<_>>()[..] {
+                [recipient_address, amount] => Ok((
+                    recipient_address
+                        .parse::<Address>()
+                        .map_err(|err| anyhow!("line {}: {}", index + 1, err))?,
+                    amount
+                        .parse::<Amount>()
+                        .map_err(|err| anyhow!("line {}: {}", index + 1, err))?,
+                )),
+                _ => bail!("line {}: expected ReceiverAddress,Amount", index + 1),
+            },
+        )
+        .collect()
+}
+
+/// parses a multisig policy from its threshold and public keys
+fn parse_multisig_policy(threshold: &str, public_keys: &[String]) -> Result<MultisigPolicy> {
+    let threshold = threshold.parse::<u8>()?;
+    let public_keys = public_keys
+        .iter()
+        .map(|key| key.parse::<PublicKey>())
+        .collect::<Result<Vec<PublicKey>, _>>()?;
+    Ok(MultisigPolicy::new(threshold, public_keys)?)
+}
+
+/// Warns if the candidate balance of `addr` is lower than the `total` spent by an operation,
+/// `None` if computing the total overflowed
+async fn warn_if_balance_too_low(client: &Client, addr: Address, total: Option<Amount>) {
+    match total {
+        Some(total) => {
+            if let Ok(addresses_info) = client.public.get_addresses(vec![addr]).await {
+                match addresses_info.get(0) {
+                    Some(info) => {
+                        if info.ledger_info.candidate_ledger_info.balance < total {
+                            client_warning!(
+                                "this operation may be rejected due to insufficient balance"
+                            );
+                        }
+                    }
+                    None => {
+                        client_warning!(format!("address {} not found", addr))
+                    }
+                }
+            }
+        }
+        None => {
+            client_warning!(
+                "the total amount hit the limit overflow, operation will certainly be rejected"
+            );
+        }
+    }
+}
+
+/// number of slots within which the fee suggested to the user should get an operation included
+const FEE_SUGGESTION_SLOT_COUNT: u64 = 5;
+
+/// fee parameter asking for the fee suggested by the pool statistics of the node
+const SUGGESTED_FEE_PARAM: &str = "auto";
+
+/// Gets the fee suggested by the pool statistics of the node for an operation of `addr`
+async fn get_suggested_fee(client: &Client, addr: Address) -> Result<Amount> {
+    let fee_stats = match client
+        .public
+        .get_pool_fee_stats(FEE_SUGGESTION_SLOT_COUNT)
+        .await
+    {
+        Ok(fee_stats) => fee_stats,
+        Err(e) => bail!("could not get the pool fee statistics of the node: {}", e),
+    };
+    match fee_stats.get(addr.get_thread(fee_stats.len() as u8) as usize) {
+        Some(stats) => Ok(stats.suggested_fee),
+        None => bail!(
+            "the node returned no fee statistics for the thread of {}",
+            addr
+        ),
+    }
+}
+
+/// Parses the fee of an operation of `addr`.
+/// `auto` stands for the fee suggested by the node.
+/// Otherwise the given fee is kept, with a warning suggesting a fee if it is too low to be included soon.
+async fn parse_fee(client: &Client, addr: Address, param: &str, json: bool) -> Result<Amount> {
+    if param.eq_ignore_ascii_case(SUGGESTED_FEE_PARAM) {
+        let fee = get_suggested_fee(client, addr).await?;
+        if !json {
+            println!("Using the fee suggested by the node: {}", fee);
+        }
+        return Ok(fee);
+    }
+    let fee = param.parse::<Amount>()?;
+    if !json {
+        if let Ok(suggested_fee) = get_suggested_fee(client, addr).await {
+            if fee < suggested_fee {
+                client_warning!(format!(
+                    "with this fee, the operation may not be included within {} slots, suggested fee: {} (use `{}` to pay it)",
+                    FEE_SUGGESTION_SLOT_COUNT, suggested_fee, SUGGESTED_FEE_PARAM
+                ));
+            }
+        }
+    }
+    Ok(fee)
+}
+
+/// Parses the function and the parameter of a smart contract call.
+/// A function written with typed arguments, like `transfer(A12..., 1.5)`, holds its own parameter:
+/// the arguments are checked against the ABI published by the target and encoded.
+/// Otherwise, the next parameter is taken as the text parameter of the call.
+///
+/// Returns the function name, the encoded parameter and the parameters following them
+async fn parse_function_call<'a>(
+    client: &Client,
+    target_addr: Address,
+    parameters: &'a [String],
+) -> Result<(String, Vec<u8>, &'a [String])> {
+    let function = match parameters.first() {
+        Some(function) => function,
+        None => bail!("wrong number of parameters"),
+    };
+    if !function.contains('(') {
+        return match parameters.get(1) {
+            Some(param) => Ok((
+                function.clone(),
+                param.as_bytes().to_vec(),
+                &parameters[2..],
+            )),
+            None => bail!("wrong number of parameters"),
+        };
+    }
+    // the interactive mode splits the command on whitespaces: join the call back
+    let call_len = match parameters.iter().position(|p| p.ends_with(')')) {
+        Some(index) => index + 1,
+        None => bail!("missing closing parenthesis in {}", function),
+    };
+    let call = parameters[..call_len].join(" ").parse::<TypedCall>()?;
+    let abi = get_contract_abi(client, target_addr).await?;
+    let param = call.encode_args(abi.as_ref())?;
+    Ok((call.function, param, &parameters[call_len..]))
+}
+
+/// Gets the ABI published by a smart contract in its datastore, the active one if it is being updated
+async fn get_contract_abi(client: &Client, address: Address) -> Result<Option<ContractAbi>> {
+    let entry = match client
+        .public
+        .get_datastore_entry(DatastoreEntryInput {
+            address,
+            key: ABI_DATASTORE_KEY.to_vec(),
+        })
+        .await
+    {
+        Ok(entry) => entry,
+        Err(e) => rpc_error!(e),
+    };
+    match entry.active_value.or(entry.final_value) {
+        Some(value) => {
+            Ok(Some(serde_json::from_slice(&value).map_err(|e| {
+                anyhow!("invalid ABI published by {}: {}", address, e)
+            })?))
+        }
+        None => Ok(None),
+    }
+}
+
+/// parses a `MaxGas` parameter, `None` meaning that it has to be estimated
+fn parse_max_gas(param: &str) -> Result<Option<u64>> {
+    if param.eq_ignore_ascii_case("auto") {
+        Ok(None)
+    } else {
+        Ok(Some(param.parse::<u64>()?))
+    }
+}
+
+/// asks the node to estimate the `max_gas` of an `ExecuteSC` or `CallSC` operation
+async fn estimate_max_gas(
+    client: &Client,
+    sender: Address,
+    op: OperationType,
+    json: bool,
+) -> Result<u64> {
+    match client
+        .public
+        .estimate_gas(GasEstimationInput { sender, op })
+        .await
+    {
+        Ok(estimation) => {
+            if !json {
+                println!(
+                    "Estimated gas cost: {}, using max gas: {}",
+                    estimation.gas_cost, estimation.max_gas
+                );
+            }
+            Ok(estimation.max_gas)
+        }
+        Err(e) => bail!("gas estimation failed: {}", e),
+    }
+}
+
+/// number of consecutive unused derived addresses after which the restoration of a wallet stops
+const ADDRESS_GAP_LIMIT: u32 = 20;
+
+/// an address is used if it holds coins or rolls, or if it was involved in an operation or a block
+fn is_address_used(info: &AddressInfo) -> bool {
+    !info.ledger_info.candidate_ledger_info.balance.is_zero()
+        || !info.candidate_sce_ledger_info.balance.is_zero()
+        || info.rolls.candidate_rolls > 0
+        || !info.involved_in_operations.is_empty()
+        || !info.blocks_created.is_empty()
+}
+
 /// TODO: ugly utilities functions
 /// takes a slice of string and makes it into a `Vec<T>`
 pub fn parse_vec<T: std::str::FromStr>(args: &[String]) -> anyhow::Result<Vec<T>, T::Err> {
@@ -1,13 +1,15 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
-use crate::cmds::{Command, ExtendedWallet};
+use crate::cmds::{Command, ExtendedWallet, WalletKeys};
 use crate::settings::SETTINGS;
 use crate::utils::longest_common_prefix;
 use console::style;
 use dialoguer::{theme::ColorfulTheme, Completion, History, Input};
 use erased_serde::{Serialize, Serializer};
 use glob::glob;
-use massa_models::api::{AddressInfo, BlockInfo, EndorsementInfo, NodeStatus, OperationInfo};
+use massa_models::api::{
+    AddressInfo, BlockInfo, EndorsementInfo, NodeStatus, OperationInfo, StakerReport,
+};
 use massa_models::composite::PubkeySig;
 use massa_models::execution::ExecuteReadOnlyResponse;
 use massa_models::output_event::SCOutputEvent;
@@ -206,7 +208,7 @@ impl dyn Output {
     }
 }
 
-impl Output for Wallet {
+impl Output for WalletKeys {
     fn pretty_print(&self) {
         println!("{}", self);
     }
@@ -264,6 +266,14 @@ impl Output for Vec<AddressInfo> {
     }
 }
 
+impl Output for Vec<StakerReport> {
+    fn pretty_print(&self) {
+        for staker_report in self {
+            println!("{}", staker_report);
+        }
+    }
+}
+
 impl Output for Vec<EndorsementInfo> {
     fn pretty_print(&self) {
         for endorsement_info in self {
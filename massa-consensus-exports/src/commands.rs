@@ -3,7 +3,11 @@
 //! Contains definitions of commands used by the controller
 use massa_graph::ledger::ConsensusLedgerSubset;
 use massa_graph::{BlockGraphExport, BootstrapableGraph, ExportBlockStatus, Status};
-use massa_models::{address::AddressState, api::EndorsementInfo, EndorsementId, OperationId};
+use massa_models::{
+    address::AddressState,
+    api::{BlockNotification, EndorsementInfo, StakerReport},
+    EndorsementId, OperationId,
+};
 use massa_models::{clique::Clique, stats::ConsensusStats};
 use massa_models::{
     Address, BlockId, OperationSearchResult, SignedEndorsement, Slot, StakersCycleProductionStats,
@@ -13,7 +17,7 @@ use massa_proof_of_stake_exports::ExportProofOfStake;
 use massa_signature::PrivateKey;
 
 use massa_models::prehash::{Map, Set};
-use tokio::sync::oneshot;
+use tokio::sync::{broadcast, oneshot};
 
 use crate::{error::ConsensusResult as Result, ConsensusError, SelectionDraws};
 
@@ -82,9 +86,30 @@ pub enum ConsensusCommand {
     /// Get all stakers
     GetActiveStakers(oneshot::Sender<Map<Address, u64>>),
     /// Add private keys to use them for staking
-    RegisterStakingPrivateKeys(Vec<PrivateKey>),
+    RegisterStakingPrivateKeys {
+        /// keys to add
+        keys: Vec<PrivateKey>,
+        /// response channel, fails if the staking keys are locked
+        response_tx: oneshot::Sender<Result<(), ConsensusError>>,
+    },
     /// Remove associated staking keys
-    RemoveStakingAddresses(Set<Address>),
+    RemoveStakingAddresses {
+        /// addresses to remove
+        addresses: Set<Address>,
+        /// response channel, fails if the staking keys are locked
+        response_tx: oneshot::Sender<Result<(), ConsensusError>>,
+    },
+    /// Decrypt the staking keys file with the given password and start staking with its keys
+    UnlockStakingKeys {
+        /// staking keys password
+        password: String,
+        /// response channel, returns the unlocked staking addresses
+        response_tx: oneshot::Sender<Result<Set<Address>, ConsensusError>>,
+    },
+    /// Forget the staking keys and their password, stop staking until they are unlocked again
+    LockStakingKeys(oneshot::Sender<()>),
+    /// Subscribe to block finality and staleness notifications
+    SubscribeBlockNotifications(oneshot::Sender<broadcast::Receiver<BlockNotification>>),
     /// Get staking addresses
     GetStakingAddresses(oneshot::Sender<Set<Address>>),
     /// Get production stats for addresses
@@ -94,6 +119,13 @@ pub enum ConsensusCommand {
         /// response channel
         response_tx: oneshot::Sender<Vec<StakersCycleProductionStats>>,
     },
+    /// Get draws, production stats and deferred credits of stakers
+    GetStakerReports {
+        /// wanted addresses
+        addresses: Vec<Address>,
+        /// response channel
+        response_tx: oneshot::Sender<Result<Vec<StakerReport>, ConsensusError>>,
+    },
     /// Get block id and status by block creator address
     GetBlockIdsByCreator {
         /// wanted address
@@ -1,7 +1,11 @@
 use massa_graph::ledger::ConsensusLedgerSubset;
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 use massa_graph::{BlockGraphExport, BootstrapableGraph, ExportBlockStatus, Status};
-use massa_models::{address::AddressState, api::EndorsementInfo, EndorsementId, OperationId};
+use massa_models::{
+    address::AddressState,
+    api::{BlockNotification, EndorsementInfo, StakerReport},
+    EndorsementId, OperationId,
+};
 use massa_models::{clique::Clique, stats::ConsensusStats};
 use massa_models::{
     Address, BlockId, OperationSearchResult, SignedEndorsement, Slot, StakersCycleProductionStats,
@@ -14,7 +18,7 @@ use std::collections::VecDeque;
 
 use massa_models::prehash::{Map, Set};
 use tokio::{
-    sync::{mpsc, oneshot},
+    sync::{broadcast, mpsc, oneshot},
     task::JoinHandle,
 };
 
@@ -338,37 +342,124 @@ impl ConsensusCommandSender {
     }
 
     /// Add some staking keys
+    /// Fails if the staking keys are locked
     pub async fn register_staking_private_keys(
         &self,
         keys: Vec<PrivateKey>,
     ) -> Result<(), ConsensusError> {
+        let (response_tx, response_rx) = oneshot::channel();
         massa_trace!(
             "consensus.consensus_controller.register_staking_private_keys",
             {}
         );
         self.0
-            .send(ConsensusCommand::RegisterStakingPrivateKeys(keys))
+            .send(ConsensusCommand::RegisterStakingPrivateKeys { keys, response_tx })
             .await
             .map_err(|_| {
                 ConsensusError::SendChannelError("send error consensus command".to_string())
-            })
+            })?;
+        response_rx.await.map_err(|_| {
+            ConsensusError::ReceiveChannelError(
+                "consensus command register_staking_private_keys response read error".to_string(),
+            )
+        })?
     }
 
     /// remove some keys from staking keys by associated address
     /// the node won't be able to stake with these keys anymore
     /// They will be erased from the staking keys file
+    /// Fails if the staking keys are locked
     pub async fn remove_staking_addresses(
         &self,
         addresses: Set<Address>,
     ) -> Result<(), ConsensusError> {
+        let (response_tx, response_rx) = oneshot::channel();
         massa_trace!("consensus.consensus_controller.remove_staking_addresses", {
         });
         self.0
-            .send(ConsensusCommand::RemoveStakingAddresses(addresses))
+            .send(ConsensusCommand::RemoveStakingAddresses {
+                addresses,
+                response_tx,
+            })
             .await
             .map_err(|_| {
                 ConsensusError::SendChannelError("send error consensus command".to_string())
+            })?;
+        response_rx.await.map_err(|_| {
+            ConsensusError::ReceiveChannelError(
+                "consensus command remove_staking_addresses response read error".to_string(),
+            )
+        })?
+    }
+
+    /// unlock the staking keys with their password
+    /// returns the addresses the node is now staking with
+    pub async fn unlock_staking_keys(
+        &self,
+        password: String,
+    ) -> Result<Set<Address>, ConsensusError> {
+        let (response_tx, response_rx) = oneshot::channel();
+        massa_trace!("consensus.consensus_controller.unlock_staking_keys", {});
+        self.0
+            .send(ConsensusCommand::UnlockStakingKeys {
+                password,
+                response_tx,
             })
+            .await
+            .map_err(|_| {
+                ConsensusError::SendChannelError(
+                    "send error consensus command unlock_staking_keys".to_string(),
+                )
+            })?;
+        response_rx.await.map_err(|_| {
+            ConsensusError::ReceiveChannelError(
+                "consensus command unlock_staking_keys response read error".to_string(),
+            )
+        })?
+    }
+
+    /// lock the staking keys
+    /// the node stops staking until they are unlocked again
+    pub async fn lock_staking_keys(&self) -> Result<(), ConsensusError> {
+        let (response_tx, response_rx) = oneshot::channel();
+        massa_trace!("consensus.consensus_controller.lock_staking_keys", {});
+        self.0
+            .send(ConsensusCommand::LockStakingKeys(response_tx))
+            .await
+            .map_err(|_| {
+                ConsensusError::SendChannelError(
+                    "send error consensus command lock_staking_keys".to_string(),
+                )
+            })?;
+        response_rx.await.map_err(|_| {
+            ConsensusError::ReceiveChannelError(
+                "consensus command lock_staking_keys response read error".to_string(),
+            )
+        })
+    }
+
+    /// subscribe to block finality and staleness notifications
+    pub async fn subscribe_block_notifications(
+        &self,
+    ) -> Result<broadcast::Receiver<BlockNotification>, ConsensusError> {
+        let (response_tx, response_rx) = oneshot::channel();
+        massa_trace!(
+            "consensus.consensus_controller.subscribe_block_notifications",
+            {}
+        );
+        self.0
+            .send(ConsensusCommand::SubscribeBlockNotifications(response_tx))
+            .await
+            .map_err(|_| {
+                ConsensusError::SendChannelError(
+                    "send error consensus command subscribe_block_notifications".to_string(),
+                )
+            })?;
+        response_rx.await.map_err(|_| {
+            ConsensusError::ReceiveChannelError(
+                "consensus command subscribe_block_notifications response read error".to_string(),
+            )
+        })
     }
 
     /// get staking addresses
@@ -415,6 +506,31 @@ impl ConsensusCommandSender {
         })
     }
 
+    /// get draws, production stats and deferred credits of stakers
+    pub async fn get_staker_reports(
+        &self,
+        addresses: Vec<Address>,
+    ) -> Result<Vec<StakerReport>, ConsensusError> {
+        let (response_tx, response_rx) = oneshot::channel();
+        massa_trace!("consensus.consensus_controller.get_staker_reports", {});
+        self.0
+            .send(ConsensusCommand::GetStakerReports {
+                addresses,
+                response_tx,
+            })
+            .await
+            .map_err(|_| {
+                ConsensusError::SendChannelError(
+                    "send error consensus command get_staker_reports".to_string(),
+                )
+            })?;
+        response_rx.await.map_err(|_| {
+            ConsensusError::ReceiveChannelError(
+                "consensus command get_staker_reports response read error".to_string(),
+            )
+        })?
+    }
+
     /// get endorsements info by involved address
     pub async fn get_endorsements_by_address(
         &self,
@@ -62,6 +62,12 @@ pub enum ConsensusError {
     ProofOfStakeError(#[from] ProofOfStakeError),
     /// slot overflow
     SlotOverflowError,
+    /// cipher error: {0}
+    CipherError(#[from] massa_cipher::CipherError),
+    /// staking keys are locked, unlock them first
+    StakingKeysLocked,
+    /// staking keys changed while they were being unlocked, unlock them again
+    StakingKeysChanged,
 }
 
 impl std::convert::From<massa_protocol_exports::ProtocolError> for ConsensusError {
@@ -13,8 +13,10 @@ pub use consensus_controller::{ConsensusCommandSender, ConsensusEventReceiver, C
 pub use error::ConsensusError;
 use massa_models::{Address, Slot};
 pub use settings::{ConsensusConfig, ConsensusSettings};
+pub use staking_keys_password::StakingKeysPassword;
 
 mod consensus_controller;
+mod staking_keys_password;
 
 /// consensus errors
 pub mod error;
@@ -66,8 +66,11 @@ use crate::{
 /// Assumes `thread_count >= 1, t0_millis >= 1, t0_millis % thread_count == 0`
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct ConsensusSettings {
-    /// Staking private keys
+    /// Staking private keys, encrypted with the staking keys password
     pub staking_keys_path: PathBuf,
+    /// Optional file containing the staking keys password
+    #[serde(default)]
+    pub staking_keys_password_path: Option<PathBuf>,
     /// Maximum number of blocks allowed in discarded blocks.
     pub max_discarded_blocks: usize,
     /// If a block is `future_block_processing_max_periods` periods in the future, it is just discarded.
@@ -481,6 +484,7 @@ impl Default for ConsensusSettings {
         use massa_models::constants::default_testing::*;
         Self {
             staking_keys_path: Default::default(),
+            staking_keys_password_path: None,
             max_discarded_blocks: MAX_DISCARDED_BLOCKS,
             future_block_processing_max_periods: FUTURE_BLOCK_PROCESSING_MAX_PERIODS,
             max_future_processing_blocks: MAX_FUTURE_PROCESSING_BLOCK,
@@ -0,0 +1,40 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use std::sync::{Arc, RwLock};
+
+/// Password of the staking keys file, `None` while the staking keys are locked.
+/// It is shared between consensus and the node, and every clone sees the updates:
+/// keys unlocked at runtime stay unlocked when consensus is restarted.
+#[derive(Clone, Default)]
+pub struct StakingKeysPassword(Arc<RwLock<Option<String>>>);
+
+impl StakingKeysPassword {
+    /// Creates a shared staking keys password
+    ///
+    /// # Arguments
+    /// * `password`: initial password, `None` to start with locked staking keys
+    pub fn new(password: Option<String>) -> Self {
+        StakingKeysPassword(Arc::new(RwLock::new(password)))
+    }
+
+    /// Gets the current password, `None` if the staking keys are locked
+    pub fn get(&self) -> Option<String> {
+        self.0
+            .read()
+            .expect("poisoned staking keys password lock")
+            .clone()
+    }
+
+    /// Returns true if the staking keys are unlocked
+    pub fn is_set(&self) -> bool {
+        self.0
+            .read()
+            .expect("poisoned staking keys password lock")
+            .is_some()
+    }
+
+    /// Sets the password, effective immediately for all the clones
+    pub fn set(&self, password: Option<String>) {
+        *self.0.write().expect("poisoned staking keys password lock") = password;
+    }
+}
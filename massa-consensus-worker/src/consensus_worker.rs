@@ -1,19 +1,25 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
+use crate::tools::{dump_staking_keys, load_staking_keys};
 use massa_consensus_exports::{
     commands::ConsensusCommand,
     error::{ConsensusError, ConsensusResult as Result},
     events::ConsensusEvent,
     settings::ConsensusWorkerChannels,
-    ConsensusConfig,
+    ConsensusConfig, StakingKeysPassword,
 };
 use massa_graph::{BlockGraph, BlockGraphExport};
 use massa_hash::Hash;
+use massa_metrics::{
+    CONSENSUS_CANDIDATE_PERIOD, CONSENSUS_FINAL_PERIOD, STAKER_MISSED_BLOCKS,
+    STAKER_PRODUCED_BLOCKS,
+};
 use massa_models::prehash::{BuildMap, Map, Set};
 use massa_models::timeslots::{get_block_slot_timestamp, get_latest_block_slot_at_timestamp};
 use massa_models::{address::AddressCycleProductionStats, stats::ConsensusStats, OperationId};
 use massa_models::{address::AddressState, signed::Signed};
+use massa_models::{api::BlockNotification, constants::CHANNEL_SIZE};
 use massa_models::{
-    api::{LedgerInfo, RollsInfo},
+    api::{DeferredCredit, IndexedSlot, LedgerInfo, RollsInfo, StakerCycleStats, StakerReport},
     SignedEndorsement,
 };
 use massa_models::{ledger_models::LedgerData, SignedOperation};
@@ -23,14 +29,41 @@ use massa_models::{
 use massa_proof_of_stake_exports::{error::ProofOfStakeError, ExportProofOfStake, ProofOfStake};
 use massa_protocol_exports::{ProtocolEvent, ProtocolEventReceiver};
 use massa_signature::{derive_public_key, PrivateKey, PublicKey};
-use massa_time::MassaTime;
+use massa_time::{ClockCompensation, MassaTime};
 use std::{cmp::max, collections::HashSet, collections::VecDeque};
 use tokio::{
-    sync::mpsc::error::SendTimeoutError,
+    sync::{broadcast, mpsc, mpsc::error::SendTimeoutError, oneshot},
+    task::JoinHandle,
     time::{sleep, sleep_until, Sleep},
 };
 use tracing::{debug, info, warn};
 
+/// Block and endorsement draws of the current and next cycles, indexed by address.
+/// Computed once per cycle for the staker reports.
+#[derive(Default)]
+struct StakerDraws {
+    /// cycle for which the draws were computed
+    cycle: u64,
+    /// false while the next cycle can not be drawn yet
+    includes_next_cycle: bool,
+    /// slots for which each address was drawn as block producer
+    block_draws: Map<Address, Vec<Slot>>,
+    /// slots and indexes for which each address was drawn as endorsement producer
+    endorsement_draws: Map<Address, Vec<IndexedSlot>>,
+}
+
+/// Staking keys decrypted in the background for an unlock request
+struct UnlockedStakingKeys {
+    /// version of the staking keys when the unlock was requested, see `staking_keys_version`
+    version: u64,
+    /// password the staking keys were decrypted with
+    password: String,
+    /// decrypted staking keys
+    staking_keys: Result<Map<Address, (PublicKey, PrivateKey)>>,
+    /// response channel of the unlock request
+    response_tx: oneshot::Sender<Result<Set<Address>>>,
+}
+
 /// Manages consensus.
 pub struct ConsensusWorker {
     /// Consensus Configuration
@@ -51,10 +84,20 @@ pub struct ConsensusWorker {
     wishlist: Set<BlockId>,
     /// latest final periods
     latest_final_periods: Vec<u64>,
-    /// clock compensation
-    clock_compensation: i64,
+    /// clock compensation, updated live by the clock synchronization
+    clock_compensation: ClockCompensation,
     /// staking keys
     staking_keys: Map<Address, (PublicKey, PrivateKey)>,
+    /// staking keys file password shared with the node, not set while the staking keys are locked
+    staking_keys_password: StakingKeysPassword,
+    /// incremented on every change of the staking keys, so that an unlock finishing after another change is discarded
+    staking_keys_version: u64,
+    /// channel sending the staking keys decrypted in the background to the worker loop
+    unlocked_staking_keys_tx: mpsc::Sender<UnlockedStakingKeys>,
+    /// channel receiving the staking keys decrypted in the background
+    unlocked_staking_keys_rx: mpsc::Receiver<UnlockedStakingKeys>,
+    /// latest background write of the staking keys file, awaited by the next one so that the writes stay in order
+    staking_keys_dump: Option<JoinHandle<()>>,
     /// stats `(block -> tx_count, creator)`
     final_block_stats: VecDeque<(MassaTime, u64, Address)>,
     /// No idea what this is used for. My guess is one timestamp per stale block
@@ -67,6 +110,10 @@ pub struct ConsensusWorker {
     launch_time: MassaTime,
     // endorsed slots cache
     endorsed_slots: HashSet<Slot>,
+    /// broadcast channel notifying subscribers of final and stale blocks
+    block_notifier: broadcast::Sender<BlockNotification>,
+    /// draws of the staker reports, see `StakerDraws`
+    staker_draws: Option<StakerDraws>,
 }
 
 impl ConsensusWorker {
@@ -85,10 +132,11 @@ impl ConsensusWorker {
         channels: ConsensusWorkerChannels,
         block_db: BlockGraph,
         pos: ProofOfStake,
-        clock_compensation: i64,
+        clock_compensation: ClockCompensation,
         staking_keys: Map<Address, (PublicKey, PrivateKey)>,
+        staking_keys_password: StakingKeysPassword,
     ) -> Result<ConsensusWorker> {
-        let now = MassaTime::compensated_now(clock_compensation)?;
+        let now = clock_compensation.now()?;
         let previous_slot = get_latest_block_slot_at_timestamp(
             cfg.thread_count,
             cfg.t0,
@@ -163,6 +211,8 @@ impl ConsensusWorker {
                 .collect(),
         );
 
+        let launch_time = clock_compensation.now()?;
+        let (unlocked_staking_keys_tx, unlocked_staking_keys_rx) = mpsc::channel(CHANNEL_SIZE);
         Ok(ConsensusWorker {
             genesis_public_key,
             block_db,
@@ -174,13 +224,20 @@ impl ConsensusWorker {
             clock_compensation,
             channels,
             staking_keys,
+            staking_keys_password,
+            staking_keys_version: 0,
+            unlocked_staking_keys_tx,
+            unlocked_staking_keys_rx,
+            staking_keys_dump: None,
             final_block_stats,
             stale_block_stats: VecDeque::new(),
             stats_desync_detection_timespan,
             stats_history_timespan: max(stats_desync_detection_timespan, cfg.stats_timespan),
             cfg,
-            launch_time: MassaTime::compensated_now(clock_compensation)?,
+            launch_time,
             endorsed_slots: HashSet::new(),
+            block_notifier: broadcast::channel(CHANNEL_SIZE).0,
+            staker_draws: None,
         })
     }
 
@@ -207,7 +264,7 @@ impl ConsensusWorker {
                 self.cfg.genesis_timestamp,
                 self.next_slot,
             )?
-            .estimate_instant(self.clock_compensation)?,
+            .estimate_instant(self.clock_compensation.get())?,
         );
 
         tokio::pin!(next_slot_timer);
@@ -224,6 +281,7 @@ impl ConsensusWorker {
                 We choose this order:
                     * manager commands: low freq, avoid having to wait to stop
                     * consensus commands (low to medium freq): respond quickly
+                    * staking keys unlocked in the background (low freq): respond quickly
                     * slot timer (low freq, timing is important but does not have to be perfect either)
                     * prune timer: low freq, timing not important but should not wait too long
                     * receive protocol events (high freq)
@@ -243,11 +301,17 @@ impl ConsensusWorker {
                     self.process_consensus_command(cmd).await?
                 },
 
+                // staking keys decrypted in the background
+                Some(unlocked) = self.unlocked_staking_keys_rx.recv() => {
+                    massa_trace!("consensus.consensus_worker.run_loop.unlocked_staking_keys", {});
+                    self.on_staking_keys_unlocked(unlocked)
+                },
+
                 // slot timer
                 _ = &mut next_slot_timer => {
                     massa_trace!("consensus.consensus_worker.run_loop.select.slot_tick", {});
                     if let Some(end) = self.cfg.end_timestamp {
-                        if MassaTime::compensated_now(self.clock_compensation)? > end {
+                        if self.clock_compensation.now()? > end {
                             info!("This episode has come to an end, please get the latest testnet node version to continue");
                             break;
                         }
@@ -276,6 +340,11 @@ impl ConsensusWorker {
             }
         }
         // after this curly brace you can find the end of the loop
+
+        // finish writing the staking keys file
+        if let Some(staking_keys_dump) = self.staking_keys_dump.take() {
+            let _ = staking_keys_dump.await;
+        }
         Ok(self.channels.protocol_event_receiver)
     }
 
@@ -286,7 +355,7 @@ impl ConsensusWorker {
     /// detects desynchronization
     /// produce quite more logs than actual stuff
     async fn slot_tick(&mut self, next_slot_timer: &mut std::pin::Pin<&mut Sleep>) -> Result<()> {
-        let now = MassaTime::compensated_now(self.clock_compensation)?;
+        let now = self.clock_compensation.now()?;
         let observed_slot = get_latest_block_slot_at_timestamp(
             self.cfg.thread_count,
             self.cfg.t0,
@@ -303,7 +372,7 @@ impl ConsensusWorker {
                     self.cfg.genesis_timestamp,
                     self.next_slot,
                 )?
-                .estimate_instant(self.clock_compensation)?,
+                .estimate_instant(self.clock_compensation.get())?,
             ));
             return Ok(());
         }
@@ -414,15 +483,59 @@ impl ConsensusWorker {
                 self.cfg.genesis_timestamp,
                 self.next_slot,
             )?
-            .estimate_instant(self.clock_compensation)?,
+            .estimate_instant(self.clock_compensation.get())?,
         ));
 
         // prune stats
         self.prune_stats()?;
 
+        // export metrics
+        self.update_metrics();
+
         Ok(())
     }
 
+    /// Updates the consensus metrics: final and candidate periods, and production of our stakers
+    fn update_metrics(&self) {
+        for (thread, (_, period)) in self
+            .block_db
+            .get_latest_final_blocks_periods()
+            .iter()
+            .enumerate()
+        {
+            CONSENSUS_FINAL_PERIOD
+                .with_label_values(&[&thread.to_string()])
+                .set(*period as i64);
+        }
+        for (thread, (_, period)) in self.block_db.get_best_parents().iter().enumerate() {
+            CONSENSUS_CANDIDATE_PERIOD
+                .with_label_values(&[&thread.to_string()])
+                .set(*period as i64);
+        }
+        let staking_addresses: Set<Address> = self.staking_keys.keys().copied().collect();
+        let mut production: Map<Address, (u64, u64)> = staking_addresses
+            .iter()
+            .map(|addr| (*addr, (0, 0)))
+            .collect();
+        for cycle_stats in self.pos.get_stakers_production_stats(&staking_addresses) {
+            for (addr, (ok_count, nok_count)) in cycle_stats.ok_nok_counts.iter() {
+                if let Some((produced, missed)) = production.get_mut(addr) {
+                    *produced += ok_count;
+                    *missed += nok_count;
+                }
+            }
+        }
+        for (addr, (produced, missed)) in production {
+            let addr = addr.to_string();
+            STAKER_PRODUCED_BLOCKS
+                .with_label_values(&[&addr])
+                .set(produced as i64);
+            STAKER_MISSED_BLOCKS
+                .with_label_values(&[&addr])
+                .set(missed as i64);
+        }
+    }
+
     /// creates a block with given address
     /// first an empty block is created then it's filled with operations
     /// the operations are retrieved from the pool
@@ -577,11 +690,11 @@ impl ConsensusWorker {
 
                 // add operation
                 operation_set.insert(op_id, (operation_set.len(), op.content.expire_period));
+                total_hash.extend(op.get_merkle_leaf(&op_id));
                 operations.push(op);
                 remaining_block_space -= op_size;
                 remaining_operation_count -= 1;
                 total_gas += op_gas;
-                total_hash.extend(op_id.to_bytes());
 
                 // check if the block still has some space
                 if remaining_block_space == 0 || remaining_operation_count == 0 {
@@ -863,25 +976,97 @@ impl ConsensusWorker {
                 }
                 Ok(())
             }
-            ConsensusCommand::RegisterStakingPrivateKeys(keys) => {
-                for key in keys.into_iter() {
-                    let public = derive_public_key(&key);
-                    let address = Address::from_public_key(&public);
-                    info!("Staking with address {}", address);
-                    self.staking_keys.insert(address, (public, key));
+            ConsensusCommand::RegisterStakingPrivateKeys { keys, response_tx } => {
+                let res = if self.staking_keys_password.is_set() {
+                    self.staking_keys_version += 1;
+                    for key in keys.into_iter() {
+                        let public = derive_public_key(&key);
+                        let address = Address::from_public_key(&public);
+                        info!("Staking with address {}", address);
+                        self.staking_keys.insert(address, (public, key));
+                    }
+                    self.pos
+                        .set_watched_addresses(self.staking_keys.keys().copied().collect());
+                    self.dump_staking_keys();
+                    Ok(())
+                } else {
+                    Err(ConsensusError::StakingKeysLocked)
+                };
+                if response_tx.send(res).is_err() {
+                    warn!("consensus: could not send register_staking_private_keys response");
                 }
-                self.pos
-                    .set_watched_addresses(self.staking_keys.keys().copied().collect());
-                self.dump_staking_keys().await;
                 Ok(())
             }
-            ConsensusCommand::RemoveStakingAddresses(addresses) => {
-                for address in addresses.into_iter() {
-                    self.staking_keys.remove(&address);
+            ConsensusCommand::RemoveStakingAddresses {
+                addresses,
+                response_tx,
+            } => {
+                let res = if self.staking_keys_password.is_set() {
+                    self.staking_keys_version += 1;
+                    for address in addresses.into_iter() {
+                        self.staking_keys.remove(&address);
+                    }
+                    self.pos
+                        .set_watched_addresses(self.staking_keys.keys().copied().collect());
+                    self.dump_staking_keys();
+                    Ok(())
+                } else {
+                    Err(ConsensusError::StakingKeysLocked)
+                };
+                if response_tx.send(res).is_err() {
+                    warn!("consensus: could not send remove_staking_addresses response");
+                }
+                Ok(())
+            }
+            ConsensusCommand::UnlockStakingKeys {
+                password,
+                response_tx,
+            } => {
+                massa_trace!(
+                    "consensus.consensus_worker.process_consensus_command.unlock_staking_keys",
+                    {}
+                );
+                // the key derivation is slow: decrypt in the background and keep processing slots
+                self.staking_keys_version += 1;
+                let version = self.staking_keys_version;
+                let path = self.cfg.staking_keys_path.clone();
+                let unlocked_staking_keys_tx = self.unlocked_staking_keys_tx.clone();
+                tokio::spawn(async move {
+                    let staking_keys = load_staking_keys(&path, &password).await;
+                    let _ = unlocked_staking_keys_tx
+                        .send(UnlockedStakingKeys {
+                            version,
+                            password,
+                            staking_keys,
+                            response_tx,
+                        })
+                        .await;
+                });
+                Ok(())
+            }
+            ConsensusCommand::LockStakingKeys(response_tx) => {
+                massa_trace!(
+                    "consensus.consensus_worker.process_consensus_command.lock_staking_keys",
+                    {}
+                );
+                self.staking_keys_version += 1;
+                self.staking_keys.clear();
+                self.staking_keys_password.set(None);
+                self.pos.set_watched_addresses(Set::default());
+                info!("staking keys locked: block and endorsement production stopped");
+                if response_tx.send(()).is_err() {
+                    warn!("consensus: could not send lock_staking_keys response");
+                }
+                Ok(())
+            }
+            ConsensusCommand::SubscribeBlockNotifications(response_tx) => {
+                massa_trace!(
+                    "consensus.consensus_worker.process_consensus_command.subscribe_block_notifications",
+                    {}
+                );
+                if response_tx.send(self.block_notifier.subscribe()).is_err() {
+                    warn!("consensus: could not send subscribe_block_notifications response");
                 }
-                self.pos
-                    .set_watched_addresses(self.staking_keys.keys().copied().collect());
-                self.dump_staking_keys().await;
                 Ok(())
             }
             ConsensusCommand::GetStakingAddresses(response_tx) => {
@@ -910,6 +1095,22 @@ impl ConsensusWorker {
                 }
                 Ok(())
             }
+            ConsensusCommand::GetStakerReports {
+                addresses,
+                response_tx,
+            } => {
+                massa_trace!(
+                    "consensus.consensus_worker.process_consensus_command.get_staker_reports",
+                    {}
+                );
+                if response_tx
+                    .send(self.get_staker_reports(&addresses))
+                    .is_err()
+                {
+                    warn!("consensus: could not send get_staker_reports response");
+                }
+                Ok(())
+            }
             ConsensusCommand::GetBlockIdsByCreator {
                 address,
                 response_tx,
@@ -947,33 +1148,66 @@ impl ConsensusWorker {
         }
     }
 
-    /// Save the staking keys to a file
-    async fn dump_staking_keys(&self) {
+    /// Applies the staking keys decrypted in the background, unless they changed since the unlock was requested
+    fn on_staking_keys_unlocked(&mut self, unlocked: UnlockedStakingKeys) {
+        let UnlockedStakingKeys {
+            version,
+            password,
+            staking_keys,
+            response_tx,
+        } = unlocked;
+        let res = match staking_keys {
+            Ok(_) if version != self.staking_keys_version => {
+                Err(ConsensusError::StakingKeysChanged)
+            }
+            Ok(staking_keys) => {
+                for addr in staking_keys.keys() {
+                    info!("Staking enabled for address: {}", addr);
+                }
+                self.staking_keys = staking_keys;
+                self.staking_keys_password.set(Some(password));
+                self.pos
+                    .set_watched_addresses(self.staking_keys.keys().copied().collect());
+                Ok(self.staking_keys.keys().copied().collect())
+            }
+            Err(err) => Err(err),
+        };
+        if response_tx.send(res).is_err() {
+            warn!("consensus: could not send unlock_staking_keys response");
+        }
+    }
+
+    /// Save the staking keys to a file encrypted with the staking keys password.
+    /// The file is written in the background, after the previous writes.
+    fn dump_staking_keys(&mut self) {
+        let password = match self.staking_keys_password.get() {
+            Some(password) => password,
+            None => {
+                warn!("Cannot dump staking keys while they are locked");
+                return;
+            }
+        };
         let keys = self
             .staking_keys
             .iter()
             .map(|(_, (_, key))| *key)
             .collect::<Vec<_>>();
-        let json = match serde_json::to_string_pretty(&keys) {
-            Ok(json) => json,
-            Err(e) => {
-                warn!("Error while serializing staking keys {}", e);
-                return;
+        let path = self.cfg.staking_keys_path.clone();
+        let previous_dump = self.staking_keys_dump.take();
+        self.staking_keys_dump = Some(tokio::spawn(async move {
+            if let Some(previous_dump) = previous_dump {
+                let _ = previous_dump.await;
             }
-        };
-
-        if let Err(e) = tokio::fs::write(self.cfg.staking_keys_path.clone(), json).await {
-            warn!("Error while dumping staking keys {}", e);
-        }
+            if let Err(e) = dump_staking_keys(path, password, keys).await {
+                warn!("Error while dumping staking keys {}", e);
+            }
+        }));
     }
 
     /// retrieve stats
     /// Used in response to a API request
     fn get_stats(&mut self) -> Result<ConsensusStats> {
-        let timespan_end = max(
-            self.launch_time,
-            MassaTime::compensated_now(self.clock_compensation)?,
-        );
+        let timespan_end = max(self.launch_time, self.clock_compensation.now()?);
         let timespan_start = max(
             timespan_end.saturating_sub(self.cfg.stats_timespan),
             self.launch_time,
@@ -1022,6 +1256,135 @@ impl ConsensusWorker {
         Ok(res)
     }
 
+    /// Takes the cached draws of the current and next cycles, computing them again if they are outdated.
+    /// The next cycle is skipped while its lookback cycle is not final.
+    /// The draws are to be put back in `staker_draws` once used.
+    fn take_staker_draws(&mut self, cur_cycle: u64) -> Result<StakerDraws> {
+        if let Some(draws) = self.staker_draws.take() {
+            if draws.cycle == cur_cycle {
+                if draws.includes_next_cycle {
+                    return Ok(draws);
+                }
+                let next_cycle_start = Slot::new((cur_cycle + 1) * self.cfg.periods_per_cycle, 0);
+                match self.pos.draw_block_producer(next_cycle_start) {
+                    Err(ProofOfStakeError::PosCycleUnavailable(_)) => return Ok(draws),
+                    Err(err) => return Err(err.into()),
+                    Ok(_) => {}
+                }
+            }
+        }
+
+        let mut draws = StakerDraws {
+            cycle: cur_cycle,
+            includes_next_cycle: true,
+            ..Default::default()
+        };
+        'cycles: for cycle in cur_cycle..=(cur_cycle + 1) {
+            for period in
+                (cycle * self.cfg.periods_per_cycle)..((cycle + 1) * self.cfg.periods_per_cycle)
+            {
+                if period == 0 {
+                    continue;
+                }
+                for thread in 0..self.cfg.thread_count {
+                    let slot = Slot::new(period, thread);
+                    let block_producer = match self.pos.draw_block_producer(slot) {
+                        Ok(block_producer) => block_producer,
+                        Err(ProofOfStakeError::PosCycleUnavailable(_)) if cycle > cur_cycle => {
+                            draws.includes_next_cycle = false;
+                            break 'cycles;
+                        }
+                        Err(err) => return Err(err.into()),
+                    };
+                    draws
+                        .block_draws
+                        .entry(block_producer)
+                        .or_default()
+                        .push(slot);
+                    for (index, endorsement_producer) in self
+                        .pos
+                        .draw_endorsement_producers(slot)?
+                        .into_iter()
+                        .enumerate()
+                    {
+                        draws
+                            .endorsement_draws
+                            .entry(endorsement_producer)
+                            .or_default()
+                            .push(IndexedSlot { slot, index });
+                    }
+                }
+            }
+        }
+        Ok(draws)
+    }
+
+    /// draws of the current and next cycles, production stats and deferred credits of stakers
+    /// Used in response to a API request
+    fn get_staker_reports(&mut self, addresses: &[Address]) -> Result<Vec<StakerReport>> {
+        let cur_cycle = self.next_slot.get_cycle(self.cfg.periods_per_cycle);
+        let draws = self.take_staker_draws(cur_cycle)?;
+        let mut reports: Vec<StakerReport> = addresses
+            .iter()
+            .map(|address| StakerReport {
+                address: *address,
+                current_cycle: cur_cycle,
+                active_rolls: 0,
+                block_draws: draws.block_draws.get(address).cloned().unwrap_or_default(),
+                endorsement_draws: draws
+                    .endorsement_draws
+                    .get(address)
+                    .cloned()
+                    .unwrap_or_default(),
+                production_stats: Vec::new(),
+                roll_deactivation_cycles: Vec::new(),
+                deferred_credits: Vec::new(),
+            })
+            .collect();
+        self.staker_draws = Some(draws);
+
+        let addrs: Set<Address> = addresses.iter().copied().collect();
+        let mut prod_stats = self.pos.get_stakers_production_stats(&addrs);
+        prod_stats.sort_unstable_by_key(|cycle_stats| cycle_stats.cycle);
+        for report in reports.iter_mut() {
+            let thread = report.address.get_thread(self.cfg.thread_count);
+            report.active_rolls = match self.pos.get_lookback_roll_count(cur_cycle, thread) {
+                Ok(rolls) => *rolls.0.get(&report.address).unwrap_or(&0),
+                Err(ProofOfStakeError::PosCycleUnavailable(_)) => 0,
+                Err(err) => return Err(err.into()),
+            };
+            report.production_stats = prod_stats
+                .iter()
+                .map(|cycle_stats| {
+                    let (produced_blocks, missed_blocks) = *cycle_stats
+                        .ok_nok_counts
+                        .get(&report.address)
+                        .unwrap_or(&(0, 0));
+                    let (produced_endorsements, missed_endorsements) = *cycle_stats
+                        .endorsement_ok_nok_counts
+                        .get(&report.address)
+                        .unwrap_or(&(0, 0));
+                    StakerCycleStats {
+                        cycle: cycle_stats.cycle,
+                        is_final: cycle_stats.is_final,
+                        produced_blocks,
+                        missed_blocks,
+                        produced_endorsements,
+                        missed_endorsements,
+                    }
+                })
+                .collect();
+            report.roll_deactivation_cycles = self.pos.get_roll_deactivation_risk(&report.address);
+            report.deferred_credits = self
+                .pos
+                .get_deferred_roll_sell_credits(&report.address)?
+                .into_iter()
+                .map(|(cycle, amount)| DeferredCredit { cycle, amount })
+                .collect();
+        }
+        Ok(reports)
+    }
+
     /// all you wanna know about an address
     /// Used in response to a API request
     fn get_addresses_info(&self, addresses: &Set<Address>) -> Result<Map<Address, AddressState>> {
@@ -1183,7 +1546,9 @@ impl ConsensusWorker {
 
     /// prune statistics according to the stats span
     fn prune_stats(&mut self) -> Result<()> {
-        let start_time = MassaTime::compensated_now(self.clock_compensation)?
+        let start_time = self
+            .clock_compensation
+            .now()?
             .saturating_sub(self.stats_history_timespan);
         self.final_block_stats.retain(|(t, _, _)| t >= &start_time);
         self.stale_block_stats.retain(|t| t >= &start_time);
@@ -1268,7 +1633,7 @@ impl ConsensusWorker {
         let mut new_final_ops: Map<OperationId, (u64, u8)> = Map::default();
         let mut new_final_blocks =
             Map::with_capacity_and_hasher(new_final_block_ids.len(), BuildMap::default());
-        let timestamp = MassaTime::compensated_now(self.clock_compensation)?;
+        let timestamp = self.clock_compensation.now()?;
         for b_id in new_final_block_ids.into_iter() {
             if let Some(a_block) = self.block_db.get_active_block(&b_id) {
                 // List new final ops
@@ -1278,6 +1643,11 @@ impl ConsensusWorker {
                         .iter()
                         .map(|(id, (_, exp))| (*id, (*exp, a_block.slot.thread))),
                 );
+                // notify subscribers (a send error only means that there is no subscriber)
+                let _ = self.block_notifier.send(BlockNotification::Final {
+                    block_id: b_id,
+                    slot: a_block.slot,
+                });
                 // List final block
                 new_final_blocks.insert(b_id, a_block);
                 // add to stats
@@ -1398,9 +1768,13 @@ impl ConsensusWorker {
 
         // add stale blocks to stats
         let new_stale_block_ids_creators_slots = self.block_db.get_new_stale_blocks();
-        let timestamp = MassaTime::compensated_now(self.clock_compensation)?;
+        let timestamp = self.clock_compensation.now()?;
         for (b_id, (b_creator, b_slot)) in new_stale_block_ids_creators_slots.into_iter() {
             self.stale_block_stats.push_back(timestamp);
+            let _ = self.block_notifier.send(BlockNotification::Stale {
+                block_id: b_id,
+                slot: b_slot,
+            });
 
             let creator_addr = Address::from_public_key(&b_creator);
             if self.staking_keys.contains_key(&creator_addr) {
@@ -50,7 +50,7 @@ impl BlockFactory {
                     &self
                         .operations
                         .iter()
-                        .flat_map(|op| op.content.compute_id().unwrap().into_bytes())
+                        .flat_map(|op| op.get_merkle_leaf(&op.content.compute_id().unwrap()))
                         .collect::<Vec<_>>()[..],
                 ),
                 endorsements: self.endorsements.clone(),
@@ -1,7 +1,7 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 use massa_consensus_exports::tools;
-use massa_consensus_exports::{settings::ConsensusChannels, ConsensusConfig};
+use massa_consensus_exports::{settings::ConsensusChannels, ConsensusConfig, StakingKeysPassword};
 use massa_execution_exports::test_exports::MockExecutionController;
 use massa_models::signed::Signable;
 use massa_models::{Address, Amount, BlockId, Slot};
@@ -521,7 +521,8 @@ async fn test_roll_block_creation() {
             None,
             None,
             storage.clone(),
-            0,
+            Default::default(),
+            StakingKeysPassword::new(Some("password".to_string())),
         )
         .await
         .expect("could not start consensus controller");
@@ -815,7 +816,8 @@ async fn test_roll_deactivation() {
             None,
             None,
             storage,
-            0,
+            Default::default(),
+            StakingKeysPassword::new(Some("password".to_string())),
         )
         .await
         .expect("could not start consensus controller");
@@ -8,7 +8,7 @@ use crate::start_consensus_controller;
 use massa_execution_exports::test_exports::MockExecutionController;
 
 use super::tools::*;
-use massa_consensus_exports::ConsensusConfig;
+use massa_consensus_exports::{ConsensusConfig, StakingKeysPassword};
 
 use massa_consensus_exports::settings::ConsensusChannels;
 use massa_graph::{ledger::Ledger, LedgerConfig};
@@ -515,7 +515,8 @@ async fn test_ledger_update_when_a_batch_of_blocks_becomes_final() {
             None,
             None,
             storage,
-            0,
+            Default::default(),
+            StakingKeysPassword::new(Some("password".to_string())),
         )
         .await
         .expect("could not start consensus controller");
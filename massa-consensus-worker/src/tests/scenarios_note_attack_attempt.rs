@@ -6,7 +6,7 @@ use super::{
     mock_protocol_controller::MockProtocolController,
 };
 use crate::start_consensus_controller;
-use massa_consensus_exports::ConsensusConfig;
+use massa_consensus_exports::{ConsensusConfig, StakingKeysPassword};
 use massa_execution_exports::test_exports::MockExecutionController;
 
 use massa_consensus_exports::settings::ConsensusChannels;
@@ -47,7 +47,8 @@ async fn test_invalid_block_notified_as_attack_attempt() {
             None,
             None,
             storage,
-            0,
+            Default::default(),
+            StakingKeysPassword::new(Some("password".to_string())),
         )
         .await
         .expect("could not start consensus controller");
@@ -113,7 +114,8 @@ async fn test_invalid_header_notified_as_attack_attempt() {
             None,
             None,
             storage,
-            0,
+            Default::default(),
+            StakingKeysPassword::new(Some("password".to_string())),
         )
         .await
         .expect("could not start consensus controller");
@@ -4,7 +4,8 @@ use massa_consensus_exports::ConsensusConfig;
 
 use massa_models::ledger_models::LedgerData;
 use massa_models::prehash::Set;
-use massa_models::{Address, Amount, Slot};
+use massa_models::signed::Signed;
+use massa_models::{Address, Amount, Operation, OperationType, Slot};
 use massa_signature::PrivateKey;
 use massa_time::MassaTime;
 use serial_test::serial;
@@ -195,3 +196,103 @@ async fn test_execution_check() {
     )
     .await;
 }
+
+#[tokio::test]
+#[serial]
+async fn test_batch_transaction_check() {
+    let thread_count = 2;
+
+    let (address_1, private_key_1, public_key_1) = random_address_on_thread(0, thread_count).into();
+    let (address_2, _, _) = random_address_on_thread(0, thread_count).into();
+    let (address_3, _, _) = random_address_on_thread(0, thread_count).into();
+
+    let mut ledger = HashMap::new();
+    ledger.insert(address_1, LedgerData::new(Amount::from_str("5").unwrap()));
+
+    let cfg = ConsensusConfig {
+        t0: 1000.into(),
+        future_block_processing_max_periods: 50,
+        operation_validity_periods: 10,
+        genesis_timestamp: MassaTime::now().unwrap().saturating_sub(10000.into()),
+        ..ConsensusConfig::default_with_staking_keys_and_ledger(&[private_key_1], &ledger)
+    };
+
+    let create_batch_transaction = |transfers: Vec<(Address, &str)>, fee: &str| {
+        let content = Operation {
+            sender_public_key: public_key_1,
+            fee: Amount::from_str(fee).unwrap(),
+            expire_period: 5,
+            op: OperationType::BatchTransaction {
+                transfers: transfers
+                    .into_iter()
+                    .map(|(recipient, amount)| (recipient, Amount::from_str(amount).unwrap()))
+                    .collect(),
+            },
+            multisig: None,
+            replaces: None,
+        };
+        Signed::new_signed(content, &private_key_1).unwrap().1
+    };
+
+    consensus_without_pool_test(
+        cfg.clone(),
+        async move |mut protocol_controller, consensus_command_sender, consensus_event_receiver| {
+            let genesis_ids = consensus_command_sender
+                .get_block_graph_status(None, None)
+                .await
+                .expect("could not get block graph status")
+                .genesis_blocks;
+
+            // receive block with a batch whose last transfer is not affordable
+            let operation_1 =
+                create_batch_transaction(vec![(address_2, "2"), (address_3, "40")], "0");
+            let (_, block_1, _) = create_block_with_operations(
+                &cfg,
+                Slot::new(1, 0),
+                &genesis_ids,
+                private_key_1,
+                vec![operation_1],
+            );
+            propagate_block(&mut protocol_controller, block_1, false, 1000).await;
+
+            // valid block A with an affordable batch
+            let operation_2 =
+                create_batch_transaction(vec![(address_2, "1"), (address_3, "3")], "1");
+            let (_, block_a, _) = create_block_with_operations(
+                &cfg,
+                Slot::new(1, 0),
+                &genesis_ids,
+                private_key_1,
+                vec![operation_2],
+            );
+            propagate_block(&mut protocol_controller, block_a, true, 150).await;
+
+            // only the transfers of the valid batch were credited
+            let addresses_info = consensus_command_sender
+                .get_addresses_info(vec![address_2, address_3].into_iter().collect())
+                .await
+                .unwrap();
+            assert_eq!(
+                addresses_info[&address_2]
+                    .ledger_info
+                    .candidate_ledger_info
+                    .balance,
+                Amount::from_str("1").unwrap()
+            );
+            assert_eq!(
+                addresses_info[&address_3]
+                    .ledger_info
+                    .candidate_ledger_info
+                    .balance,
+                Amount::from_str("3").unwrap()
+            );
+
+            (
+                protocol_controller,
+                consensus_command_sender,
+                consensus_event_receiver,
+            )
+        },
+    )
+    .await;
+}
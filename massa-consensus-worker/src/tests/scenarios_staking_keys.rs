@@ -0,0 +1,131 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use super::tools::*;
+use massa_consensus_exports::{ConsensusConfig, ConsensusError, StakingKeysPassword};
+use massa_models::prehash::Set;
+use massa_time::MassaTime;
+use serial_test::serial;
+
+#[tokio::test]
+#[serial]
+async fn test_lock_unlock_staking_keys() {
+    let staker = random_address();
+    let mut cfg = ConsensusConfig::default_with_staking_keys(&[staker.private_key]);
+    // avoid producing blocks during the test
+    cfg.genesis_timestamp = MassaTime::now().unwrap().saturating_add(300000.into());
+
+    consensus_without_pool_test(
+        cfg.clone(),
+        async move |protocol_controller, consensus_command_sender, consensus_event_receiver| {
+            // the plaintext staking keys file was migrated at startup
+            assert!(massa_cipher::decrypt(
+                "password",
+                &std::fs::read(&cfg.staking_keys_path).unwrap()
+            )
+            .is_ok());
+            let addresses = consensus_command_sender
+                .get_staking_addresses()
+                .await
+                .unwrap();
+            assert_eq!(addresses, Set::from_iter([staker.address]));
+
+            // lock: no staking address left and the keys cannot be modified
+            consensus_command_sender.lock_staking_keys().await.unwrap();
+            assert!(consensus_command_sender
+                .get_staking_addresses()
+                .await
+                .unwrap()
+                .is_empty());
+            let other = random_address();
+            assert!(matches!(
+                consensus_command_sender
+                    .register_staking_private_keys(vec![other.private_key])
+                    .await,
+                Err(ConsensusError::StakingKeysLocked)
+            ));
+
+            // unlock with a wrong password then with the right one
+            assert!(consensus_command_sender
+                .unlock_staking_keys("wrong password".to_string())
+                .await
+                .is_err());
+            let addresses = consensus_command_sender
+                .unlock_staking_keys("password".to_string())
+                .await
+                .unwrap();
+            assert_eq!(addresses, Set::from_iter([staker.address]));
+            consensus_command_sender
+                .register_staking_private_keys(vec![other.private_key])
+                .await
+                .unwrap();
+            assert_eq!(
+                consensus_command_sender
+                    .get_staking_addresses()
+                    .await
+                    .unwrap(),
+                Set::from_iter([staker.address, other.address])
+            );
+            (
+                protocol_controller,
+                consensus_command_sender,
+                consensus_event_receiver,
+            )
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn test_staking_keys_password_kept_across_restart() {
+    let staker = random_address();
+    let mut cfg = ConsensusConfig::default_with_staking_keys(&[staker.private_key]);
+    // avoid producing blocks during the test
+    cfg.genesis_timestamp = MassaTime::now().unwrap().saturating_add(300000.into());
+
+    // started locked, then unlocked at runtime: the shared password is set
+    let staking_keys_password = StakingKeysPassword::default();
+    let shared_password = staking_keys_password.clone();
+    consensus_without_pool_test_with_password(
+        cfg.clone(),
+        staking_keys_password.clone(),
+        async move |protocol_controller, consensus_command_sender, consensus_event_receiver| {
+            assert!(!shared_password.is_set());
+            consensus_command_sender
+                .unlock_staking_keys("password".to_string())
+                .await
+                .unwrap();
+            assert_eq!(shared_password.get(), Some("password".to_string()));
+            (
+                protocol_controller,
+                consensus_command_sender,
+                consensus_event_receiver,
+            )
+        },
+    )
+    .await;
+
+    // restarted with the shared password: the staking keys are still unlocked
+    let shared_password = staking_keys_password.clone();
+    consensus_without_pool_test_with_password(
+        cfg.clone(),
+        staking_keys_password,
+        async move |protocol_controller, consensus_command_sender, consensus_event_receiver| {
+            assert_eq!(
+                consensus_command_sender
+                    .get_staking_addresses()
+                    .await
+                    .unwrap(),
+                Set::from_iter([staker.address])
+            );
+            consensus_command_sender.lock_staking_keys().await.unwrap();
+            assert!(!shared_password.is_set());
+            (
+                protocol_controller,
+                consensus_command_sender,
+                consensus_event_receiver,
+            )
+        },
+    )
+    .await;
+}
@@ -8,6 +8,7 @@ use super::{
 use crate::start_consensus_controller;
 use massa_consensus_exports::{
     settings::ConsensusChannels, ConsensusCommandSender, ConsensusConfig, ConsensusEventReceiver,
+    StakingKeysPassword,
 };
 use massa_execution_exports::test_exports::MockExecutionController;
 use massa_graph::{export_active_block::ExportActiveBlock, BlockGraphExport, BootstrapableGraph};
@@ -345,6 +346,8 @@ pub fn create_roll_transaction(
         fee: Amount::from_str(&fee.to_string()).unwrap(),
         expire_period,
         op,
+        multisig: None,
+        replaces: None,
     };
     Signed::new_signed(content, &priv_key).unwrap().1
 }
@@ -388,6 +391,8 @@ pub fn create_transaction(
         fee: Amount::from_str(&fee.to_string()).unwrap(),
         expire_period,
         op,
+        multisig: None,
+        replaces: None,
     };
     Signed::new_signed(content, &priv_key).unwrap().1
 }
@@ -415,6 +420,8 @@ pub fn create_executesc(
         fee: Amount::from_str(&fee.to_string()).unwrap(),
         expire_period,
         op,
+        multisig: None,
+        replaces: None,
     };
     Signed::new_signed(content, &priv_key).unwrap().1
 }
@@ -432,6 +439,8 @@ pub fn create_roll_buy(
         fee: Amount::from_str(&fee.to_string()).unwrap(),
         expire_period,
         op,
+        multisig: None,
+        replaces: None,
     };
     Signed::new_signed(content, &priv_key).unwrap().1
 }
@@ -449,6 +458,8 @@ pub fn create_roll_sell(
         fee: Amount::from_str(&fee.to_string()).unwrap(),
         expire_period,
         op,
+        multisig: None,
+        replaces: None,
     };
     Signed::new_signed(content, &priv_key).unwrap().1
 }
@@ -694,7 +705,8 @@ pub async fn consensus_pool_test<F, V>(
             boot_pos,
             boot_graph,
             storage.clone(),
-            0,
+            Default::default(),
+            StakingKeysPassword::new(Some("password".to_string())),
         )
         .await
         .expect("could not start consensus controller");
@@ -787,7 +799,8 @@ pub async fn consensus_pool_test_with_storage<F, V>(
             boot_pos,
             boot_graph,
             storage.clone(),
-            0,
+            Default::default(),
+            StakingKeysPassword::new(Some("password".to_string())),
         )
         .await
         .expect("could not start consensus controller");
@@ -833,6 +846,29 @@ where
             ConsensusEventReceiver,
         ),
     >,
+{
+    consensus_without_pool_test_with_password(
+        cfg,
+        StakingKeysPassword::new(Some("password".to_string())),
+        test,
+    )
+    .await
+}
+
+/// Runs a consensus test started with the given staking keys password, without passing a mock pool controller to it.
+pub async fn consensus_without_pool_test_with_password<F, V>(
+    cfg: ConsensusConfig,
+    staking_keys_password: StakingKeysPassword,
+    test: F,
+) where
+    F: FnOnce(MockProtocolController, ConsensusCommandSender, ConsensusEventReceiver) -> V,
+    V: Future<
+        Output = (
+            MockProtocolController,
+            ConsensusCommandSender,
+            ConsensusEventReceiver,
+        ),
+    >,
 {
     let storage: Storage = Default::default();
     // mock protocol & pool
@@ -862,7 +898,8 @@ where
             None,
             None,
             storage.clone(),
-            0,
+            Default::default(),
+            staking_keys_password,
         )
         .await
         .expect("could not start consensus controller");
@@ -929,7 +966,8 @@ where
             None,
             None,
             storage.clone(),
-            0,
+            Default::default(),
+            StakingKeysPassword::new(Some("password".to_string())),
         )
         .await
         .expect("could not start consensus controller");
@@ -3,33 +3,57 @@ use massa_consensus_exports::{
     error::{ConsensusError, ConsensusResult as Result},
     events::ConsensusEvent,
     settings::{ConsensusChannels, ConsensusWorkerChannels},
-    ConsensusCommandSender, ConsensusEventReceiver, ConsensusManager,
+    ConsensusCommandSender, ConsensusEventReceiver, ConsensusManager, StakingKeysPassword,
 };
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 
 use crate::consensus_worker::ConsensusWorker;
+use massa_cipher::{decrypt, encrypt};
 use massa_consensus_exports::settings::ConsensusConfig;
 use massa_graph::{settings::GraphConfig, BlockGraph, BootstrapableGraph};
 use massa_models::{constants::CHANNEL_SIZE, prehash::Map, Address};
 use massa_proof_of_stake_exports::{ExportProofOfStake, ProofOfStake, ProofOfStakeConfig};
 use massa_signature::{derive_public_key, PrivateKey, PublicKey};
 use massa_storage::Storage;
-use std::path::Path;
+use massa_time::ClockCompensation;
+use std::path::{Path, PathBuf};
 use tokio::sync::mpsc;
 
-/// Load staking keys from file
+/// Load staking keys from a file encrypted with `password`
 /// and derive public keys and addresses
 ///
-/// Returns default map if path isn't a file
-/// Maybe it would be worth considering returning the default map
-/// when the read to string or the parse is failing
-/// but eh that's left for another refactor
-async fn load_initial_staking_keys(path: &Path) -> Result<Map<Address, (PublicKey, PrivateKey)>> {
+/// Returns default map if path isn't a file.
+/// A file still containing the keys in plain JSON is migrated:
+/// it is overwritten with its encrypted version.
+/// The key derivation is memory-hard, so it runs on the blocking threads of the runtime.
+pub(crate) async fn load_staking_keys(
+    path: &Path,
+    password: &str,
+) -> Result<Map<Address, (PublicKey, PrivateKey)>> {
     if !std::path::Path::is_file(path) {
         return Ok(Map::default());
     }
-    serde_json::from_str::<Vec<PrivateKey>>(&tokio::fs::read_to_string(path).await?)?
-        .iter()
+    let content = tokio::fs::read(path).await?;
+    let password = password.to_string();
+    let (keys, migrated_content) =
+        tokio::task::spawn_blocking(move || -> Result<(Vec<PrivateKey>, Option<Vec<u8>>)> {
+            match decrypt(&password, &content) {
+                Ok(decrypted_content) => Ok((
+                    serde_json::from_slice::<Vec<PrivateKey>>(&decrypted_content)?,
+                    None,
+                )),
+                Err(err) => match serde_json::from_slice::<Vec<PrivateKey>>(&content) {
+                    Ok(keys) => Ok((keys, Some(encrypt(&password, &content)?))),
+                    Err(_) => Err(ConsensusError::from(err)),
+                },
+            }
+        })
+        .await??;
+    if let Some(encrypted_content) = migrated_content {
+        tokio::fs::write(path, encrypted_content).await?;
+        info!("plaintext staking keys file {:?} has been encrypted", path);
+    }
+    keys.iter()
         .map(|private_key| {
             let public_key = derive_public_key(private_key);
             Ok((
@@ -40,19 +64,36 @@ async fn load_initial_staking_keys(path: &Path) -> Result<Map<Address, (PublicKe
         .collect()
 }
 
+/// Save the staking keys to a file encrypted with `password`.
+/// The key derivation is memory-hard, so the encryption runs on the blocking threads of the runtime.
+pub(crate) async fn dump_staking_keys(
+    path: PathBuf,
+    password: String,
+    keys: Vec<PrivateKey>,
+) -> Result<()> {
+    let json = serde_json::to_string_pretty(&keys)?;
+    let encrypted_content =
+        tokio::task::spawn_blocking(move || encrypt(&password, json.as_bytes())).await??;
+    tokio::fs::write(path, encrypted_content).await?;
+    Ok(())
+}
+
 /// Creates a new consensus controller.
 ///
 /// # Arguments
 /// * `cfg`: consensus configuration
 /// * `protocol_command_sender`: a `ProtocolCommandSender` instance to send commands to Protocol.
 /// * `protocol_event_receiver`: a `ProtocolEventReceiver` instance to receive events from Protocol.
+/// * `clock_compensation`: clock compensation, updated live by the clock synchronization
+/// * `staking_keys_password`: password of the staking keys file shared with the node, the staking keys stay locked while it is not set
 pub async fn start_consensus_controller(
     cfg: ConsensusConfig,
     channels: ConsensusChannels,
     boot_pos: Option<ExportProofOfStake>,
     boot_graph: Option<BootstrapableGraph>,
     storage: Storage,
-    clock_compensation: i64,
+    clock_compensation: ClockCompensation,
+    staking_keys_password: StakingKeysPassword,
 ) -> Result<(
     ConsensusCommandSender,
     ConsensusEventReceiver,
@@ -81,7 +122,15 @@ pub async fn start_consensus_controller(
             "thread_count should divide t0".to_string(),
         ));
     }
-    let staking_keys = load_initial_staking_keys(&cfg.staking_keys_path).await?;
+    let staking_keys = match staking_keys_password.get() {
+        Some(password) => load_staking_keys(&cfg.staking_keys_path, &password).await?,
+        None => {
+            warn!(
+                "staking keys are locked: unlock them to start producing blocks and endorsements"
+            );
+            Map::default()
+        }
+    };
 
     // start worker
     let block_db = BlockGraph::new(GraphConfig::from(&cfg), boot_graph, storage).await?;
@@ -112,6 +161,7 @@ pub async fn start_consensus_controller(
             pos,
             clock_compensation,
             staking_keys,
+            staking_keys_password,
         )
         .await?
         .run_loop()
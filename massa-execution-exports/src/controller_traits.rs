@@ -2,17 +2,18 @@
 
 //! This module exports generic traits representing interfaces for interacting with the Execution worker
 
-use crate::types::ExecutionOutput;
+use crate::types::HistoricalLedgerEntry;
+use crate::types::ReadOnlyExecutionOutput;
 use crate::types::ReadOnlyExecutionRequest;
 use crate::ExecutionError;
-use massa_hash::Hash;
 use massa_ledger_exports::LedgerEntry;
-use massa_models::api::EventFilter;
+use massa_models::api::{EventFilter, SCOutputEventNotification};
 use massa_models::output_event::SCOutputEvent;
 use massa_models::Address;
 use massa_models::BlockId;
 use massa_models::Slot;
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
+use tokio::sync::broadcast;
 
 /// interface that communicates with the execution worker thread
 pub trait ExecutionController: Send + Sync {
@@ -35,6 +36,14 @@ pub trait ExecutionController: Send + Sync {
     /// * operation id
     fn get_filtered_sc_output_event(&self, filter: EventFilter) -> Vec<SCOutputEvent>;
 
+    /// Subscribe to the SC output events as they are emitted by executions.
+    /// Events from active slots are notified as non-final, and again as final once their slot is executed as final.
+    /// Slow receivers lag behind and lose the oldest notifications.
+    fn subscribe_sc_output_events(&self) -> broadcast::Receiver<SCOutputEventNotification>;
+
+    /// Get the slot at the output of which the final execution state is attached
+    fn get_final_slot(&self) -> Slot;
+
     /// Get a copy of a single datastore entry with its final and active values
     ///
     /// # Return value
@@ -42,9 +51,25 @@ pub trait ExecutionController: Send + Sync {
     fn get_final_and_active_data_entry(
         &self,
         addr: &Address,
-        key: &Hash,
+        key: &[u8],
     ) -> (Option<Vec<u8>>, Option<Vec<u8>>);
 
+    /// Get a page of the datastore keys of an address starting with a prefix, with their final and active values
+    ///
+    /// # Arguments
+    /// * `cursor`: only keys strictly greater than the cursor are returned
+    /// * `count`: maximum number of keys in each page
+    ///
+    /// # Return value
+    /// * `(final_keys, active_keys)`
+    fn get_final_and_active_datastore_keys(
+        &self,
+        addr: &Address,
+        prefix: &[u8],
+        cursor: Option<&[u8]>,
+        count: usize,
+    ) -> (BTreeSet<Vec<u8>>, BTreeSet<Vec<u8>>);
+
     /// Get a copy of a full ledger entry with its final and active values
     ///
     /// # Return value
@@ -54,18 +79,32 @@ pub trait ExecutionController: Send + Sync {
         addr: &Address,
     ) -> (Option<LedgerEntry>, Option<LedgerEntry>);
 
+    /// Get the balance, bytecode and some datastore entries of an address at the output of a final slot.
+    /// Recent slots are covered by the final state changes history, older ones by the ledger archive if enabled.
+    ///
+    /// # arguments
+    /// * `addr`: queried address
+    /// * `datastore_keys`: keys of the queried datastore entries
+    /// * `slot`: final slot at the output of which the entry is queried
+    fn get_historical_ledger_entry(
+        &self,
+        addr: &Address,
+        datastore_keys: &[Vec<u8>],
+        slot: Slot,
+    ) -> Result<HistoricalLedgerEntry, ExecutionError>;
+
     /// Execute read-only SC function call without causing modifications to the consensus state
     ///
     /// # arguments
     /// * `req`: an instance of `ReadOnlyCallRequest` describing the parameters of the execution
     ///
     /// # returns
-    /// An instance of `ExecutionOutput` containing a summary of the effects of the execution,
-    /// or an error if the execution failed.
+    /// An instance of `ReadOnlyExecutionOutput` containing a summary of the effects of the execution,
+    /// the gas it consumed and a trace of its calls, or an error if the execution failed.
     fn execute_readonly_request(
         &self,
         req: ReadOnlyExecutionRequest,
-    ) -> Result<ExecutionOutput, ExecutionError>;
+    ) -> Result<ReadOnlyExecutionOutput, ExecutionError>;
 
     /// Returns a boxed clone of self.
     /// Useful to allow cloning `Box<dyn ExecutionController>`.
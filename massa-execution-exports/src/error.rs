@@ -15,6 +15,9 @@ pub enum ExecutionError {
     /// Runtime error: {0}
     RuntimeError(String),
 
+    /// History error: {0}
+    HistoryError(String),
+
     /// `MassaHashError`: {0}
     MassaHashError(#[from] massa_hash::MassaHashError),
 
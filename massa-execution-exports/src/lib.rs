@@ -55,7 +55,8 @@ pub use error::ExecutionError;
 pub use event_store::EventStore;
 pub use settings::ExecutionConfig;
 pub use types::{
-    ExecutionOutput, ExecutionStackElement, ReadOnlyCallRequest, ReadOnlyExecutionRequest,
+    ExecutionCallTrace, ExecutionOutput, ExecutionStackElement, HistoricalLedgerEntry,
+    ReadOnlyCallRequest, ReadOnlyExecutionOutput, ReadOnlyExecutionRequest,
     ReadOnlyExecutionTarget,
 };
 
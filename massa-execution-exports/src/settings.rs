@@ -2,7 +2,8 @@
 
 //! This module provides the structures used to provide configuration parameters to the Execution system
 
-use massa_time::MassaTime;
+use massa_time::{ClockCompensation, MassaTime};
+use std::path::PathBuf;
 
 /// Execution module configuration
 #[derive(Debug, Clone)]
@@ -17,10 +18,17 @@ pub struct ExecutionConfig {
     pub thread_count: u8,
     /// extra lag to add on the execution cursor to improve performance
     pub cursor_delay: MassaTime,
-    /// time compensation in milliseconds
-    pub clock_compensation: i64,
+    /// clock compensation, updated live by the clock synchronization
+    pub clock_compensation: ClockCompensation,
     /// genesis timestamp
     pub genesis_timestamp: MassaTime,
     /// period duration
     pub t0: MassaTime,
+    /// optional path of the on-disk store of final SC output events.
+    /// If `None`, final events are only kept in memory (see `max_final_events`)
+    pub event_db_path: Option<PathBuf>,
+    /// number of periods during which final events are kept in the on-disk store, unlimited if `None`
+    pub event_db_retention_periods: Option<u64>,
+    /// maximum size in bytes of the on-disk event store, unlimited if `None`
+    pub event_db_max_bytes: Option<u64>,
 }
@@ -15,7 +15,7 @@ impl Default for ExecutionConfig {
             max_async_gas: 10_000_000,
             thread_count: 2,
             cursor_delay: 0.into(),
-            clock_compensation: 0,
+            clock_compensation: Default::default(),
             genesis_timestamp: MassaTime::now().unwrap(),
             t0: 1000.into(),
         }
@@ -2,17 +2,24 @@
 
 //! This file defines utilities to mock the crate for testing purposes
 
-use crate::{ExecutionController, ExecutionError, ExecutionOutput, ReadOnlyExecutionRequest};
-use massa_hash::Hash;
+use crate::{
+    ExecutionController, ExecutionError, HistoricalLedgerEntry, ReadOnlyExecutionOutput,
+    ReadOnlyExecutionRequest,
+};
 use massa_ledger_exports::LedgerEntry;
-use massa_models::{api::EventFilter, output_event::SCOutputEvent, Address, BlockId, Slot};
+use massa_models::{
+    api::{EventFilter, SCOutputEventNotification},
+    output_event::SCOutputEvent,
+    Address, BlockId, Slot,
+};
 use std::{
-    collections::HashMap,
+    collections::{BTreeSet, HashMap},
     sync::{
         mpsc::{self, Receiver},
         Arc, Mutex,
     },
 };
+use tokio::sync::broadcast;
 
 /// List of possible messages coming from the mock.
 /// Each variant corresponds to a unique method in `ExecutionController`,
@@ -35,6 +42,15 @@ pub enum MockExecutionControllerMessage {
         /// response channel
         response_tx: mpsc::Sender<Vec<SCOutputEvent>>,
     },
+    /// get a datastore entry
+    GetDataEntry {
+        /// address
+        addr: Address,
+        /// datastore key
+        key: Vec<u8>,
+        /// response channel
+        response_tx: mpsc::Sender<(Option<Vec<u8>>, Option<Vec<u8>>)>,
+    },
     /// get full ledger entry
     GetFullLedgerEntry {
         /// address
@@ -47,7 +63,7 @@ pub enum MockExecutionControllerMessage {
         /// read only execution request
         req: ReadOnlyExecutionRequest,
         /// response channel
-        response_tx: mpsc::Sender<Result<ExecutionOutput, ExecutionError>>,
+        response_tx: mpsc::Sender<Result<ReadOnlyExecutionOutput, ExecutionError>>,
     },
 }
 
@@ -107,12 +123,41 @@ impl ExecutionController for MockExecutionController {
         response_rx.recv().unwrap()
     }
 
+    /// The mock never emits events: the returned subscription is already closed
+    fn subscribe_sc_output_events(&self) -> broadcast::Receiver<SCOutputEventNotification> {
+        broadcast::channel(1).1
+    }
+
+    fn get_final_slot(&self) -> Slot {
+        Slot::new(0, 0)
+    }
+
     fn get_final_and_active_data_entry(
         &self,
-        _addr: &Address,
-        _key: &Hash,
+        addr: &Address,
+        key: &[u8],
     ) -> (Option<Vec<u8>>, Option<Vec<u8>>) {
-        (None, None)
+        let (response_tx, response_rx) = mpsc::channel();
+        self.0
+            .lock()
+            .unwrap()
+            .send(MockExecutionControllerMessage::GetDataEntry {
+                addr: *addr,
+                key: key.to_vec(),
+                response_tx,
+            })
+            .unwrap();
+        response_rx.recv().unwrap()
+    }
+
+    fn get_final_and_active_datastore_keys(
+        &self,
+        _addr: &Address,
+        _prefix: &[u8],
+        _cursor: Option<&[u8]>,
+        _count: usize,
+    ) -> (BTreeSet<Vec<u8>>, BTreeSet<Vec<u8>>) {
+        (BTreeSet::new(), BTreeSet::new())
     }
 
     fn get_final_and_active_ledger_entry(
@@ -131,10 +176,19 @@ impl ExecutionController for MockExecutionController {
         response_rx.recv().unwrap()
     }
 
+    fn get_historical_ledger_entry(
+        &self,
+        _addr: &Address,
+        _datastore_keys: &[Vec<u8>],
+        _slot: Slot,
+    ) -> Result<HistoricalLedgerEntry, ExecutionError> {
+        Ok(HistoricalLedgerEntry::default())
+    }
+
     fn execute_readonly_request(
         &self,
         req: ReadOnlyExecutionRequest,
-    ) -> Result<ExecutionOutput, ExecutionError> {
+    ) -> Result<ReadOnlyExecutionOutput, ExecutionError> {
         let (response_tx, response_rx) = mpsc::channel();
         self.0
             .lock()
@@ -21,6 +21,9 @@ impl Default for ExecutionConfig {
             // reset genesis timestamp because we are in test mode that can take a while to process
             genesis_timestamp: MassaTime::now().expect("Impossible to reset the timestamp in test"),
             t0: 10.into(),
+            event_db_path: None,
+            event_db_retention_periods: None,
+            event_db_max_bytes: None,
         }
     }
 }
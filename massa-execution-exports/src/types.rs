@@ -5,6 +5,7 @@
 use crate::event_store::EventStore;
 use massa_final_state::StateChanges;
 use massa_models::{Address, Amount, BlockId, Slot};
+use std::collections::BTreeMap;
 
 /// structure describing the output of a single execution
 #[derive(Debug, Clone)]
@@ -17,6 +18,45 @@ pub struct ExecutionOutput {
     pub state_changes: StateChanges,
     /// events emitted by the execution step
     pub events: EventStore,
+    /// gas consumed by the operations executed in the slot
+    pub gas_used: u64,
+}
+
+/// structure describing the output of a read-only execution
+#[derive(Debug, Clone)]
+pub struct ReadOnlyExecutionOutput {
+    /// output of the execution: state changes it would have caused and emitted events
+    pub out: ExecutionOutput,
+    /// gas consumed by the execution
+    pub gas_cost: u64,
+    /// parallel balances before the execution of the addresses whose balance it changes
+    pub parallel_balances_before: BTreeMap<Address, Amount>,
+    /// trace of the calls made during the execution, rooted at the top of the initial call stack
+    pub call_trace: Option<ExecutionCallTrace>,
+}
+
+/// structure describing a call traced during a read-only execution
+#[derive(Debug, Clone)]
+pub struct ExecutionCallTrace {
+    /// Called address
+    pub address: Address,
+    /// Coins transferred to the called address
+    pub coins: Amount,
+    /// Gas consumed by the call, including its nested calls
+    pub gas_cost: u64,
+    /// Calls made from this call, in execution order
+    pub sub_calls: Vec<ExecutionCallTrace>,
+}
+
+/// structure describing a ledger entry at the output of a given final slot
+#[derive(Debug, Clone, Default)]
+pub struct HistoricalLedgerEntry {
+    /// Parallel balance, `None` if the ledger entry did not exist
+    pub parallel_balance: Option<Amount>,
+    /// Executable bytecode, `None` if the ledger entry did not exist
+    pub bytecode: Option<Vec<u8>>,
+    /// Queried datastore entries, the value is `None` if the entry did not exist
+    pub datastore: BTreeMap<Vec<u8>, Option<Vec<u8>>>,
 }
 
 /// structure describing different types of read-only execution request
@@ -45,7 +85,7 @@ pub enum ReadOnlyExecutionTarget {
         /// Target function
         target_func: String,
         /// Parameter to pass to the target function
-        parameter: String,
+        parameter: Vec<u8>,
     },
 }
 
@@ -63,7 +103,7 @@ pub struct ReadOnlyCallRequest {
     /// Target function
     pub target_func: String,
     /// Parameter to pass to the target function
-    pub parameter: String,
+    pub parameter: Vec<u8>,
 }
 
 /// Structure describing an element of the execution stack.
@@ -10,17 +10,24 @@
 use crate::speculative_async_pool::SpeculativeAsyncPool;
 use crate::speculative_ledger::SpeculativeLedger;
 use massa_async_pool::{AsyncMessage, AsyncMessageId};
-use massa_execution_exports::{EventStore, ExecutionError, ExecutionOutput, ExecutionStackElement};
+use massa_execution_exports::{
+    EventStore, ExecutionCallTrace, ExecutionError, ExecutionOutput, ExecutionStackElement,
+};
 use massa_final_state::{FinalState, StateChanges};
 use massa_hash::Hash;
 use massa_ledger_exports::LedgerChanges;
 use massa_models::{
+    bytecode_policy::{
+        BytecodePolicy, PendingBytecodeUpgrade, BYTECODE_POLICY_DATASTORE_KEY,
+        BYTECODE_UPGRADE_DATASTORE_KEY,
+    },
     output_event::{EventExecutionContext, SCOutputEvent},
     Address, Amount, BlockId, OperationId, Slot,
 };
 use parking_lot::RwLock;
 use rand::SeedableRng;
 use rand_xoshiro::Xoshiro256PlusPlus;
+use std::collections::BTreeSet;
 use std::sync::Arc;
 use tracing::debug;
 
@@ -96,6 +103,15 @@ pub(crate) struct ExecutionContext {
 
     /// operation id that originally caused this execution (if any)
     pub origin_operation_id: Option<OperationId>,
+
+    /// calls of a read-only execution that are not finished yet, the root call first
+    pub call_trace: Vec<ExecutionCallTrace>,
+
+    /// gas remaining when each unfinished call of `call_trace` started
+    pub call_trace_gas: Vec<u64>,
+
+    /// gas consumed by the operations executed so far in the slot
+    pub gas_used: u64,
 }
 
 impl ExecutionContext {
@@ -135,6 +151,9 @@ impl ExecutionContext {
             events: Default::default(),
             unsafe_rng: Xoshiro256PlusPlus::from_seed([0u8; 32]),
             origin_operation_id: Default::default(),
+            call_trace: Default::default(),
+            call_trace_gas: Default::default(),
+            gas_used: Default::default(),
         }
     }
 
@@ -200,6 +219,20 @@ impl ExecutionContext {
         // but not cryptographically secure (and that's ok because the internal state is exposed anyways)
         let unsafe_rng = Xoshiro256PlusPlus::from_seed(seed);
 
+        // trace the calls made from the top of the initial call stack,
+        // which starts with all the gas of the execution
+        let call_trace: Vec<_> = call_stack
+            .last()
+            .map(|element| ExecutionCallTrace {
+                address: element.address,
+                coins: element.coins,
+                gas_cost: 0,
+                sub_calls: Vec::new(),
+            })
+            .into_iter()
+            .collect();
+        let call_trace_gas = call_trace.iter().map(|_| max_gas).collect();
+
         // return readonly context
         ExecutionContext {
             max_gas,
@@ -208,6 +241,8 @@ impl ExecutionContext {
             stack: call_stack,
             read_only: true,
             unsafe_rng,
+            call_trace,
+            call_trace_gas,
             ..ExecutionContext::new(final_state, previous_changes)
         }
     }
@@ -309,6 +344,17 @@ impl ExecutionContext {
         self.stack.iter().map(|v| v.address).collect()
     }
 
+    /// Checks whether an address is the current call or its immediate caller.
+    /// Deeper callers are ignored, so that a contract called by an address
+    /// cannot act on its behalf when calling other contracts.
+    fn is_current_call_or_caller(&self, address: &Address) -> bool {
+        self.stack
+            .iter()
+            .rev()
+            .take(2)
+            .any(|element| &element.address == address)
+    }
+
     /// Checks whether the context currently grants write access to a given address
     pub fn has_write_rights_on(&self, addr: &Address) -> bool {
         self.stack
@@ -373,15 +419,27 @@ impl ExecutionContext {
     }
 
     /// gets the data from a datastore entry of an address if it exists in the speculative ledger, or returns None
-    pub fn get_data_entry(&self, address: &Address, key: &Hash) -> Option<Vec<u8>> {
+    pub fn get_data_entry(&self, address: &Address, key: &[u8]) -> Option<Vec<u8>> {
         self.speculative_ledger.get_data_entry(address, key)
     }
 
     /// checks if a datastore entry exists in the speculative ledger
-    pub fn has_data_entry(&self, address: &Address, key: &Hash) -> bool {
+    pub fn has_data_entry(&self, address: &Address, key: &[u8]) -> bool {
         self.speculative_ledger.has_data_entry(address, key)
     }
 
+    /// gets a page of the datastore keys of an address starting with a prefix, in lexicographic order
+    pub fn get_datastore_keys(
+        &self,
+        address: &Address,
+        prefix: &[u8],
+        cursor: Option<&[u8]>,
+        count: usize,
+    ) -> BTreeSet<Vec<u8>> {
+        self.speculative_ledger
+            .get_datastore_keys(address, prefix, cursor, count)
+    }
+
     /// gets the effective parallel balance of an address
     pub fn get_parallel_balance(&self, address: &Address) -> Option<Amount> {
         self.speculative_ledger.get_parallel_balance(address)
@@ -398,7 +456,7 @@ impl ExecutionContext {
     pub fn set_data_entry(
         &mut self,
         address: &Address,
-        key: Hash,
+        key: Vec<u8>,
         data: Vec<u8>,
     ) -> Result<(), ExecutionError> {
         // check access right
@@ -409,6 +467,9 @@ impl ExecutionContext {
             )));
         }
 
+        // check that the bytecode policy allows the write
+        self.check_datastore_write(address, &key, Some(data.as_slice()))?;
+
         // set data entry
         self.speculative_ledger.set_data_entry(address, key, data)
     }
@@ -424,7 +485,7 @@ impl ExecutionContext {
     pub fn append_data_entry(
         &mut self,
         address: &Address,
-        key: Hash,
+        key: Vec<u8>,
         data: Vec<u8>,
     ) -> Result<(), ExecutionError> {
         // check access right
@@ -441,7 +502,7 @@ impl ExecutionContext {
             .get_data_entry(address, &key)
             .ok_or_else(|| {
                 ExecutionError::RuntimeError(format!(
-                    "appending to the datastore of address {} failed: entry {} not found",
+                    "appending to the datastore of address {} failed: entry {:?} not found",
                     address, key
                 ))
             })?;
@@ -449,6 +510,9 @@ impl ExecutionContext {
         // append data
         res_data.extend(data);
 
+        // check that the bytecode policy allows the write
+        self.check_datastore_write(address, &key, Some(res_data.as_slice()))?;
+
         // set data entry
         self.speculative_ledger
             .set_data_entry(address, key, res_data)
@@ -463,7 +527,7 @@ impl ExecutionContext {
     pub fn delete_data_entry(
         &mut self,
         address: &Address,
-        key: &Hash,
+        key: &[u8],
     ) -> Result<(), ExecutionError> {
         // check access right
         if !self.has_write_rights_on(address) {
@@ -473,6 +537,9 @@ impl ExecutionContext {
             )));
         }
 
+        // check that the bytecode policy allows the deletion
+        self.check_datastore_write(address, key, None)?;
+
         // delete entry
         self.speculative_ledger.delete_data_entry(address, key)
     }
@@ -526,6 +593,51 @@ impl ExecutionContext {
         }
     }
 
+    /// Adds the gas consumed by an operation to the gas used in the slot
+    pub fn add_gas_used(&mut self, gas: u64) {
+        self.gas_used = self.gas_used.saturating_add(gas);
+    }
+
+    /// Starts tracing a call made during a read-only execution.
+    /// Calls are not traced outside of read-only executions.
+    ///
+    /// # Arguments
+    /// * `address`: the called address
+    /// * `coins`: the coins transferred to the called address
+    /// * `remaining_gas`: the gas remaining when the call starts
+    pub fn start_call_trace(&mut self, address: Address, coins: Amount, remaining_gas: u64) {
+        if self.read_only {
+            self.call_trace.push(ExecutionCallTrace {
+                address,
+                coins,
+                gas_cost: 0,
+                sub_calls: Vec::new(),
+            });
+            self.call_trace_gas.push(remaining_gas);
+        }
+    }
+
+    /// Finishes tracing the latest call started during a read-only execution.
+    /// Its gas cost is the gas consumed between its start and its end, including its nested calls.
+    /// A nested call is attached to its caller, the root call is kept in the trace.
+    ///
+    /// # Arguments
+    /// * `remaining_gas`: the gas remaining when the call finishes
+    pub fn finish_call_trace(&mut self, remaining_gas: u64) {
+        if let (Some(call), Some(start_gas)) =
+            (self.call_trace.last_mut(), self.call_trace_gas.pop())
+        {
+            call.gas_cost = start_gas.saturating_sub(remaining_gas);
+        }
+        if self.call_trace.len() > 1 {
+            if let Some(call) = self.call_trace.pop() {
+                if let Some(caller) = self.call_trace.last_mut() {
+                    caller.sub_calls.push(call);
+                }
+            }
+        }
+    }
+
     /// Finishes a slot and generates the execution output.
     /// Settles emitted asynchronous messages, reimburse the senders of deleted messages.
     /// Moves the output of the execution out of the context,
@@ -550,12 +662,17 @@ impl ExecutionContext {
             block_id: std::mem::take(&mut self.opt_block_id),
             state_changes,
             events: std::mem::take(&mut self.events),
+            gas_used: std::mem::take(&mut self.gas_used),
         }
     }
 
     /// Sets a bytecode for an address in the speculative ledger.
     /// Fail if the address is absent from the ledger.
     ///
+    /// Replacing an existing bytecode is subject to the bytecode policy of the address.
+    /// Under a timelock, the first call only schedules the upgrade,
+    /// and setting the same bytecode again once the timelock expired applies it.
+    ///
     /// # Arguments
     /// * address: the address of the ledger entry
     /// * data: the bytecode to set
@@ -572,8 +689,132 @@ impl ExecutionContext {
             )));
         }
 
-        // set data entry
-        self.speculative_ledger.set_bytecode(address, bytecode)
+        // setting a first bytecode is always allowed
+        let is_upgrade = self
+            .get_bytecode(address)
+            .map_or(false, |current| !current.is_empty());
+        if !is_upgrade {
+            return self.speculative_ledger.set_bytecode(address, bytecode);
+        }
+
+        // check the bytecode policy
+        let bytecode_hash = Hash::compute_from(&bytecode);
+        match self.get_bytecode_policy(address)? {
+            BytecodePolicy::Upgradeable => {}
+            BytecodePolicy::Immutable => {
+                return Err(ExecutionError::RuntimeError(format!(
+                    "the bytecode of address {} is immutable",
+                    address
+                )));
+            }
+            BytecodePolicy::Admin(admin) => {
+                if !self.is_current_call_or_caller(&admin) {
+                    return Err(ExecutionError::RuntimeError(format!(
+                        "the bytecode of address {} can only be upgraded by its admin {}",
+                        address, admin
+                    )));
+                }
+            }
+            BytecodePolicy::Timelock(delay) => {
+                let pending = self
+                    .get_data_entry(address, BYTECODE_UPGRADE_DATASTORE_KEY)
+                    .map(|value| PendingBytecodeUpgrade::from_bytes(&value))
+                    .transpose()
+                    .map_err(|err| {
+                        ExecutionError::RuntimeError(format!(
+                            "invalid pending bytecode upgrade of address {}: {}",
+                            address, err
+                        ))
+                    })?;
+                match pending {
+                    Some(pending) if pending.bytecode_hash == bytecode_hash => {
+                        if self.slot.period < pending.activation_period {
+                            return Err(ExecutionError::RuntimeError(format!(
+                                "the bytecode upgrade of address {} is timelocked until period {}",
+                                address, pending.activation_period
+                            )));
+                        }
+                        self.speculative_ledger
+                            .delete_data_entry(address, BYTECODE_UPGRADE_DATASTORE_KEY)?;
+                    }
+                    _ => {
+                        // schedule the upgrade, replacing any other pending one
+                        let pending = PendingBytecodeUpgrade {
+                            activation_period: self.slot.period.saturating_add(delay),
+                            bytecode_hash,
+                        };
+                        self.speculative_ledger.set_data_entry(
+                            address,
+                            BYTECODE_UPGRADE_DATASTORE_KEY.to_vec(),
+                            pending.to_bytes(),
+                        )?;
+                        return self.generate_event(format!(
+                            "bytecode upgrade of address {} to {} scheduled at period {}",
+                            address, bytecode_hash, pending.activation_period
+                        ));
+                    }
+                }
+            }
+        }
+
+        // set the bytecode
+        self.speculative_ledger.set_bytecode(address, bytecode)?;
+        self.generate_event(format!(
+            "bytecode of address {} upgraded to {}",
+            address, bytecode_hash
+        ))
+    }
+
+    /// Gets the bytecode policy of an address from its datastore
+    fn get_bytecode_policy(&self, address: &Address) -> Result<BytecodePolicy, ExecutionError> {
+        BytecodePolicy::from_datastore_value(
+            self.get_data_entry(address, BYTECODE_POLICY_DATASTORE_KEY)
+                .as_deref(),
+        )
+        .map_err(|err| {
+            ExecutionError::RuntimeError(format!(
+                "invalid bytecode policy of address {}: {}",
+                address, err
+            ))
+        })
+    }
+
+    /// Checks that writing or deleting (`value` being `None`) a datastore entry is allowed by the bytecode policy.
+    /// The policy can only be changed as the current one allows it,
+    /// and the pending upgrade under a timelock is only written by `set_bytecode`.
+    fn check_datastore_write(
+        &self,
+        address: &Address,
+        key: &[u8],
+        value: Option<&[u8]>,
+    ) -> Result<(), ExecutionError> {
+        if key == BYTECODE_UPGRADE_DATASTORE_KEY {
+            return Err(ExecutionError::RuntimeError(format!(
+                "the pending bytecode upgrade of address {} can only be changed by setting its bytecode",
+                address
+            )));
+        }
+        if key != BYTECODE_POLICY_DATASTORE_KEY {
+            return Ok(());
+        }
+        let new_policy = BytecodePolicy::from_datastore_value(value).map_err(|err| {
+            ExecutionError::RuntimeError(format!(
+                "invalid bytecode policy for address {}: {}",
+                address, err
+            ))
+        })?;
+        let policy = self.get_bytecode_policy(address)?;
+        let by_admin = match policy {
+            BytecodePolicy::Admin(admin) => self.is_current_call_or_caller(&admin),
+            _ => false,
+        };
+        if !policy.can_change_to(&new_policy, by_admin) {
+            return Err(ExecutionError::RuntimeError(format!(
+                "the bytecode policy of address {} cannot be changed from {} to {}",
+                address, policy, new_policy
+            )));
+        }
+        Ok(())
     }
 
     /// Emits an execution event to be stored.
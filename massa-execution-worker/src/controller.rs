@@ -6,18 +6,18 @@
 use crate::execution::ExecutionState;
 use crate::request_queue::{RequestQueue, RequestWithResponseSender};
 use massa_execution_exports::{
-    ExecutionConfig, ExecutionController, ExecutionError, ExecutionManager, ExecutionOutput,
-    ReadOnlyExecutionRequest,
+    ExecutionConfig, ExecutionController, ExecutionError, ExecutionManager, HistoricalLedgerEntry,
+    ReadOnlyExecutionOutput, ReadOnlyExecutionRequest,
 };
-use massa_hash::Hash;
 use massa_ledger_exports::LedgerEntry;
-use massa_models::api::EventFilter;
+use massa_models::api::{EventFilter, SCOutputEventNotification};
 use massa_models::output_event::SCOutputEvent;
 use massa_models::Address;
 use massa_models::{BlockId, Slot};
 use parking_lot::{Condvar, Mutex, RwLock};
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::sync::Arc;
+use tokio::sync::broadcast;
 use tracing::info;
 
 /// structure used to communicate with execution thread
@@ -29,7 +29,7 @@ pub(crate) struct ExecutionInputData {
     /// new blockclique (if there is a new one), blocks indexed by slot
     pub new_blockclique: Option<HashMap<Slot, BlockId>>,
     /// queue for read-only execution requests and response MPSCs to send back their outputs
-    pub readonly_requests: RequestQueue<ReadOnlyExecutionRequest, ExecutionOutput>,
+    pub readonly_requests: RequestQueue<ReadOnlyExecutionRequest, ReadOnlyExecutionOutput>,
 }
 
 impl ExecutionInputData {
@@ -95,6 +95,16 @@ impl ExecutionController for ExecutionControllerImpl {
             .get_filtered_sc_output_event(filter)
     }
 
+    /// Subscribe to the SC output events emitted by speculative and final executions
+    fn subscribe_sc_output_events(&self) -> broadcast::Receiver<SCOutputEventNotification> {
+        self.execution_state.read().subscribe_sc_output_events()
+    }
+
+    /// Get the slot at the output of which the final execution state is attached
+    fn get_final_slot(&self) -> Slot {
+        self.execution_state.read().final_cursor
+    }
+
     /// Get a copy of a single datastore entry with its final and active values
     ///
     /// # Return value
@@ -102,13 +112,29 @@ impl ExecutionController for ExecutionControllerImpl {
     fn get_final_and_active_data_entry(
         &self,
         addr: &Address,
-        key: &Hash,
+        key: &[u8],
     ) -> (Option<Vec<u8>>, Option<Vec<u8>>) {
         self.execution_state
             .read()
             .get_final_and_active_data_entry(addr, key)
     }
 
+    /// Get a page of the datastore keys of an address starting with a prefix, with their final and active values
+    ///
+    /// # Return value
+    /// * `(final_keys, active_keys)`
+    fn get_final_and_active_datastore_keys(
+        &self,
+        addr: &Address,
+        prefix: &[u8],
+        cursor: Option<&[u8]>,
+        count: usize,
+    ) -> (BTreeSet<Vec<u8>>, BTreeSet<Vec<u8>>) {
+        self.execution_state
+            .read()
+            .get_final_and_active_datastore_keys(addr, prefix, cursor, count)
+    }
+
     /// Get a copy of a full ledger entry with its final and active values
     ///
     /// # Return value
@@ -122,12 +148,24 @@ impl ExecutionController for ExecutionControllerImpl {
             .get_final_and_active_ledger_entry(addr)
     }
 
+    /// Get the balance, bytecode and some datastore entries of an address at the output of a final slot
+    fn get_historical_ledger_entry(
+        &self,
+        addr: &Address,
+        datastore_keys: &[Vec<u8>],
+        slot: Slot,
+    ) -> Result<HistoricalLedgerEntry, ExecutionError> {
+        self.execution_state
+            .read()
+            .get_historical_ledger_entry(addr, datastore_keys, slot)
+    }
+
     /// Executes a read-only request
     /// Read-only requests do not modify consensus state
     fn execute_readonly_request(
         &self,
         req: ReadOnlyExecutionRequest,
-    ) -> Result<ExecutionOutput, ExecutionError> {
+    ) -> Result<ReadOnlyExecutionOutput, ExecutionError> {
         let resp_rx = {
             let mut input_data = self.input_data.1.lock();
 
@@ -140,7 +178,7 @@ impl ExecutionController for ExecutionControllerImpl {
 
             // prepare the channel to send back the result of the read-only execution
             let (resp_tx, resp_rx) =
-                std::sync::mpsc::channel::<Result<ExecutionOutput, ExecutionError>>();
+                std::sync::mpsc::channel::<Result<ReadOnlyExecutionOutput, ExecutionError>>();
 
             // append the request to the queue of input read-only requests
             input_data
@@ -0,0 +1,294 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! This module provides an optional on-disk store for final execution events.
+//! Unlike the in-memory `EventStore`, it survives restarts and is indexed by
+//! slot, emitter address, original caller address and origin operation id.
+//!
+//! Events are stored in a RocksDB instance with the following column families:
+//! * `events`: event key -> JSON serialized event
+//! * `events_by_emitter`, `events_by_caller`, `events_by_operation`:
+//!   index prefix (address or operation id bytes) followed by the event key -> empty value
+//! * `metadata`: total size of the stored events in bytes
+//!
+//! Event keys are the slot period (big endian), the slot thread and the index of the event in the slot (big endian),
+//! so that iterating over keys returns events in chronological order.
+
+use massa_models::api::EventFilter;
+use massa_models::output_event::SCOutputEvent;
+use massa_models::Slot;
+use rocksdb::{
+    ColumnFamilyDescriptor, Direction, IteratorMode, Options, ReadOptions, WriteBatch, DB,
+};
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use tracing::warn;
+
+const EVENTS_CF: &str = "events";
+const EVENTS_BY_EMITTER_CF: &str = "events_by_emitter";
+const EVENTS_BY_CALLER_CF: &str = "events_by_caller";
+const EVENTS_BY_OPERATION_CF: &str = "events_by_operation";
+const METADATA_CF: &str = "metadata";
+const OPEN_ERROR: &str = "critical: rocksdb open operation failed";
+const CRUD_ERROR: &str = "critical: rocksdb crud operation failed";
+const CF_ERROR: &str = "critical: rocksdb column family operation failed";
+const SIZE_KEY: &[u8; 1] = b"b";
+
+/// Size of a serialized slot: period (8 bytes) and thread (1 byte)
+const SLOT_KEY_SIZE: usize = 9;
+
+/// Size of an event key: slot and index of the event in the slot (8 bytes)
+const EVENT_KEY_SIZE: usize = SLOT_KEY_SIZE + 8;
+
+/// Key prefix of the events of a slot, ordered chronologically
+fn slot_key(slot: &Slot) -> Vec<u8> {
+    [&slot.period.to_be_bytes()[..], &[slot.thread]].concat()
+}
+
+/// Key of an event, ordered chronologically
+fn event_key(event: &SCOutputEvent) -> Vec<u8> {
+    [
+        &slot_key(&event.context.slot)[..],
+        &event.context.index_in_slot.to_be_bytes()[..],
+    ]
+    .concat()
+}
+
+/// For a given start prefix (inclusive), returns the correct end prefix (non-inclusive).
+/// Returns `None` if there is no bounded limit.
+fn end_prefix(prefix: &[u8]) -> Option<Vec<u8>> {
+    let mut end_range = prefix.to_vec();
+    while let Some(0xff) = end_range.last() {
+        end_range.pop();
+    }
+    if let Some(byte) = end_range.last_mut() {
+        *byte += 1;
+        Some(end_range)
+    } else {
+        None
+    }
+}
+
+/// Index entries `(column family, key)` of an event
+fn index_keys(event: &SCOutputEvent, key: &[u8]) -> Vec<(&'static str, Vec<u8>)> {
+    let mut keys = Vec::new();
+    if let Some(emitter) = event.context.call_stack.front() {
+        keys.push((
+            EVENTS_BY_EMITTER_CF,
+            [&emitter.to_bytes()[..], key].concat(),
+        ));
+    }
+    if let Some(caller) = event.context.call_stack.back() {
+        keys.push((EVENTS_BY_CALLER_CF, [&caller.to_bytes()[..], key].concat()));
+    }
+    if let Some(operation_id) = event.context.origin_operation_id {
+        keys.push((
+            EVENTS_BY_OPERATION_CF,
+            [&operation_id.to_bytes()[..], key].concat(),
+        ));
+    }
+    keys
+}
+
+/// On-disk store of final execution events
+pub(crate) struct EventDB {
+    /// RocksDB instance
+    db: DB,
+    /// events emitted more than this number of periods before the latest stored slot are pruned
+    retention_periods: Option<u64>,
+    /// oldest events are pruned while the total size of the stored events exceeds this number of bytes
+    max_bytes: Option<u64>,
+}
+
+impl EventDB {
+    /// Open or create the event store
+    ///
+    /// # Arguments
+    /// * `path`: path to the RocksDB directory
+    /// * `retention_periods`: optional retention policy in periods
+    /// * `max_bytes`: optional retention policy in bytes
+    pub fn new(path: PathBuf, retention_periods: Option<u64>, max_bytes: Option<u64>) -> Self {
+        let mut db_opts = Options::default();
+        db_opts.create_if_missing(true);
+        db_opts.create_missing_column_families(true);
+
+        let db = DB::open_cf_descriptors(
+            &db_opts,
+            path,
+            vec![
+                ColumnFamilyDescriptor::new(EVENTS_CF, Options::default()),
+                ColumnFamilyDescriptor::new(EVENTS_BY_EMITTER_CF, Options::default()),
+                ColumnFamilyDescriptor::new(EVENTS_BY_CALLER_CF, Options::default()),
+                ColumnFamilyDescriptor::new(EVENTS_BY_OPERATION_CF, Options::default()),
+                ColumnFamilyDescriptor::new(METADATA_CF, Options::default()),
+            ],
+        )
+        .expect(OPEN_ERROR);
+
+        EventDB {
+            db,
+            retention_periods,
+            max_bytes,
+        }
+    }
+
+    /// Total size in bytes of the stored events
+    fn get_size(&self) -> u64 {
+        let handle = self.db.cf_handle(METADATA_CF).expect(CF_ERROR);
+        self.db
+            .get_cf(handle, SIZE_KEY)
+            .expect(CRUD_ERROR)
+            .and_then(|bytes| bytes.try_into().ok().map(u64::from_be_bytes))
+            .unwrap_or(0)
+    }
+
+    /// Store the events of a final slot, then apply the retention policy
+    ///
+    /// # Arguments
+    /// * `slot`: the final slot at which the events were emitted
+    /// * `events`: the events emitted at that slot
+    pub fn insert_events(&self, slot: Slot, events: VecDeque<SCOutputEvent>) {
+        let mut size = self.get_size();
+        let mut batch = WriteBatch::default();
+        let handle = self.db.cf_handle(EVENTS_CF).expect(CF_ERROR);
+        for event in events {
+            let value = match serde_json::to_vec(&event) {
+                Ok(value) => value,
+                Err(err) => {
+                    warn!("could not serialize event for the event store: {}", err);
+                    continue;
+                }
+            };
+            let key = event_key(&event);
+            // an event can be stored again if the node re-executes a slot after a restart
+            if let Some(previous) = self.db.get_cf(handle, &key).expect(CRUD_ERROR) {
+                size = size.saturating_sub(previous.len() as u64);
+            }
+            for (cf, index_key) in index_keys(&event, &key) {
+                batch.put_cf(self.db.cf_handle(cf).expect(CF_ERROR), index_key, b"");
+            }
+            size = size.saturating_add(value.len() as u64);
+            batch.put_cf(handle, key, value);
+        }
+        self.set_size(size, &mut batch);
+        self.db.write(batch).expect(CRUD_ERROR);
+        self.prune(slot);
+    }
+
+    /// Write the total size of the stored events in the batch
+    fn set_size(&self, size: u64, batch: &mut WriteBatch) {
+        let handle = self.db.cf_handle(METADATA_CF).expect(CF_ERROR);
+        batch.put_cf(handle, SIZE_KEY, size.to_be_bytes());
+    }
+
+    /// Remove the oldest events until the retention policy is satisfied
+    ///
+    /// # Arguments
+    /// * `last_slot`: latest final slot
+    fn prune(&self, last_slot: Slot) {
+        let min_period = self
+            .retention_periods
+            .map(|periods| last_slot.period.saturating_sub(periods));
+        let mut size = self.get_size();
+        let mut batch = WriteBatch::default();
+        let handle = self.db.cf_handle(EVENTS_CF).expect(CF_ERROR);
+        for (key, value) in self.db.iterator_cf(handle, IteratorMode::Start) {
+            let too_old = match min_period {
+                Some(min_period) => {
+                    u64::from_be_bytes(key[..8].try_into().expect(CRUD_ERROR)) < min_period
+                }
+                None => false,
+            };
+            let too_big = match self.max_bytes {
+                Some(max_bytes) => size > max_bytes,
+                None => false,
+            };
+            if !too_old && !too_big {
+                break;
+            }
+            if let Ok(event) = serde_json::from_slice::<SCOutputEvent>(&value) {
+                for (cf, index_key) in index_keys(&event, &key) {
+                    batch.delete_cf(self.db.cf_handle(cf).expect(CF_ERROR), index_key);
+                }
+            }
+            size = size.saturating_sub(value.len() as u64);
+            batch.delete_cf(handle, key);
+        }
+        self.set_size(size, &mut batch);
+        self.db.write(batch).expect(CRUD_ERROR);
+    }
+
+    /// Get the keys of the events matching an index prefix, within the slot range of the filter
+    fn get_indexed_keys(&self, cf: &str, prefix: &[u8], filter: &EventFilter) -> Vec<Vec<u8>> {
+        let handle = self.db.cf_handle(cf).expect(CF_ERROR);
+        let start = match filter.start {
+            Some(start) => [prefix, &slot_key(&start)[..]].concat(),
+            None => prefix.to_vec(),
+        };
+        let mut opt = ReadOptions::default();
+        match filter.end {
+            Some(end) => opt.set_iterate_upper_bound([prefix, &slot_key(&end)[..]].concat()),
+            None => {
+                if let Some(end) = end_prefix(prefix) {
+                    opt.set_iterate_upper_bound(end);
+                }
+            }
+        }
+        self.db
+            .iterator_cf_opt(handle, opt, IteratorMode::From(&start, Direction::Forward))
+            .filter_map(|(key, _)| {
+                key.strip_prefix(prefix)
+                    .filter(|event_key| event_key.len() == EVENT_KEY_SIZE)
+                    .map(|event_key| event_key.to_vec())
+            })
+            .collect()
+    }
+
+    /// Get events optionally filtered by:
+    /// * start slot
+    /// * end slot
+    /// * emitter address
+    /// * original caller address
+    /// * operation id
+    ///
+    /// The most selective available index is used to find candidate events.
+    pub fn get_filtered_sc_output_event(&self, filter: &EventFilter) -> Vec<SCOutputEvent> {
+        let handle = self.db.cf_handle(EVENTS_CF).expect(CF_ERROR);
+        let events: Vec<SCOutputEvent> = if let Some(operation_id) = filter.original_operation_id {
+            self.get_events(self.get_indexed_keys(
+                EVENTS_BY_OPERATION_CF,
+                operation_id.to_bytes(),
+                filter,
+            ))
+        } else if let Some(emitter) = filter.emitter_address {
+            self.get_events(self.get_indexed_keys(EVENTS_BY_EMITTER_CF, emitter.to_bytes(), filter))
+        } else if let Some(caller) = filter.original_caller_address {
+            self.get_events(self.get_indexed_keys(EVENTS_BY_CALLER_CF, caller.to_bytes(), filter))
+        } else {
+            let mut opt = ReadOptions::default();
+            if let Some(end) = filter.end {
+                opt.set_iterate_upper_bound(slot_key(&end));
+            }
+            let start = filter
+                .start
+                .map(|start| slot_key(&start))
+                .unwrap_or_default();
+            self.db
+                .iterator_cf_opt(handle, opt, IteratorMode::From(&start, Direction::Forward))
+                .filter_map(|(_, value)| serde_json::from_slice(&value).ok())
+                .collect()
+        };
+        events
+            .into_iter()
+            .filter(|event| filter.matches(event))
+            .collect()
+    }
+
+    /// Read events by key, ignoring missing ones
+    fn get_events(&self, keys: Vec<Vec<u8>>) -> Vec<SCOutputEvent> {
+        let handle = self.db.cf_handle(EVENTS_CF).expect(CF_ERROR);
+        keys.into_iter()
+            .filter_map(|key| self.db.get_cf(handle, key).expect(CRUD_ERROR))
+            .filter_map(|value| serde_json::from_slice(&value).ok())
+            .collect()
+    }
+}
@@ -9,20 +9,22 @@
 //! * the output of the execution is extracted from the context
 
 use crate::context::ExecutionContext;
+use crate::event_db::EventDB;
 use crate::interface_impl::InterfaceImpl;
 use massa_async_pool::AsyncMessage;
 use massa_execution_exports::{
     EventStore, ExecutionConfig, ExecutionError, ExecutionOutput, ExecutionStackElement,
-    ReadOnlyExecutionRequest, ReadOnlyExecutionTarget,
+    HistoricalLedgerEntry, ReadOnlyExecutionOutput, ReadOnlyExecutionRequest,
+    ReadOnlyExecutionTarget,
 };
-use massa_final_state::{FinalState, StateChanges};
-use massa_hash::Hash;
-
+use massa_final_state::{FinalState, FinalStateError, StateChanges};
 use massa_ledger_exports::{
     Applicable, LedgerEntry, LedgerEntryUpdate, SetOrDelete, SetOrKeep, SetUpdateOrDelete,
 };
-use massa_models::api::EventFilter;
+use massa_models::api::{EventFilter, SCOutputEventNotification};
+use massa_models::constants::CHANNEL_SIZE;
 use massa_models::output_event::SCOutputEvent;
+use massa_models::sc_args::param_to_runtime_string;
 use massa_models::signed::Signable;
 use massa_models::{Address, BlockId, OperationId, OperationType, SignedOperation};
 use massa_models::{Amount, Slot};
@@ -31,9 +33,10 @@ use massa_storage::Storage;
 use parking_lot::{Mutex, RwLock};
 use std::usize;
 use std::{
-    collections::{HashMap, VecDeque},
+    collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
     sync::Arc,
 };
+use tokio::sync::broadcast;
 use tracing::debug;
 
 /// Used to acquire a lock on the execution context
@@ -59,6 +62,10 @@ pub(crate) struct ExecutionState {
     pub final_cursor: Slot,
     // store containing execution events that became final
     final_events: EventStore,
+    // optional on-disk store replacing `final_events` when configured
+    final_events_db: Option<EventDB>,
+    // broadcast channel notifying subscribers of emitted events
+    event_notifier: broadcast::Sender<SCOutputEventNotification>,
     // final state with atomic R/W access
     final_state: Arc<RwLock<FinalState>>,
     // execution context (see documentation in context.rs)
@@ -106,6 +113,15 @@ impl ExecutionState {
             execution_context.clone(),
         ));
 
+        // open the on-disk final event store if configured
+        let final_events_db = config.event_db_path.clone().map(|path| {
+            EventDB::new(
+                path,
+                config.event_db_retention_periods,
+                config.event_db_max_bytes,
+            )
+        });
+
         // build the execution state
         ExecutionState {
             config,
@@ -116,6 +132,8 @@ impl ExecutionState {
             active_history: Default::default(),
             // empty final event store: it is not recovered through bootstrap
             final_events: Default::default(),
+            final_events_db,
+            event_notifier: broadcast::channel(CHANNEL_SIZE).0,
             // no active slots executed yet: set active_cursor to the last final block
             active_cursor: last_final_slot,
             final_cursor: last_final_slot,
@@ -136,7 +154,7 @@ impl ExecutionState {
     ///
     /// # Arguments
     /// * `exec_ou`t: execution output to apply
-    pub fn apply_final_execution_output(&mut self, exec_out: ExecutionOutput) {
+    pub fn apply_final_execution_output(&mut self, mut exec_out: ExecutionOutput) {
         if self.final_cursor >= exec_out.slot {
             panic!("attempting to apply a final execution output at or before the current final_cursor");
         }
@@ -154,9 +172,15 @@ impl ExecutionState {
             self.active_cursor = self.final_cursor;
         }
 
-        // append generated events to the final event store
-        self.final_events.extend(exec_out.events);
-        self.final_events.prune(self.config.max_final_events);
+        // notify subscribers, then append generated events to the final event store
+        self.notify_events(&exec_out.events, true);
+        match &self.final_events_db {
+            Some(db) => db.insert_events(exec_out.slot, exec_out.events.take()),
+            None => {
+                self.final_events.extend(exec_out.events);
+                self.final_events.prune(self.config.max_final_events);
+            }
+        }
     }
 
     /// Applies an execution output to the active (non-final) state
@@ -175,10 +199,36 @@ impl ExecutionState {
         // update active cursor to reflect the new latest active slot
         self.active_cursor = exec_out.slot;
 
+        // notify subscribers of the speculative events
+        self.notify_events(&exec_out.events, false);
+
         // add the execution output at the end of the output history
         self.active_history.push_back(exec_out);
     }
 
+    /// Sends events to the subscribers of the event stream
+    ///
+    /// # Arguments
+    /// * `events`: the emitted events
+    /// * `is_final`: whether the events were emitted by a final execution
+    fn notify_events(&self, events: &EventStore, is_final: bool) {
+        // a send error only means that there is no subscriber
+        if self.event_notifier.receiver_count() == 0 {
+            return;
+        }
+        for event in events.iter() {
+            let _ = self.event_notifier.send(SCOutputEventNotification {
+                is_final,
+                event: event.clone(),
+            });
+        }
+    }
+
+    /// Subscribe to the events emitted by speculative and final executions
+    pub fn subscribe_sc_output_events(&self) -> broadcast::Receiver<SCOutputEventNotification> {
+        self.event_notifier.subscribe()
+    }
+
     /// Clear the whole execution history,
     /// deleting caches on executed non-final slots.
     pub fn clear_history(&mut self) {
@@ -303,7 +353,7 @@ impl ExecutionState {
         &self,
         slot: Slot,
         addr: &Address,
-        key: &Hash,
+        key: &[u8],
     ) -> HistorySearchResult<Vec<u8>> {
         self.verify_active_slot(slot);
 
@@ -371,11 +421,15 @@ impl ExecutionState {
         match &operation.content.op {
             OperationType::ExecuteSC { .. } => {}
             OperationType::CallSC { .. } => {}
-            _ => return Ok(()),
+            // transfers, batched or not, and roll operations only change the consensus ledger
+            OperationType::Transaction { .. }
+            | OperationType::BatchTransaction { .. }
+            | OperationType::RollBuy { .. }
+            | OperationType::RollSell { .. } => return Ok(()),
         };
 
         // get the operation's sender address
-        let sender_addr = Address::from_public_key(&operation.content.sender_public_key);
+        let sender_addr = operation.content.get_sender_address();
 
         // get operation ID
         // TODO have operation_id contained in the Operation object in the future to avoid recomputation
@@ -484,16 +538,23 @@ impl ExecutionState {
 
         // run the VM on the bytecode contained in the operation
         let run_result = massa_sc_runtime::run_main(bytecode, *max_gas, &*self.execution_interface);
-        if let Err(err) = run_result {
-            // there was an error during bytecode execution:
-            // cancel the effects of the execution by resetting the context to the previously saved snapshot
-            let mut context = context_guard!(self);
-            context.origin_operation_id = None;
-            context.reset_to_snapshot(context_snapshot);
-            return Err(ExecutionError::RuntimeError(format!(
-                "bytecode execution error: {}",
-                err
-            )));
+        match run_result {
+            Ok(remaining_gas) => {
+                context_guard!(self).add_gas_used(max_gas.saturating_sub(remaining_gas));
+            }
+            Err(err) => {
+                // there was an error during bytecode execution:
+                // cancel the effects of the execution by resetting the context to the previously saved snapshot
+                let mut context = context_guard!(self);
+                context.origin_operation_id = None;
+                context.reset_to_snapshot(context_snapshot);
+                // the runtime does not report the gas consumed by a failed execution
+                context.add_gas_used(*max_gas);
+                return Err(ExecutionError::RuntimeError(format!(
+                    "bytecode execution error: {}",
+                    err
+                )));
+            }
         }
 
         Ok(())
@@ -628,19 +689,26 @@ impl ExecutionState {
             &bytecode,
             max_gas,
             target_func,
-            param,
+            &param_to_runtime_string(param),
             &*self.execution_interface,
         );
-        if let Err(err) = run_result {
-            // there was an error during bytecode execution:
-            // cancel the effects of the execution by resetting the context to the previously saved snapshot
-            let mut context = context_guard!(self);
-            context.origin_operation_id = None;
-            context.reset_to_snapshot(context_snapshot);
-            return Err(ExecutionError::RuntimeError(format!(
-                "bytecode execution error: {}",
-                err
-            )));
+        match run_result {
+            Ok(remaining_gas) => {
+                context_guard!(self).add_gas_used(max_gas.saturating_sub(remaining_gas));
+            }
+            Err(err) => {
+                // there was an error during bytecode execution:
+                // cancel the effects of the execution by resetting the context to the previously saved snapshot
+                let mut context = context_guard!(self);
+                context.origin_operation_id = None;
+                context.reset_to_snapshot(context_snapshot);
+                // the runtime does not report the gas consumed by a failed execution
+                context.add_gas_used(max_gas);
+                return Err(ExecutionError::RuntimeError(format!(
+                    "bytecode execution error: {}",
+                    err
+                )));
+            }
         }
 
         Ok(())
@@ -657,19 +725,13 @@ impl ExecutionState {
         message: AsyncMessage,
         bytecode: Option<Vec<u8>>,
     ) -> Result<(), ExecutionError> {
-        // If there is no target bytecode or if message data is invalid,
-        // directly reimburse sender with coins and quit
-        let (bytecode, data) = match (bytecode, std::str::from_utf8(&message.data)) {
-            (Some(bc), Ok(d)) => (bc, d),
-            (bc, _d) => {
+        // If there is no target bytecode, directly reimburse sender with coins and quit
+        let bytecode = match bytecode {
+            Some(bc) => bc,
+            None => {
                 context_guard!(self).cancel_async_message(&message);
-                if bc.is_none() {
-                    return Err(ExecutionError::RuntimeError(
-                        "no target bytecode found".into(),
-                    ));
-                }
                 return Err(ExecutionError::RuntimeError(
-                    "message data does not convert to utf-8".into(),
+                    "no target bytecode found".into(),
                 ));
             }
         };
@@ -713,7 +775,7 @@ impl ExecutionState {
             &bytecode,
             message.max_gas,
             &message.handler,
-            data,
+            &param_to_runtime_string(&message.data),
             &*self.execution_interface,
         ) {
             // execution failed: reset context to snapshot and reimburse sender
@@ -805,7 +867,7 @@ impl ExecutionState {
     pub(crate) fn execute_readonly_request(
         &self,
         req: ReadOnlyExecutionRequest,
-    ) -> Result<ExecutionOutput, ExecutionError> {
+    ) -> Result<ReadOnlyExecutionOutput, ExecutionError> {
         // set the execution slot to be the one after the latest executed active slot
         let slot = self
             .active_cursor
@@ -826,14 +888,14 @@ impl ExecutionState {
         );
 
         // run the intepreter according to the target type
-        match req.target {
+        let remaining_gas = match req.target {
             ReadOnlyExecutionTarget::BytecodeExecution(bytecode) => {
                 // set the execution context for execution
                 *context_guard!(self) = execution_context;
 
                 // run the bytecode's main function
                 massa_sc_runtime::run_main(&bytecode, req.max_gas, &*self.execution_interface)
-                    .map_err(|err| ExecutionError::RuntimeError(err.to_string()))?;
+                    .map_err(|err| ExecutionError::RuntimeError(err.to_string()))?
             }
             ReadOnlyExecutionTarget::FunctionCall {
                 target_addr,
@@ -853,21 +915,56 @@ impl ExecutionState {
                     &bytecode,
                     req.max_gas,
                     &target_func,
-                    &parameter,
+                    &param_to_runtime_string(&parameter),
                     &*self.execution_interface,
                 )
-                .map_err(|err| ExecutionError::RuntimeError(err.to_string()))?;
+                .map_err(|err| ExecutionError::RuntimeError(err.to_string()))?
             }
-        }
+        };
+        let gas_cost = req.max_gas.saturating_sub(remaining_gas);
+
+        // get the execution output and the trace of its calls, finishing the root call
+        let (out, call_trace) = {
+            let mut context = context_guard!(self);
+            context.finish_call_trace(remaining_gas);
+            let call_trace = std::mem::take(&mut context.call_trace).into_iter().next();
+            (context.settle_slot(), call_trace)
+        };
+
+        // get the balances before the execution of the addresses whose balance it changes
+        let parallel_balances_before: BTreeMap<Address, Amount> = out
+            .state_changes
+            .ledger_changes
+            .0
+            .iter()
+            .filter(|(_addr, change)| match change {
+                SetUpdateOrDelete::Update(update) => {
+                    matches!(update.parallel_balance, SetOrKeep::Set(_))
+                }
+                SetUpdateOrDelete::Set(_) | SetUpdateOrDelete::Delete => true,
+            })
+            .map(|(addr, _change)| {
+                (
+                    *addr,
+                    self.get_final_and_active_parallel_balance(addr)
+                        .1
+                        .unwrap_or_default(),
+                )
+            })
+            .collect();
 
         // return the execution output
-        Ok(context_guard!(self).settle_slot())
+        Ok(ReadOnlyExecutionOutput {
+            out,
+            gas_cost,
+            parallel_balances_before,
+            call_trace,
+        })
     }
 
     /// Gets a parallel balance both at the latest final and active executed slots
     ///
     /// NOTE: temporary, needs to be done in the speculative ledger
-    #[allow(dead_code)]
     pub fn get_final_and_active_parallel_balance(
         &self,
         address: &Address,
@@ -894,7 +991,7 @@ impl ExecutionState {
     pub fn get_final_and_active_data_entry(
         &self,
         address: &Address,
-        key: &Hash,
+        key: &[u8],
     ) -> (Option<Vec<u8>>, Option<Vec<u8>>) {
         let final_entry = self.final_state.read().ledger.get_data_entry(address, key);
         let next_slot = self
@@ -960,6 +1057,74 @@ impl ExecutionState {
         (final_entry, active_entry)
     }
 
+    /// Gets a page of the datastore keys of an address starting with a prefix,
+    /// both at the latest final and active executed slots
+    ///
+    /// # Arguments
+    /// * `cursor`: only keys strictly greater than the cursor are returned
+    /// * `count`: maximum number of keys in each page
+    ///
+    /// # Returns
+    /// `(final_keys, active_keys)`
+    pub fn get_final_and_active_datastore_keys(
+        &self,
+        addr: &Address,
+        prefix: &[u8],
+        cursor: Option<&[u8]>,
+        count: usize,
+    ) -> (BTreeSet<Vec<u8>>, BTreeSet<Vec<u8>>) {
+        let final_keys = |count| {
+            self.final_state
+                .read()
+                .ledger
+                .get_datastore_keys(addr, prefix, cursor, count)
+        };
+        // see `get_final_and_active_ledger_entry` for why the slot after the active one is used
+        let next_slot = self
+            .active_cursor
+            .get_next_slot(self.config.thread_count)
+            .expect("slot overflow when getting speculative ledger");
+        let active_keys = self
+            .get_accumulated_active_changes_at_slot(next_slot)
+            .ledger_changes
+            .get_datastore_keys_or_else(addr, prefix, cursor, count, final_keys);
+        (final_keys(count), active_keys)
+    }
+
+    /// Gets the balance, bytecode and some datastore entries of an address at the output of a final slot
+    ///
+    /// # Arguments
+    /// * `addr`: queried address
+    /// * `datastore_keys`: keys of the queried datastore entries
+    /// * `slot`: final slot at the output of which the entry is queried
+    pub fn get_historical_ledger_entry(
+        &self,
+        addr: &Address,
+        datastore_keys: &[Vec<u8>],
+        slot: Slot,
+    ) -> Result<HistoricalLedgerEntry, ExecutionError> {
+        let history_error = |err: FinalStateError| ExecutionError::HistoryError(err.to_string());
+        let final_state = self.final_state.read();
+        let mut datastore = BTreeMap::new();
+        for key in datastore_keys {
+            datastore.insert(
+                key.clone(),
+                final_state
+                    .get_data_entry_at(addr, key, slot)
+                    .map_err(history_error)?,
+            );
+        }
+        Ok(HistoricalLedgerEntry {
+            parallel_balance: final_state
+                .get_parallel_balance_at(addr, slot)
+                .map_err(history_error)?,
+            bytecode: final_state
+                .get_bytecode_at(addr, slot)
+                .map_err(history_error)?,
+            datastore,
+        })
+    }
+
     /// Gets execution events optionally filtered by:
     /// * start slot
     /// * end slot
@@ -967,8 +1132,14 @@ impl ExecutionState {
     /// * original caller address
     /// * operation id
     pub fn get_filtered_sc_output_event(&self, filter: EventFilter) -> Vec<SCOutputEvent> {
-        self.final_events
-            .get_filtered_sc_output_event(&filter)
+        let final_events = match &self.final_events_db {
+            Some(db) => db.get_filtered_sc_output_event(&filter),
+            None => self
+                .final_events
+                .get_filtered_sc_output_event(&filter)
+                .into(),
+        };
+        final_events
             .into_iter()
             .chain(
                 self.active_history
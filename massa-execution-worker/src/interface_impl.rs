@@ -71,10 +71,11 @@ impl Interface for InterfaceImpl {
     /// # Arguments
     /// * `address`: string representation of the target address on which the bytecode will be called
     /// * `raw_coins`: raw representation (without decimal factor) of the amount of parallel coins to transfer from the caller address to the target address at the beginning of the call
+    /// * `remaining_gas`: gas remaining when the call starts, used to measure its cost in read-only executions
     ///
     /// # Returns
     /// The target bytecode or an error
-    fn init_call(&self, address: &str, raw_coins: u64) -> Result<Vec<u8>> {
+    fn init_call(&self, address: &str, raw_coins: u64, remaining_gas: u64) -> Result<Vec<u8>> {
         // get target address
         let to_address = massa_models::Address::from_str(address)?;
 
@@ -113,6 +114,7 @@ impl Interface for InterfaceImpl {
             coins,
             owned_addresses: vec![to_address],
         });
+        context.start_call_trace(to_address, coins, remaining_gas);
 
         // return the target bytecode
         Ok(bytecode)
@@ -120,12 +122,16 @@ impl Interface for InterfaceImpl {
 
     /// Called to finish the call process after a bytecode calls a function from another one.
     /// This function just pops away the top element of the call stack.
-    fn finish_call(&self) -> Result<()> {
+    ///
+    /// # Arguments
+    /// * `remaining_gas`: gas remaining when the call finishes, used to measure its cost in read-only executions
+    fn finish_call(&self, remaining_gas: u64) -> Result<()> {
         let mut context = context_guard!(self);
 
         if context.stack.pop().is_none() {
             bail!("call stack out of bounds")
         }
+        context.finish_call_trace(remaining_gas);
 
         Ok(())
     }
@@ -185,7 +191,7 @@ impl Interface for InterfaceImpl {
     /// The datastore value matching the provided key, if found, otherwise an error.
     fn raw_get_data_for(&self, address: &str, key: &str) -> Result<Vec<u8>> {
         let addr = &massa_models::Address::from_str(address)?;
-        let key = massa_hash::Hash::compute_from(key.as_bytes());
+        let key = key.as_bytes().to_vec();
         let context = context_guard!(self);
         match context.get_data_entry(addr, &key) {
             Some(value) => Ok(value),
@@ -203,7 +209,7 @@ impl Interface for InterfaceImpl {
     /// * value: new value to set
     fn raw_set_data_for(&self, address: &str, key: &str, value: &[u8]) -> Result<()> {
         let addr = massa_models::Address::from_str(address)?;
-        let key = massa_hash::Hash::compute_from(key.as_bytes());
+        let key = key.as_bytes().to_vec();
         let mut context = context_guard!(self);
         context.set_data_entry(&addr, key, value.to_vec())?;
         Ok(())
@@ -218,7 +224,7 @@ impl Interface for InterfaceImpl {
     /// * value: value to append
     fn raw_append_data_for(&self, address: &str, key: &str, value: &[u8]) -> Result<()> {
         let addr = massa_models::Address::from_str(address)?;
-        let key = massa_hash::Hash::compute_from(key.as_bytes());
+        let key = key.as_bytes().to_vec();
         context_guard!(self).append_data_entry(&addr, key, value.to_vec())?;
         Ok(())
     }
@@ -231,7 +237,7 @@ impl Interface for InterfaceImpl {
     /// * key: string key of the datastore entry to delete
     fn raw_delete_data_for(&self, address: &str, key: &str) -> Result<()> {
         let addr = &massa_models::Address::from_str(address)?;
-        let key = massa_hash::Hash::compute_from(key.as_bytes());
+        let key = key.as_bytes().to_vec();
         context_guard!(self).delete_data_entry(addr, &key)?;
         Ok(())
     }
@@ -246,7 +252,7 @@ impl Interface for InterfaceImpl {
     /// true if the address exists and has the entry matching the provided key in its datastore, otherwise false
     fn has_data_for(&self, address: &str, key: &str) -> Result<bool> {
         let addr = massa_models::Address::from_str(address)?;
-        let key = massa_hash::Hash::compute_from(key.as_bytes());
+        let key = key.as_bytes().to_vec();
         let context = context_guard!(self);
         Ok(context.has_data_entry(&addr, &key))
     }
@@ -259,7 +265,7 @@ impl Interface for InterfaceImpl {
     /// # Returns
     /// The datastore value matching the provided key, if found, otherwise an error.
     fn raw_get_data(&self, key: &str) -> Result<Vec<u8>> {
-        let key = massa_hash::Hash::compute_from(key.as_bytes());
+        let key = key.as_bytes().to_vec();
         let context = context_guard!(self);
         let addr = context.get_current_address()?;
         match context.get_data_entry(&addr, &key) {
@@ -277,7 +283,7 @@ impl Interface for InterfaceImpl {
     /// * key: string key of the datastore entry to set
     /// * value: new value to set
     fn raw_set_data(&self, key: &str, value: &[u8]) -> Result<()> {
-        let key = massa_hash::Hash::compute_from(key.as_bytes());
+        let key = key.as_bytes().to_vec();
         let mut context = context_guard!(self);
         let addr = context.get_current_address()?;
         context.set_data_entry(&addr, key, value.to_vec())?;
@@ -292,7 +298,7 @@ impl Interface for InterfaceImpl {
     /// * key: string key of the datastore entry
     /// * value: value to append
     fn raw_append_data(&self, key: &str, value: &[u8]) -> Result<()> {
-        let key = massa_hash::Hash::compute_from(key.as_bytes());
+        let key = key.as_bytes().to_vec();
         let mut context = context_guard!(self);
         let addr = context.get_current_address()?;
         context.append_data_entry(&addr, key, value.to_vec())?;
@@ -305,7 +311,7 @@ impl Interface for InterfaceImpl {
     /// # Arguments
     /// * key: string key of the datastore entry to delete
     fn raw_delete_data(&self, key: &str) -> Result<()> {
-        let key = massa_hash::Hash::compute_from(key.as_bytes());
+        let key = key.as_bytes().to_vec();
         let mut context = context_guard!(self);
         let addr = context.get_current_address()?;
         context.delete_data_entry(&addr, &key)?;
@@ -320,12 +326,47 @@ impl Interface for InterfaceImpl {
     /// # Returns
     /// true if the address exists and has the entry matching the provided key in its datastore, otherwise false
     fn has_data(&self, key: &str) -> Result<bool> {
-        let key = massa_hash::Hash::compute_from(key.as_bytes());
+        let key = key.as_bytes().to_vec();
         let context = context_guard!(self);
         let addr = context.get_current_address()?;
         Ok(context.has_data_entry(&addr, &key))
     }
 
+    /// Lists the datastore keys of the current address (top of the call stack) starting with a prefix.
+    ///
+    /// # Arguments
+    /// * prefix: string prefix of the keys to list, empty to list all keys
+    ///
+    /// # Returns
+    /// The matching keys in lexicographic order
+    fn get_keys(&self, prefix: &str) -> Result<Vec<String>> {
+        let context = context_guard!(self);
+        let addr = context.get_current_address()?;
+        context
+            .get_datastore_keys(&addr, prefix.as_bytes(), None, usize::MAX)
+            .into_iter()
+            .map(|key| Ok(String::from_utf8(key)?))
+            .collect()
+    }
+
+    /// Lists the datastore keys of a given address starting with a prefix.
+    ///
+    /// # Arguments
+    /// * address: string representation of the address
+    /// * prefix: string prefix of the keys to list, empty to list all keys
+    ///
+    /// # Returns
+    /// The matching keys in lexicographic order, empty if the address does not exist
+    fn get_keys_for(&self, address: &str, prefix: &str) -> Result<Vec<String>> {
+        let addr = massa_models::Address::from_str(address)?;
+        let context = context_guard!(self);
+        context
+            .get_datastore_keys(&addr, prefix.as_bytes(), None, usize::MAX)
+            .into_iter()
+            .map(|key| Ok(String::from_utf8(key)?))
+            .collect()
+    }
+
     /// Hashes arbitrary data
     ///
     /// # Arguments
@@ -61,6 +61,10 @@
 //! A speculative (non-final) ledger that supports canceling already-executed operations
 //! in the case of some blockclique changes.
 //!
+//! ## `event_db.rs`
+//! An optional persistent store of final execution events, indexed by slot, emitter, caller and operation id.
+//! When enabled, final events are kept on disk instead of in the bounded in-memory event store.
+//!
 //! ## `request_queue.rs`
 //! This module contains the implementation of a generic finite-size execution request queue.
 //! It handles requests that come with an MPSC to send back the result of their execution once it's done.
@@ -72,6 +76,7 @@
 
 mod context;
 mod controller;
+mod event_db;
 mod execution;
 mod interface_impl;
 mod request_queue;
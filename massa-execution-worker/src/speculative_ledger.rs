@@ -7,10 +7,11 @@
 
 use massa_execution_exports::ExecutionError;
 use massa_final_state::FinalState;
-use massa_hash::Hash;
 use massa_ledger_exports::{Applicable, LedgerChanges};
+use massa_models::constants::MAX_DATASTORE_KEY_LENGTH;
 use massa_models::{Address, Amount};
 use parking_lot::RwLock;
+use std::collections::BTreeSet;
 use std::sync::Arc;
 
 /// The `SpeculativeLedger` contains an thread-safe shared reference to the final ledger (read-only),
@@ -208,7 +209,7 @@ impl SpeculativeLedger {
     ///
     /// # Returns
     /// `Some(Vec<u8>)` if the value was found, `None` if the address does not exist or if the key is not in its datastore.
-    pub fn get_data_entry(&self, addr: &Address, key: &Hash) -> Option<Vec<u8>> {
+    pub fn get_data_entry(&self, addr: &Address, key: &[u8]) -> Option<Vec<u8>> {
         // try to read from added_changes, then previous_changes, then ledger in final_state
         self.added_changes.get_data_entry_or_else(addr, key, || {
             self.previous_changes.get_data_entry_or_else(addr, key, || {
@@ -225,7 +226,7 @@ impl SpeculativeLedger {
     ///
     /// # Returns
     /// true if the key exists in the address datastore, false otherwise
-    pub fn has_data_entry(&self, addr: &Address, key: &Hash) -> bool {
+    pub fn has_data_entry(&self, addr: &Address, key: &[u8]) -> bool {
         // try to read from added_changes, then previous_changes, then ledger in final_state
         self.added_changes.has_data_entry_or_else(addr, key, || {
             self.previous_changes.has_data_entry_or_else(addr, key, || {
@@ -234,6 +235,41 @@ impl SpeculativeLedger {
         })
     }
 
+    /// Gets a page of the datastore keys of a given address starting with a prefix
+    ///
+    /// # Arguments
+    /// * `addr`: address to query
+    /// * `prefix`: only keys starting with this prefix are returned
+    /// * `cursor`: only keys strictly greater than the cursor are returned
+    /// * `count`: maximum number of returned keys
+    ///
+    /// # Returns
+    /// The first `count` matching keys in lexicographic order, empty if the address does not exist.
+    pub fn get_datastore_keys(
+        &self,
+        addr: &Address,
+        prefix: &[u8],
+        cursor: Option<&[u8]>,
+        count: usize,
+    ) -> BTreeSet<Vec<u8>> {
+        // apply added_changes on top of previous_changes on top of the ledger in final_state
+        self.added_changes
+            .get_datastore_keys_or_else(addr, prefix, cursor, count, |count| {
+                self.previous_changes.get_datastore_keys_or_else(
+                    addr,
+                    prefix,
+                    cursor,
+                    count,
+                    |count| {
+                        self.final_state
+                            .read()
+                            .ledger
+                            .get_datastore_keys(addr, prefix, cursor, count)
+                    },
+                )
+            })
+    }
+
     /// Sets a data set entry for a given address in the ledger.
     /// Fails if the address doesn't exist.
     /// If the datastore entry does not exist, it is created.
@@ -245,9 +281,17 @@ impl SpeculativeLedger {
     pub fn set_data_entry(
         &mut self,
         addr: &Address,
-        key: Hash,
+        key: Vec<u8>,
         data: Vec<u8>,
     ) -> Result<(), ExecutionError> {
+        // check the key length
+        if key.len() > MAX_DATASTORE_KEY_LENGTH as usize {
+            return Err(ExecutionError::RuntimeError(format!(
+                "could not set data for address {}: datastore key is longer than {} bytes",
+                addr, MAX_DATASTORE_KEY_LENGTH
+            )));
+        }
+
         // check for address existence
         if !self.entry_exists(addr) {
             return Err(ExecutionError::RuntimeError(format!(
@@ -268,17 +312,17 @@ impl SpeculativeLedger {
     /// # Arguments
     /// * `addr`: address
     /// * `key`: key of the entry to delete in the address' datastore
-    pub fn delete_data_entry(&mut self, addr: &Address, key: &Hash) -> Result<(), ExecutionError> {
+    pub fn delete_data_entry(&mut self, addr: &Address, key: &[u8]) -> Result<(), ExecutionError> {
         // check if the entry exists
         if !self.has_data_entry(addr, key) {
             return Err(ExecutionError::RuntimeError(format!(
-                "could not delete data entry {} for address {}: entry does not exist",
+                "could not delete data entry {:?} for address {}: entry does not exist",
                 key, addr
             )));
         }
 
         // delete entry
-        self.added_changes.delete_data_entry(*addr, *key);
+        self.added_changes.delete_data_entry(*addr, key.to_vec());
 
         Ok(())
     }
@@ -0,0 +1,73 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use crate::event_db::EventDB;
+use massa_models::api::EventFilter;
+use massa_models::output_event::{EventExecutionContext, SCOutputEvent};
+use massa_models::{Address, Slot};
+use massa_signature::{derive_public_key, generate_random_private_key};
+use std::collections::VecDeque;
+use tempfile::TempDir;
+
+fn random_address() -> Address {
+    Address::from_public_key(&derive_public_key(&generate_random_private_key()))
+}
+
+fn event(slot: Slot, index_in_slot: u64, call_stack: Vec<Address>) -> SCOutputEvent {
+    SCOutputEvent {
+        context: EventExecutionContext {
+            slot,
+            block: None,
+            read_only: false,
+            index_in_slot,
+            call_stack: call_stack.into(),
+            origin_operation_id: None,
+        },
+        data: format!("{} {}", slot, index_in_slot),
+    }
+}
+
+#[test]
+fn test_event_db_filters_and_retention() {
+    let temp_dir = TempDir::new().unwrap();
+    let db = EventDB::new(temp_dir.path().to_path_buf(), Some(5), None);
+    let (emitter, caller) = (random_address(), random_address());
+    for period in 0..10 {
+        let slot = Slot::new(period, 0);
+        db.insert_events(
+            slot,
+            VecDeque::from(vec![
+                event(slot, 0, vec![emitter, caller]),
+                event(slot, 1, vec![caller]),
+            ]),
+        );
+    }
+
+    // periods 0 to 3 were pruned
+    let all = db.get_filtered_sc_output_event(&EventFilter::default());
+    assert_eq!(all.len(), 12);
+    assert_eq!(all[0].context.slot, Slot::new(4, 0));
+
+    let by_emitter = db.get_filtered_sc_output_event(&EventFilter {
+        emitter_address: Some(emitter),
+        start: Some(Slot::new(6, 0)),
+        end: Some(Slot::new(8, 0)),
+        ..Default::default()
+    });
+    assert_eq!(by_emitter.len(), 2);
+    assert!(by_emitter.iter().all(|e| e.context.index_in_slot == 0));
+
+    let by_caller = db.get_filtered_sc_output_event(&EventFilter {
+        original_caller_address: Some(caller),
+        ..Default::default()
+    });
+    assert_eq!(by_caller.len(), 12);
+
+    // reopening keeps the events
+    drop(db);
+    let db = EventDB::new(temp_dir.path().to_path_buf(), Some(5), None);
+    assert_eq!(
+        db.get_filtered_sc_output_event(&EventFilter::default())
+            .len(),
+        12
+    );
+}
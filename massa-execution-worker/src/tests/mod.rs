@@ -1,3 +1,7 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+mod event_db;
+mod scenarios_bytecode_policy;
+mod scenarios_call_trace;
+mod scenarios_datastore_keys;
 mod scenarios_mandatories;
@@ -0,0 +1,183 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use super::scenarios_mandatories::{get_random_address, get_sample_state};
+use crate::{context::ExecutionContext, interface_impl::InterfaceImpl};
+use massa_execution_exports::{ExecutionConfig, ExecutionStackElement};
+use massa_models::{
+    bytecode_policy::{
+        PendingBytecodeUpgrade, BYTECODE_POLICY_DATASTORE_KEY, BYTECODE_UPGRADE_DATASTORE_KEY,
+    },
+    Address, Amount, Slot,
+};
+use massa_sc_runtime::Interface;
+use parking_lot::Mutex;
+use serial_test::serial;
+use std::sync::Arc;
+use tempfile::{NamedTempFile, TempDir};
+
+const POLICY_KEY: &str = "massa_bytecode_policy";
+const UPGRADE_KEY: &str = "massa_bytecode_upgrade";
+
+/// Active execution context at the given period, with an interface on it,
+/// and the files of the ledger to keep until the end of the test
+fn get_context(
+    period: u64,
+) -> (
+    Arc<Mutex<ExecutionContext>>,
+    InterfaceImpl,
+    NamedTempFile,
+    TempDir,
+) {
+    let (sample_state, keep_file, keep_dir) = get_sample_state().unwrap();
+    let context = Arc::new(Mutex::new(ExecutionContext::active_slot(
+        Slot::new(period, 0),
+        None,
+        Default::default(),
+        sample_state,
+    )));
+    let interface = InterfaceImpl::new(ExecutionConfig::default(), context.clone());
+    (context, interface, keep_file, keep_dir)
+}
+
+/// Call stack element of an address owning itself
+fn stack_element(address: Address) -> ExecutionStackElement {
+    ExecutionStackElement {
+        address,
+        coins: Amount::default(),
+        owned_addresses: vec![address],
+    }
+}
+
+/// Creates a contract from a fresh address, with the given bytecode and policy
+fn create_contract(context: &Mutex<ExecutionContext>, bytecode: &[u8], policy: &str) -> Address {
+    let mut context = context.lock();
+    context.stack = vec![stack_element(get_random_address())];
+    let address = context.create_new_sc_address(bytecode.to_vec()).unwrap();
+    context
+        .set_data_entry(
+            &address,
+            BYTECODE_POLICY_DATASTORE_KEY.to_vec(),
+            policy.as_bytes().to_vec(),
+        )
+        .unwrap();
+    address
+}
+
+/// Sets the call stack, the contract being called last
+fn set_call_stack(context: &Mutex<ExecutionContext>, callers: &[Address], contract: Address) {
+    context.lock().stack = callers
+        .iter()
+        .chain(std::iter::once(&contract))
+        .map(|address| stack_element(*address))
+        .collect();
+}
+
+#[test]
+#[serial]
+fn test_immutable_bytecode() {
+    let (context, interface, _keep_file, _keep_dir) = get_context(1);
+    let contract = create_contract(&context, b"v1", "immutable");
+    set_call_stack(&context, &[get_random_address()], contract);
+
+    assert!(interface.raw_set_bytecode(b"v2").is_err());
+    assert!(interface.raw_set_data(POLICY_KEY, b"upgradeable").is_err());
+    interface.raw_set_data(POLICY_KEY, b"immutable").unwrap();
+    assert!(interface.raw_delete_data(POLICY_KEY).is_err());
+    assert_eq!(context.lock().get_bytecode(&contract).unwrap(), b"v1");
+}
+
+#[test]
+#[serial]
+fn test_admin_bytecode() {
+    let (context, interface, _keep_file, _keep_dir) = get_context(1);
+    let admin = get_random_address();
+    let other = get_random_address();
+    let contract = create_contract(&context, b"v1", &format!("admin:{}", admin));
+
+    // an address that is not the admin cannot upgrade
+    set_call_stack(&context, &[other], contract);
+    assert!(interface.raw_set_bytecode(b"v2").is_err());
+    assert!(interface.raw_set_data(POLICY_KEY, b"upgradeable").is_err());
+
+    // the admin deeper in the call stack cannot upgrade through another address
+    set_call_stack(&context, &[admin, other], contract);
+    assert!(interface.raw_set_bytecode(b"v2").is_err());
+    assert!(interface.raw_set_data(POLICY_KEY, b"upgradeable").is_err());
+    assert_eq!(context.lock().get_bytecode(&contract).unwrap(), b"v1");
+
+    // the admin calling the contract can upgrade it and change the policy
+    set_call_stack(&context, &[other, admin], contract);
+    interface.raw_set_bytecode(b"v2").unwrap();
+    assert_eq!(context.lock().get_bytecode(&contract).unwrap(), b"v2");
+    interface.raw_set_data(POLICY_KEY, b"timelock:10").unwrap();
+}
+
+#[test]
+#[serial]
+fn test_timelock_bytecode() {
+    let (context, interface, _keep_file, _keep_dir) = get_context(1);
+    let contract = create_contract(&context, b"v1", "timelock:10");
+    set_call_stack(&context, &[get_random_address()], contract);
+
+    // the first request schedules the upgrade
+    interface.raw_set_bytecode(b"v2").unwrap();
+    assert_eq!(context.lock().get_bytecode(&contract).unwrap(), b"v1");
+    let pending = PendingBytecodeUpgrade::from_bytes(
+        &context
+            .lock()
+            .get_data_entry(&contract, BYTECODE_UPGRADE_DATASTORE_KEY)
+            .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(pending.activation_period, 11);
+
+    // the pending upgrade is only written by the node
+    assert!(interface.raw_set_data(UPGRADE_KEY, b"").is_err());
+    assert!(interface.raw_delete_data(UPGRADE_KEY).is_err());
+
+    // the upgrade cannot be applied before the end of the timelock
+    context.lock().slot = Slot::new(10, 0);
+    assert!(interface.raw_set_bytecode(b"v2").is_err());
+
+    // another bytecode replaces the pending upgrade and restarts the timelock
+    interface.raw_set_bytecode(b"v3").unwrap();
+    context.lock().slot = Slot::new(11, 0);
+    assert!(interface.raw_set_bytecode(b"v3").is_err());
+
+    // the upgrade is applied once the timelock expired
+    context.lock().slot = Slot::new(20, 0);
+    interface.raw_set_bytecode(b"v3").unwrap();
+    assert_eq!(context.lock().get_bytecode(&contract).unwrap(), b"v3");
+    assert!(!context
+        .lock()
+        .has_data_entry(&contract, BYTECODE_UPGRADE_DATASTORE_KEY));
+}
+
+#[test]
+#[serial]
+fn test_bytecode_policy_transitions() {
+    let (context, interface, _keep_file, _keep_dir) = get_context(1);
+    let contract = create_contract(&context, b"v1", "upgradeable");
+    set_call_stack(&context, &[get_random_address()], contract);
+
+    // an invalid policy is rejected
+    assert!(interface.raw_set_data(POLICY_KEY, b"frozen").is_err());
+
+    // a timelock can only be extended or made immutable
+    interface.raw_set_data(POLICY_KEY, b"timelock:10").unwrap();
+    assert!(interface.raw_set_data(POLICY_KEY, b"timelock:5").is_err());
+    assert!(interface.raw_set_data(POLICY_KEY, b"upgradeable").is_err());
+    assert!(interface.raw_delete_data(POLICY_KEY).is_err());
+    interface.raw_set_data(POLICY_KEY, b"timelock:20").unwrap();
+    interface.raw_set_data(POLICY_KEY, b"immutable").unwrap();
+
+    // an immutable policy is final
+    assert!(interface.raw_set_data(POLICY_KEY, b"timelock:30").is_err());
+    assert_eq!(
+        context
+            .lock()
+            .get_data_entry(&contract, BYTECODE_POLICY_DATASTORE_KEY)
+            .unwrap(),
+        b"immutable"
+    );
+}
@@ -0,0 +1,87 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use super::scenarios_mandatories::{get_random_address, get_sample_state};
+use crate::{context::ExecutionContext, interface_impl::InterfaceImpl};
+use massa_execution_exports::{ExecutionConfig, ExecutionStackElement};
+use massa_final_state::StateChanges;
+use massa_ledger_exports::{LedgerEntry, SetUpdateOrDelete};
+use massa_models::{Address, Amount, Slot};
+use massa_sc_runtime::Interface;
+use parking_lot::Mutex;
+use serial_test::serial;
+use std::sync::Arc;
+use tempfile::{NamedTempFile, TempDir};
+
+const MAX_GAS: u64 = 1000;
+
+/// Read-only execution context called by an address with `MAX_GAS`,
+/// two contracts it can call, an interface on the context,
+/// and the files of the ledger to keep until the end of the test
+fn get_readonly_context() -> (
+    Arc<Mutex<ExecutionContext>>,
+    InterfaceImpl,
+    [Address; 2],
+    NamedTempFile,
+    TempDir,
+) {
+    let (sample_state, keep_file, keep_dir) = get_sample_state().unwrap();
+    let caller = get_random_address();
+    let mut previous_changes = StateChanges::default();
+    previous_changes.ledger_changes.0.insert(
+        caller,
+        SetUpdateOrDelete::Set(LedgerEntry {
+            parallel_balance: Amount::from_raw(1),
+            ..Default::default()
+        }),
+    );
+    let mut context = ExecutionContext::readonly(
+        Slot::new(1, 0),
+        MAX_GAS,
+        Amount::default(),
+        vec![ExecutionStackElement {
+            address: caller,
+            coins: Amount::default(),
+            owned_addresses: vec![caller],
+        }],
+        previous_changes,
+        sample_state,
+    );
+    let contracts = [
+        context.create_new_sc_address(b"first".to_vec()).unwrap(),
+        context.create_new_sc_address(b"second".to_vec()).unwrap(),
+    ];
+    let context = Arc::new(Mutex::new(context));
+    let interface = InterfaceImpl::new(ExecutionConfig::default(), context.clone());
+    (context, interface, contracts, keep_file, keep_dir)
+}
+
+#[test]
+#[serial]
+fn test_call_trace_gas() {
+    let (context, interface, [first, second], _keep_file, _keep_dir) = get_readonly_context();
+
+    // the root call nests a call that nests another one, then makes a second call
+    interface.init_call(&first.to_string(), 0, 900).unwrap();
+    interface.init_call(&second.to_string(), 0, 850).unwrap();
+    interface.finish_call(800).unwrap();
+    interface.finish_call(600).unwrap();
+    interface.init_call(&second.to_string(), 0, 550).unwrap();
+    interface.finish_call(500).unwrap();
+    context.lock().finish_call_trace(400);
+
+    // each call costs the gas consumed between its start and its end, including its nested calls
+    let root = std::mem::take(&mut context.lock().call_trace);
+    assert_eq!(root.len(), 1);
+    assert_eq!(root[0].gas_cost, MAX_GAS - 400);
+    let calls = &root[0].sub_calls;
+    assert_eq!(calls.len(), 2);
+    assert_eq!(calls[0].address, first);
+    assert_eq!(calls[0].gas_cost, 300);
+    assert_eq!(calls[0].sub_calls.len(), 1);
+    assert_eq!(calls[0].sub_calls[0].address, second);
+    assert_eq!(calls[0].sub_calls[0].gas_cost, 50);
+    assert_eq!(calls[1].address, second);
+    assert_eq!(calls[1].gas_cost, 50);
+    assert!(calls[1].sub_calls.is_empty());
+    assert!(context.lock().call_trace_gas.is_empty());
+}
@@ -0,0 +1,132 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use super::scenarios_mandatories::{get_random_address, get_sample_state};
+use crate::{
+    context::ExecutionContext, interface_impl::InterfaceImpl, speculative_ledger::SpeculativeLedger,
+};
+use massa_execution_exports::{ExecutionConfig, ExecutionStackElement};
+use massa_final_state::{FinalState, StateChanges};
+use massa_ledger_exports::{LedgerChanges, LedgerEntry, SetUpdateOrDelete};
+use massa_models::{Address, Amount, Slot};
+use massa_sc_runtime::Interface;
+use parking_lot::{Mutex, RwLock};
+use serial_test::serial;
+use std::{collections::BTreeSet, sync::Arc};
+
+/// Writes an entry with the keys `a1`, `a3`, `b1` and `c1` in its datastore to the final ledger
+fn set_final_entry(final_state: &RwLock<FinalState>) -> Address {
+    let address = get_random_address();
+    let entry = LedgerEntry {
+        parallel_balance: Amount::from_raw(1),
+        bytecode: Vec::new(),
+        datastore: ["a1", "a3", "b1", "c1"]
+            .iter()
+            .map(|key| (key.as_bytes().to_vec(), b"value".to_vec()))
+            .collect(),
+    };
+    let mut changes = LedgerChanges::default();
+    changes.0.insert(address, SetUpdateOrDelete::Set(entry));
+    final_state
+        .write()
+        .ledger
+        .apply_changes(changes, Default::default(), Some(Slot::new(1, 0)));
+    address
+}
+
+/// Previous changes setting `a2` and deleting `a3`
+fn previous_changes(address: Address) -> LedgerChanges {
+    let mut changes = LedgerChanges::default();
+    changes.set_data_entry(address, b"a2".to_vec(), b"value".to_vec());
+    changes.delete_data_entry(address, b"a3".to_vec());
+    changes
+}
+
+fn keys(keys: &[&str]) -> BTreeSet<Vec<u8>> {
+    keys.iter().map(|key| key.as_bytes().to_vec()).collect()
+}
+
+#[test]
+#[serial]
+fn test_speculative_datastore_keys() {
+    let (final_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
+    let address = set_final_entry(&final_state);
+    let mut ledger = SpeculativeLedger::new(final_state, previous_changes(address));
+    ledger
+        .set_data_entry(&address, b"a4".to_vec(), b"value".to_vec())
+        .unwrap();
+    ledger.delete_data_entry(&address, b"a1").unwrap();
+
+    // the keys of the final ledger, previous changes and added changes are merged in order
+    assert_eq!(
+        ledger.get_datastore_keys(&address, b"", None, usize::MAX),
+        keys(&["a2", "a4", "b1", "c1"])
+    );
+    assert_eq!(
+        ledger.get_datastore_keys(&address, b"a", None, usize::MAX),
+        keys(&["a2", "a4"])
+    );
+
+    // pages start after the cursor and deleted keys do not shorten them
+    assert_eq!(
+        ledger.get_datastore_keys(&address, b"", None, 2),
+        keys(&["a2", "a4"])
+    );
+    assert_eq!(
+        ledger.get_datastore_keys(&address, b"", Some(b"a2"), 2),
+        keys(&["a4", "b1"])
+    );
+    assert!(ledger
+        .get_datastore_keys(&address, b"a", Some(b"a4"), usize::MAX)
+        .is_empty());
+
+    // an unknown address has no keys
+    assert!(ledger
+        .get_datastore_keys(&get_random_address(), b"", None, usize::MAX)
+        .is_empty());
+}
+
+#[test]
+#[serial]
+fn test_execution_datastore_keys() {
+    let (final_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
+    let address = set_final_entry(&final_state);
+    let context = Arc::new(Mutex::new(ExecutionContext::active_slot(
+        Slot::new(2, 0),
+        None,
+        StateChanges {
+            ledger_changes: previous_changes(address),
+            async_pool_changes: Default::default(),
+        },
+        final_state,
+    )));
+    context.lock().stack = vec![ExecutionStackElement {
+        address,
+        coins: Amount::default(),
+        owned_addresses: vec![address],
+    }];
+    let interface = InterfaceImpl::new(ExecutionConfig::default(), context.clone());
+    {
+        let mut context = context.lock();
+        context
+            .set_data_entry(&address, b"a4".to_vec(), b"value".to_vec())
+            .unwrap();
+        context.delete_data_entry(&address, b"a1").unwrap();
+        assert_eq!(
+            context.get_datastore_keys(&address, b"", Some(b"a2"), 2),
+            keys(&["a4", "b1"])
+        );
+    }
+
+    // contracts list the keys in lexicographic order, for themselves or any address
+    assert_eq!(interface.get_keys("").unwrap(), ["a2", "a4", "b1", "c1"]);
+    assert_eq!(interface.get_keys("a").unwrap(), ["a2", "a4"]);
+    assert_eq!(
+        interface.get_keys_for(&address.to_string(), "c").unwrap(),
+        ["c1"]
+    );
+    assert!(interface
+        .get_keys_for(&get_random_address().to_string(), "")
+        .unwrap()
+        .is_empty());
+    assert!(interface.get_keys_for("invalid", "").is_err());
+}
@@ -3,7 +3,8 @@
 use crate::start_execution_worker;
 use massa_async_pool::AsyncPoolConfig;
 use massa_execution_exports::{
-    ExecutionConfig, ExecutionError, ReadOnlyExecutionRequest, ReadOnlyExecutionTarget,
+    ExecutionConfig, ExecutionError, ExecutionStackElement, ReadOnlyExecutionRequest,
+    ReadOnlyExecutionTarget,
 };
 use massa_final_state::{FinalState, FinalStateConfig};
 use massa_hash::Hash;
@@ -11,7 +12,7 @@ use massa_ledger_exports::{LedgerConfig, LedgerError};
 use massa_ledger_worker::FinalLedger;
 use massa_models::{
     api::EventFilter,
-    constants::{AMOUNT_DECIMAL_FACTOR, FINAL_HISTORY_LENGTH, THREAD_COUNT},
+    constants::{AMOUNT_DECIMAL_FACTOR, FINAL_HISTORY_LENGTH, MAX_GAS_PER_BLOCK, THREAD_COUNT},
     Block, BlockHeader, BlockId, Operation, OperationType, SerializeCompact, SignedHeader,
     SignedOperation,
 };
@@ -42,7 +43,8 @@ pub fn get_random_address() -> Address {
     get_random_address_full().0
 }
 
-fn get_sample_state() -> Result<(Arc<RwLock<FinalState>>, NamedTempFile, TempDir), LedgerError> {
+pub fn get_sample_state() -> Result<(Arc<RwLock<FinalState>>, NamedTempFile, TempDir), LedgerError>
+{
     let mut initial: BTreeMap<Address, Amount> = Default::default();
     initial.insert(get_random_address(), Amount::from_str("129").unwrap());
     initial.insert(get_random_address(), Amount::from_str("878").unwrap());
@@ -87,7 +89,7 @@ fn test_sending_read_only_execution_command() {
     let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
     let (mut manager, controller) =
         start_execution_worker(ExecutionConfig::default(), sample_state, Default::default());
-    controller
+    let output = controller
         .execute_readonly_request(ReadOnlyExecutionRequest {
             max_gas: 1_000_000,
             simulated_gas_price: Amount::from_raw(1_000_000 * AMOUNT_DECIMAL_FACTOR),
@@ -97,6 +99,8 @@ fn test_sending_read_only_execution_command() {
             ),
         })
         .unwrap();
+    assert!(output.gas_cost > 0 && output.gas_cost <= 1_000_000);
+    assert!(output.call_trace.is_none());
     manager.stop();
 }
 
@@ -129,7 +133,7 @@ fn test_nested_call_gas_usage() {
     let bytecode = include_bytes!("./wasm/nested_call.wasm");
     // create the block containing the smart contract execution operation
     let (block_id, block) = create_block(
-        vec![create_execute_sc_operation(priv_key, pub_key, bytecode).unwrap()],
+        vec![create_execute_sc_operation(priv_key, pub_key, u64::MAX, bytecode).unwrap()],
         Slot::new(1, 0),
     )
     .unwrap();
@@ -160,7 +164,7 @@ fn test_nested_call_gas_usage() {
         Amount::from_str("0").unwrap(),
         Address::from_str(&address).unwrap(),
         String::from("test"),
-        address,
+        address.into_bytes(),
     )
     .unwrap();
     let (block_id, block) = create_block(vec![operation], Slot::new(1, 1)).unwrap();
@@ -226,7 +230,7 @@ fn send_and_receive_async_message() {
     let bytecode = include_bytes!("./wasm/send_message.wasm");
     // create the block contaning the smart contract execution operation
     let (block_id, block) = create_block(
-        vec![create_execute_sc_operation(priv_key, pub_key, bytecode).unwrap()],
+        vec![create_execute_sc_operation(priv_key, pub_key, u64::MAX, bytecode).unwrap()],
         Slot::new(1, 0),
     )
     .unwrap();
@@ -271,10 +275,13 @@ fn generate_events() {
     let (sender_address, sender_private_key, sender_public_key) = get_random_address_full();
     let event_test_data = include_bytes!("./wasm/event_test.wasm");
     let (block_id, block) = create_block(
-        vec![
-            create_execute_sc_operation(sender_private_key, sender_public_key, event_test_data)
-                .unwrap(),
-        ],
+        vec![create_execute_sc_operation(
+            sender_private_key,
+            sender_public_key,
+            u64::MAX,
+            event_test_data,
+        )
+        .unwrap()],
         Slot::new(1, 0),
     )
     .unwrap();
@@ -299,16 +306,89 @@ fn generate_events() {
     manager.stop();
 }
 
+/// Gas estimation
+///
+/// 1. the gas of `event_test.wasm` executed by a sender is estimated by a read-only execution
+/// 2. a block containing an `execute_sc` operation of the sender with the estimated gas as `max_gas` is executed
+/// 3. the bytecode ran to completion: the event it emits was kept
+/// 4. the estimation of invalid bytecode fails
+#[test]
+#[serial]
+fn test_gas_estimation() {
+    massa_models::init_serialization_context(massa_models::SerializationContext::default());
+    let exec_cfg = ExecutionConfig {
+        t0: 10.into(),
+        ..ExecutionConfig::default()
+    };
+    let storage: Storage = Default::default();
+    let (sample_state, _keep_file, _keep_dir) = get_sample_state().unwrap();
+    let (mut manager, controller) = start_execution_worker(exec_cfg, sample_state, storage.clone());
+    let (sender_address, sender_private_key, sender_public_key) = get_random_address_full();
+    let bytecode = include_bytes!("./wasm/event_test.wasm");
+    let estimation_request = |bytecode: Vec<u8>| ReadOnlyExecutionRequest {
+        max_gas: MAX_GAS_PER_BLOCK,
+        simulated_gas_price: Amount::from_raw(AMOUNT_DECIMAL_FACTOR),
+        call_stack: vec![ExecutionStackElement {
+            address: sender_address,
+            coins: Amount::default(),
+            owned_addresses: vec![sender_address],
+        }],
+        target: ReadOnlyExecutionTarget::BytecodeExecution(bytecode),
+    };
+
+    // estimate the gas used by the bytecode
+    let gas_cost = controller
+        .execute_readonly_request(estimation_request(bytecode.to_vec()))
+        .unwrap()
+        .gas_cost;
+    assert!(gas_cost > 0 && gas_cost < MAX_GAS_PER_BLOCK);
+
+    // the estimated gas is enough to execute the bytecode in a block
+    let (block_id, block) = create_block(
+        vec![create_execute_sc_operation(
+            sender_private_key,
+            sender_public_key,
+            gas_cost,
+            bytecode,
+        )
+        .unwrap()],
+        Slot::new(1, 0),
+    )
+    .unwrap();
+    let slot = block.header.content.slot;
+    storage.store_block(block_id, block, Default::default());
+    let mut blockclique: HashMap<Slot, BlockId> = Default::default();
+    blockclique.insert(slot, block_id);
+    controller.update_blockclique_status(Default::default(), blockclique);
+    std::thread::sleep(Duration::from_millis(1000));
+    let events = controller.get_filtered_sc_output_event(EventFilter {
+        start: Some(slot),
+        emitter_address: Some(sender_address),
+        ..Default::default()
+    });
+    assert!(
+        !events.is_empty(),
+        "the execution with the estimated gas did not complete"
+    );
+
+    // invalid bytecode cannot be estimated
+    assert!(controller
+        .execute_readonly_request(estimation_request(b"invalid".to_vec()))
+        .is_err());
+    manager.stop();
+}
+
 /// Create an operation for the given sender with `data` as bytecode.
 /// Return a result that should be unwrapped in the root `#[test]` routine.
 fn create_execute_sc_operation(
     sender_private_key: PrivateKey,
     sender_public_key: PublicKey,
+    max_gas: u64,
     data: &[u8],
 ) -> Result<SignedOperation, ExecutionError> {
     let op = OperationType::ExecuteSC {
         data: data.to_vec(),
-        max_gas: u64::MAX,
+        max_gas,
         coins: Amount::from_raw(u64::MAX),
         gas_price: Amount::from_raw(AMOUNT_DECIMAL_FACTOR),
     };
@@ -318,6 +398,8 @@ fn create_execute_sc_operation(
             fee: Amount::zero(),
             expire_period: 10,
             op,
+            multisig: None,
+            replaces: None,
         },
         &sender_private_key,
     )?;
@@ -333,7 +415,7 @@ fn create_call_sc_operation(
     gas_price: Amount,
     target_addr: Address,
     target_func: String,
-    param: String,
+    param: Vec<u8>,
 ) -> Result<SignedOperation, ExecutionError> {
     let op = OperationType::CallSC {
         max_gas,
@@ -350,6 +432,8 @@ fn create_call_sc_operation(
             fee: Amount::zero(),
             expire_period: 10,
             op,
+            multisig: None,
+            replaces: None,
         },
         &sender_private_key,
     )?;
@@ -9,10 +9,14 @@ use crate::controller::{ExecutionControllerImpl, ExecutionInputData, ExecutionMa
 use crate::execution::ExecutionState;
 use crate::request_queue::RequestQueue;
 use massa_execution_exports::{
-    ExecutionConfig, ExecutionController, ExecutionError, ExecutionManager, ExecutionOutput,
-    ReadOnlyExecutionRequest,
+    ExecutionConfig, ExecutionController, ExecutionError, ExecutionManager,
+    ReadOnlyExecutionOutput, ReadOnlyExecutionRequest,
 };
 use massa_final_state::FinalState;
+use massa_metrics::{
+    EXECUTION_ACTIVE_SLOT_LAG, EXECUTION_FINAL_SLOT_LAG, EXECUTION_READONLY_QUEUE_LENGTH,
+    EXECUTION_SLOT_GAS,
+};
 use massa_models::BlockId;
 use massa_models::{
     timeslots::{get_block_slot_timestamp, get_latest_block_slot_at_timestamp},
@@ -47,7 +51,7 @@ pub(crate) struct ExecutionThread {
     // Execution state (see execution.rs) to which execution requests are sent
     execution_state: Arc<RwLock<ExecutionState>>,
     /// queue for read-only requests and response MPSCs to send back their outputs
-    readonly_requests: RequestQueue<ReadOnlyExecutionRequest, ExecutionOutput>,
+    readonly_requests: RequestQueue<ReadOnlyExecutionRequest, ReadOnlyExecutionOutput>,
 }
 
 impl ExecutionThread {
@@ -163,7 +167,10 @@ impl ExecutionThread {
     /// The latest slot at or before `now() - self.config.cursor_delay` if there is any,
     /// or None if it falls behind the genesis timestamp.
     fn get_end_active_slot(&self) -> Option<Slot> {
-        let target_time = MassaTime::compensated_now(self.config.clock_compensation)
+        let target_time = self
+            .config
+            .clock_compensation
+            .now()
             .expect("could not read current time")
             .saturating_sub(self.config.cursor_delay);
         get_latest_block_slot_at_timestamp(
@@ -258,6 +265,7 @@ impl ExecutionThread {
                 // speculative execution front result matches what we want to compute
 
                 // apply the cached output and return
+                EXECUTION_SLOT_GAS.observe(exec_out.gas_used as f64);
                 exec_state.apply_final_execution_output(exec_out);
                 return true;
             } else {
@@ -282,6 +290,7 @@ impl ExecutionThread {
         let exec_out = exec_state.execute_slot(slot, exec_target);
 
         // apply execution output to final state
+        EXECUTION_SLOT_GAS.observe(exec_out.gas_used as f64);
         exec_state.apply_final_execution_output(exec_out);
 
         true
@@ -347,7 +356,10 @@ impl ExecutionThread {
         .expect("could not compute block timestamp in VM");
 
         // get the current timestamp minus the cursor delay
-        let end_time = MassaTime::compensated_now(self.config.clock_compensation)
+        let end_time = self
+            .config
+            .clock_compensation
+            .now()
             .expect("could not get current time in VM")
             .saturating_sub(self.config.cursor_delay);
 
@@ -374,7 +386,7 @@ impl ExecutionThread {
     /// Cancel those that are in excess if there are too many.
     fn update_readonly_requests(
         &mut self,
-        new_requests: RequestQueue<ReadOnlyExecutionRequest, ExecutionOutput>,
+        new_requests: RequestQueue<ReadOnlyExecutionRequest, ReadOnlyExecutionOutput>,
     ) {
         // Append incoming readonly requests to our readonly request queue
         // Excess requests are cancelled
@@ -403,6 +415,22 @@ impl ExecutionThread {
         false
     }
 
+    /// Exports the state of the execution queues as metrics
+    fn update_metrics(&self) {
+        let active_cursor = self.execution_state.read().active_cursor;
+        let active_slot_lag = self
+            .get_end_active_slot()
+            .and_then(|end_slot| {
+                end_slot
+                    .slots_since(&active_cursor, self.config.thread_count)
+                    .ok()
+            })
+            .unwrap_or(0);
+        EXECUTION_ACTIVE_SLOT_LAG.set(active_slot_lag as i64);
+        EXECUTION_FINAL_SLOT_LAG.set(self.ready_final_slots.len() as i64);
+        EXECUTION_READONLY_QUEUE_LENGTH.set(self.readonly_requests.len() as i64);
+    }
+
     /// Internal function tool used in `self.wait_loop_event()`, check in the
     /// first place the content of `input_data` set by the controller.
     ///
@@ -538,6 +566,9 @@ impl ExecutionThread {
             // update the sequence of read-only requests
             self.update_readonly_requests(input_data.readonly_requests);
 
+            // export the state of the queues
+            self.update_metrics();
+
             // execute one slot as final, if there is one ready for final execution
             if self.execute_one_final_slot() {
                 // A slot was executed as final: restart the loop
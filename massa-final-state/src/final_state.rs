@@ -8,7 +8,7 @@
 use crate::{config::FinalStateConfig, error::FinalStateError, state_changes::StateChanges};
 use massa_async_pool::{AsyncMessageId, AsyncPool, AsyncPoolChanges, Change};
 use massa_ledger_exports::{LedgerChanges, LedgerController};
-use massa_models::{constants::THREAD_COUNT, Address, Slot};
+use massa_models::{constants::THREAD_COUNT, Address, Amount, Slot};
 use std::collections::VecDeque;
 
 /// Represents a final state `(ledger, async pool)`
@@ -28,7 +28,8 @@ pub struct FinalState {
 }
 
 impl FinalState {
-    /// Initializes a new `FinalState`
+    /// Initializes a new `FinalState`.
+    /// If the ledger holds a persisted final state, the state is resumed at its slot.
     ///
     /// # Arguments
     /// * `config`: the configuration of the execution state
@@ -36,8 +37,11 @@ impl FinalState {
         config: FinalStateConfig,
         ledger: Box<dyn LedgerController>,
     ) -> Result<Self, FinalStateError> {
-        // attach at the output of the latest initial final slot, that is the last genesis slot
-        let slot = Slot::new(0, config.thread_count.saturating_sub(1));
+        // attach at the output of the persisted final slot if any,
+        // otherwise at the output of the latest initial final slot, that is the last genesis slot
+        let slot = ledger
+            .get_slot()
+            .unwrap_or_else(|| Slot::new(0, config.thread_count.saturating_sub(1)));
 
         // NOTE: THIS SHOULD NOT BE DONE HERE
         // load the initial final ledger from file
@@ -45,8 +49,11 @@ impl FinalState {
         //     FinalStateError::LedgerError(format!("could not initialize ledger: {}", err))
         // })?;
 
-        // create the async pool
-        let async_pool = AsyncPool::new(config.async_pool_config.clone());
+        // create the async pool from the persisted messages
+        let async_pool = AsyncPool::from_messages(
+            config.async_pool_config.clone(),
+            ledger.get_async_pool_messages(),
+        );
 
         // generate the final state
         Ok(FinalState {
@@ -75,9 +82,12 @@ impl FinalState {
         // update current slot
         self.slot = slot;
 
-        // apply changes
-        self.ledger
-            .apply_changes(changes.ledger_changes.clone(), self.slot);
+        // apply changes, persisting the async pool changes and the slot along with the ledger ones
+        self.ledger.apply_changes(
+            changes.ledger_changes.clone(),
+            changes.async_pool_changes.clone(),
+            Some(self.slot),
+        );
         self.async_pool
             .apply_changes_unchecked(changes.async_pool_changes.clone());
 
@@ -90,6 +100,165 @@ impl FinalState {
         }
     }
 
+    /// Gets the parallel balance that a ledger entry had at the output of a given final slot.
+    ///
+    /// # Returns
+    /// The parallel balance, or None if the ledger entry was not found.
+    /// Fails if the slot is not covered by the changes history nor by the ledger archive.
+    pub fn get_parallel_balance_at(
+        &self,
+        addr: &Address,
+        slot: Slot,
+    ) -> Result<Option<Amount>, FinalStateError> {
+        self.get_ledger_value_at(
+            slot,
+            |changes, f| changes.get_parallel_balance_or_else(addr, f),
+            || self.ledger.get_parallel_balance(addr),
+            || self.ledger.get_archived_parallel_balance(addr, slot),
+        )
+    }
+
+    /// Gets a copy of the bytecode that a ledger entry had at the output of a given final slot.
+    ///
+    /// # Returns
+    /// The bytecode, or None if the ledger entry was not found.
+    /// Fails if the slot is not covered by the changes history nor by the ledger archive.
+    pub fn get_bytecode_at(
+        &self,
+        addr: &Address,
+        slot: Slot,
+    ) -> Result<Option<Vec<u8>>, FinalStateError> {
+        self.get_ledger_value_at(
+            slot,
+            |changes, f| changes.get_bytecode_or_else(addr, f),
+            || self.ledger.get_bytecode(addr),
+            || self.ledger.get_archived_bytecode(addr, slot),
+        )
+    }
+
+    /// Gets a copy of the value that a datastore entry had at the output of a given final slot.
+    ///
+    /// # Returns
+    /// The datastore value, or None if the ledger entry or datastore entry was not found.
+    /// Fails if the slot is not covered by the changes history nor by the ledger archive.
+    pub fn get_data_entry_at(
+        &self,
+        addr: &Address,
+        key: &[u8],
+        slot: Slot,
+    ) -> Result<Option<Vec<u8>>, FinalStateError> {
+        self.get_ledger_value_at(
+            slot,
+            |changes, f| changes.get_data_entry_or_else(addr, key, f),
+            || self.ledger.get_data_entry(addr, key),
+            || self.ledger.get_archived_data_entry(addr, key, slot),
+        )
+    }
+
+    /// Gets a ledger value at the output of a given final slot.
+    ///
+    /// If the changes history covers every slot after `slot`, the value is known when it was not changed since then,
+    /// or when it was set by one of the history changes up to `slot`.
+    /// Otherwise, the ledger archive is used.
+    ///
+    /// # Arguments
+    /// * `slot`: slot at the output of which the value is queried
+    /// * `get_in_changes`: gets the value from ledger changes, or from the provided fallback if the changes do not determine it
+    /// * `get_current`: gets the current value from the ledger
+    /// * `get_archived`: gets the value at `slot` from the ledger archive, `None` if the archive does not cover it
+    fn get_ledger_value_at<T>(
+        &self,
+        slot: Slot,
+        get_in_changes: impl Fn(&LedgerChanges, &mut dyn FnMut() -> Option<T>) -> Option<T>,
+        get_current: impl FnOnce() -> Option<T>,
+        get_archived: impl FnOnce() -> Option<Option<T>>,
+    ) -> Result<Option<T>, FinalStateError> {
+        if slot > self.slot {
+            return Err(FinalStateError::HistoryError(format!(
+                "slot {} is after the final slot {}",
+                slot, self.slot
+            )));
+        }
+
+        // returns the value if it is determined by the given changes
+        let get_known = |changes: &LedgerChanges| {
+            let mut known = true;
+            let value = get_in_changes(changes, &mut || {
+                known = false;
+                None
+            });
+            if known {
+                Some(value)
+            } else {
+                None
+            }
+        };
+
+        // the history covers the slot if it contains every slot after it
+        let covered = match self.changes_history.front() {
+            Some((first_slot, _)) => slot
+                .get_next_slot(self.config.thread_count)
+                .map_or(false, |next_slot| *first_slot <= next_slot),
+            None => slot == self.slot,
+        };
+        if covered {
+            let changed_after = self
+                .changes_history
+                .iter()
+                .rev()
+                .take_while(|(change_slot, _)| *change_slot > slot)
+                .any(|(_, changes)| get_known(&changes.ledger_changes).is_some());
+            if !changed_after {
+                return Ok(get_current());
+            }
+            if let Some(value) = self
+                .changes_history
+                .iter()
+                .rev()
+                .skip_while(|(change_slot, _)| *change_slot > slot)
+                .find_map(|(_, changes)| get_known(&changes.ledger_changes))
+            {
+                return Ok(value);
+            }
+        }
+
+        get_archived().ok_or_else(|| {
+            FinalStateError::HistoryError(format!(
+                "the state at slot {} is neither in the changes history nor in the ledger archive",
+                slot
+            ))
+        })
+    }
+
+    /// Used for bootstrap
+    /// Get the cursor from which a bootstrap can resume the persisted final state,
+    /// so that only the missing changes are streamed.
+    ///
+    /// # Returns
+    /// The last ledger key, the final slot and the last async pool message id,
+    /// or `None` if there is no complete persisted state to resume from
+    pub fn get_bootstrap_cursor(&self) -> Option<(Vec<u8>, Slot, AsyncMessageId)> {
+        let slot = self.ledger.get_slot()?;
+        let last_key = self.ledger.get_last_key()?;
+        // an empty pool is resumed from the message id with the highest priority
+        let last_id = self.async_pool.get_last_id().unwrap_or((
+            std::cmp::Reverse(Amount::from_raw(u64::MAX)),
+            Slot::new(0, 0),
+            0,
+        ));
+        Some((last_key, slot, last_id))
+    }
+
+    /// Used for bootstrap
+    /// Reset the final state to an empty state attached at the last genesis slot,
+    /// removing the persisted state from the disk.
+    pub fn reset(&mut self) {
+        self.ledger.reset();
+        self.async_pool = AsyncPool::new(self.config.async_pool_config.clone());
+        self.slot = Slot::new(0, self.config.thread_count.saturating_sub(1));
+        self.changes_history.clear();
+    }
+
     /// Used for bootstrap
     /// Take a part of the final state changes (ledger and async pool) using a `Slot`, a `Address` and a `AsyncMessageId`.
     /// Every ledgers changes that are after `last_slot` and before or equal of `last_address` must be returned.
@@ -140,7 +309,6 @@ impl FinalState {
                     })
                     .collect(),
             );
-            res_changes.ledger_changes = ledger_changes;
 
             //Get async pool changes that concern ids <= last_id_async_pool
             let async_pool_changes: AsyncPoolChanges = AsyncPoolChanges(
@@ -156,7 +324,12 @@ impl FinalState {
                     })
                     .collect(),
             );
-            res_changes.async_pool_changes = async_pool_changes;
+
+            // accumulate the changes of every slot, a resuming node can be many slots behind
+            res_changes.apply(StateChanges {
+                ledger_changes,
+                async_pool_changes,
+            });
         }
         Ok(res_changes)
     }
@@ -167,10 +340,11 @@ mod tests {
 
     use std::collections::VecDeque;
 
-    use crate::{FinalState, StateChanges};
+    use crate::{FinalState, FinalStateConfig, StateChanges};
     use massa_async_pool::test_exports::get_random_message;
-    use massa_ledger_exports::SetUpdateOrDelete;
-    use massa_models::{Address, Slot};
+    use massa_ledger_exports::{LedgerConfig, LedgerEntry, SetUpdateOrDelete};
+    use massa_ledger_worker::FinalLedger;
+    use massa_models::{Address, Amount, Slot};
     use massa_signature::{derive_public_key, generate_random_private_key};
 
     fn get_random_address() -> Address {
@@ -226,4 +400,130 @@ mod tests {
             .unwrap();
         assert_eq!(part.ledger_changes.0.len(), 1);
     }
+
+    #[test]
+    fn get_ledger_values_at() {
+        massa_models::init_serialization_context(Default::default());
+        let address = get_random_address();
+        let mut final_state: FinalState = Default::default();
+
+        let mut state_changes = StateChanges::default();
+        state_changes.ledger_changes.0.insert(
+            address,
+            SetUpdateOrDelete::Set(LedgerEntry {
+                parallel_balance: Amount::from_raw(10),
+                ..Default::default()
+            }),
+        );
+        final_state.finalize(Slot::new(1, 0), state_changes);
+        let mut state_changes = StateChanges::default();
+        state_changes
+            .ledger_changes
+            .set_parallel_balance(address, Amount::from_raw(20));
+        final_state.finalize(Slot::new(1, 1), state_changes);
+        let mut state_changes = StateChanges::default();
+        state_changes
+            .ledger_changes
+            .set_data_entry(address, b"k".to_vec(), b"v".to_vec());
+        final_state.finalize(Slot::new(2, 0), state_changes);
+
+        // values set by the history
+        assert_eq!(
+            final_state
+                .get_parallel_balance_at(&address, Slot::new(1, 0))
+                .unwrap(),
+            Some(Amount::from_raw(10))
+        );
+        assert_eq!(
+            final_state
+                .get_data_entry_at(&address, b"k", Slot::new(1, 1))
+                .unwrap(),
+            None
+        );
+        // values that did not change since the slot
+        assert_eq!(
+            final_state
+                .get_parallel_balance_at(&address, Slot::new(1, 1))
+                .unwrap(),
+            Some(Amount::from_raw(20))
+        );
+        assert_eq!(
+            final_state
+                .get_data_entry_at(&address, b"k", Slot::new(2, 0))
+                .unwrap(),
+            Some(b"v".to_vec())
+        );
+        // unknown values without archive, and slots in the future
+        assert!(final_state
+            .get_parallel_balance_at(&address, Slot::new(0, 1))
+            .is_err());
+        assert!(final_state
+            .get_parallel_balance_at(&address, Slot::new(2, 1))
+            .is_err());
+    }
+
+    /// The final state persisted by a node is resumed at its slot when the node restarts,
+    /// unless it was persisted for another network or genesis
+    #[test]
+    fn resume_persisted_final_state() {
+        massa_models::init_serialization_context(Default::default());
+        let address = get_random_address();
+        let (ledger_config, _keep_file, _keep_dir) = LedgerConfig::sample(&Default::default());
+        let open = |ledger_config: &LedgerConfig| {
+            let config = FinalStateConfig {
+                ledger_config: ledger_config.clone(),
+                ..Default::default()
+            };
+            let ledger = FinalLedger::new(ledger_config.clone()).unwrap();
+            FinalState::new(config, Box::new(ledger)).unwrap()
+        };
+
+        // a fresh node starts at the last genesis slot
+        let mut final_state = open(&ledger_config);
+        assert_eq!(final_state.slot, Slot::new(0, 1));
+        assert!(final_state.get_bootstrap_cursor().is_none());
+        let message = get_random_message();
+        let mut state_changes = StateChanges::default();
+        state_changes
+            .ledger_changes
+            .set_parallel_balance(address, Amount::from_raw(10));
+        state_changes
+            .async_pool_changes
+            .0
+            .push(massa_async_pool::Change::Add(
+                message.compute_id(),
+                message.clone(),
+            ));
+        final_state.finalize(Slot::new(1, 0), state_changes);
+        final_state.finalize(Slot::new(1, 1), StateChanges::default());
+        drop(final_state);
+
+        // the restarted node resumes the stored state
+        let final_state = open(&ledger_config);
+        assert_eq!(final_state.slot, Slot::new(1, 1));
+        assert_eq!(
+            final_state.ledger.get_parallel_balance(&address),
+            Some(Amount::from_raw(10))
+        );
+        assert_eq!(
+            final_state.async_pool.get_last_id(),
+            Some(message.compute_id())
+        );
+        assert_eq!(
+            final_state.get_bootstrap_cursor().map(|(_, slot, _)| slot),
+            Some(Slot::new(1, 1))
+        );
+        drop(final_state);
+
+        // the stored state of another network is discarded
+        let other_network = LedgerConfig {
+            network_id: "OTHER".into(),
+            ..ledger_config
+        };
+        let final_state = open(&other_network);
+        assert_eq!(final_state.slot, Slot::new(0, 1));
+        assert!(final_state.ledger.get_parallel_balance(&address).is_none());
+        assert!(final_state.async_pool.get_last_id().is_none());
+        assert!(final_state.get_bootstrap_cursor().is_none());
+    }
 }
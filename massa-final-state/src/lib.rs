@@ -16,6 +16,8 @@
 //! the latest executed final slot. It contains the final ledger and the asynchronous event pool.
 //! It can be manipulated using `StateChanges` (see `state_changes.rs`).
 //! The `FinalState` is bootstrapped using tooling available in bootstrap.rs
+//! The asynchronous pool and the final slot are persisted along with the final ledger,
+//! so that the `FinalState` is resumed from disk after a restart.
 //!
 //! ## `state_changes.rs`
 //! Represents a list of changes the final state.
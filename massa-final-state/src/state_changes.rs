@@ -47,7 +47,8 @@ impl Default for StateChangesSerializer {
 impl Serializer<StateChanges> for StateChangesSerializer {
     /// ```
     /// use massa_serialization::Serializer;
-    /// use massa_models::{Address, prehash::Map, Amount, Slot};
+    /// use massa_models::{Address, Amount, Slot};
+    /// use std::collections::BTreeMap;
     /// use massa_final_state::{StateChanges, StateChangesSerializer};
     /// use std::str::FromStr;
     /// use massa_ledger_exports::{LedgerEntryUpdate, SetOrKeep, SetUpdateOrDelete, LedgerChanges};
@@ -75,7 +76,7 @@ impl Serializer<StateChanges> for StateChangesSerializer {
     /// let ledger_entry = LedgerEntryUpdate {
     ///    parallel_balance: SetOrKeep::Set(amount),
     ///    bytecode: SetOrKeep::Set(bytecode),
-    ///    datastore: Map::default(),
+    ///    datastore: BTreeMap::default(),
     /// };
     /// let mut ledger_changes = LedgerChanges::default();
     /// ledger_changes.0.insert(
@@ -120,7 +121,8 @@ impl Default for StateChangesDeserializer {
 impl Deserializer<StateChanges> for StateChangesDeserializer {
     /// ```
     /// use massa_serialization::{Serializer, Deserializer, DeserializeError};
-    /// use massa_models::{Address, prehash::Map, Amount, Slot};
+    /// use massa_models::{Address, Amount, Slot};
+    /// use std::collections::BTreeMap;
     /// use massa_final_state::{StateChanges, StateChangesSerializer, StateChangesDeserializer};
     /// use std::str::FromStr;
     /// use massa_ledger_exports::{LedgerEntryUpdate, SetOrKeep, SetUpdateOrDelete, LedgerChanges};
@@ -148,7 +150,7 @@ impl Deserializer<StateChanges> for StateChangesDeserializer {
     /// let ledger_entry = LedgerEntryUpdate {
     ///    parallel_balance: SetOrKeep::Set(amount),
     ///    bytecode: SetOrKeep::Set(bytecode),
-    ///    datastore: Map::default(),
+    ///    datastore: BTreeMap::default(),
     /// };
     /// let mut ledger_changes = LedgerChanges::default();
     /// ledger_changes.0.insert(
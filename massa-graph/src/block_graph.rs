@@ -2430,6 +2430,7 @@ impl BlockGraph {
     /// `consensus/pos.md#block-reception-process`
     ///
     /// Checks performed:
+    /// - Check that multisig ops are signed by enough keys of their policy.
     /// - Check that ops were not reused in previous blocks.
     fn check_operations(
         &self,
@@ -2440,8 +2441,24 @@ impl BlockGraph {
         // check that ops are not reused in previous blocks. Note that in-block reuse was checked in protocol.
         let mut dependencies: Set<BlockId> = Set::<BlockId>::default();
         for operation in block_to_check.operations.iter() {
+            // protocol skips the signature check of ops it has already seen,
+            // so the co-signatures carried by this block are checked again
+            if operation.content.multisig.is_some() {
+                if let Err(err) = operation.verify_integrity() {
+                    warn!(
+                        "block graph check_operations error, invalid multisig operation: {}",
+                        err
+                    );
+                    return Ok(BlockOperationsCheckOutcome::Discard(
+                        DiscardReason::Invalid(format!("invalid multisig operation: {}", err)),
+                    ));
+                }
+            }
+
             // get thread
-            let op_thread = Address::from_public_key(&operation.content.sender_public_key)
+            let op_thread = operation
+                .content
+                .get_sender_address()
                 .get_thread(self.cfg.thread_count);
 
             let op_start_validity_period = *operation
@@ -3134,6 +3151,8 @@ impl BlockGraph {
                 }
                 // update new final blocks list
                 self.new_final_blocks.insert(final_block_hash);
+                // archive the final block before it gets pruned, if the block archive is enabled
+                self.storage.archive_final_block(&final_block_hash);
             } else {
                 return Err(GraphError::ContainerInconsistency(format!("inconsistency inside block statuses updating final blocks adding {} - block {} is missing", add_block_id, final_block_hash)));
             }
@@ -73,7 +73,7 @@ impl OperationLedgerInterface for Operation {
         let mut res = LedgerChanges::default();
 
         // sender fee
-        let sender_address = Address::from_public_key(&self.sender_public_key);
+        let sender_address = self.get_sender_address();
         res.apply(
             &sender_address,
             &LedgerChange {
@@ -161,6 +161,26 @@ impl OperationLedgerInterface for Operation {
                     },
                 )?;
             }
+            // the changes of all transfers are accumulated and applied together,
+            // so the whole batch is rejected if the sender can not afford it
+            massa_models::OperationType::BatchTransaction { transfers } => {
+                for (recipient_address, amount) in transfers.iter() {
+                    res.apply(
+                        &sender_address,
+                        &LedgerChange {
+                            balance_delta: (*amount),
+                            balance_increment: false,
+                        },
+                    )?;
+                    res.apply(
+                        recipient_address,
+                        &LedgerChange {
+                            balance_delta: (*amount),
+                            balance_increment: true,
+                        },
+                    )?;
+                }
+            }
         }
 
         Ok(res)
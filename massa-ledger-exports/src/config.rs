@@ -11,4 +11,9 @@ pub struct LedgerConfig {
     pub initial_sce_ledger_path: PathBuf,
     /// disk ledger db directory
     pub disk_ledger_path: PathBuf,
+    /// keep reverse diffs of every final slot on disk to answer historical queries
+    pub archive_mode: bool,
+    /// identifier of the network and genesis of the final state:
+    /// a final state persisted with another identifier is not resumed
+    pub network_id: String,
 }
@@ -1,13 +1,41 @@
-use massa_hash::Hash;
+use massa_async_pool::{AsyncMessage, AsyncMessageId, AsyncPoolChanges};
 use massa_models::{Address, Amount, ModelsError, Slot};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::Debug;
 
 use crate::{LedgerChanges, LedgerEntry};
 
 pub trait LedgerController: Send + Sync + Debug {
-    /// Allows applying `LedgerChanges` to the final ledger
-    fn apply_changes(&mut self, changes: LedgerChanges, slot: Slot);
+    /// Allows applying `LedgerChanges` to the final ledger.
+    /// The `AsyncPoolChanges` of the final state are persisted in the same batch,
+    /// along with the slot at the output of which the persisted final state is attached.
+    ///
+    /// # Arguments
+    /// * `changes`: ledger changes to apply
+    /// * `async_pool_changes`: asynchronous pool changes to persist
+    /// * `slot`: new final slot, or `None` to detach the persisted state from any slot
+    ///   so that it is not resumed after a restart (used while bootstrapping)
+    fn apply_changes(
+        &mut self,
+        changes: LedgerChanges,
+        async_pool_changes: AsyncPoolChanges,
+        slot: Option<Slot>,
+    );
+
+    /// Gets the slot at the output of which the persisted final state is attached
+    ///
+    /// # Returns
+    /// The slot, or `None` if the persisted state is detached (empty or partially bootstrapped)
+    fn get_slot(&self) -> Option<Slot>;
+
+    /// Gets the persisted asynchronous pool messages
+    fn get_async_pool_messages(&self) -> BTreeMap<AsyncMessageId, AsyncMessage>;
+
+    /// Gets the last key of the ledger, used to resume a bootstrap
+    fn get_last_key(&self) -> Option<Vec<u8>>;
+
+    /// Removes every ledger entry, asynchronous pool message and the slot from the disk
+    fn reset(&mut self);
 
     /// Gets the parallel balance of a ledger entry
     ///
@@ -35,7 +63,7 @@ pub trait LedgerController: Send + Sync + Debug {
     ///
     /// # Returns
     /// A copy of the datastore value, or `None` if the ledger entry or datastore entry was not found
-    fn get_data_entry(&self, addr: &Address, key: &Hash) -> Option<Vec<u8>>;
+    fn get_data_entry(&self, addr: &Address, key: &[u8]) -> Option<Vec<u8>>;
 
     /// Checks for the existence of a datastore entry for a given address.
     ///
@@ -45,11 +73,57 @@ pub trait LedgerController: Send + Sync + Debug {
     ///
     /// # Returns
     /// true if the datastore entry was found, or false if the ledger entry or datastore entry was not found
-    fn has_data_entry(&self, addr: &Address, key: &Hash) -> bool;
+    fn has_data_entry(&self, addr: &Address, key: &[u8]) -> bool;
 
     /// # Returns
     /// A copy of the datastore sorted by key
-    fn get_entire_datastore(&self, addr: &Address) -> BTreeMap<Hash, Vec<u8>>;
+    fn get_entire_datastore(&self, addr: &Address) -> BTreeMap<Vec<u8>, Vec<u8>>;
+
+    /// Gets a page of the datastore keys of a given address, sorted in lexicographic order.
+    ///
+    /// # Arguments
+    /// * `addr`: target address
+    /// * `prefix`: only keys starting with this prefix are returned
+    /// * `cursor`: only keys strictly greater than the cursor are returned
+    /// * `count`: maximum number of returned keys
+    ///
+    /// # Returns
+    /// The first `count` matching datastore keys, empty if the ledger entry was not found
+    fn get_datastore_keys(
+        &self,
+        addr: &Address,
+        prefix: &[u8],
+        cursor: Option<&[u8]>,
+        count: usize,
+    ) -> BTreeSet<Vec<u8>>;
+
+    /// Gets the parallel balance that a ledger entry had at the output of a given slot, using the archive
+    ///
+    /// # Returns
+    /// `None` if the archive does not cover the slot, otherwise the optional parallel balance
+    fn get_archived_parallel_balance(&self, addr: &Address, slot: Slot) -> Option<Option<Amount>>;
+
+    /// Gets a copy of the bytecode that a ledger entry had at the output of a given slot, using the archive
+    ///
+    /// # Returns
+    /// `None` if the archive does not cover the slot, otherwise the optional bytecode
+    fn get_archived_bytecode(&self, addr: &Address, slot: Slot) -> Option<Option<Vec<u8>>>;
+
+    /// Gets a copy of the value that a datastore entry had at the output of a given slot, using the archive
+    ///
+    /// # Arguments
+    /// * `addr`: target address
+    /// * `key`: datastore key
+    /// * `slot`: slot at the output of which the value is queried
+    ///
+    /// # Returns
+    /// `None` if the archive does not cover the slot, otherwise the optional datastore value
+    fn get_archived_data_entry(
+        &self,
+        addr: &Address,
+        key: &[u8],
+        slot: Slot,
+    ) -> Option<Option<Vec<u8>>>;
 
     /// TODO: remove when API is updated
     fn get_full_entry(&self, addr: &Address) -> Option<LedgerEntry>;
@@ -1,7 +1,8 @@
-use massa_hash::HashDeserializer;
-use massa_models::{address::AddressDeserializer, Address};
+use massa_models::constants::{ADDRESS_SIZE_BYTES, MAX_DATASTORE_KEY_LENGTH};
+use massa_models::{address::AddressDeserializer, Address, VecU8Deserializer, VecU8Serializer};
 use massa_serialization::{DeserializeError, Deserializer, Serializer};
 use nom::error::{ContextError, ParseError};
+use std::ops::Bound::Included;
 
 pub const BALANCE_IDENT: u8 = 0u8;
 pub const BYTECODE_IDENT: u8 = 1u8;
@@ -25,18 +26,14 @@ macro_rules! bytecode_key {
     };
 }
 
-/// Datastore entry key formatting macro
+/// Datastore entry key formatting macro.
+/// The original datastore key bytes are kept so that the keys of an address can be listed.
 ///
 /// TODO: add a separator identifier if the need comes to have multiple datastores
 #[macro_export]
 macro_rules! data_key {
     ($addr:expr, $key:expr) => {
-        [
-            &$addr.to_bytes()[..],
-            &[DATASTORE_IDENT],
-            &$key.to_bytes()[..],
-        ]
-        .concat()
+        [&$addr.to_bytes()[..], &[DATASTORE_IDENT], &$key[..]].concat()
     };
 }
 
@@ -57,14 +54,27 @@ pub fn get_address_from_key(key: &[u8]) -> Option<Address> {
         .ok()
 }
 
-/// Basic key serializer
-#[derive(Default)]
-pub struct KeySerializer;
+/// Basic key serializer.
+/// Datastore keys have a variable length, so they are prefixed by their length.
+pub struct KeySerializer {
+    datastore_key_serializer: VecU8Serializer,
+}
 
 impl KeySerializer {
     /// Creates a new `KeySerializer`
     pub fn new() -> Self {
-        Self
+        Self {
+            datastore_key_serializer: VecU8Serializer::new(
+                Included(0),
+                Included(MAX_DATASTORE_KEY_LENGTH as u64),
+            ),
+        }
+    }
+}
+
+impl Default for KeySerializer {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -73,16 +83,14 @@ impl Serializer<Vec<u8>> for KeySerializer {
     /// use massa_models::address::Address;
     /// use massa_ledger_exports::KeySerializer;
     /// use massa_serialization::Serializer;
-    /// use massa_hash::Hash;
     /// use std::str::FromStr;
     ///
     /// let mut serialized = Vec::new();
     /// let address = Address::from_str("A12dG5xP1RDEB5ocdHkymNVvvSJmUL9BgHwCksDowqmGWxfpm93x").unwrap();
-    /// let store_key = Hash::compute_from(b"test");
     /// let mut key = Vec::new();
     /// key.extend(address.to_bytes());
     /// key.push(2u8);
-    /// key.extend(store_key.to_bytes());
+    /// key.extend(b"test");
     /// KeySerializer::new().serialize(&key, &mut serialized).unwrap();
     /// ```
     fn serialize(
@@ -90,16 +98,24 @@ impl Serializer<Vec<u8>> for KeySerializer {
         value: &Vec<u8>,
         buffer: &mut Vec<u8>,
     ) -> Result<(), massa_serialization::SerializeError> {
-        buffer.extend(value);
-        Ok(())
+        match value.get(ADDRESS_SIZE_BYTES) {
+            Some(&DATASTORE_IDENT) => {
+                buffer.extend(&value[..=ADDRESS_SIZE_BYTES]);
+                self.datastore_key_serializer
+                    .serialize(&value[ADDRESS_SIZE_BYTES + 1..].to_vec(), buffer)
+            }
+            _ => {
+                buffer.extend(value);
+                Ok(())
+            }
+        }
     }
 }
 
 /// Basic key deserializer
-#[derive(Default)]
 pub struct KeyDeserializer {
     address_deserializer: AddressDeserializer,
-    hash_deserializer: HashDeserializer,
+    datastore_key_deserializer: VecU8Deserializer,
 }
 
 impl KeyDeserializer {
@@ -107,27 +123,34 @@ impl KeyDeserializer {
     pub fn new() -> Self {
         Self {
             address_deserializer: AddressDeserializer::new(),
-            hash_deserializer: HashDeserializer::new(),
+            datastore_key_deserializer: VecU8Deserializer::new(
+                Included(0),
+                Included(MAX_DATASTORE_KEY_LENGTH as u64),
+            ),
         }
     }
 }
 
+impl Default for KeyDeserializer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 // TODO: deserialize keys into a rust type
 impl Deserializer<Vec<u8>> for KeyDeserializer {
     /// ```
     /// use massa_models::address::Address;
     /// use massa_ledger_exports::{KeyDeserializer, KeySerializer};
     /// use massa_serialization::{Deserializer, Serializer, DeserializeError};
-    /// use massa_hash::Hash;
     /// use std::str::FromStr;
     ///
     /// let mut serialized = Vec::new();
     /// let address = Address::from_str("A12dG5xP1RDEB5ocdHkymNVvvSJmUL9BgHwCksDowqmGWxfpm93x").unwrap();
-    /// let store_key = Hash::compute_from(b"test");
     /// let mut key = Vec::new();
     /// key.extend(address.to_bytes());
     /// key.push(2u8);
-    /// key.extend(store_key.to_bytes());
+    /// key.extend(b"test");
     /// KeySerializer::new().serialize(&key, &mut serialized).unwrap();
     /// let (rest, key_deser) = KeyDeserializer::new().deserialize::<DeserializeError>(&serialized).unwrap();
     /// assert!(rest.is_empty());
@@ -147,8 +170,8 @@ impl Deserializer<Vec<u8>> for KeyDeserializer {
                 BALANCE_IDENT => Ok((&rest[1..], balance_key!(address))),
                 BYTECODE_IDENT => Ok((&rest[1..], bytecode_key!(address))),
                 DATASTORE_IDENT => {
-                    let (rest, hash) = self.hash_deserializer.deserialize(&rest[1..])?;
-                    Ok((rest, data_key!(address, hash)))
+                    let (rest, key) = self.datastore_key_deserializer.deserialize(&rest[1..])?;
+                    Ok((rest, data_key!(address, key)))
                 }
                 _ => Err(error),
             },
@@ -8,9 +8,9 @@ use crate::types::{
     SetOrKeepDeserializer, SetOrKeepSerializer, SetUpdateOrDelete, SetUpdateOrDeleteDeserializer,
     SetUpdateOrDeleteSerializer,
 };
-use massa_hash::{Hash, HashDeserializer};
 use massa_models::address::AddressDeserializer;
 use massa_models::amount::{AmountDeserializer, AmountSerializer};
+use massa_models::constants::MAX_DATASTORE_KEY_LENGTH;
 use massa_models::{prehash::Map, Address, Amount};
 use massa_models::{VecU8Deserializer, VecU8Serializer};
 use massa_serialization::{
@@ -20,7 +20,7 @@ use nom::error::{context, ContextError, ParseError};
 use nom::multi::length_count;
 use nom::sequence::tuple;
 use nom::{IResult, Parser};
-use std::collections::hash_map;
+use std::collections::{hash_map, BTreeMap, BTreeSet};
 use std::ops::Bound::Included;
 
 /// represents an update to one or more fields of a `LedgerEntry`
@@ -31,12 +31,13 @@ pub struct LedgerEntryUpdate {
     /// change the executable bytecode
     pub bytecode: SetOrKeep<Vec<u8>>,
     /// change datastore entries
-    pub datastore: Map<Hash, SetOrDelete<Vec<u8>>>,
+    pub datastore: BTreeMap<Vec<u8>, SetOrDelete<Vec<u8>>>,
 }
 
 /// Serializer for `datastore` field of `LedgerEntryUpdate`
 pub struct DatastoreUpdateSerializer {
     u64_serializer: U64VarIntSerializer,
+    key_serializer: VecU8Serializer,
     value_serializer: SetOrDeleteSerializer<Vec<u8>, VecU8Serializer>,
 }
 
@@ -45,6 +46,10 @@ impl DatastoreUpdateSerializer {
     pub fn new() -> Self {
         Self {
             u64_serializer: U64VarIntSerializer::new(Included(u64::MIN), Included(u64::MAX)),
+            key_serializer: VecU8Serializer::new(
+                Included(u64::MIN),
+                Included(MAX_DATASTORE_KEY_LENGTH as u64),
+            ),
             value_serializer: SetOrDeleteSerializer::new(VecU8Serializer::new(
                 Included(u64::MIN),
                 Included(u64::MAX),
@@ -53,10 +58,10 @@ impl DatastoreUpdateSerializer {
     }
 }
 
-impl Serializer<Map<Hash, SetOrDelete<Vec<u8>>>> for DatastoreUpdateSerializer {
+impl Serializer<BTreeMap<Vec<u8>, SetOrDelete<Vec<u8>>>> for DatastoreUpdateSerializer {
     fn serialize(
         &self,
-        value: &Map<Hash, SetOrDelete<Vec<u8>>>,
+        value: &BTreeMap<Vec<u8>, SetOrDelete<Vec<u8>>>,
         buffer: &mut Vec<u8>,
     ) -> Result<(), SerializeError> {
         let entry_count: u64 = value.len().try_into().map_err(|err| {
@@ -67,7 +72,7 @@ impl Serializer<Map<Hash, SetOrDelete<Vec<u8>>>> for DatastoreUpdateSerializer {
         })?;
         self.u64_serializer.serialize(&entry_count, buffer)?;
         for (key, value) in value.iter() {
-            buffer.extend(key.to_bytes());
+            self.key_serializer.serialize(key, buffer)?;
             self.value_serializer.serialize(value, buffer)?;
         }
         Ok(())
@@ -77,7 +82,7 @@ impl Serializer<Map<Hash, SetOrDelete<Vec<u8>>>> for DatastoreUpdateSerializer {
 /// Serializer for `datastore` field of `LedgerEntryUpdate`
 pub struct DatastoreUpdateDeserializer {
     u64_deserializer: U64VarIntDeserializer,
-    hash_deserializer: HashDeserializer,
+    key_deserializer: VecU8Deserializer,
     value_deserializer: SetOrDeleteDeserializer<Vec<u8>, VecU8Deserializer>,
 }
 
@@ -86,7 +91,10 @@ impl DatastoreUpdateDeserializer {
     pub fn new() -> Self {
         Self {
             u64_deserializer: U64VarIntDeserializer::new(Included(u64::MIN), Included(u64::MAX)),
-            hash_deserializer: HashDeserializer::default(),
+            key_deserializer: VecU8Deserializer::new(
+                Included(u64::MIN),
+                Included(MAX_DATASTORE_KEY_LENGTH as u64),
+            ),
             value_deserializer: SetOrDeleteDeserializer::new(VecU8Deserializer::new(
                 Included(u64::MIN),
                 Included(u64::MAX),
@@ -95,11 +103,11 @@ impl DatastoreUpdateDeserializer {
     }
 }
 
-impl Deserializer<Map<Hash, SetOrDelete<Vec<u8>>>> for DatastoreUpdateDeserializer {
+impl Deserializer<BTreeMap<Vec<u8>, SetOrDelete<Vec<u8>>>> for DatastoreUpdateDeserializer {
     fn deserialize<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
         &self,
         buffer: &'a [u8],
-    ) -> IResult<&'a [u8], Map<Hash, SetOrDelete<Vec<u8>>>, E> {
+    ) -> IResult<&'a [u8], BTreeMap<Vec<u8>, SetOrDelete<Vec<u8>>>, E> {
         context(
             "Failed Datastore deserialization",
             length_count(
@@ -108,7 +116,7 @@ impl Deserializer<Map<Hash, SetOrDelete<Vec<u8>>>> for DatastoreUpdateDeserializ
                 }),
                 |input| {
                     tuple((
-                        |input| self.hash_deserializer.deserialize(input),
+                        |input| self.key_deserializer.deserialize(input),
                         |input| self.value_deserializer.deserialize(input),
                     ))(input)
                 },
@@ -152,14 +160,14 @@ impl Default for LedgerEntryUpdateSerializer {
 impl Serializer<LedgerEntryUpdate> for LedgerEntryUpdateSerializer {
     /// ```
     /// use massa_serialization::Serializer;
-    /// use massa_models::{prehash::Map, Address, Amount};
+    /// use massa_models::{Address, Amount};
+    /// use std::collections::BTreeMap;
     /// use std::str::FromStr;
     /// use massa_ledger_exports::{SetOrDelete, SetOrKeep, LedgerEntryUpdate, LedgerEntryUpdateSerializer};
-    /// use massa_hash::Hash;
     ///
-    /// let hash = Hash::compute_from(&"hello world".as_bytes());
-    /// let mut store = Map::default();
-    /// store.insert(hash, SetOrDelete::Set(vec![1, 2, 3]));
+    /// let key = "hello world".as_bytes().to_vec();
+    /// let mut store = BTreeMap::new();
+    /// store.insert(key, SetOrDelete::Set(vec![1, 2, 3]));
     /// let amount = Amount::from_str("1").unwrap();
     /// let bytecode = vec![1, 2, 3];
     /// let ledger_entry = LedgerEntryUpdate {
@@ -219,14 +227,14 @@ impl Default for LedgerEntryUpdateDeserializer {
 impl Deserializer<LedgerEntryUpdate> for LedgerEntryUpdateDeserializer {
     /// ```
     /// use massa_serialization::{Deserializer, Serializer, DeserializeError};
-    /// use massa_models::{prehash::Map, Address, Amount};
+    /// use massa_models::{Address, Amount};
+    /// use std::collections::BTreeMap;
     /// use std::str::FromStr;
     /// use massa_ledger_exports::{SetOrDelete, SetOrKeep, LedgerEntryUpdate, LedgerEntryUpdateSerializer, LedgerEntryUpdateDeserializer};
-    /// use massa_hash::Hash;
     ///
-    /// let hash = Hash::compute_from(&"hello world".as_bytes());
-    /// let mut store = Map::default();
-    /// store.insert(hash, SetOrDelete::Set(vec![1, 2, 3]));
+    /// let key = "hello world".as_bytes().to_vec();
+    /// let mut store = BTreeMap::new();
+    /// store.insert(key, SetOrDelete::Set(vec![1, 2, 3]));
     /// let amount = Amount::from_str("1").unwrap();
     /// let bytecode = vec![1, 2, 3];
     /// let ledger_entry = LedgerEntryUpdate {
@@ -321,11 +329,10 @@ impl Serializer<LedgerChanges> for LedgerChangesSerializer {
     /// use std::str::FromStr;
     /// use std::collections::BTreeMap;
     /// use massa_models::{Amount, Address};
-    /// use massa_hash::Hash;
     ///
-    /// let hash = Hash::compute_from(&"hello world".as_bytes());
+    /// let key = "hello world".as_bytes().to_vec();
     /// let mut store = BTreeMap::new();
-    /// store.insert(hash, vec![1, 2, 3]);
+    /// store.insert(key, vec![1, 2, 3]);
     /// let amount = Amount::from_str("1").unwrap();
     /// let bytecode = vec![1, 2, 3];
     /// let ledger_entry = LedgerEntry {
@@ -393,11 +400,10 @@ impl Deserializer<LedgerChanges> for LedgerChangesDeserializer {
     /// use std::str::FromStr;
     /// use std::collections::BTreeMap;
     /// use massa_models::{Amount, Address};
-    /// use massa_hash::Hash;
     ///
-    /// let hash = Hash::compute_from(&"hello world".as_bytes());
+    /// let key = "hello world".as_bytes().to_vec();
     /// let mut store = BTreeMap::new();
-    /// store.insert(hash, vec![1, 2, 3]);
+    /// store.insert(key, vec![1, 2, 3]);
     /// let amount = Amount::from_str("1").unwrap();
     /// let bytecode = vec![1, 2, 3];
     /// let ledger_entry = LedgerEntry {
@@ -705,7 +711,7 @@ impl LedgerChanges {
     pub fn get_data_entry_or_else<F: FnOnce() -> Option<Vec<u8>>>(
         &self,
         addr: &Address,
-        key: &Hash,
+        key: &[u8],
         f: F,
     ) -> Option<Vec<u8>> {
         // Get the current changes being applied to the ledger entry associated to that address
@@ -760,7 +766,7 @@ impl LedgerChanges {
     pub fn has_data_entry_or_else<F: FnOnce() -> bool>(
         &self,
         addr: &Address,
-        key: &Hash,
+        key: &[u8],
         f: F,
     ) -> bool {
         // Get the current changes being applied to the ledger entry associated to that address
@@ -796,6 +802,72 @@ impl LedgerChanges {
         }
     }
 
+    /// Tries to return a page of the datastore keys of a given address starting with a prefix,
+    /// or gets them from a function if the status of the datastore is unknown.
+    ///
+    /// # Arguments
+    /// * `addr`: target address
+    /// * `prefix`: only keys starting with this prefix are returned
+    /// * `cursor`: only keys strictly greater than the cursor are returned
+    /// * `count`: maximum number of returned keys
+    /// * `f`: fallback function returning the given number of first keys of the page known before these changes
+    ///
+    /// # Returns
+    /// The first `count` datastore keys of the address starting with `prefix` once the changes are applied
+    pub fn get_datastore_keys_or_else<F: FnOnce(usize) -> BTreeSet<Vec<u8>>>(
+        &self,
+        addr: &Address,
+        prefix: &[u8],
+        cursor: Option<&[u8]>,
+        count: usize,
+        f: F,
+    ) -> BTreeSet<Vec<u8>> {
+        let in_page = |key: &Vec<u8>| {
+            key.starts_with(prefix) && cursor.map_or(true, |cursor| key.as_slice() > cursor)
+        };
+        match self.0.get(addr) {
+            // This ledger entry is being replaced by a new one:
+            // list the keys of the replacement datastore
+            Some(SetUpdateOrDelete::Set(v)) => v
+                .datastore
+                .keys()
+                .filter(|key| in_page(key))
+                .take(count)
+                .cloned()
+                .collect(),
+
+            // This ledger entry is being updated:
+            // apply the datastore updates to the keys returned by the fallback function
+            Some(SetUpdateOrDelete::Update(LedgerEntryUpdate { datastore, .. })) => {
+                // each deleted key can remove one of the keys returned by the fallback function,
+                // so as many more keys are requested to still fill the page
+                let deleted_count = datastore
+                    .iter()
+                    .filter(|(key, update)| in_page(key) && matches!(update, SetOrDelete::Delete))
+                    .count();
+                let mut keys = f(count.saturating_add(deleted_count));
+                for (key, update) in datastore.iter().filter(|(key, _)| in_page(key)) {
+                    match update {
+                        SetOrDelete::Set(_) => {
+                            keys.insert(key.clone());
+                        }
+                        SetOrDelete::Delete => {
+                            keys.remove(key);
+                        }
+                    }
+                }
+                keys.into_iter().take(count).collect()
+            }
+
+            // This ledger entry is being deleted: it has no datastore anymore
+            Some(SetUpdateOrDelete::Delete) => BTreeSet::new(),
+
+            // This ledger entry is not being changed.
+            // We call the fallback function and return its output.
+            None => f(count),
+        }
+    }
+
     /// Set a datastore entry for a given address.
     /// If the address doesn't exist, its ledger entry is created.
     /// If the datastore entry exists, its value is replaced, otherwise it is created.
@@ -804,7 +876,7 @@ impl LedgerChanges {
     /// * `addr`: target address
     /// * `key`: datastore key
     /// * `data`: datastore value to set
-    pub fn set_data_entry(&mut self, addr: Address, key: Hash, data: Vec<u8>) {
+    pub fn set_data_entry(&mut self, addr: Address, key: Vec<u8>, data: Vec<u8>) {
         // Get the changes being applied to the ledger entry associated to that address
         match self.0.entry(addr) {
             // There are changes currently being applied to the ledger entry
@@ -852,7 +924,7 @@ impl LedgerChanges {
     /// # Arguments
     /// * `addr`: target address
     /// * `key`: datastore key
-    pub fn delete_data_entry(&mut self, addr: Address, key: Hash) {
+    pub fn delete_data_entry(&mut self, addr: Address, key: Vec<u8>) {
         // Get the changes being applied to the ledger entry associated to that address
         match self.0.entry(addr) {
             // There are changes currently being applied to the ledger entry
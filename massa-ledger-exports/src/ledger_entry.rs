@@ -4,12 +4,10 @@
 
 use crate::ledger_changes::LedgerEntryUpdate;
 use crate::types::{Applicable, SetOrDelete};
-use massa_hash::HASH_SIZE_BYTES;
-use massa_hash::{Hash, HashDeserializer};
 use massa_models::amount::{AmountDeserializer, AmountSerializer};
+use massa_models::constants::MAX_DATASTORE_KEY_LENGTH;
 use massa_models::{
-    array_from_slice, Amount, DeserializeVarInt, ModelsError, SerializeVarInt, VecU8Deserializer,
-    VecU8Serializer,
+    Amount, DeserializeVarInt, ModelsError, SerializeVarInt, VecU8Deserializer, VecU8Serializer,
 };
 use massa_models::{DeserializeCompact, SerializeCompact};
 use massa_serialization::{
@@ -33,13 +31,14 @@ pub struct LedgerEntry {
     /// Executable bytecode
     pub bytecode: Vec<u8>,
 
-    /// A key-value store associating a hash to arbitrary bytes
-    pub datastore: BTreeMap<Hash, Vec<u8>>,
+    /// A key-value store associating arbitrary byte keys to arbitrary bytes
+    pub datastore: BTreeMap<Vec<u8>, Vec<u8>>,
 }
 
 /// Serializer for `Datastore` field in `LedgerEntry`
 pub struct DatastoreSerializer {
     u64_serializer: U64VarIntSerializer,
+    key_serializer: VecU8Serializer,
     value_serializer: VecU8Serializer,
 }
 
@@ -48,15 +47,19 @@ impl DatastoreSerializer {
     pub fn new() -> Self {
         Self {
             u64_serializer: U64VarIntSerializer::new(Included(u64::MIN), Included(u64::MAX)),
+            key_serializer: VecU8Serializer::new(
+                Included(u64::MIN),
+                Included(MAX_DATASTORE_KEY_LENGTH as u64),
+            ),
             value_serializer: VecU8Serializer::new(Included(u64::MIN), Included(u64::MAX)),
         }
     }
 }
 
-impl Serializer<BTreeMap<Hash, Vec<u8>>> for DatastoreSerializer {
+impl Serializer<BTreeMap<Vec<u8>, Vec<u8>>> for DatastoreSerializer {
     fn serialize(
         &self,
-        value: &BTreeMap<Hash, Vec<u8>>,
+        value: &BTreeMap<Vec<u8>, Vec<u8>>,
         buffer: &mut Vec<u8>,
     ) -> Result<(), SerializeError> {
         let entry_count: u64 = value.len().try_into().map_err(|err| {
@@ -67,7 +70,7 @@ impl Serializer<BTreeMap<Hash, Vec<u8>>> for DatastoreSerializer {
         })?;
         self.u64_serializer.serialize(&entry_count, buffer)?;
         for (key, value) in value.iter() {
-            buffer.extend(key.to_bytes());
+            self.key_serializer.serialize(key, buffer)?;
             self.value_serializer.serialize(value, buffer)?;
         }
         Ok(())
@@ -77,7 +80,7 @@ impl Serializer<BTreeMap<Hash, Vec<u8>>> for DatastoreSerializer {
 /// Deserializer for `Datastore` field in `LedgerEntry`
 pub struct DatastoreDeserializer {
     u64_deserializer: U64VarIntDeserializer,
-    hash_deserializer: HashDeserializer,
+    key_deserializer: VecU8Deserializer,
     value_deserializer: VecU8Deserializer,
 }
 
@@ -86,17 +89,20 @@ impl DatastoreDeserializer {
     pub fn new() -> Self {
         Self {
             u64_deserializer: U64VarIntDeserializer::new(Included(u64::MIN), Included(u64::MAX)),
-            hash_deserializer: HashDeserializer::new(),
+            key_deserializer: VecU8Deserializer::new(
+                Included(u64::MIN),
+                Included(MAX_DATASTORE_KEY_LENGTH as u64),
+            ),
             value_deserializer: VecU8Deserializer::new(Included(u64::MIN), Included(u64::MAX)),
         }
     }
 }
 
-impl Deserializer<BTreeMap<Hash, Vec<u8>>> for DatastoreDeserializer {
+impl Deserializer<BTreeMap<Vec<u8>, Vec<u8>>> for DatastoreDeserializer {
     fn deserialize<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
         &self,
         buffer: &'a [u8],
-    ) -> IResult<&'a [u8], BTreeMap<Hash, Vec<u8>>, E> {
+    ) -> IResult<&'a [u8], BTreeMap<Vec<u8>, Vec<u8>>, E> {
         context(
             "Failed Datastore deserialization",
             length_count(
@@ -104,7 +110,7 @@ impl Deserializer<BTreeMap<Hash, Vec<u8>>> for DatastoreDeserializer {
                     self.u64_deserializer.deserialize(input)
                 }),
                 tuple((
-                    |input| self.hash_deserializer.deserialize(input),
+                    |input| self.key_deserializer.deserialize(input),
                     |input| self.value_deserializer.deserialize(input),
                 )),
             ),
@@ -145,11 +151,10 @@ impl Serializer<LedgerEntry> for LedgerEntrySerializer {
     /// use std::str::FromStr;
     /// use massa_models::Amount;
     /// use massa_ledger_exports::{LedgerEntry, LedgerEntrySerializer};
-    /// use massa_hash::Hash;
     ///
-    /// let hash = Hash::compute_from(&"hello world".as_bytes());
+    /// let key = "hello world".as_bytes().to_vec();
     /// let mut store = BTreeMap::new();
-    /// store.insert(hash, vec![1, 2, 3]);
+    /// store.insert(key, vec![1, 2, 3]);
     /// let amount = Amount::from_str("1").unwrap();
     /// let bytecode = vec![1, 2, 3];
     /// let ledger_entry = LedgerEntry {
@@ -202,11 +207,10 @@ impl Deserializer<LedgerEntry> for LedgerEntryDeserializer {
     /// use std::str::FromStr;
     /// use massa_models::Amount;
     /// use massa_ledger_exports::{LedgerEntry, LedgerEntrySerializer, LedgerEntryDeserializer};
-    /// use massa_hash::Hash;
     ///
-    /// let hash = Hash::compute_from(&"hello world".as_bytes());
+    /// let key = "hello world".as_bytes().to_vec();
     /// let mut store = BTreeMap::new();
-    /// store.insert(hash, vec![1, 2, 3]);
+    /// store.insert(key, vec![1, 2, 3]);
     /// let amount = Amount::from_str("1").unwrap();
     /// let bytecode = vec![1, 2, 3];
     /// let ledger_entry = LedgerEntry {
@@ -303,8 +307,19 @@ impl SerializeCompact for LedgerEntry {
 
         // datastore
         for (key, value) in &self.datastore {
+            // key length
+            let key_len: u64 = key.len().try_into().map_err(|_| {
+                ModelsError::SerializeError("could not convert datastore key size to u64".into())
+            })?;
+            if key_len > MAX_DATASTORE_KEY_LENGTH as u64 {
+                return Err(ModelsError::SerializeError(
+                    "datastore key is too long".into(),
+                ));
+            }
+            res.extend(key_len.to_varint_bytes());
+
             // key
-            res.extend(key.to_bytes());
+            res.extend(key);
 
             // value length
             let value_len: u64 = value.len().try_into().map_err(|_| {
@@ -356,11 +371,27 @@ impl DeserializeCompact for LedgerEntry {
         cursor += delta;
 
         // datastore entries
-        let mut datastore: BTreeMap<Hash, Vec<u8>> = BTreeMap::new();
+        let mut datastore: BTreeMap<Vec<u8>, Vec<u8>> = BTreeMap::new();
         for _ in 0..datastore_len {
+            // key length
+            let (key_len, delta) = u64::from_varint_bytes(&buffer[cursor..])?;
+            if key_len > MAX_DATASTORE_KEY_LENGTH as u64 {
+                return Err(ModelsError::DeserializeError(
+                    "could not deserialize ledger entry datastore key: key is too long".into(),
+                ));
+            }
+            let key_len = key_len as usize;
+            cursor += delta;
+
             // key
-            let key = Hash::from_bytes(&array_from_slice(&buffer[cursor..])?);
-            cursor += HASH_SIZE_BYTES;
+            let key = if let Some(slice) = buffer.get(cursor..(cursor + key_len)) {
+                cursor += key_len;
+                slice.to_vec()
+            } else {
+                return Err(ModelsError::DeserializeError(
+                    "could not deserialize ledger entry datastore key: buffer too small".into(),
+                ));
+            };
 
             // value length
             let (value_len, delta) = u64::from_varint_bytes(&buffer[cursor..])?;
@@ -15,6 +15,8 @@ impl Default for LedgerConfig {
             // a NamedTempFile in addition)
             initial_sce_ledger_path: "".into(),
             disk_ledger_path: "".into(),
+            archive_mode: false,
+            network_id: "".into(),
         }
     }
 }
@@ -34,6 +36,8 @@ impl LedgerConfig {
             Self {
                 initial_sce_ledger_path: initial_ledger.path().to_path_buf(),
                 disk_ledger_path: disk_ledger.path().to_path_buf(),
+                archive_mode: false,
+                network_id: "TEST".into(),
             },
             initial_ledger,
             disk_ledger,
@@ -3,14 +3,15 @@
 //! This file defines the final ledger associating addresses to their balances, bytecode and data.
 
 use crate::ledger_db::{LedgerDB, LedgerSubEntry};
-use massa_hash::Hash;
+use massa_async_pool::{AsyncMessage, AsyncMessageId, AsyncPoolChanges};
 use massa_ledger_exports::{
     LedgerChanges, LedgerConfig, LedgerController, LedgerEntry, LedgerError,
 };
 use massa_models::{Address, Amount, ModelsError};
 use massa_models::{DeserializeCompact, Slot};
 use nom::AsBytes;
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use tracing::warn;
 
 /// Represents a final ledger associating addresses to their balances, bytecode and data.
 /// The final ledger is part of the final state which is attached to a final slot, can be bootstrapped and allows others to bootstrap.
@@ -42,6 +43,8 @@ pub(crate) use init_file_error;
 
 impl FinalLedger {
     /// Initializes a new `FinalLedger` by reading its initial state from file.
+    /// If the disk ledger is attached to a slot, it was persisted by a previous run and is resumed as is,
+    /// unless it belongs to another network or genesis than the configured one.
     pub fn new(config: LedgerConfig) -> Result<Self, LedgerError> {
         // load the ledger tree from file
         let initial_ledger: HashMap<Address, LedgerEntry> =
@@ -62,9 +65,24 @@ impl FinalLedger {
             })
             .collect();
 
-        // create and initialize the disk ledger
-        let mut sorted_ledger = LedgerDB::new(config.disk_ledger_path.clone());
-        sorted_ledger.set_initial_ledger(initial_ledger);
+        // create the disk ledger and initialize it unless a persisted final state can be resumed:
+        // a state persisted for another network or genesis is discarded so that it is bootstrapped again
+        let mut sorted_ledger = LedgerDB::new(config.disk_ledger_path.clone(), config.archive_mode);
+        let same_network =
+            sorted_ledger.get_network_id().as_deref() == Some(config.network_id.as_bytes());
+        if sorted_ledger.get_slot().is_some() && !same_network {
+            warn!(
+                "the disk ledger belongs to another network or genesis than {}: resetting the disk ledger",
+                config.network_id
+            );
+        }
+        if sorted_ledger.get_slot().is_none() || !same_network {
+            sorted_ledger.reset();
+            sorted_ledger.set_initial_ledger(initial_ledger);
+        }
+        if !same_network {
+            sorted_ledger.set_network_id(config.network_id.as_bytes());
+        }
 
         // generate the final ledger
         Ok(FinalLedger {
@@ -76,8 +94,35 @@ impl FinalLedger {
 
 impl LedgerController for FinalLedger {
     /// Allows applying `LedgerChanges` to the final ledger
-    fn apply_changes(&mut self, changes: LedgerChanges, slot: Slot) {
-        self.sorted_ledger.apply_changes(changes, slot);
+    /// and persisting `AsyncPoolChanges` along with the final slot
+    fn apply_changes(
+        &mut self,
+        changes: LedgerChanges,
+        async_pool_changes: AsyncPoolChanges,
+        slot: Option<Slot>,
+    ) {
+        self.sorted_ledger
+            .apply_changes(changes, async_pool_changes, slot);
+    }
+
+    /// Gets the slot at the output of which the persisted final state is attached
+    fn get_slot(&self) -> Option<Slot> {
+        self.sorted_ledger.get_slot()
+    }
+
+    /// Gets the persisted asynchronous pool messages
+    fn get_async_pool_messages(&self) -> BTreeMap<AsyncMessageId, AsyncMessage> {
+        self.sorted_ledger.get_async_pool_messages()
+    }
+
+    /// Gets the last key of the ledger, used to resume a bootstrap
+    fn get_last_key(&self) -> Option<Vec<u8>> {
+        self.sorted_ledger.get_last_key()
+    }
+
+    /// Removes every ledger entry, asynchronous pool message and the slot from the disk
+    fn reset(&mut self) {
+        self.sorted_ledger.reset();
     }
 
     /// Gets the parallel balance of a ledger entry
@@ -121,9 +166,9 @@ impl LedgerController for FinalLedger {
     ///
     /// # Returns
     /// A copy of the datastore value, or `None` if the ledger entry or datastore entry was not found
-    fn get_data_entry(&self, addr: &Address, key: &Hash) -> Option<Vec<u8>> {
+    fn get_data_entry(&self, addr: &Address, key: &[u8]) -> Option<Vec<u8>> {
         self.sorted_ledger
-            .get_sub_entry(addr, LedgerSubEntry::Datastore(*key))
+            .get_sub_entry(addr, LedgerSubEntry::Datastore(key.to_vec()))
     }
 
     /// Checks for the existence of a datastore entry for a given address.
@@ -134,18 +179,86 @@ impl LedgerController for FinalLedger {
     ///
     /// # Returns
     /// true if the datastore entry was found, or false if the ledger entry or datastore entry was not found
-    fn has_data_entry(&self, addr: &Address, key: &Hash) -> bool {
+    fn has_data_entry(&self, addr: &Address, key: &[u8]) -> bool {
         self.sorted_ledger
-            .get_sub_entry(addr, LedgerSubEntry::Datastore(*key))
+            .get_sub_entry(addr, LedgerSubEntry::Datastore(key.to_vec()))
             .is_some()
     }
 
     /// # Returns
     /// A copy of the datastore sorted by key
-    fn get_entire_datastore(&self, addr: &Address) -> BTreeMap<Hash, Vec<u8>> {
+    fn get_entire_datastore(&self, addr: &Address) -> BTreeMap<Vec<u8>, Vec<u8>> {
         self.sorted_ledger.get_entire_datastore(addr)
     }
 
+    /// Gets a page of the datastore keys of a given address, sorted in lexicographic order.
+    ///
+    /// # Arguments
+    /// * `addr`: target address
+    /// * `prefix`: only keys starting with this prefix are returned
+    /// * `cursor`: only keys strictly greater than the cursor are returned
+    /// * `count`: maximum number of returned keys
+    ///
+    /// # Returns
+    /// The first `count` matching datastore keys, empty if the ledger entry was not found
+    fn get_datastore_keys(
+        &self,
+        addr: &Address,
+        prefix: &[u8],
+        cursor: Option<&[u8]>,
+        count: usize,
+    ) -> BTreeSet<Vec<u8>> {
+        self.sorted_ledger
+            .get_datastore_keys(addr, prefix, cursor, count)
+    }
+
+    /// Gets the parallel balance that a ledger entry had at the output of a given slot, using the archive
+    ///
+    /// # Returns
+    /// `None` if the archive does not cover the slot, otherwise the optional parallel balance
+    fn get_archived_parallel_balance(&self, addr: &Address, slot: Slot) -> Option<Option<Amount>> {
+        self.sorted_ledger
+            .get_archived_sub_entry(addr, LedgerSubEntry::Balance, slot)
+            .map(|value| {
+                value.map(|bytes| {
+                    Amount::from_bytes_compact(&bytes)
+                        .expect("critical: invalid balance format")
+                        .0
+                })
+            })
+    }
+
+    /// Gets a copy of the bytecode that a ledger entry had at the output of a given slot, using the archive
+    ///
+    /// # Returns
+    /// `None` if the archive does not cover the slot, otherwise the optional bytecode
+    fn get_archived_bytecode(&self, addr: &Address, slot: Slot) -> Option<Option<Vec<u8>>> {
+        self.sorted_ledger
+            .get_archived_sub_entry(addr, LedgerSubEntry::Bytecode, slot)
+    }
+
+    /// Gets a copy of the value that a datastore entry had at the output of a given slot, using the archive
+    ///
+    /// # Arguments
+    /// * `addr`: target address
+    /// * `key`: datastore key
+    /// * `slot`: slot at the output of which the value is queried
+    ///
+    /// # Returns
+    /// `None` if the archive does not cover the slot, otherwise the optional datastore value
+    fn get_archived_data_entry(
+        &self,
+        addr: &Address,
+        key: &[u8],
+        slot: Slot,
+    ) -> Option<Option<Vec<u8>>> {
+        self.sorted_ledger.get_archived_sub_entry(
+            addr,
+            LedgerSubEntry::Datastore(key.to_vec()),
+            slot,
+        )
+    }
+
     /// TODO: remove when API is updated
     fn get_full_entry(&self, addr: &Address) -> Option<LedgerEntry> {
         self.get_parallel_balance(addr)
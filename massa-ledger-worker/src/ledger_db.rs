@@ -2,13 +2,17 @@
 
 //! Module to interact with the disk ledger
 
-use massa_hash::{Hash, HASH_SIZE_BYTES};
+use massa_async_pool::{
+    AsyncMessage, AsyncMessageDeserializer, AsyncMessageId, AsyncMessageIdDeserializer,
+    AsyncMessageIdSerializer, AsyncMessageSerializer, AsyncPoolChanges, Change,
+};
 use massa_ledger_exports::*;
-use massa_models::constants::LEDGER_PART_SIZE_MESSAGE_BYTES;
+use massa_models::constants::{ADDRESS_SIZE_BYTES, LEDGER_PART_SIZE_MESSAGE_BYTES};
 use massa_models::{
-    Address, ModelsError, SerializeCompact, Slot, VecU8Deserializer, VecU8Serializer,
+    Address, DeserializeCompact, ModelsError, SerializeCompact, Slot, VecU8Deserializer,
+    VecU8Serializer,
 };
-use massa_serialization::{Deserializer, Serializer};
+use massa_serialization::{DeserializeError, Deserializer, Serializer};
 use nom::multi::many0;
 use nom::sequence::tuple;
 use rocksdb::{
@@ -17,20 +21,29 @@ use rocksdb::{
 use std::collections::HashMap;
 use std::ops::Bound;
 use std::rc::Rc;
-use std::{collections::BTreeMap, path::PathBuf};
-
-#[cfg(feature = "testing")]
-use massa_models::{address::AddressDeserializer, Amount, DeserializeCompact};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::PathBuf,
+};
+use tracing::warn;
 
 #[cfg(feature = "testing")]
-use massa_serialization::DeserializeError;
+use massa_models::{address::AddressDeserializer, Amount};
 
 const LEDGER_CF: &str = "ledger";
+const ASYNC_POOL_CF: &str = "async_pool";
 const METADATA_CF: &str = "metadata";
+const ARCHIVE_CF: &str = "archive";
 const OPEN_ERROR: &str = "critical: rocksdb open operation failed";
 const CRUD_ERROR: &str = "critical: rocksdb crud operation failed";
 const CF_ERROR: &str = "critical: rocksdb column family operation failed";
 const SLOT_KEY: &[u8; 1] = b"s";
+const ARCHIVE_START_KEY: &[u8; 1] = b"a";
+const VERSION_KEY: &[u8; 1] = b"v";
+const NETWORK_KEY: &[u8; 1] = b"n";
+/// Version of the disk layout, increased whenever the stored keys or values change format.
+/// Databases without a version key use the initial layout with hashed datastore keys.
+const LEDGER_DB_VERSION: u32 = 1;
 
 /// Ledger sub entry enum
 pub enum LedgerSubEntry {
@@ -39,14 +52,19 @@ pub enum LedgerSubEntry {
     /// Bytecode
     Bytecode,
     /// Datastore entry
-    Datastore(Hash),
+    Datastore(Vec<u8>),
 }
 
 /// Disk ledger DB module
 ///
 /// Contains a RocksDB DB instance
 #[derive(Debug)]
-pub(crate) struct LedgerDB(DB);
+pub(crate) struct LedgerDB {
+    /// RocksDB instance
+    db: DB,
+    /// whether the reverse diffs of every final slot are kept in `ARCHIVE_CF`
+    archive_mode: bool,
+}
 
 /// For a given start prefix (inclusive), returns the correct end prefix (non-inclusive).
 /// This assumes the key bytes are ordered in lexicographical order.
@@ -71,13 +89,38 @@ fn test_end_prefix() {
     assert_eq!(end_prefix(&[5, 6, 255]), Some(vec![5, 7]));
 }
 
-// TODO: save attached slot in metadata for a lighter bootstrap after disconnection
+/// Key of a sub-entry of a given address in `LEDGER_CF`
+fn sub_entry_key(addr: &Address, ty: &LedgerSubEntry) -> Vec<u8> {
+    match ty {
+        LedgerSubEntry::Balance => balance_key!(addr),
+        LedgerSubEntry::Bytecode => bytecode_key!(addr),
+        LedgerSubEntry::Datastore(key) => data_key!(addr, key),
+    }
+}
+
+/// Prefix of the archived values of a given `LEDGER_CF` key in `ARCHIVE_CF`.
+/// The key length comes first so that the prefix of a key never matches a longer one.
+fn archive_prefix(ledger_key: &[u8]) -> Vec<u8> {
+    [&(ledger_key.len() as u32).to_be_bytes()[..], ledger_key].concat()
+}
+
+/// Key of an asynchronous pool message in `ASYNC_POOL_CF`
+fn async_message_key(id: &AsyncMessageId) -> Vec<u8> {
+    let mut key = Vec::new();
+    // AsyncMessageIdSerializer::serialize() never fails
+    AsyncMessageIdSerializer::new()
+        .serialize(id, &mut key)
+        .unwrap();
+    key
+}
+
 impl LedgerDB {
     /// Create and initialize a new LedgerDB.
     ///
     /// # Arguments
     /// * path: path to the desired disk ledger db directory
-    pub fn new(path: PathBuf) -> Self {
+    /// * archive_mode: whether to keep the reverse diffs of every final slot for historical queries
+    pub fn new(path: PathBuf, archive_mode: bool) -> Self {
         let mut db_opts = Options::default();
         db_opts.create_if_missing(true);
         db_opts.create_missing_column_families(true);
@@ -87,12 +130,45 @@ impl LedgerDB {
             path,
             vec![
                 ColumnFamilyDescriptor::new(LEDGER_CF, Options::default()),
+                ColumnFamilyDescriptor::new(ASYNC_POOL_CF, Options::default()),
                 ColumnFamilyDescriptor::new(METADATA_CF, Options::default()),
+                ColumnFamilyDescriptor::new(ARCHIVE_CF, Options::default()),
             ],
         )
         .expect(OPEN_ERROR);
 
-        LedgerDB(db)
+        let mut ledger_db = LedgerDB { db, archive_mode };
+        ledger_db.migrate();
+        ledger_db
+    }
+
+    /// Bring a database written with a previous disk layout to the current one.
+    ///
+    /// The datastore keys used to be stored hashed and the original keys cannot be recovered from them,
+    /// so an outdated database is wiped: the final state is then rebuilt from the initial ledger or bootstrap.
+    fn migrate(&mut self) {
+        let version = self.get_version();
+        if version == Some(LEDGER_DB_VERSION) {
+            return;
+        }
+        if self.get_slot().is_some() || self.get_last_key().is_some() {
+            warn!(
+                "disk ledger layout version {} is outdated (expected {}): resetting the disk ledger",
+                version.unwrap_or(0),
+                LEDGER_DB_VERSION
+            );
+        }
+        self.reset();
+    }
+
+    /// Get the disk layout version of the database, `None` if it predates versioning
+    fn get_version(&self) -> Option<u32> {
+        let handle = self.db.cf_handle(METADATA_CF).expect(CF_ERROR);
+
+        self.db
+            .get_cf(handle, VERSION_KEY)
+            .expect(CRUD_ERROR)
+            .map(|bytes| u32::from_be_bytes(bytes[..].try_into().expect(CRUD_ERROR)))
     }
 
     /// Set the initial disk ledger
@@ -111,10 +187,18 @@ impl LedgerDB {
     ///
     /// # Arguments
     /// * changes: ledger changes to be applied
-    /// * slot: new slot associated to the final ledger
-    pub fn apply_changes(&mut self, changes: LedgerChanges, slot: Slot) {
+    /// * async_pool_changes: asynchronous pool changes to be persisted in the same batch
+    /// * slot: new slot associated to the final ledger, `None` to detach the disk state from any slot
+    pub fn apply_changes(
+        &mut self,
+        changes: LedgerChanges,
+        async_pool_changes: AsyncPoolChanges,
+        slot: Option<Slot>,
+    ) {
         // create the batch
         let mut batch = WriteBatch::default();
+        // keep the previous values of the changed sub-entries in the archive
+        self.archive_changes(&changes, slot, &mut batch);
         // for all incoming changes
         for (addr, change) in changes.0 {
             match change {
@@ -136,31 +220,281 @@ impl LedgerDB {
                 }
             }
         }
+        // persist the asynchronous pool changes
+        self.apply_async_pool_changes(async_pool_changes, &mut batch);
         // set the associated slot in metadata
         self.set_metadata(slot, &mut batch);
         // write the batch
         self.write_batch(batch);
     }
 
+    /// Write the current values of the sub-entries about to be changed at the given slot to the archive.
+    /// The archive only covers the slots following its start slot, which is the first slot it was attached to:
+    /// it is interrupted (and restarted at a later slot) whenever the disk state is detached from its slot.
+    ///
+    /// # Arguments
+    /// * changes: ledger changes about to be applied
+    /// * slot: new slot associated to the final ledger
+    /// * batch: the given operation batch to update
+    fn archive_changes(&self, changes: &LedgerChanges, slot: Option<Slot>, batch: &mut WriteBatch) {
+        let metadata_handle = self.db.cf_handle(METADATA_CF).expect(CF_ERROR);
+        let (slot, previous_slot) = match (self.archive_mode, slot, self.get_slot()) {
+            (true, Some(slot), Some(previous_slot)) => (slot, previous_slot),
+            _ => {
+                batch.delete_cf(metadata_handle, ARCHIVE_START_KEY);
+                return;
+            }
+        };
+        if self.get_archive_start().is_none() {
+            // Slot::to_bytes_compact() never fails
+            batch.put_cf(
+                metadata_handle,
+                ARCHIVE_START_KEY,
+                previous_slot.to_bytes_compact().unwrap(),
+            );
+        }
+
+        let ledger_handle = self.db.cf_handle(LEDGER_CF).expect(CF_ERROR);
+        let archive_handle = self.db.cf_handle(ARCHIVE_CF).expect(CF_ERROR);
+        for (addr, change) in changes.0.iter() {
+            let mut changed_keys = Vec::new();
+            match change {
+                SetUpdateOrDelete::Set(new_entry) => {
+                    changed_keys.push(balance_key!(addr));
+                    changed_keys.push(bytecode_key!(addr));
+                    changed_keys.extend(new_entry.datastore.keys().map(|key| data_key!(addr, key)));
+                }
+                SetUpdateOrDelete::Update(entry_update) => {
+                    if let SetOrKeep::Set(_) = entry_update.parallel_balance {
+                        changed_keys.push(balance_key!(addr));
+                    }
+                    if let SetOrKeep::Set(_) = entry_update.bytecode {
+                        changed_keys.push(bytecode_key!(addr));
+                    }
+                    changed_keys.extend(
+                        entry_update
+                            .datastore
+                            .keys()
+                            .map(|key| data_key!(addr, key)),
+                    );
+                }
+                SetUpdateOrDelete::Delete => {
+                    changed_keys.push(balance_key!(addr));
+                    changed_keys.push(bytecode_key!(addr));
+                    let mut opt = ReadOptions::default();
+                    opt.set_iterate_upper_bound(end_prefix(data_prefix!(addr)).unwrap());
+                    changed_keys.extend(
+                        self.db
+                            .iterator_cf_opt(
+                                ledger_handle,
+                                opt,
+                                IteratorMode::From(data_prefix!(addr), Direction::Forward),
+                            )
+                            .map(|(key, _)| key.to_vec()),
+                    );
+                }
+            }
+            for key in changed_keys {
+                // an absent value is archived as `[0]`, a present one as `[1]` followed by its bytes
+                let value = match self.db.get_cf(ledger_handle, &key).expect(CRUD_ERROR) {
+                    Some(bytes) => [&[1u8][..], &bytes[..]].concat(),
+                    None => vec![0u8],
+                };
+                batch.put_cf(
+                    archive_handle,
+                    [archive_prefix(&key), slot.to_bytes_key().to_vec()].concat(),
+                    value,
+                );
+            }
+        }
+    }
+
+    /// Get the first slot covered by the archive.
+    fn get_archive_start(&self) -> Option<Slot> {
+        let handle = self.db.cf_handle(METADATA_CF).expect(CF_ERROR);
+
+        self.db
+            .get_cf(handle, ARCHIVE_START_KEY)
+            .expect(CRUD_ERROR)
+            .map(|bytes| Slot::from_bytes_compact(&bytes).expect(CRUD_ERROR).0)
+    }
+
+    /// Get the value that the given sub-entry of a given address had at the output of a given slot, using the archive.
+    ///
+    /// # Arguments
+    /// * addr: associated address
+    /// * ty: type of the queried sub-entry
+    /// * slot: slot at the output of which the value is queried
+    ///
+    /// # Returns
+    /// `None` if the archive does not cover the slot, otherwise the optional sub-entry value as bytes
+    pub fn get_archived_sub_entry(
+        &self,
+        addr: &Address,
+        ty: LedgerSubEntry,
+        slot: Slot,
+    ) -> Option<Option<Vec<u8>>> {
+        if !self.archive_mode {
+            return None;
+        }
+        let archive_start = self.get_archive_start()?;
+        if slot < archive_start || Some(slot) > self.get_slot() {
+            return None;
+        }
+
+        let handle = self.db.cf_handle(ARCHIVE_CF).expect(CF_ERROR);
+        let key = sub_entry_key(addr, &ty);
+        let prefix = archive_prefix(&key);
+        let start = [prefix.clone(), slot.to_bytes_key().to_vec()].concat();
+        let mut opt = ReadOptions::default();
+        // the prefix starts with the key length, so it is bounded
+        opt.set_iterate_upper_bound(end_prefix(&prefix).unwrap());
+
+        // the value archived at the first change following the slot is the value at the output of the slot,
+        // if the sub-entry did not change since then its current value is returned
+        let archived = self
+            .db
+            .iterator_cf_opt(handle, opt, IteratorMode::From(&start, Direction::Forward))
+            .find(|(archive_key, _)| archive_key[..] != start[..]);
+        match archived {
+            Some((_, value)) => match value.split_first() {
+                Some((&1, bytes)) => Some(Some(bytes.to_vec())),
+                _ => Some(None),
+            },
+            None => Some(self.get_sub_entry(addr, ty)),
+        }
+    }
+
+    /// Add or remove asynchronous pool messages.
+    ///
+    /// # Arguments
+    /// * changes: asynchronous pool changes to be persisted
+    /// * batch: the given operation batch to update
+    fn apply_async_pool_changes(&self, changes: AsyncPoolChanges, batch: &mut WriteBatch) {
+        let handle = self.db.cf_handle(ASYNC_POOL_CF).expect(CF_ERROR);
+        let message_serializer = AsyncMessageSerializer::new();
+
+        for change in changes.0 {
+            match change {
+                Change::Add(id, message) => {
+                    let mut value = Vec::new();
+                    // AsyncMessageSerializer::serialize() never fails
+                    message_serializer.serialize(&message, &mut value).unwrap();
+                    batch.put_cf(handle, async_message_key(&id), value);
+                }
+                Change::Delete(id) => batch.delete_cf(handle, async_message_key(&id)),
+            }
+        }
+    }
+
+    /// Get the slot at the output of which the disk state is attached.
+    ///
+    /// # Returns
+    /// The slot, or `None` if the disk state is not attached to any slot
+    pub fn get_slot(&self) -> Option<Slot> {
+        let handle = self.db.cf_handle(METADATA_CF).expect(CF_ERROR);
+
+        self.db
+            .get_cf(handle, SLOT_KEY)
+            .expect(CRUD_ERROR)
+            .map(|bytes| Slot::from_bytes_compact(&bytes).expect(CRUD_ERROR).0)
+    }
+
+    /// Get the identifier of the network and genesis the disk state belongs to.
+    ///
+    /// # Returns
+    /// The identifier, or `None` if the disk state predates it
+    pub fn get_network_id(&self) -> Option<Vec<u8>> {
+        let handle = self.db.cf_handle(METADATA_CF).expect(CF_ERROR);
+
+        self.db.get_cf(handle, NETWORK_KEY).expect(CRUD_ERROR)
+    }
+
+    /// Set the identifier of the network and genesis the disk state belongs to.
+    pub fn set_network_id(&mut self, network_id: &[u8]) {
+        let handle = self.db.cf_handle(METADATA_CF).expect(CF_ERROR);
+        let mut batch = WriteBatch::default();
+        batch.put_cf(handle, NETWORK_KEY, network_id);
+        self.write_batch(batch);
+    }
+
+    /// Get every persisted asynchronous pool message.
+    ///
+    /// # Returns
+    /// A BTreeMap with the message id as key and the message as value
+    pub fn get_async_pool_messages(&self) -> BTreeMap<AsyncMessageId, AsyncMessage> {
+        let handle = self.db.cf_handle(ASYNC_POOL_CF).expect(CF_ERROR);
+        let id_deserializer = AsyncMessageIdDeserializer::new();
+        let message_deserializer = AsyncMessageDeserializer::new();
+
+        self.db
+            .iterator_cf(handle, IteratorMode::Start)
+            .map(|(key, value)| {
+                let (_, id) = id_deserializer
+                    .deserialize::<DeserializeError>(&key)
+                    .expect(CRUD_ERROR);
+                let (_, message) = message_deserializer
+                    .deserialize::<DeserializeError>(&value)
+                    .expect(CRUD_ERROR);
+                (id, message)
+            })
+            .collect()
+    }
+
+    /// Get the last key of the disk ledger.
+    pub fn get_last_key(&self) -> Option<Vec<u8>> {
+        let handle = self.db.cf_handle(LEDGER_CF).expect(CF_ERROR);
+
+        self.db
+            .iterator_cf(handle, IteratorMode::End)
+            .next()
+            .map(|(key, _)| key.to_vec())
+    }
+
+    /// Delete every ledger entry, asynchronous pool message, archived value and metadata from the disk.
+    /// The disk layout version and the network identifier are kept.
+    pub fn reset(&mut self) {
+        let mut batch = WriteBatch::default();
+        for cf in [LEDGER_CF, ASYNC_POOL_CF, METADATA_CF, ARCHIVE_CF] {
+            let handle = self.db.cf_handle(cf).expect(CF_ERROR);
+            for (key, _) in self.db.iterator_cf(handle, IteratorMode::Start) {
+                if cf == METADATA_CF && (key[..] == VERSION_KEY[..] || key[..] == NETWORK_KEY[..]) {
+                    continue;
+                }
+                batch.delete_cf(handle, key);
+            }
+        }
+        let metadata_handle = self.db.cf_handle(METADATA_CF).expect(CF_ERROR);
+        batch.put_cf(
+            metadata_handle,
+            VERSION_KEY,
+            LEDGER_DB_VERSION.to_be_bytes(),
+        );
+        self.write_batch(batch);
+    }
+
     /// Apply the given operation batch to the disk ledger.
     ///
     /// NOTE: the batch is not saved within the object because it cannot be shared between threads safely
     fn write_batch(&self, batch: WriteBatch) {
-        self.0.write(batch).expect(CRUD_ERROR);
+        self.db.write(batch).expect(CRUD_ERROR);
     }
 
     /// Set the disk ledger metadata
     ///
     /// # Arguments
-    /// * slot: associated slot of the current ledger
+    /// * slot: associated slot of the current ledger, `None` to remove it
     /// * batch: the given operation batch to update
     ///
     /// NOTE: right now the metadata is only a Slot, use a struct in the future
-    fn set_metadata(&self, slot: Slot, batch: &mut WriteBatch) {
-        let handle = self.0.cf_handle(METADATA_CF).expect(CF_ERROR);
+    fn set_metadata(&self, slot: Option<Slot>, batch: &mut WriteBatch) {
+        let handle = self.db.cf_handle(METADATA_CF).expect(CF_ERROR);
 
-        // Slot::to_bytes_compact() never fails
-        batch.put_cf(handle, SLOT_KEY, slot.to_bytes_compact().unwrap());
+        match slot {
+            // Slot::to_bytes_compact() never fails
+            Some(slot) => batch.put_cf(handle, SLOT_KEY, slot.to_bytes_compact().unwrap()),
+            None => batch.delete_cf(handle, SLOT_KEY),
+        }
     }
 
     /// Add every sub-entry individually for a given entry.
@@ -170,7 +504,7 @@ impl LedgerDB {
     /// * ledger_entry: complete entry to be added
     /// * batch: the given operation batch to update
     fn put_entry(&mut self, addr: &Address, ledger_entry: LedgerEntry, batch: &mut WriteBatch) {
-        let handle = self.0.cf_handle(LEDGER_CF).expect(CF_ERROR);
+        let handle = self.db.cf_handle(LEDGER_CF).expect(CF_ERROR);
 
         // balance
         batch.put_cf(
@@ -184,8 +518,8 @@ impl LedgerDB {
         batch.put_cf(handle, bytecode_key!(addr), ledger_entry.bytecode);
 
         // datastore
-        for (hash, entry) in ledger_entry.datastore {
-            batch.put_cf(handle, data_key!(addr, hash), entry);
+        for (key, entry) in ledger_entry.datastore {
+            batch.put_cf(handle, data_key!(addr, key), entry);
         }
     }
 
@@ -198,19 +532,11 @@ impl LedgerDB {
     /// # Returns
     /// An Option of the sub-entry value as bytes
     pub fn get_sub_entry(&self, addr: &Address, ty: LedgerSubEntry) -> Option<Vec<u8>> {
-        let handle = self.0.cf_handle(LEDGER_CF).expect(CF_ERROR);
-
-        match ty {
-            LedgerSubEntry::Balance => self.0.get_cf(handle, balance_key!(addr)).expect(CRUD_ERROR),
-            LedgerSubEntry::Bytecode => self
-                .0
-                .get_cf(handle, bytecode_key!(addr))
-                .expect(CRUD_ERROR),
-            LedgerSubEntry::Datastore(hash) => self
-                .0
-                .get_cf(handle, data_key!(addr, hash))
-                .expect(CRUD_ERROR),
-        }
+        let handle = self.db.cf_handle(LEDGER_CF).expect(CF_ERROR);
+
+        self.db
+            .get_cf(handle, sub_entry_key(addr, &ty))
+            .expect(CRUD_ERROR)
     }
 
     /// Get every address and their corresponding balance.
@@ -220,10 +546,10 @@ impl LedgerDB {
     /// A BTreeMap with the address as key and the balance as value
     #[cfg(feature = "testing")]
     pub fn get_every_address(&self) -> BTreeMap<Address, Amount> {
-        let handle = self.0.cf_handle(LEDGER_CF).expect(CF_ERROR);
+        let handle = self.db.cf_handle(LEDGER_CF).expect(CF_ERROR);
 
         let ledger = self
-            .0
+            .db
             .iterator_cf(handle, IteratorMode::Start)
             .collect::<Vec<_>>();
 
@@ -243,25 +569,60 @@ impl LedgerDB {
     /// Get the entire datastore for a given address.
     ///
     /// # Returns
-    /// A BTreeMap with the entry hash as key and the data bytes as value
-    pub fn get_entire_datastore(&self, addr: &Address) -> BTreeMap<Hash, Vec<u8>> {
-        let handle = self.0.cf_handle(LEDGER_CF).expect(CF_ERROR);
+    /// A BTreeMap with the entry key as key and the data bytes as value
+    pub fn get_entire_datastore(&self, addr: &Address) -> BTreeMap<Vec<u8>, Vec<u8>> {
+        let handle = self.db.cf_handle(LEDGER_CF).expect(CF_ERROR);
 
         let mut opt = ReadOptions::default();
         opt.set_iterate_upper_bound(end_prefix(data_prefix!(addr)).unwrap());
 
-        self.0
+        self.db
             .iterator_cf_opt(
                 handle,
                 opt,
                 IteratorMode::From(data_prefix!(addr), Direction::Forward),
             )
-            .map(|(key, data)| {
-                (
-                    Hash::from_bytes(key.split_at(HASH_SIZE_BYTES + 1).1.try_into().unwrap()),
-                    data.to_vec(),
-                )
-            })
+            .map(|(key, data)| (key[ADDRESS_SIZE_BYTES + 1..].to_vec(), data.to_vec()))
+            .collect()
+    }
+
+    /// Get a page of the datastore keys of a given address starting with a given prefix.
+    /// The iteration stops as soon as the page is full.
+    ///
+    /// # Arguments
+    /// * addr: associated address
+    /// * prefix: prefix of the returned datastore keys
+    /// * cursor: only the keys strictly greater than the cursor are returned
+    /// * count: maximum number of returned keys
+    ///
+    /// # Returns
+    /// A BTreeSet of the first `count` matching datastore keys
+    pub fn get_datastore_keys(
+        &self,
+        addr: &Address,
+        prefix: &[u8],
+        cursor: Option<&[u8]>,
+        count: usize,
+    ) -> BTreeSet<Vec<u8>> {
+        let handle = self.db.cf_handle(LEDGER_CF).expect(CF_ERROR);
+        let prefix_key = data_key!(addr, prefix);
+
+        let mut opt = ReadOptions::default();
+        // the datastore prefix of the address always ends with DATASTORE_IDENT, so it is bounded
+        opt.set_iterate_upper_bound(
+            end_prefix(&prefix_key).unwrap_or_else(|| end_prefix(data_prefix!(addr)).unwrap()),
+        );
+
+        // start from the cursor when it is beyond the prefix
+        let start = match cursor {
+            Some(cursor) if cursor > prefix => data_key!(addr, cursor),
+            _ => prefix_key,
+        };
+        self.db
+            .iterator_cf_opt(handle, opt, IteratorMode::From(&start, Direction::Forward))
+            .map(|(key, _)| key[ADDRESS_SIZE_BYTES + 1..].to_vec())
+            .filter(|key| cursor.map_or(true, |cursor| key.as_slice() > cursor))
+            .take(count)
             .collect()
     }
 
@@ -276,7 +637,7 @@ impl LedgerDB {
         entry_update: LedgerEntryUpdate,
         batch: &mut WriteBatch,
     ) {
-        let handle = self.0.cf_handle(LEDGER_CF).expect(CF_ERROR);
+        let handle = self.db.cf_handle(LEDGER_CF).expect(CF_ERROR);
 
         // balance
         if let SetOrKeep::Set(balance) = entry_update.parallel_balance {
@@ -294,10 +655,10 @@ impl LedgerDB {
         }
 
         // datastore
-        for (hash, update) in entry_update.datastore {
+        for (key, update) in entry_update.datastore {
             match update {
-                SetOrDelete::Set(entry) => batch.put_cf(handle, data_key!(addr, hash), entry),
-                SetOrDelete::Delete => batch.delete_cf(handle, data_key!(addr, hash)),
+                SetOrDelete::Set(entry) => batch.put_cf(handle, data_key!(addr, key), entry),
+                SetOrDelete::Delete => batch.delete_cf(handle, data_key!(addr, key)),
             }
         }
     }
@@ -307,18 +668,18 @@ impl LedgerDB {
     /// # Arguments
     /// * batch: the given operation batch to update
     fn delete_entry(&self, addr: &Address, batch: &mut WriteBatch) {
-        let handle = self.0.cf_handle(LEDGER_CF).expect(CF_ERROR);
+        let handle = self.db.cf_handle(LEDGER_CF).expect(CF_ERROR);
 
         // balance
         batch.delete_cf(handle, balance_key!(addr));
 
         // bytecode
-        batch.delete_cf(handle, balance_key!(addr));
+        batch.delete_cf(handle, bytecode_key!(addr));
 
         // datastore
         let mut opt = ReadOptions::default();
         opt.set_iterate_upper_bound(end_prefix(data_prefix!(addr)).unwrap());
-        for (key, _) in self.0.iterator_cf_opt(
+        for (key, _) in self.db.iterator_cf_opt(
             handle,
             opt,
             IteratorMode::From(data_prefix!(addr), Direction::Forward),
@@ -343,19 +704,19 @@ impl LedgerDB {
     ) -> Result<(Vec<u8>, Option<Vec<u8>>), ModelsError> {
         let ser = VecU8Serializer::new(Bound::Included(0), Bound::Excluded(u64::MAX));
         let key_serializer = KeySerializer::new();
-        let handle = self.0.cf_handle(LEDGER_CF).expect(CF_ERROR);
+        let handle = self.db.cf_handle(LEDGER_CF).expect(CF_ERROR);
         let mut part = Vec::new();
         let opt = ReadOptions::default();
 
         // Creates an iterator from the next element after the last if defined, otherwise initialize it at the first key of the ledger.
         let db_iterator = if let Some(key) = last_key {
             let mut iter =
-                self.0
+                self.db
                     .iterator_cf_opt(handle, opt, IteratorMode::From(key, Direction::Forward));
             iter.next();
             iter
         } else {
-            self.0.iterator_cf_opt(handle, opt, IteratorMode::Start)
+            self.db.iterator_cf_opt(handle, opt, IteratorMode::Start)
         };
         let mut last_key = None;
 
@@ -382,7 +743,7 @@ impl LedgerDB {
     /// # Returns
     /// The last key of the inserted entry (this is an optimization to easily keep a reference to the last key)
     pub fn set_ledger_part<'a>(&self, data: &'a [u8]) -> Result<Option<Vec<u8>>, ModelsError> {
-        let handle = self.0.cf_handle(LEDGER_CF).expect(CF_ERROR);
+        let handle = self.db.cf_handle(LEDGER_CF).expect(CF_ERROR);
         let vec_u8_deserializer =
             VecU8Deserializer::new(Bound::Included(0), Bound::Excluded(u64::MAX));
         let key_deserializer = KeyDeserializer::new();
@@ -405,7 +766,7 @@ impl LedgerDB {
 
         // Every byte should have been read
         if rest.is_empty() {
-            self.0.write(batch).expect(CRUD_ERROR);
+            self.db.write(batch).expect(CRUD_ERROR);
             Ok((*last_key).clone())
         } else {
             Err(ModelsError::SerializeError(
@@ -417,23 +778,25 @@ impl LedgerDB {
 
 #[cfg(test)]
 mod tests {
-    use super::LedgerDB;
+    use super::{LedgerDB, LEDGER_DB_VERSION, METADATA_CF, VERSION_KEY};
     use crate::ledger_db::LedgerSubEntry;
-    use massa_hash::Hash;
-    use massa_ledger_exports::{LedgerEntry, LedgerEntryUpdate, SetOrKeep};
-    use massa_models::{Address, Amount, DeserializeCompact};
+    use massa_async_pool::{AsyncMessage, AsyncPoolChanges, Change};
+    use massa_ledger_exports::{
+        LedgerChanges, LedgerEntry, LedgerEntryUpdate, SetOrKeep, SetUpdateOrDelete,
+    };
+    use massa_models::{Address, Amount, DeserializeCompact, SerializeCompact, Slot};
     use massa_signature::{derive_public_key, generate_random_private_key};
     use rocksdb::WriteBatch;
     use std::collections::BTreeMap;
     use tempfile::TempDir;
 
     #[cfg(test)]
-    fn init_test_ledger(addr: Address) -> (LedgerDB, BTreeMap<Hash, Vec<u8>>) {
+    fn init_test_ledger(addr: Address) -> (LedgerDB, BTreeMap<Vec<u8>, Vec<u8>>) {
         // init data
         let mut data = BTreeMap::new();
-        data.insert(Hash::compute_from(b"1"), b"a".to_vec());
-        data.insert(Hash::compute_from(b"2"), b"b".to_vec());
-        data.insert(Hash::compute_from(b"3"), b"c".to_vec());
+        data.insert(b"1".to_vec(), b"a".to_vec());
+        data.insert(b"12".to_vec(), b"b".to_vec());
+        data.insert(b"3".to_vec(), b"c".to_vec());
         let entry = LedgerEntry {
             parallel_balance: Amount::from_raw(42),
             datastore: data.clone(),
@@ -447,7 +810,7 @@ mod tests {
 
         // write data
         let temp_dir = TempDir::new().unwrap();
-        let mut db = LedgerDB::new(temp_dir.path().to_path_buf());
+        let mut db = LedgerDB::new(temp_dir.path().to_path_buf(), false);
         let mut batch = WriteBatch::default();
         db.put_entry(&addr, entry, &mut batch);
         db.update_entry(&addr, entry_update, &mut batch);
@@ -477,6 +840,21 @@ mod tests {
         );
         assert!(db.get_sub_entry(&b, LedgerSubEntry::Balance).is_none());
         assert_eq!(data, db.get_entire_datastore(&a));
+        assert_eq!(
+            db.get_datastore_keys(&a, b"1", None, usize::MAX)
+                .into_iter()
+                .collect::<Vec<_>>(),
+            vec![b"1".to_vec(), b"12".to_vec()]
+        );
+        assert_eq!(db.get_datastore_keys(&a, b"", None, usize::MAX).len(), 3);
+        assert_eq!(
+            db.get_datastore_keys(&a, b"", Some(b"1"), 1)
+                .into_iter()
+                .collect::<Vec<_>>(),
+            vec![b"12".to_vec()]
+        );
+        assert!(db.get_datastore_keys(&a, b"1", Some(b"12"), 10).is_empty());
+        assert!(db.get_datastore_keys(&b, b"", None, usize::MAX).is_empty());
 
         // delete entry
         let mut batch = WriteBatch::default();
@@ -488,6 +866,37 @@ mod tests {
         assert!(db.get_entire_datastore(&a).is_empty());
     }
 
+    /// Deleting an entry removes its bytecode along with its balance and datastore
+    #[test]
+    fn test_delete_entry_bytecode() {
+        let pub_a = derive_public_key(&generate_random_private_key());
+        let a = Address::from_public_key(&pub_a);
+        let temp_dir = TempDir::new().unwrap();
+        let mut db = LedgerDB::new(temp_dir.path().to_path_buf(), false);
+        let mut batch = WriteBatch::default();
+        db.put_entry(
+            &a,
+            LedgerEntry {
+                parallel_balance: Amount::from_raw(42),
+                bytecode: vec![1, 2, 3],
+                ..Default::default()
+            },
+            &mut batch,
+        );
+        db.write_batch(batch);
+        assert_eq!(
+            db.get_sub_entry(&a, LedgerSubEntry::Bytecode),
+            Some(vec![1, 2, 3])
+        );
+
+        let mut batch = WriteBatch::default();
+        db.delete_entry(&a, &mut batch);
+        db.write_batch(batch);
+        assert!(db.get_sub_entry(&a, LedgerSubEntry::Balance).is_none());
+        assert!(db.get_sub_entry(&a, LedgerSubEntry::Bytecode).is_none());
+        assert!(db.get_last_key().is_none());
+    }
+
     #[test]
     fn test_ledger_parts() {
         let pub_a = derive_public_key(&generate_random_private_key());
@@ -496,4 +905,161 @@ mod tests {
         let res = db.get_ledger_part(&None).unwrap();
         db.set_ledger_part(&res.0[..]).unwrap();
     }
+
+    /// Persisted asynchronous pool and slot survive alongside the ledger and are cleared by a reset,
+    /// which keeps the network identifier
+    #[test]
+    fn test_persisted_final_state() {
+        massa_models::init_serialization_context(massa_models::SerializationContext::default());
+        let pub_a = derive_public_key(&generate_random_private_key());
+        let a = Address::from_public_key(&pub_a);
+        let (mut db, _) = init_test_ledger(a);
+        assert!(db.get_slot().is_none());
+
+        let message = AsyncMessage {
+            emission_slot: Slot::new(1, 0),
+            emission_index: 0,
+            sender: a,
+            destination: a,
+            handler: String::from("test"),
+            max_gas: 1000,
+            gas_price: Amount::from_raw(1),
+            coins: Amount::from_raw(1),
+            validity_start: Slot::new(2, 0),
+            validity_end: Slot::new(4, 0),
+            data: vec![1, 2, 3],
+        };
+        let id = message.compute_id();
+        db.apply_changes(
+            LedgerChanges::default(),
+            AsyncPoolChanges(vec![Change::Add(id, message.clone())]),
+            Some(Slot::new(3, 1)),
+        );
+        assert_eq!(db.get_slot(), Some(Slot::new(3, 1)));
+        assert_eq!(
+            db.get_async_pool_messages().into_iter().collect::<Vec<_>>(),
+            vec![(id, message)]
+        );
+        assert!(db.get_last_key().is_some());
+
+        db.apply_changes(
+            LedgerChanges::default(),
+            AsyncPoolChanges(vec![Change::Delete(id)]),
+            None,
+        );
+        assert!(db.get_slot().is_none());
+        assert!(db.get_async_pool_messages().is_empty());
+
+        db.set_network_id(b"TEST");
+        db.reset();
+        assert!(db.get_last_key().is_none());
+        assert!(db.get_sub_entry(&a, LedgerSubEntry::Balance).is_none());
+        assert_eq!(db.get_network_id(), Some(b"TEST".to_vec()));
+    }
+
+    /// A database written before the disk layout versioning is reset when reopened
+    #[test]
+    fn test_outdated_layout_reset() {
+        massa_models::init_serialization_context(massa_models::SerializationContext::default());
+        let pub_a = derive_public_key(&generate_random_private_key());
+        let a = Address::from_public_key(&pub_a);
+        let temp_dir = TempDir::new().unwrap();
+        let mut db = LedgerDB::new(temp_dir.path().to_path_buf(), false);
+        let mut changes = LedgerChanges::default();
+        changes.set_parallel_balance(a, Amount::from_raw(42));
+        db.apply_changes(changes, AsyncPoolChanges::default(), Some(Slot::new(1, 0)));
+        assert_eq!(db.get_version(), Some(LEDGER_DB_VERSION));
+
+        // a database with the current layout is kept as is
+        drop(db);
+        let db = LedgerDB::new(temp_dir.path().to_path_buf(), false);
+        assert_eq!(db.get_slot(), Some(Slot::new(1, 0)));
+        assert!(db.get_sub_entry(&a, LedgerSubEntry::Balance).is_some());
+
+        // remove the version key to simulate a database with the initial layout
+        let mut batch = WriteBatch::default();
+        batch.delete_cf(db.db.cf_handle(METADATA_CF).unwrap(), VERSION_KEY);
+        db.write_batch(batch);
+        drop(db);
+        let db = LedgerDB::new(temp_dir.path().to_path_buf(), false);
+        assert_eq!(db.get_version(), Some(LEDGER_DB_VERSION));
+        assert!(db.get_slot().is_none());
+        assert!(db.get_last_key().is_none());
+    }
+
+    /// Archived values give the state of the ledger at the output of the slots following the archive start
+    #[test]
+    fn test_archive_mode() {
+        massa_models::init_serialization_context(massa_models::SerializationContext::default());
+        let pub_a = derive_public_key(&generate_random_private_key());
+        let a = Address::from_public_key(&pub_a);
+        let temp_dir = TempDir::new().unwrap();
+        let mut db = LedgerDB::new(temp_dir.path().to_path_buf(), true);
+        let mut datastore = BTreeMap::new();
+        datastore.insert(b"k".to_vec(), b"v1".to_vec());
+        db.set_initial_ledger(
+            vec![(
+                a,
+                LedgerEntry {
+                    parallel_balance: Amount::from_raw(10),
+                    datastore,
+                    ..Default::default()
+                },
+            )]
+            .into_iter()
+            .collect(),
+        );
+        let balance = |raw| Some(Some(Amount::from_raw(raw).to_bytes_compact().unwrap()));
+
+        // the archive starts at the first slot the disk state is attached to
+        db.apply_changes(
+            LedgerChanges::default(),
+            AsyncPoolChanges::default(),
+            Some(Slot::new(1, 0)),
+        );
+        let mut changes = LedgerChanges::default();
+        changes.set_parallel_balance(a, Amount::from_raw(20));
+        changes.set_data_entry(a, b"k".to_vec(), b"v2".to_vec());
+        db.apply_changes(changes, AsyncPoolChanges::default(), Some(Slot::new(1, 1)));
+        let mut changes = LedgerChanges::default();
+        changes.0.insert(a, SetUpdateOrDelete::Delete);
+        db.apply_changes(changes, AsyncPoolChanges::default(), Some(Slot::new(2, 0)));
+
+        let k = || LedgerSubEntry::Datastore(b"k".to_vec());
+        assert_eq!(
+            db.get_archived_sub_entry(&a, LedgerSubEntry::Balance, Slot::new(0, 1)),
+            None
+        );
+        assert_eq!(
+            db.get_archived_sub_entry(&a, LedgerSubEntry::Balance, Slot::new(1, 0)),
+            balance(10)
+        );
+        assert_eq!(
+            db.get_archived_sub_entry(&a, k(), Slot::new(1, 0)),
+            Some(Some(b"v1".to_vec()))
+        );
+        assert_eq!(
+            db.get_archived_sub_entry(&a, LedgerSubEntry::Balance, Slot::new(1, 1)),
+            balance(20)
+        );
+        assert_eq!(
+            db.get_archived_sub_entry(&a, k(), Slot::new(1, 1)),
+            Some(Some(b"v2".to_vec()))
+        );
+        assert_eq!(
+            db.get_archived_sub_entry(&a, LedgerSubEntry::Balance, Slot::new(2, 0)),
+            Some(None)
+        );
+        assert_eq!(
+            db.get_archived_sub_entry(&a, LedgerSubEntry::Balance, Slot::new(2, 1)),
+            None
+        );
+
+        // detaching the disk state interrupts the archive
+        db.apply_changes(LedgerChanges::default(), AsyncPoolChanges::default(), None);
+        assert_eq!(
+            db.get_archived_sub_entry(&a, LedgerSubEntry::Balance, Slot::new(1, 0)),
+            None
+        );
+    }
 }
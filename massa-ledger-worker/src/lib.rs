@@ -27,6 +27,8 @@
 //! Defines the `FinalLedger` that matches an address to a `LedgerEntry` (see `ledger_entry.rs`),
 //! and can be manipulated using `LedgerChanges` (see `ledger_changes.rs`).
 //! The `FinalLedger` is bootstrapped using tooling available in bootstrap.rs
+//! Its disk database also persists the asynchronous pool and the final slot of the `FinalState`,
+//! written in the same batch as the ledger changes.
 //!
 //! ## `ledger_entry.rs`
 //! Represents an entry in the ledger for a given address.
@@ -14,7 +14,7 @@ pub fn create_final_ledger(
     config: LedgerConfig,
 ) -> FinalLedger {
     let temp_dir = TempDir::new().unwrap();
-    let mut db = LedgerDB::new(temp_dir.path().to_path_buf());
+    let mut db = LedgerDB::new(temp_dir.path().to_path_buf(), config.archive_mode);
     db.set_initial_ledger(initial_ledger.unwrap_or_default());
     FinalLedger {
         _config: config,
@@ -9,7 +9,7 @@ use crate::{ledger_db::LedgerDB, FinalLedger};
 impl Default for FinalLedger {
     fn default() -> Self {
         let temp_dir = TempDir::new().unwrap();
-        let db = LedgerDB::new(temp_dir.path().to_path_buf());
+        let db = LedgerDB::new(temp_dir.path().to_path_buf(), false);
         FinalLedger {
             _config: Default::default(),
             sorted_ledger: db,
@@ -0,0 +1,177 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+//! Prometheus metrics of the node internals.
+//!
+//! The metrics are global and updated by each component where the measured values change.
+//! When enabled, they are exposed in the Prometheus text format on the `/metrics` path of an HTTP server.
+
+#![warn(missing_docs)]
+#![warn(unused_crate_dependencies)]
+
+use hyper::{header::CONTENT_TYPE, Body, Method, Request, Response, StatusCode};
+use lazy_static::lazy_static;
+use prometheus::{
+    exponential_buckets, register_histogram, register_int_counter, register_int_counter_vec,
+    register_int_gauge, register_int_gauge_vec, Encoder, Histogram, IntCounter, IntCounterVec,
+    IntGauge, IntGaugeVec, TextEncoder,
+};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use tracing::warn;
+
+mod server;
+pub use server::{start_http_server, HttpServerStopHandle};
+
+lazy_static! {
+    /// period of the latest final block of each thread
+    pub static ref CONSENSUS_FINAL_PERIOD: IntGaugeVec = register_int_gauge_vec!(
+        "massa_consensus_final_period",
+        "period of the latest final block of each thread",
+        &["thread"]
+    )
+    .unwrap();
+    /// period of the best parent of each thread
+    pub static ref CONSENSUS_CANDIDATE_PERIOD: IntGaugeVec = register_int_gauge_vec!(
+        "massa_consensus_candidate_period",
+        "period of the best parent of each thread",
+        &["thread"]
+    )
+    .unwrap();
+    /// blocks produced by our staking addresses during the cycles kept in memory
+    pub static ref STAKER_PRODUCED_BLOCKS: IntGaugeVec = register_int_gauge_vec!(
+        "massa_staker_produced_blocks",
+        "blocks produced by our staking addresses during the cycles kept in memory",
+        &["address"]
+    )
+    .unwrap();
+    /// blocks missed by our staking addresses during the cycles kept in memory
+    pub static ref STAKER_MISSED_BLOCKS: IntGaugeVec = register_int_gauge_vec!(
+        "massa_staker_missed_blocks",
+        "blocks missed by our staking addresses during the cycles kept in memory",
+        &["address"]
+    )
+    .unwrap();
+    /// number of operations in the pool, per thread
+    pub static ref POOL_OPERATIONS: IntGaugeVec = register_int_gauge_vec!(
+        "massa_pool_operations",
+        "number of operations in the pool, per thread",
+        &["thread"]
+    )
+    .unwrap();
+    /// number of endorsements in the pool
+    pub static ref POOL_ENDORSEMENTS: IntGauge = register_int_gauge!(
+        "massa_pool_endorsements",
+        "number of endorsements in the pool"
+    )
+    .unwrap();
+    /// number of slots between the latest slot to execute and the speculative execution cursor
+    pub static ref EXECUTION_ACTIVE_SLOT_LAG: IntGauge = register_int_gauge!(
+        "massa_execution_active_slot_lag",
+        "number of slots between the latest slot to execute and the speculative execution cursor"
+    )
+    .unwrap();
+    /// number of final slots ready for execution but not executed yet
+    pub static ref EXECUTION_FINAL_SLOT_LAG: IntGauge = register_int_gauge!(
+        "massa_execution_final_slot_lag",
+        "number of final slots ready for execution but not executed yet"
+    )
+    .unwrap();
+    /// number of read-only execution requests waiting in the queue
+    pub static ref EXECUTION_READONLY_QUEUE_LENGTH: IntGauge = register_int_gauge!(
+        "massa_execution_readonly_queue_length",
+        "number of read-only execution requests waiting in the queue"
+    )
+    .unwrap();
+    /// gas consumed by the operations of each final slot
+    pub static ref EXECUTION_SLOT_GAS: Histogram = register_histogram!(
+        "massa_execution_slot_gas",
+        "gas consumed by the operations of each final slot",
+        exponential_buckets(100_000.0, 4.0, 10).unwrap()
+    )
+    .unwrap();
+    /// number of active connections, per peer type and direction
+    pub static ref NETWORK_CONNECTIONS: IntGaugeVec = register_int_gauge_vec!(
+        "massa_network_connections",
+        "number of active connections, per peer type and direction",
+        &["peer_type", "direction"]
+    )
+    .unwrap();
+    /// number of known peers
+    pub static ref NETWORK_KNOWN_PEERS: IntGauge = register_int_gauge!(
+        "massa_network_known_peers",
+        "number of known peers"
+    )
+    .unwrap();
+    /// number of banned peers
+    pub static ref NETWORK_BANNED_PEERS: IntGauge = register_int_gauge!(
+        "massa_network_banned_peers",
+        "number of banned peers"
+    )
+    .unwrap();
+    /// bytes sent to peers
+    pub static ref NETWORK_SENT_BYTES: IntCounter = register_int_counter!(
+        "massa_network_sent_bytes_total",
+        "bytes sent to peers"
+    )
+    .unwrap();
+    /// bytes received from peers
+    pub static ref NETWORK_RECEIVED_BYTES: IntCounter = register_int_counter!(
+        "massa_network_received_bytes_total",
+        "bytes received from peers"
+    )
+    .unwrap();
+    /// bootstrap sessions served, per result
+    pub static ref BOOTSTRAP_SESSIONS: IntCounterVec = register_int_counter_vec!(
+        "massa_bootstrap_sessions_total",
+        "bootstrap sessions served, per result",
+        &["result"]
+    )
+    .unwrap();
+}
+
+/// Encodes all the metrics in the Prometheus text format
+pub fn encode_metrics() -> Vec<u8> {
+    let mut buffer = Vec::new();
+    if let Err(err) = TextEncoder::new().encode(&prometheus::gather(), &mut buffer) {
+        warn!("could not encode metrics: {}", err);
+    }
+    buffer
+}
+
+/// Answers an HTTP request to the metrics server
+async fn handle_request(request: Request<Body>) -> Result<Response<Body>, Infallible> {
+    let response = match (request.method(), request.uri().path()) {
+        (&Method::GET, "/metrics") => Response::builder()
+            .header(CONTENT_TYPE, TextEncoder::new().format_type())
+            .body(Body::from(encode_metrics())),
+        _ => Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(Body::empty()),
+    };
+    // the response is built from valid parts
+    Ok(response.unwrap())
+}
+
+/// Used to be able to stop the metrics server
+pub type MetricsStopHandle = HttpServerStopHandle;
+
+/// Starts an HTTP server exposing the metrics on `/metrics`
+///
+/// # Arguments
+/// * `bind`: address the server listens on
+pub fn start_metrics_server(bind: &SocketAddr) -> MetricsStopHandle {
+    start_http_server("metrics server", bind, handle_request)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_metrics() {
+        CONSENSUS_FINAL_PERIOD.with_label_values(&["3"]).set(12);
+        NETWORK_SENT_BYTES.inc_by(42);
+        let metrics = String::from_utf8(encode_metrics()).unwrap();
+        assert!(metrics.contains("massa_consensus_final_period{thread=\"3\"} 12"));
+        assert!(metrics.contains("massa_network_sent_bytes_total 42"));
+    }
+}
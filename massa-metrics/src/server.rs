@@ -0,0 +1,70 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+//! Minimal HTTP server, shared by the metrics endpoint and the other plain HTTP services of the node.
+
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Request, Response, Server};
+use std::convert::Infallible;
+use std::future::Future;
+use std::net::SocketAddr;
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+/// Used to be able to stop an HTTP server started with `start_http_server`
+pub struct HttpServerStopHandle {
+    /// name of the server, for logs
+    name: &'static str,
+    stop_tx: oneshot::Sender<()>,
+    join_handle: JoinHandle<()>,
+}
+
+impl HttpServerStopHandle {
+    /// stop the server gracefully
+    pub async fn stop(self) {
+        let _ = self.stop_tx.send(());
+        if let Err(err) = self.join_handle.await {
+            warn!("{} task panicked: {}", self.name, err);
+        } else {
+            info!("{} finished cleanly", self.name);
+        }
+    }
+}
+
+/// Starts an HTTP server answering each request with `handler`
+///
+/// # Arguments
+/// * `name`: name of the server, for logs
+/// * `bind`: address the server listens on
+/// * `handler`: builds the response to a request
+pub fn start_http_server<F, R>(
+    name: &'static str,
+    bind: &SocketAddr,
+    handler: F,
+) -> HttpServerStopHandle
+where
+    F: Fn(Request<Body>) -> R + Clone + Send + 'static,
+    R: Future<Output = Result<Response<Body>, Infallible>> + Send + 'static,
+{
+    let make_service = make_service_fn(move |_| {
+        let handler = handler.clone();
+        async move { Ok::<_, Infallible>(service_fn(handler)) }
+    });
+    let server = Server::bind(bind).serve(make_service);
+    let (stop_tx, stop_rx) = oneshot::channel();
+    let join_handle = tokio::spawn(async move {
+        if let Err(err) = server
+            .with_graceful_shutdown(async {
+                let _ = stop_rx.await;
+            })
+            .await
+        {
+            warn!("{} error: {}", name, err);
+        }
+    });
+    info!("{} listening on {}", name, bind);
+    HttpServerStopHandle {
+        name,
+        stop_tx,
+        join_handle,
+    }
+}
@@ -1,17 +1,21 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 use crate::address::AddressCycleProductionStats;
+use crate::bytecode_policy::{
+    BytecodePolicy, PendingBytecodeUpgrade, BYTECODE_POLICY_DATASTORE_KEY,
+    BYTECODE_UPGRADE_DATASTORE_KEY,
+};
 use crate::ledger_models::LedgerData;
 use crate::node::NodeId;
-use crate::prehash::Map;
+use crate::output_event::SCOutputEvent;
 use crate::prehash::Set;
 use crate::stats::{ConsensusStats, NetworkStats, PoolStats};
 use crate::SignedEndorsement;
 use crate::SignedOperation;
 use crate::{
-    Address, Amount, Block, BlockId, CompactConfig, EndorsementId, OperationId, Slot, Version,
+    Address, Amount, Block, BlockId, CompactConfig, EndorsementId, OperationId, OperationType,
+    Slot, Version,
 };
-use massa_hash::Hash;
 use massa_time::MassaTime;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
@@ -28,6 +32,11 @@ pub struct NodeStatus {
     pub version: Version,
     /// now
     pub current_time: MassaTime,
+    /// compensation in milliseconds applied to the local clock
+    pub clock_compensation: i64,
+    /// drift in milliseconds of the compensated clock from the bootstrap servers at the last clock synchronization,
+    /// none if it was not measured yet
+    pub clock_drift: Option<i64>,
     /// current cycle
     pub current_cycle: u64,
     /// connected nodes (node id, ip address, true if the connection is outgoing, false if incoming)
@@ -61,6 +70,10 @@ impl std::fmt::Display for NodeStatus {
         writeln!(f)?;
 
         writeln!(f, "Current time: {}", self.current_time.to_utc_string())?;
+        writeln!(f, "Clock compensation: {} ms", self.clock_compensation)?;
+        if let Some(clock_drift) = self.clock_drift {
+            writeln!(f, "Clock drift at last synchronization: {} ms", clock_drift)?;
+        }
         writeln!(f, "Current cycle: {}", self.current_cycle)?;
         if self.last_slot.is_some() {
             writeln!(f, "Last slot: {}", self.last_slot.unwrap())?;
@@ -132,6 +145,34 @@ impl std::fmt::Display for OperationInfo {
     }
 }
 
+/// An operation waiting in the pool
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct PendingOperationInfo {
+    /// id
+    pub id: OperationId,
+    /// thread in which the operation can be included
+    pub thread: u8,
+    /// first period during which the operation can be included
+    pub validity_start_period: u64,
+    /// last period during which the operation can be included
+    pub expire_period: u64,
+    /// the operation itself
+    pub operation: SignedOperation,
+}
+
+impl std::fmt::Display for PendingOperationInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Operation's ID: {}", self.id)?;
+        writeln!(
+            f,
+            "Thread: {}, valid from period {} to period {}",
+            self.thread, self.validity_start_period, self.expire_period
+        )?;
+        writeln!(f, "{}", self.operation)?;
+        Ok(())
+    }
+}
+
 /// Current Parallel balance ledger info
 #[derive(Debug, Deserialize, Serialize, Clone, Copy)]
 pub struct LedgerInfo {
@@ -183,13 +224,57 @@ pub struct SCELedgerInfo {
     pub balance: Amount,
     /// stored bytes
     pub module: Vec<u8>,
-    /// datastore
-    pub datastore: Map<Hash, Vec<u8>>,
+    /// datastore `(key, value)` entries, sorted by key
+    pub datastore: Vec<(Vec<u8>, Vec<u8>)>,
+    /// policy restricting the replacement of the bytecode
+    pub bytecode_policy: BytecodePolicy,
+    /// bytecode upgrade waiting for the end of a timelock
+    pub pending_bytecode_upgrade: Option<PendingBytecodeUpgrade>,
+}
+
+impl SCELedgerInfo {
+    /// Creates the balance state of an address, reading its bytecode policy from its datastore
+    ///
+    /// # Arguments
+    /// * `balance`: sequential coins
+    /// * `module`: bytecode
+    /// * `datastore`: datastore entries, sorted by key
+    pub fn new(balance: Amount, module: Vec<u8>, datastore: Vec<(Vec<u8>, Vec<u8>)>) -> Self {
+        let get_value = |key: &[u8]| {
+            datastore
+                .iter()
+                .find(|(entry_key, _)| entry_key == key)
+                .map(|(_, value)| value.as_slice())
+        };
+        // the execution only lets valid policies be written
+        let bytecode_policy =
+            BytecodePolicy::from_datastore_value(get_value(BYTECODE_POLICY_DATASTORE_KEY))
+                .unwrap_or_default();
+        let pending_bytecode_upgrade = get_value(BYTECODE_UPGRADE_DATASTORE_KEY)
+            .and_then(|value| PendingBytecodeUpgrade::from_bytes(value).ok());
+        SCELedgerInfo {
+            balance,
+            module,
+            datastore,
+            bytecode_policy,
+            pending_bytecode_upgrade,
+        }
+    }
 }
 
 impl std::fmt::Display for SCELedgerInfo {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "\tBalance: {}", self.balance)?;
+        if !self.module.is_empty() {
+            writeln!(f, "\tBytecode policy: {}", self.bytecode_policy)?;
+        }
+        if let Some(pending) = &self.pending_bytecode_upgrade {
+            writeln!(
+                f,
+                "\tPending bytecode upgrade: {} at period {}",
+                pending.bytecode_hash, pending.activation_period
+            )?;
+        }
         // I choose not to display neither the module nor the datastore because bytes
         Ok(())
     }
@@ -308,7 +393,7 @@ impl AddressInfo {
 }
 
 /// When an address is drawn to create an endorsement it is selected for a specific index
-#[derive(Debug, Deserialize, Serialize, Hash, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Hash, PartialEq, Eq)]
 pub struct IndexedSlot {
     /// slot
     pub slot: Slot,
@@ -322,6 +407,111 @@ impl std::fmt::Display for IndexedSlot {
     }
 }
 
+/// Block and endorsement production of a staker during a cycle
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct StakerCycleStats {
+    /// cycle number
+    pub cycle: u64,
+    /// true if that cycle is final
+    pub is_final: bool,
+    /// blocks created by the staker
+    pub produced_blocks: u64,
+    /// blocks the staker was drawn for but that are missing
+    pub missed_blocks: u64,
+    /// endorsements of the staker included in blocks.
+    /// Endorsement counts are local to the node: they restart at zero after a bootstrap.
+    pub produced_endorsements: u64,
+    /// endorsements the staker was drawn for but that no block includes
+    pub missed_endorsements: u64,
+}
+
+/// Roll sale coins not credited yet
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct DeferredCredit {
+    /// cycle at which the coins are credited
+    pub cycle: u64,
+    /// credited coins
+    pub amount: Amount,
+}
+
+/// Everything a staker needs to monitor its address
+#[derive(Debug, Deserialize, Serialize)]
+pub struct StakerReport {
+    /// the address
+    pub address: Address,
+    /// cycle of the next slot
+    pub current_cycle: u64,
+    /// rolls taken into account for the draws of the current cycle
+    pub active_rolls: u64,
+    /// slots of the current and next cycles at which the address is selected to create a block
+    pub block_draws: Vec<Slot>,
+    /// slots of the current and next cycles at which the address is selected to create an endorsement
+    pub endorsement_draws: Vec<IndexedSlot>,
+    /// production of the cycles still in memory
+    pub production_stats: Vec<StakerCycleStats>,
+    /// cycles at which the rolls of the address are going to be deactivated because of missed blocks
+    pub roll_deactivation_cycles: Vec<u64>,
+    /// roll sale coins not credited yet
+    pub deferred_credits: Vec<DeferredCredit>,
+}
+
+impl std::fmt::Display for StakerReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Address: {}", self.address)?;
+        writeln!(f, "Current cycle: {}", self.current_cycle)?;
+        writeln!(f, "Active rolls: {}", self.active_rolls)?;
+        writeln!(
+            f,
+            "Block draws of the current and next cycles: {}",
+            self.block_draws
+                .iter()
+                .fold("\n".to_string(), |acc, s| format!("{}    {}\n", acc, s))
+        )?;
+        writeln!(
+            f,
+            "Endorsement draws of the current and next cycles: {}",
+            self.endorsement_draws
+                .iter()
+                .fold("\n".to_string(), |acc, s| format!("{}    {}", acc, s))
+        )?;
+        writeln!(f, "Production stats:")?;
+        for cycle_stat in self.production_stats.iter() {
+            writeln!(
+                f,
+                "\t cycle {} {}: produced {} and missed {} blocks, produced {} and missed {} endorsements",
+                cycle_stat.cycle,
+                if cycle_stat.is_final {
+                    "(final)"
+                } else {
+                    "(non-final)"
+                },
+                cycle_stat.produced_blocks,
+                cycle_stat.missed_blocks,
+                cycle_stat.produced_endorsements,
+                cycle_stat.missed_endorsements
+            )?;
+        }
+        if self.roll_deactivation_cycles.is_empty() {
+            writeln!(f, "Roll deactivation risk: none")?;
+        } else {
+            writeln!(
+                f,
+                "Roll deactivation risk: too many missed blocks, rolls deactivated at cycle(s) {}",
+                self.roll_deactivation_cycles
+                    .iter()
+                    .map(|cycle| cycle.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )?;
+        }
+        writeln!(f, "Deferred credits:")?;
+        for credit in self.deferred_credits.iter() {
+            writeln!(f, "\t {} at cycle {}", credit.amount, credit.cycle)?;
+        }
+        Ok(())
+    }
+}
+
 /// Less information about an address
 #[derive(Debug, Serialize)]
 pub struct CompactAddressInfo {
@@ -488,7 +678,7 @@ pub struct DatastoreEntryInput {
     /// associated address of the entry
     pub address: Address,
     /// datastore key
-    pub key: Hash,
+    pub key: Vec<u8>,
 }
 
 /// Datastore entry query output struct
@@ -500,6 +690,59 @@ pub struct DatastoreEntryOutput {
     pub active_value: Option<Vec<u8>>,
 }
 
+/// Datastore keys query input struct
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct DatastoreKeysInput {
+    /// associated address of the keys
+    pub address: Address,
+    /// only keys starting with this prefix are returned
+    #[serde(default)]
+    pub prefix: Vec<u8>,
+    /// only keys strictly greater than this one are returned, used to fetch the next page
+    pub cursor: Option<Vec<u8>>,
+}
+
+/// Datastore keys query output struct
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct DatastoreKeysOutput {
+    /// a page of active datastore keys, sorted in lexicographic order
+    pub keys: Vec<Vec<u8>>,
+    /// cursor to query the next page, `None` if this page is the last one
+    pub next_cursor: Option<Vec<u8>>,
+}
+
+/// Historical ledger query input struct
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct HistoricalLedgerInput {
+    /// queried address
+    pub address: Address,
+    /// final slot at the output of which the ledger entry is queried
+    pub slot: Slot,
+    /// keys of the queried datastore entries
+    #[serde(default)]
+    pub datastore_keys: Vec<Vec<u8>>,
+}
+
+/// Historical ledger query output struct
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct HistoricalLedgerOutput {
+    /// parallel balance at the queried slot, `None` if the address had no ledger entry
+    pub parallel_balance: Option<Amount>,
+    /// bytecode at the queried slot, `None` if the address had no ledger entry
+    pub bytecode: Option<Vec<u8>>,
+    /// queried datastore entries at the queried slot, in the order of the query
+    pub datastore: Vec<HistoricalDatastoreEntry>,
+}
+
+/// Datastore entry of a historical ledger query
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct HistoricalDatastoreEntry {
+    /// datastore key
+    pub key: Vec<u8>,
+    /// datastore value, `None` if the entry did not exist
+    pub value: Option<Vec<u8>>,
+}
+
 /// filter used when retrieving SC output events
 #[derive(Default, Debug, Deserialize, Clone, Serialize)]
 pub struct EventFilter {
@@ -515,6 +758,72 @@ pub struct EventFilter {
     pub original_operation_id: Option<OperationId>,
 }
 
+impl EventFilter {
+    /// Returns true if the event satisfies every criterion of the filter
+    pub fn matches(&self, event: &SCOutputEvent) -> bool {
+        if let Some(start) = self.start {
+            if event.context.slot < start {
+                return false;
+            }
+        }
+        if let Some(end) = self.end {
+            if event.context.slot >= end {
+                return false;
+            }
+        }
+        match (self.emitter_address, event.context.call_stack.front()) {
+            (Some(addr1), Some(addr2)) if addr1 != *addr2 => return false,
+            (Some(_), None) => return false,
+            _ => (),
+        }
+        match (
+            self.original_caller_address,
+            event.context.call_stack.back(),
+        ) {
+            (Some(addr1), Some(addr2)) if addr1 != *addr2 => return false,
+            (Some(_), None) => return false,
+            _ => (),
+        }
+        match (
+            self.original_operation_id,
+            event.context.origin_operation_id,
+        ) {
+            (Some(addr1), Some(addr2)) if addr1 != addr2 => return false,
+            (Some(_), None) => return false,
+            _ => (),
+        }
+        true
+    }
+}
+
+/// SC output event pushed to the subscribers of the event stream
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct SCOutputEventNotification {
+    /// false if the event was emitted by a speculative execution that can still be reverted
+    pub is_final: bool,
+    /// the emitted event
+    pub event: SCOutputEvent,
+}
+
+/// Block status change pushed to the subscribers of the block stream
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub enum BlockNotification {
+    /// the block became final
+    Final {
+        /// block id
+        block_id: BlockId,
+        /// block slot
+        slot: Slot,
+    },
+    /// the block became stale
+    Stale {
+        /// block id
+        block_id: BlockId,
+        /// block slot
+        slot: Slot,
+    },
+}
+
 /// read only bytecode execution request
 #[derive(Debug, Deserialize, Clone, Serialize)]
 pub struct ReadOnlyBytecodeExecution {
@@ -540,7 +849,33 @@ pub struct ReadOnlyCall {
     /// target function
     pub target_function: String,
     /// function parameter
-    pub parameter: String,
+    #[serde(with = "crate::sc_args::serde_param")]
+    pub parameter: Vec<u8>,
     /// caller's address, optional
     pub caller_address: Option<Address>,
 }
+
+/// gas estimation request
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct GasEstimationInput {
+    /// address of the operation sender
+    pub sender: Address,
+    /// unsigned `ExecuteSC` or `CallSC` operation, its `max_gas` is ignored
+    pub op: OperationType,
+}
+
+/// gas estimation result
+#[derive(Debug, Deserialize, Clone, Serialize)]
+pub struct GasEstimation {
+    /// gas consumed by the speculative execution of the operation
+    pub gas_cost: u64,
+    /// suggested `max_gas`: the gas cost increased by the safety margin of the node
+    pub max_gas: u64,
+}
+
+impl std::fmt::Display for GasEstimation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Gas cost: {}", self.gas_cost)?;
+        writeln!(f, "Suggested max gas: {}", self.max_gas)
+    }
+}
@@ -0,0 +1,179 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Bytecode policies: who can replace the bytecode of an address, and when.
+//!
+//! The policy of an address is written as text in the `BYTECODE_POLICY_DATASTORE_KEY` entry of its datastore,
+//! an address without policy being upgradeable by any caller with write access on it.
+//! The policy only applies to the replacement of an existing bytecode, and constrains its own changes:
+//! an immutable policy is final, an admin policy can only be changed by the admin
+//! and a timelock can only be extended or made immutable.
+
+use crate::{Address, ModelsError};
+use massa_hash::{Hash, HASH_SIZE_BYTES};
+use serde::{Deserialize, Serialize};
+use std::{fmt::Display, str::FromStr};
+
+/// Datastore key holding the bytecode policy of an address
+pub const BYTECODE_POLICY_DATASTORE_KEY: &[u8] = b"massa_bytecode_policy";
+
+/// Datastore key holding the pending bytecode upgrade of an address under a timelock.
+/// It is only written by the node.
+pub const BYTECODE_UPGRADE_DATASTORE_KEY: &[u8] = b"massa_bytecode_upgrade";
+
+/// Policy restricting the replacement of the bytecode of an address
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BytecodePolicy {
+    /// the bytecode can be replaced by any caller with write access on the address
+    #[default]
+    Upgradeable,
+    /// the bytecode can never be replaced
+    Immutable,
+    /// the bytecode can only be replaced if the admin address is the current call or its immediate caller
+    Admin(Address),
+    /// the bytecode is replaced a given number of periods after the upgrade is requested
+    Timelock(u64),
+}
+
+impl BytecodePolicy {
+    /// Reads a policy from its datastore entry, the default policy applying if there is none
+    pub fn from_datastore_value(value: Option<&[u8]>) -> Result<Self, ModelsError> {
+        match value {
+            Some(value) => std::str::from_utf8(value)
+                .map_err(|_| {
+                    ModelsError::BytecodePolicyError("policy is not valid UTF-8".to_string())
+                })?
+                .parse(),
+            None => Ok(BytecodePolicy::default()),
+        }
+    }
+
+    /// Checks whether the policy can be replaced by another one
+    ///
+    /// # Arguments
+    /// * `new_policy`: replacing policy
+    /// * `by_admin`: whether the admin of an admin policy requests the change
+    pub fn can_change_to(&self, new_policy: &BytecodePolicy, by_admin: bool) -> bool {
+        match (self, new_policy) {
+            (BytecodePolicy::Upgradeable, _) => true,
+            (BytecodePolicy::Immutable, new_policy) => new_policy == &BytecodePolicy::Immutable,
+            (BytecodePolicy::Admin(_), _) => by_admin,
+            (BytecodePolicy::Timelock(_), BytecodePolicy::Immutable) => true,
+            (BytecodePolicy::Timelock(delay), BytecodePolicy::Timelock(new_delay)) => {
+                new_delay >= delay
+            }
+            (BytecodePolicy::Timelock(_), _) => false,
+        }
+    }
+}
+
+impl Display for BytecodePolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BytecodePolicy::Upgradeable => write!(f, "upgradeable"),
+            BytecodePolicy::Immutable => write!(f, "immutable"),
+            BytecodePolicy::Admin(admin) => write!(f, "admin:{}", admin),
+            BytecodePolicy::Timelock(delay) => write!(f, "timelock:{}", delay),
+        }
+    }
+}
+
+impl FromStr for BytecodePolicy {
+    type Err = ModelsError;
+
+    /// ```
+    /// use massa_models::bytecode_policy::BytecodePolicy;
+    ///
+    /// assert_eq!("timelock:100".parse::<BytecodePolicy>().unwrap(), BytecodePolicy::Timelock(100));
+    /// assert_eq!("immutable".parse::<BytecodePolicy>().unwrap().to_string(), "immutable");
+    /// ```
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            ModelsError::BytecodePolicyError(format!(
+                "`{}` is not one of upgradeable, immutable, admin:<address> or timelock:<periods>",
+                s
+            ))
+        };
+        match s.trim().split_once(':') {
+            None if s.trim() == "upgradeable" => Ok(BytecodePolicy::Upgradeable),
+            None if s.trim() == "immutable" => Ok(BytecodePolicy::Immutable),
+            Some(("admin", admin)) => Ok(BytecodePolicy::Admin(
+                admin.trim().parse().map_err(|_| invalid())?,
+            )),
+            Some(("timelock", delay)) => Ok(BytecodePolicy::Timelock(
+                delay.trim().parse().map_err(|_| invalid())?,
+            )),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// Bytecode upgrade requested under a timelock
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingBytecodeUpgrade {
+    /// period from which the upgrade can be applied
+    pub activation_period: u64,
+    /// hash of the new bytecode
+    pub bytecode_hash: Hash,
+}
+
+impl PendingBytecodeUpgrade {
+    /// Serializes the pending upgrade to its datastore value:
+    /// activation period on 8 bytes little endian, followed by the bytecode hash
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut res = Vec::with_capacity(8 + HASH_SIZE_BYTES);
+        res.extend(self.activation_period.to_le_bytes());
+        res.extend(self.bytecode_hash.to_bytes());
+        res
+    }
+
+    /// Deserializes a pending upgrade from its datastore value
+    pub fn from_bytes(data: &[u8]) -> Result<Self, ModelsError> {
+        if data.len() != 8 + HASH_SIZE_BYTES {
+            return Err(ModelsError::BytecodePolicyError(format!(
+                "pending upgrade of {} bytes instead of {}",
+                data.len(),
+                8 + HASH_SIZE_BYTES
+            )));
+        }
+        let (period, hash) = data.split_at(8);
+        Ok(PendingBytecodeUpgrade {
+            activation_period: u64::from_le_bytes(period.try_into().expect("length checked")),
+            bytecode_hash: Hash::from_bytes(hash.try_into().expect("length checked")),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_signature::{derive_public_key, generate_random_private_key};
+
+    #[test]
+    fn test_bytecode_policy_changes() {
+        let admin = Address::from_public_key(&derive_public_key(&generate_random_private_key()));
+        let policy: BytecodePolicy = format!("admin:{}", admin).parse().unwrap();
+        assert_eq!(policy, BytecodePolicy::Admin(admin));
+        assert_eq!(
+            BytecodePolicy::from_datastore_value(None).unwrap(),
+            BytecodePolicy::Upgradeable
+        );
+        assert!("timelock:-1".parse::<BytecodePolicy>().is_err());
+
+        assert!(BytecodePolicy::Upgradeable.can_change_to(&BytecodePolicy::Immutable, false));
+        assert!(!BytecodePolicy::Immutable.can_change_to(&BytecodePolicy::Upgradeable, true));
+        assert!(!policy.can_change_to(&BytecodePolicy::Upgradeable, false));
+        assert!(policy.can_change_to(&BytecodePolicy::Upgradeable, true));
+        assert!(BytecodePolicy::Timelock(10).can_change_to(&BytecodePolicy::Timelock(20), false));
+        assert!(!BytecodePolicy::Timelock(10).can_change_to(&BytecodePolicy::Timelock(5), false));
+        assert!(!BytecodePolicy::Timelock(10).can_change_to(&policy, false));
+
+        let pending = PendingBytecodeUpgrade {
+            activation_period: 42,
+            bytecode_hash: Hash::compute_from(b"bytecode"),
+        };
+        assert_eq!(
+            PendingBytecodeUpgrade::from_bytes(&pending.to_bytes()).unwrap(),
+            pending
+        );
+    }
+}
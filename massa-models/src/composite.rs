@@ -63,6 +63,8 @@ pub struct StakersCycleProductionStats {
     /// map address to produced valid block count and not valid but expected block count
     /// really a re arranged `[crate::address::AddressCycleProductionStats]`
     pub ok_nok_counts: Map<Address, (u64, u64)>,
+    /// map address to included endorsement count and missed endorsement count
+    pub endorsement_ok_nok_counts: Map<Address, (u64, u64)>,
 }
 
 /// just a public key and a signature it has produced
@@ -48,6 +48,12 @@ pub enum ModelsError {
     AmountOverflowError,
     /// Wrong prefix for hash: expected {0}, got {1}
     WrongPrefix(String, String),
+    /// invalid multisig: {0}
+    MultisigError(String),
+    /// invalid smart contract call arguments: {0}
+    ArgsError(String),
+    /// invalid bytecode policy: {0}
+    BytecodePolicyError(String),
 }
 
 impl From<nom::Err<nom::error::Error<&[u8]>>> for ModelsError {
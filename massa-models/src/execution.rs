@@ -1,6 +1,6 @@
 use std::{collections::VecDeque, fmt::Display};
 
-use crate::{output_event::SCOutputEvent, Slot};
+use crate::{output_event::SCOutputEvent, Address, Amount, Slot};
 use serde::{Deserialize, Serialize};
 
 /// The result of the read-only execution.
@@ -22,6 +22,132 @@ pub struct ExecuteReadOnlyResponse {
     pub result: ReadOnlyResult,
     /// The output events generated by the read-only execution.
     pub output_events: VecDeque<SCOutputEvent>,
+    /// The gas consumed by the read-only execution.
+    pub gas_cost: u64,
+    /// The state changes the execution would have caused.
+    pub state_changes: ReadOnlyStateChanges,
+    /// The calls made during the execution, rooted at the called address.
+    pub call_trace: Option<ReadOnlyCallTrace>,
+}
+
+/// The state changes a read-only execution would have caused.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct ReadOnlyStateChanges {
+    /// Changes to ledger entries.
+    pub ledger_changes: Vec<ReadOnlyLedgerChange>,
+    /// Asynchronous messages emitted by the execution.
+    pub emitted_messages: Vec<ReadOnlyAsyncMessage>,
+}
+
+/// The changes a read-only execution would have caused to a ledger entry.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ReadOnlyLedgerChange {
+    /// Address of the ledger entry.
+    pub address: Address,
+    /// True if the ledger entry is deleted.
+    pub deleted: bool,
+    /// Parallel balance before and after the execution, if it changed.
+    pub parallel_balance: Option<BalanceChange>,
+    /// New bytecode, if it changed.
+    pub bytecode: Option<Vec<u8>>,
+    /// Datastore entries written, as `(key, value)` pairs.
+    pub datastore_writes: Vec<(Vec<u8>, Vec<u8>)>,
+    /// Keys of the datastore entries deleted.
+    pub datastore_deletes: Vec<Vec<u8>>,
+}
+
+/// A balance before and after an execution.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct BalanceChange {
+    /// Balance before the execution.
+    pub before: Amount,
+    /// Balance after the execution.
+    pub after: Amount,
+}
+
+/// An asynchronous message emitted by a read-only execution.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ReadOnlyAsyncMessage {
+    /// The address that sent the message.
+    pub sender: Address,
+    /// The address towards which the message is sent.
+    pub destination: Address,
+    /// The handler function name within the destination address' bytecode.
+    pub handler: String,
+    /// Maximum gas to use when processing the message.
+    pub max_gas: u64,
+    /// Gas price of the message.
+    pub gas_price: Amount,
+    /// Coins sent to the destination address.
+    pub coins: Amount,
+    /// Slot at which the message starts being valid.
+    pub validity_start: Slot,
+    /// Slot at which the message stops being valid.
+    pub validity_end: Slot,
+    /// Raw payload data of the message.
+    pub data: Vec<u8>,
+}
+
+/// A call made during a read-only execution.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ReadOnlyCallTrace {
+    /// Called address.
+    pub address: Address,
+    /// Coins transferred to the called address.
+    pub coins: Amount,
+    /// Gas consumed by the call, including its nested calls.
+    pub gas_cost: u64,
+    /// Calls made from this call, in execution order.
+    pub sub_calls: Vec<ReadOnlyCallTrace>,
+}
+
+impl ReadOnlyCallTrace {
+    fn fmt_indented(&self, f: &mut std::fmt::Formatter<'_>, depth: usize) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{}{} (coins: {}, gas: {})",
+            "    ".repeat(depth),
+            self.address,
+            self.coins,
+            self.gas_cost
+        )?;
+        for call in self.sub_calls.iter() {
+            call.fmt_indented(f, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for ReadOnlyCallTrace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_indented(f, 0)
+    }
+}
+
+impl Display for ReadOnlyLedgerChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.deleted {
+            return writeln!(f, "{}: deleted", self.address);
+        }
+        writeln!(f, "{}:", self.address)?;
+        if let Some(balance) = &self.parallel_balance {
+            writeln!(
+                f,
+                "    parallel balance: {} -> {}",
+                balance.before, balance.after
+            )?;
+        }
+        if let Some(bytecode) = &self.bytecode {
+            writeln!(f, "    new bytecode: {} bytes", bytecode.len())?;
+        }
+        for (key, value) in self.datastore_writes.iter() {
+            writeln!(f, "    datastore write: {:?} = {:?}", key, value)?;
+        }
+        for key in self.datastore_deletes.iter() {
+            writeln!(f, "    datastore delete: {:?}", key)?;
+        }
+        Ok(())
+    }
 }
 
 impl Display for ExecuteReadOnlyResponse {
@@ -36,6 +162,34 @@ impl Display for ExecuteReadOnlyResponse {
                 ReadOnlyResult::Ok => "ok".to_string(),
             }
         )?;
+        writeln!(f, "Gas cost: {}", self.gas_cost)?;
+        if let Some(call_trace) = &self.call_trace {
+            writeln!(f, "Calls:")?;
+            write!(f, "{}", call_trace)?;
+        }
+        if !self.state_changes.ledger_changes.is_empty() {
+            writeln!(f, "Ledger changes:")?;
+            for change in self.state_changes.ledger_changes.iter() {
+                write!(f, "{}", change)?;
+            }
+        }
+        if !self.state_changes.emitted_messages.is_empty() {
+            writeln!(f, "Emitted asynchronous messages:")?;
+            for msg in self.state_changes.emitted_messages.iter() {
+                writeln!(
+                    f,
+                    "{} -> {}::{} (max gas: {}, gas price: {}, coins: {}, valid from {} to {})",
+                    msg.sender,
+                    msg.destination,
+                    msg.handler,
+                    msg.max_gas,
+                    msg.gas_price,
+                    msg.coins,
+                    msg.validity_start,
+                    msg.validity_end
+                )?;
+            }
+        }
         if !self.output_events.is_empty() {
             writeln!(f, "Generated events:",)?;
             for event in self.output_events.iter() {
@@ -35,6 +35,8 @@ pub mod amount;
 /// structure use by the API
 pub mod api;
 mod block;
+/// policies restricting the replacement of the bytecode of an address
+pub mod bytecode_policy;
 /// clique
 pub mod clique;
 /// various structures
@@ -46,6 +48,8 @@ pub mod error;
 pub mod execution;
 /// ledger related structures
 pub mod ledger_models;
+/// multi-signature addresses
+pub mod multisig;
 /// node related structure
 pub mod node;
 mod node_configuration;
@@ -57,6 +61,8 @@ pub mod output_event;
 pub mod prehash;
 /// rolls
 pub mod rolls;
+/// typed arguments of smart contract calls and their ABI
+pub mod sc_args;
 mod serialization;
 mod serialization_context;
 /// trait for signed structure
@@ -0,0 +1,198 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Multi-signature addresses.
+//!
+//! A multisig address is derived from a set of public keys and a threshold.
+//! An operation sent from a multisig address is valid once it is signed by at least `threshold` of the keys.
+
+use crate::{
+    array_from_slice, u8_from_slice, Address, DeserializeCompact, DeserializeVarInt, ModelsError,
+    SerializeCompact, SerializeVarInt,
+};
+use massa_hash::Hash;
+use massa_signature::{PublicKey, PUBLIC_KEY_SIZE_BYTES};
+use serde::{Deserialize, Serialize};
+
+/// Maximum number of public keys of a multisig address
+pub const MAX_MULTISIG_PUBLIC_KEYS: usize = 16;
+
+/// Domain separation of the multisig addresses from the single key ones
+const MULTISIG_ADDRESS_CONTEXT: &[u8] = b"massa_multisig_address";
+
+/// Set of public keys and number of them required to sign on behalf of a multisig address
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MultisigPolicy {
+    /// number of signatures required
+    pub threshold: u8,
+    /// public keys allowed to sign, sorted by their serialized representation
+    pub public_keys: Vec<PublicKey>,
+}
+
+impl MultisigPolicy {
+    /// Creates a policy, sorting the public keys so that the address does not depend on their order
+    ///
+    /// # Arguments
+    /// * `threshold`: number of signatures required
+    /// * `public_keys`: public keys allowed to sign
+    pub fn new(threshold: u8, mut public_keys: Vec<PublicKey>) -> Result<Self, ModelsError> {
+        public_keys.sort_unstable_by_key(|key| key.to_bytes());
+        let policy = MultisigPolicy {
+            threshold,
+            public_keys,
+        };
+        policy.check()?;
+        Ok(policy)
+    }
+
+    /// Checks that the threshold can be reached and that the keys are sorted and unique
+    pub fn check(&self) -> Result<(), ModelsError> {
+        if self.public_keys.len() > MAX_MULTISIG_PUBLIC_KEYS {
+            return Err(ModelsError::MultisigError(format!(
+                "{} public keys, at most {} are allowed",
+                self.public_keys.len(),
+                MAX_MULTISIG_PUBLIC_KEYS
+            )));
+        }
+        if self.threshold == 0 || self.threshold as usize > self.public_keys.len() {
+            return Err(ModelsError::MultisigError(format!(
+                "threshold {} is out of the range of the {} public keys",
+                self.threshold,
+                self.public_keys.len()
+            )));
+        }
+        if self
+            .public_keys
+            .windows(2)
+            .any(|pair| pair[0].to_bytes() >= pair[1].to_bytes())
+        {
+            return Err(ModelsError::MultisigError(
+                "public keys are not sorted or contain duplicates".into(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Gets the multisig address
+    pub fn get_address(&self) -> Address {
+        let mut bytes = MULTISIG_ADDRESS_CONTEXT.to_vec();
+        bytes.push(self.threshold);
+        for public_key in self.public_keys.iter() {
+            bytes.extend(public_key.to_bytes());
+        }
+        Address(Hash::compute_from(&bytes))
+    }
+
+    /// Gets the index of a public key in the policy
+    pub fn get_key_index(&self, public_key: &PublicKey) -> Option<u8> {
+        self.public_keys
+            .iter()
+            .position(|key| key == public_key)
+            .map(|index| index as u8)
+    }
+}
+
+impl std::fmt::Display for MultisigPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{} of {} public keys:",
+            self.threshold,
+            self.public_keys.len()
+        )?;
+        for public_key in self.public_keys.iter() {
+            writeln!(f, "\t- {}", public_key)?;
+        }
+        Ok(())
+    }
+}
+
+impl SerializeCompact for MultisigPolicy {
+    fn to_bytes_compact(&self) -> Result<Vec<u8>, ModelsError> {
+        let mut res: Vec<u8> = Vec::new();
+
+        // threshold
+        res.push(self.threshold);
+
+        // public keys
+        let key_count: u32 = self.public_keys.len().try_into().map_err(|err| {
+            ModelsError::SerializeError(format!("too many multisig public keys: {}", err))
+        })?;
+        res.extend(key_count.to_varint_bytes());
+        for public_key in self.public_keys.iter() {
+            res.extend(public_key.to_bytes());
+        }
+
+        Ok(res)
+    }
+}
+
+/// Checks performed:
+/// - Validity of the public keys count.
+/// - Validity of the public keys.
+/// - Validity of the policy (see `MultisigPolicy::check`).
+impl DeserializeCompact for MultisigPolicy {
+    fn from_bytes_compact(buffer: &[u8]) -> Result<(Self, usize), ModelsError> {
+        let mut cursor = 0usize;
+
+        // threshold
+        let threshold = u8_from_slice(&buffer[cursor..])?;
+        cursor += 1;
+
+        // public keys
+        let (key_count, delta) = u32::from_varint_bytes(&buffer[cursor..])?;
+        cursor += delta;
+        if key_count as usize > MAX_MULTISIG_PUBLIC_KEYS {
+            return Err(ModelsError::DeserializeError(format!(
+                "{} multisig public keys, at most {} are allowed",
+                key_count, MAX_MULTISIG_PUBLIC_KEYS
+            )));
+        }
+        let mut public_keys = Vec::with_capacity(key_count as usize);
+        for _ in 0..key_count {
+            public_keys.push(PublicKey::from_bytes(&array_from_slice(
+                &buffer[cursor..],
+            )?)?);
+            cursor += PUBLIC_KEY_SIZE_BYTES;
+        }
+
+        let policy = MultisigPolicy {
+            threshold,
+            public_keys,
+        };
+        policy
+            .check()
+            .map_err(|err| ModelsError::DeserializeError(err.to_string()))?;
+        Ok((policy, cursor))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_signature::{derive_public_key, generate_random_private_key};
+
+    #[test]
+    fn test_multisig_policy() {
+        let keys: Vec<PublicKey> = (0..3)
+            .map(|_| derive_public_key(&generate_random_private_key()))
+            .collect();
+        let policy = MultisigPolicy::new(2, keys.clone()).unwrap();
+
+        // the address does not depend on the order of the keys
+        let reversed = MultisigPolicy::new(2, keys.iter().rev().copied().collect()).unwrap();
+        assert_eq!(policy.get_address(), reversed.get_address());
+        assert_ne!(
+            policy.get_address(),
+            MultisigPolicy::new(3, keys.clone()).unwrap().get_address()
+        );
+
+        let bytes = policy.to_bytes_compact().unwrap();
+        let (deserialized, size) = MultisigPolicy::from_bytes_compact(&bytes).unwrap();
+        assert_eq!(size, bytes.len());
+        assert_eq!(deserialized, policy);
+
+        assert!(MultisigPolicy::new(0, keys.clone()).is_err());
+        assert!(MultisigPolicy::new(4, keys.clone()).is_err());
+        assert!(MultisigPolicy::new(1, vec![keys[0], keys[0]]).is_err());
+    }
+}
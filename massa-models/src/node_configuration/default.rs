@@ -68,6 +68,17 @@ lazy_static::lazy_static! {
         .parse()
         .unwrap()
     };
+    /// first node version encrypting the peer-to-peer connections, enabled by the next minor release.
+    /// Connections with peers of older versions stay in plaintext.
+    pub static ref ENCRYPTED_TRANSPORT_MIN_VERSION: Version = {
+        if cfg!(feature = "sandbox") {
+            "SAND.0.0"
+        } else {
+            "TEST.11.4"
+        }
+        .parse()
+        .unwrap()
+    };
 }
 
 #[cfg(feature = "sandbox")]
@@ -176,6 +187,10 @@ pub const ENDORSEMENT_ID_SIZE_BYTES: usize = massa_hash::HASH_SIZE_BYTES;
 pub const OPERATION_ID_SIZE_BYTES: usize = massa_hash::HASH_SIZE_BYTES;
 /// slot as a key size
 pub const SLOT_KEY_SIZE: usize = 9;
+/// maximum length in bytes of a datastore key
+pub const MAX_DATASTORE_KEY_LENGTH: u8 = 255;
+/// maximum number of datastore keys returned by a single API query
+pub const DATASTORE_KEYS_PAGE_SIZE: usize = 1000;
 
 /// Size of the event id hash used in execution module, safe to import
 pub const EVENT_ID_SIZE_BYTES: usize = massa_hash::HASH_SIZE_BYTES;
@@ -50,7 +50,9 @@ lazy_static::lazy_static! {
     /// the latest test. That's the reason why we choose to reset it each time we get a `ConsensusConfig`.
     pub static ref POS_MISS_RATE_DEACTIVATION_THRESHOLD: Ratio<u64> = Ratio::new(1, 1);
     /// node version
-    pub static ref VERSION: Version = "DEVE.0.0".parse().unwrap();
+    pub static ref VERSION: Version = "DEVE.0.1".parse().unwrap();
+    /// first node version encrypting the peer-to-peer connections
+    pub static ref ENCRYPTED_TRANSPORT_MIN_VERSION: Version = "DEVE.0.1".parse().unwrap();
 }
 
 /// Size of the random bytes array used for the bootstrap, safe to import
@@ -103,6 +105,10 @@ pub const MAX_BOOTSTRAP_CLIQUES: u32 = 100;
 pub const MAX_BOOTSTRAP_DEPS: u32 = 100;
 /// max duplex buffer size
 pub const MAX_DUPLEX_BUFFER_SIZE: usize = 1024;
+/// maximum length in bytes of a datastore key
+pub const MAX_DATASTORE_KEY_LENGTH: u8 = 255;
+/// maximum number of datastore keys returned by a single API query
+pub const DATASTORE_KEYS_PAGE_SIZE: usize = 1000;
 /// max endorsements per message
 pub const MAX_ENDORSEMENTS_PER_MESSAGE: u32 = 1024;
 /// max bootstrap message size
@@ -1,7 +1,9 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 use crate::constants::{ADDRESS_SIZE_BYTES, OPERATION_ID_SIZE_BYTES};
+use crate::multisig::MultisigPolicy;
 use crate::prehash::{BuildMap, PreHashed, Set};
+use crate::sc_args::ArgValue;
 use crate::signed::{Id, Signable, Signed};
 use crate::with_serialization_context;
 use crate::{
@@ -14,12 +16,16 @@ use massa_hash::Hash;
 use massa_signature::{PublicKey, PUBLIC_KEY_SIZE_BYTES};
 use num_enum::{IntoPrimitive, TryFromPrimitive};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
 use std::convert::TryInto;
 use std::fmt::Formatter;
 use std::{ops::RangeInclusive, str::FromStr};
 
 const OPERATION_ID_STRING_PREFIX: &str = "OPE";
 
+/// Maximum number of transfers of a batch transaction
+pub const MAX_BATCH_TRANSFERS: usize = 256;
+
 /// operation id
 #[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
 pub struct OperationId(Hash);
@@ -115,8 +121,18 @@ enum OperationTypeId {
     RollSell = 2,
     ExecuteSC = 3,
     CallSC = 4,
+    BatchTransaction = 5,
 }
 
+/// Value taking the place of the operation type id to mark the operations of a multisig sender:
+/// it is followed by the multisig policy, then by the operation type.
+/// The other operations keep the format, and therefore the ids, they had before multisig senders.
+const MULTISIG_OPERATION_MARKER: u32 = u32::MAX;
+
+/// Value taking the place of the operation type id to mark the operations replacing a pending one:
+/// it is followed by the id of the replaced operation, then by the multisig marker or the operation type.
+const REPLACEMENT_OPERATION_MARKER: u32 = u32::MAX - 1;
+
 /// the operation as sent in the network
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Operation {
@@ -128,6 +144,15 @@ pub struct Operation {
     pub expire_period: u64,
     /// the type specific operation part
     pub op: OperationType,
+    /// if set, the operation is sent from the multisig address of the policy,
+    /// `sender_public_key` being the one of its keys that proposed the operation
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub multisig: Option<MultisigPolicy>,
+    /// if set, the sender declares that the operation replaces this pending operation of theirs.
+    /// The pools only accept the replacement if it pays a strictly higher fee (replace-by-fee),
+    /// it is not a consensus rule: a block may still include both operations
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replaces: Option<OperationId>,
 }
 
 impl std::fmt::Display for Operation {
@@ -136,11 +161,22 @@ impl std::fmt::Display for Operation {
         writeln!(f, "Fee: {}", self.fee)?;
         writeln!(f, "Expire period: {}", self.expire_period)?;
         writeln!(f, "Operation type: {}", self.op)?;
+        if let Some(policy) = &self.multisig {
+            writeln!(f, "Multisig sender {}: {}", policy.get_address(), policy)?;
+        }
+        if let Some(replaced_id) = &self.replaces {
+            writeln!(f, "Replaces: {}", replaced_id)?;
+        }
         Ok(())
     }
 }
 
-impl Signable<OperationId> for Operation {}
+impl Signable<OperationId> for Operation {
+    /// the signatures of the other keys of a multisig sender are co-signatures
+    fn accepts_co_signatures(&self) -> bool {
+        self.multisig.is_some()
+    }
+}
 
 /// signed operation
 pub type SignedOperation = Signed<Operation, OperationId>;
@@ -182,8 +218,9 @@ pub enum OperationType {
         target_addr: Address,
         /// Target function name. No function is called if empty.
         target_func: String,
-        /// Parameter to pass to the target function
-        param: String,
+        /// Parameter to pass to the target function: text, or typed arguments (see `sc_args`)
+        #[serde(with = "crate::sc_args::serde_param")]
+        param: Vec<u8>,
         /// The maximum amount of gas that the execution of the contract is allowed to cost.
         max_gas: u64,
         /// Extra coins that are spent from the caller's sequential balance and transferred to the target
@@ -193,6 +230,11 @@ pub enum OperationType {
         /// The price per unit of gas that the caller is willing to pay for the execution.
         gas_price: Amount,
     },
+    /// transfer coins from sender to several recipients, all transfers succeeding or failing together
+    BatchTransaction {
+        /// recipient addresses and amounts, at most `MAX_BATCH_TRANSFERS`
+        transfers: Vec<(Address, Amount)>,
+    },
 }
 
 impl std::fmt::Display for OperationType {
@@ -238,12 +280,22 @@ impl std::fmt::Display for OperationType {
                 writeln!(f, "CallSC:")?;
                 writeln!(f, "\t- target address:{}", target_addr)?;
                 writeln!(f, "\t- target function:{}", target_func)?;
-                writeln!(f, "\t- target parameter:{}", param)?;
+                match std::str::from_utf8(param) {
+                    Ok(text) => writeln!(f, "\t- target parameter:{}", text)?,
+                    Err(_) => writeln!(f, "\t- target parameter:{}", ArgValue::Bytes(param.clone()))?,
+                }
                 writeln!(f, "\t- max_gas:{}", max_gas)?;
                 writeln!(f, "\t- gas_price:{}", gas_price)?;
                 writeln!(f, "\t- sequential coins:{}", sequential_coins)?;
                 writeln!(f, "\t- parallel coins:{}", parallel_coins)?;
             }
+            OperationType::BatchTransaction { transfers } => {
+                writeln!(f, "Batch transaction:")?;
+                for (recipient_address, amount) in transfers.iter() {
+                    writeln!(f, "\t- Recipient:{}", recipient_address)?;
+                    writeln!(f, "\t  Amount:{}", amount)?;
+                }
+            }
         }
         Ok(())
     }
@@ -251,6 +303,7 @@ impl std::fmt::Display for OperationType {
 
 /// Checks performed:
 /// - Validity of the amount.
+/// - Validity of the transfer count(for batch transactions).
 impl SerializeCompact for OperationType {
     fn to_bytes_compact(&self) -> Result<Vec<u8>, ModelsError> {
         let mut res: Vec<u8> = Vec::new();
@@ -347,14 +400,32 @@ impl SerializeCompact for OperationType {
                 res.extend(func_name_bytes);
 
                 // Parameter
-                let param_bytes = param.as_bytes();
-                let param_len: u16 = param_bytes.len().try_into().map_err(|_| {
+                let param_len: u16 = param.len().try_into().map_err(|_| {
                     ModelsError::SerializeError(
                         "CallSC parameter length does not fit in u16".into(),
                     )
                 })?;
                 res.extend(param_len.to_varint_bytes());
-                res.extend(param_bytes);
+                res.extend(param);
+            }
+            OperationType::BatchTransaction { transfers } => {
+                // type id
+                res.extend(u32::from(OperationTypeId::BatchTransaction).to_varint_bytes());
+
+                // transfer count
+                if transfers.is_empty() || transfers.len() > MAX_BATCH_TRANSFERS {
+                    return Err(ModelsError::SerializeError(format!(
+                        "a batch transaction needs 1 to {} transfers",
+                        MAX_BATCH_TRANSFERS
+                    )));
+                }
+                res.extend((transfers.len() as u32).to_varint_bytes());
+
+                // transfers
+                for (recipient_address, amount) in transfers.iter() {
+                    res.extend(recipient_address.to_bytes());
+                    res.extend(&amount.to_bytes_compact()?);
+                }
             }
         }
         Ok(res)
@@ -366,6 +437,7 @@ impl SerializeCompact for OperationType {
 /// - Validity of the address(for transactions).
 /// - Validity of the amount(for transactions).
 /// - Validity of the roll count(for roll buy/sell).
+/// - Validity of the transfer count(for batch transactions).
 impl DeserializeCompact for OperationType {
     fn from_bytes_compact(buffer: &[u8]) -> Result<(Self, usize), ModelsError> {
         let mut cursor = 0;
@@ -487,9 +559,7 @@ impl DeserializeCompact for OperationType {
                 let param = match buffer.get(cursor..(cursor + param_len as usize)) {
                     Some(s) => {
                         cursor += s.len();
-                        String::from_utf8(s.to_vec()).map_err(|_| {
-                            ModelsError::DeserializeError("string is not utf8".into())
-                        })?
+                        s.to_vec()
                     }
                     None => {
                         return Err(ModelsError::DeserializeError("buffer too small".into()));
@@ -506,6 +576,30 @@ impl DeserializeCompact for OperationType {
                     param,
                 }
             }
+            OperationTypeId::BatchTransaction => {
+                // transfer count
+                let (transfer_count, delta) = u32::from_varint_bytes(&buffer[cursor..])?;
+                cursor += delta;
+                if transfer_count == 0 || transfer_count as usize > MAX_BATCH_TRANSFERS {
+                    return Err(ModelsError::DeserializeError(format!(
+                        "a batch transaction needs 1 to {} transfers",
+                        MAX_BATCH_TRANSFERS
+                    )));
+                }
+
+                // transfers
+                let mut transfers = Vec::with_capacity(transfer_count as usize);
+                for _ in 0..transfer_count {
+                    let recipient_address =
+                        Address::from_bytes(&array_from_slice(&buffer[cursor..])?);
+                    cursor += ADDRESS_SIZE_BYTES;
+                    let (amount, delta) = Amount::from_bytes_compact(&buffer[cursor..])?;
+                    cursor += delta;
+                    transfers.push((recipient_address, amount));
+                }
+
+                OperationType::BatchTransaction { transfers }
+            }
         };
         Ok((res, cursor))
     }
@@ -527,6 +621,18 @@ impl SerializeCompact for Operation {
         // sender public key
         res.extend(&self.sender_public_key.to_bytes());
 
+        // replaced operation, only written for replacement operations
+        if let Some(replaced_id) = &self.replaces {
+            res.extend(REPLACEMENT_OPERATION_MARKER.to_varint_bytes());
+            res.extend(replaced_id.to_bytes());
+        }
+
+        // multisig policy, only written for multisig senders
+        if let Some(policy) = &self.multisig {
+            res.extend(MULTISIG_OPERATION_MARKER.to_varint_bytes());
+            res.extend(policy.to_bytes_compact()?);
+        }
+
         // operation type
         res.extend(&self.op.to_bytes_compact()?);
 
@@ -555,6 +661,33 @@ impl DeserializeCompact for Operation {
         let sender_public_key = PublicKey::from_bytes(&array_from_slice(&buffer[cursor..])?)?;
         cursor += PUBLIC_KEY_SIZE_BYTES;
 
+        // replaced operation, if the marker is found in place of the operation type id
+        let (marker, delta) = u32::from_varint_bytes(&buffer[cursor..])?;
+        let replaces = if marker == REPLACEMENT_OPERATION_MARKER {
+            cursor += delta;
+            let replaced_id = OperationId::from_bytes(&array_from_slice(&buffer[cursor..])?);
+            cursor += OPERATION_ID_SIZE_BYTES;
+            Some(replaced_id)
+        } else {
+            None
+        };
+
+        // multisig policy, if the marker is found in place of the operation type id
+        let (marker, delta) = u32::from_varint_bytes(&buffer[cursor..])?;
+        let multisig = if marker == MULTISIG_OPERATION_MARKER {
+            cursor += delta;
+            let (policy, delta) = MultisigPolicy::from_bytes_compact(&buffer[cursor..])?;
+            cursor += delta;
+            if policy.get_key_index(&sender_public_key).is_none() {
+                return Err(ModelsError::DeserializeError(
+                    "the sender public key is not a key of the multisig policy".into(),
+                ));
+            }
+            Some(policy)
+        } else {
+            None
+        };
+
         // op
         let (op, delta) = OperationType::from_bytes_compact(&buffer[cursor..])?;
         cursor += delta;
@@ -565,6 +698,8 @@ impl DeserializeCompact for Operation {
                 expire_period,
                 sender_public_key,
                 op,
+                multisig,
+                replaces,
             },
             cursor,
         ))
@@ -572,14 +707,107 @@ impl DeserializeCompact for Operation {
 }
 
 impl SignedOperation {
+    /// Gets the bytes committing the operation in the operation merkle root of a block.
+    /// The co-signatures are not covered by the operation id, so they are committed along with it:
+    /// a block relayed without them does not match the header of the original block.
+    ///
+    /// # Arguments
+    /// * `operation_id`: id of the operation
+    pub fn get_merkle_leaf(&self, operation_id: &OperationId) -> Vec<u8> {
+        if self.co_signatures.is_empty() {
+            return operation_id.to_bytes().to_vec();
+        }
+        let mut bytes = operation_id.to_bytes().to_vec();
+        for (index, signature) in self.co_signatures.iter() {
+            bytes.push(*index);
+            bytes.extend(signature.to_bytes());
+        }
+        Hash::compute_from(&bytes).to_bytes().to_vec()
+    }
+
     /// Verifies the signature and integrity of the operation and computes operation ID
+    ///
+    /// The operation of a multisig sender needs the signatures of at least `threshold` distinct keys of the policy:
+    /// the signature of the proposer (`sender_public_key`) and co-signatures of other keys.
     pub fn verify_integrity(&self) -> Result<OperationId, ModelsError> {
         self.verify_signature(&self.content.sender_public_key)?;
+        match &self.content.multisig {
+            Some(policy) => {
+                policy.check()?;
+                let proposer_index = policy
+                    .get_key_index(&self.content.sender_public_key)
+                    .ok_or_else(|| {
+                        ModelsError::MultisigError(
+                            "the sender public key is not a key of the policy".into(),
+                        )
+                    })?;
+                let mut signer_indices = BTreeSet::new();
+                signer_indices.insert(proposer_index);
+                for (position, (index, _)) in self.co_signatures.iter().enumerate() {
+                    let public_key = policy.public_keys.get(*index as usize).ok_or_else(|| {
+                        ModelsError::MultisigError(format!("no key at index {}", index))
+                    })?;
+                    if !signer_indices.insert(*index) {
+                        return Err(ModelsError::MultisigError(format!(
+                            "key {} signed more than once",
+                            index
+                        )));
+                    }
+                    self.verify_co_signature(position, public_key)?;
+                }
+                if signer_indices.len() < policy.threshold as usize {
+                    return Err(ModelsError::MultisigError(format!(
+                        "{} signatures, {} are required",
+                        signer_indices.len(),
+                        policy.threshold
+                    )));
+                }
+            }
+            None => {
+                if !self.co_signatures.is_empty() {
+                    return Err(ModelsError::MultisigError(
+                        "co-signatures on an operation without multisig sender".into(),
+                    ));
+                }
+            }
+        }
         self.content.compute_id()
     }
+
+    /// Encodes the signed operation as a base58check string, to be broadcast from another machine
+    pub fn to_bs58_check(&self) -> Result<String, ModelsError> {
+        Ok(bs58::encode(self.to_bytes_compact()?)
+            .with_check()
+            .into_string())
+    }
+
+    /// Decodes a signed operation encoded with `to_bs58_check`
+    pub fn from_bs58_check(data: &str) -> Result<SignedOperation, ModelsError> {
+        decode_bs58_check(data)
+    }
 }
 
 impl Operation {
+    /// Encodes the unsigned operation as a base58check string, to be signed on another machine
+    pub fn to_bs58_check(&self) -> Result<String, ModelsError> {
+        Ok(bs58::encode(self.to_bytes_compact()?)
+            .with_check()
+            .into_string())
+    }
+
+    /// Decodes an unsigned operation encoded with `to_bs58_check`
+    pub fn from_bs58_check(data: &str) -> Result<Operation, ModelsError> {
+        decode_bs58_check(data)
+    }
+
+    /// get the address of the sender, the multisig one if the operation has a multisig policy
+    pub fn get_sender_address(&self) -> Address {
+        match &self.multisig {
+            Some(policy) => policy.get_address(),
+            None => Address::from_public_key(&self.sender_public_key),
+        }
+    }
+
     /// get the range of periods during which an operation is valid
     pub fn get_validity_range(&self, operation_validity_period: u64) -> RangeInclusive<u64> {
         let start = self.expire_period.saturating_sub(operation_validity_period);
@@ -594,6 +822,7 @@ impl Operation {
             OperationType::RollBuy { .. } => 0,
             OperationType::RollSell { .. } => 0,
             OperationType::Transaction { .. } => 0,
+            OperationType::BatchTransaction { .. } => 0,
         }
     }
 
@@ -609,13 +838,14 @@ impl Operation {
             OperationType::RollBuy { .. } => Amount::default(),
             OperationType::RollSell { .. } => Amount::default(),
             OperationType::Transaction { .. } => Amount::default(),
+            OperationType::BatchTransaction { .. } => Amount::default(),
         }
     }
 
     /// get the addresses that are involved in this operation from a ledger point of view
     pub fn get_ledger_involved_addresses(&self) -> Set<Address> {
         let mut res = Set::<Address>::default();
-        let emitter_address = Address::from_public_key(&self.sender_public_key);
+        let emitter_address = self.get_sender_address();
         res.insert(emitter_address);
         match &self.op {
             OperationType::Transaction {
@@ -629,6 +859,13 @@ impl Operation {
             OperationType::CallSC { target_addr, .. } => {
                 res.insert(*target_addr);
             }
+            OperationType::BatchTransaction { transfers } => {
+                res.extend(
+                    transfers
+                        .iter()
+                        .map(|(recipient_address, _)| *recipient_address),
+                );
+            }
         }
         res
     }
@@ -639,18 +876,34 @@ impl Operation {
         match self.op {
             OperationType::Transaction { .. } => {}
             OperationType::RollBuy { .. } => {
-                res.insert(Address::from_public_key(&self.sender_public_key));
+                res.insert(self.get_sender_address());
             }
             OperationType::RollSell { .. } => {
-                res.insert(Address::from_public_key(&self.sender_public_key));
+                res.insert(self.get_sender_address());
             }
             OperationType::ExecuteSC { .. } => {}
             OperationType::CallSC { .. } => {}
+            OperationType::BatchTransaction { .. } => {}
         }
         Ok(res)
     }
 }
 
+/// decodes a base58check string into a structure that must span all the decoded bytes
+fn decode_bs58_check<T: DeserializeCompact>(data: &str) -> Result<T, ModelsError> {
+    let bytes = bs58::decode(data.trim())
+        .with_check(None)
+        .into_vec()
+        .map_err(|err| ModelsError::DeserializeError(format!("invalid base58check: {}", err)))?;
+    let (res, size) = T::from_bytes_compact(&bytes)?;
+    if size != bytes.len() {
+        return Err(ModelsError::DeserializeError(
+            "trailing bytes after the encoded structure".into(),
+        ));
+    }
+    Ok(res)
+}
+
 /// Set of operation ids
 pub type OperationIds = Set<OperationId>;
 
@@ -737,7 +990,7 @@ impl DeserializeCompact for Operations {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use massa_signature::{derive_public_key, generate_random_private_key};
+    use massa_signature::{derive_public_key, generate_random_private_key, PrivateKey};
     use serial_test::serial;
 
     #[test]
@@ -762,6 +1015,8 @@ mod tests {
             sender_public_key: sender_pub,
             op,
             expire_period: 50,
+            multisig: None,
+            replaces: None,
         };
 
         let ser_content = content.to_bytes_compact().unwrap();
@@ -798,6 +1053,8 @@ mod tests {
             sender_public_key: sender_pub,
             op,
             expire_period: 50,
+            multisig: None,
+            replaces: None,
         };
 
         let ser_content = content.to_bytes_compact().unwrap();
@@ -830,7 +1087,7 @@ mod tests {
             sequential_coins: Amount::from_str("123.111").unwrap(),
             gas_price: Amount::from_str("772.122").unwrap(),
             target_func: "target function".to_string(),
-            param: "parameter".to_string(),
+            param: b"parameter".to_vec(),
         };
         let ser_type = op.to_bytes_compact().unwrap();
         let (res_type, _) = OperationType::from_bytes_compact(&ser_type).unwrap();
@@ -841,6 +1098,8 @@ mod tests {
             sender_public_key: sender_pub,
             op,
             expire_period: 50,
+            multisig: None,
+            replaces: None,
         };
 
         let ser_content = content.to_bytes_compact().unwrap();
@@ -855,4 +1114,206 @@ mod tests {
 
         assert_eq!(op.content.get_validity_range(10), 40..=50);
     }
+
+    #[test]
+    #[serial]
+    fn test_batch_transaction() {
+        let sender_priv = generate_random_private_key();
+        let transfers: Vec<(Address, Amount)> = (0..3)
+            .map(|i| {
+                (
+                    Address::from_public_key(&derive_public_key(&generate_random_private_key())),
+                    Amount::from_str(&(i + 1).to_string()).unwrap(),
+                )
+            })
+            .collect();
+        let op = OperationType::BatchTransaction {
+            transfers: transfers.clone(),
+        };
+        let ser_type = op.to_bytes_compact().unwrap();
+        let (res_type, _) = OperationType::from_bytes_compact(&ser_type).unwrap();
+        assert_eq!(format!("{}", res_type), format!("{}", op));
+
+        let content = Operation {
+            fee: Amount::from_str("20").unwrap(),
+            sender_public_key: derive_public_key(&sender_priv),
+            op,
+            expire_period: 50,
+            multisig: None,
+            replaces: None,
+        };
+        let involved = content.get_ledger_involved_addresses();
+        assert_eq!(involved.len(), 4);
+        assert!(transfers.iter().all(|(addr, _)| involved.contains(addr)));
+
+        let op = Signed::new_signed(content, &sender_priv).unwrap().1;
+        let ser_op = op.to_bytes_compact().unwrap();
+        let (res_op, _) = Signed::<Operation, OperationId>::from_bytes_compact(&ser_op).unwrap();
+        assert_eq!(format!("{}", res_op), format!("{}", op));
+
+        // empty and oversized batches are rejected
+        assert!(OperationType::BatchTransaction { transfers: vec![] }
+            .to_bytes_compact()
+            .is_err());
+        assert!(OperationType::BatchTransaction {
+            transfers: vec![transfers[0]; MAX_BATCH_TRANSFERS + 1]
+        }
+        .to_bytes_compact()
+        .is_err());
+    }
+
+    #[test]
+    #[serial]
+    fn test_single_signer_operation_format() {
+        let sender_priv = generate_random_private_key();
+        let content = Operation {
+            fee: Amount::from_str("20").unwrap(),
+            sender_public_key: derive_public_key(&sender_priv),
+            op: OperationType::RollBuy { roll_count: 3 },
+            expire_period: 50,
+            multisig: None,
+            replaces: None,
+        };
+
+        // the operations of a single signer have no multisig or replacement field in their bytes
+        let expected = [
+            content.fee.to_bytes_compact().unwrap(),
+            content.expire_period.to_varint_bytes(),
+            content.sender_public_key.to_bytes().to_vec(),
+            content.op.to_bytes_compact().unwrap(),
+        ]
+        .concat();
+        assert_eq!(content.to_bytes_compact().unwrap(), expected);
+
+        let op = Signed::new_signed(content, &sender_priv).unwrap().1;
+        let ser_op = op.to_bytes_compact().unwrap();
+        assert_eq!(
+            ser_op,
+            [expected, op.signature.to_bytes().to_vec()].concat()
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_replacement_operation() {
+        let sender_priv = generate_random_private_key();
+        let mut content = Operation {
+            fee: Amount::from_str("20").unwrap(),
+            sender_public_key: derive_public_key(&sender_priv),
+            op: OperationType::RollBuy { roll_count: 3 },
+            expire_period: 50,
+            multisig: None,
+            replaces: None,
+        };
+        let replaced_id = content.compute_id().unwrap();
+
+        // the same operation at a higher fee, declaring the operation it replaces
+        content.fee = Amount::from_str("30").unwrap();
+        content.replaces = Some(replaced_id);
+        let ser_content = content.to_bytes_compact().unwrap();
+        let (res_content, _) = Operation::from_bytes_compact(&ser_content).unwrap();
+        assert_eq!(res_content.replaces, Some(replaced_id));
+        assert_eq!(
+            res_content.compute_id().unwrap(),
+            content.compute_id().unwrap()
+        );
+
+        // the declaration is covered by the operation id
+        content.replaces = None;
+        assert_ne!(
+            Operation::from_bytes_compact(&ser_content)
+                .unwrap()
+                .0
+                .compute_id()
+                .unwrap(),
+            content.compute_id().unwrap()
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_multisig_transaction() {
+        let private_keys: Vec<PrivateKey> = (0..3).map(|_| generate_random_private_key()).collect();
+        let policy =
+            MultisigPolicy::new(2, private_keys.iter().map(derive_public_key).collect()).unwrap();
+        let key_index = |private_key: &PrivateKey| {
+            policy
+                .get_key_index(&derive_public_key(private_key))
+                .unwrap()
+        };
+
+        let content = Operation {
+            fee: Amount::from_str("20").unwrap(),
+            sender_public_key: derive_public_key(&private_keys[0]),
+            op: OperationType::Transaction {
+                recipient_address: Address::from_public_key(&derive_public_key(
+                    &generate_random_private_key(),
+                )),
+                amount: Amount::from_str("10").unwrap(),
+            },
+            expire_period: 50,
+            multisig: Some(policy.clone()),
+            replaces: None,
+        };
+        assert_eq!(content.get_sender_address(), policy.get_address());
+
+        let ser_content = content.to_bytes_compact().unwrap();
+        let (res_content, _) = Operation::from_bytes_compact(&ser_content).unwrap();
+        assert_eq!(res_content.multisig, Some(policy.clone()));
+
+        // the proposer alone does not reach the threshold
+        let mut op = Signed::new_signed(content, &private_keys[0]).unwrap().1;
+        assert!(op.verify_integrity().is_err());
+
+        // the proposer can not co-sign
+        op.add_co_signature(key_index(&private_keys[0]), &private_keys[0])
+            .unwrap();
+        assert!(op.verify_integrity().is_err());
+
+        // a co-signature made with the wrong key is rejected
+        op.co_signatures.clear();
+        op.add_co_signature(key_index(&private_keys[1]), &private_keys[2])
+            .unwrap();
+        assert!(op.verify_integrity().is_err());
+
+        op.add_co_signature(key_index(&private_keys[1]), &private_keys[1])
+            .unwrap();
+        let id = op.verify_integrity().unwrap();
+
+        let ser_op = op.to_bytes_compact().unwrap();
+        let (res_op, _) = Signed::<Operation, OperationId>::from_bytes_compact(&ser_op).unwrap();
+        assert_eq!(res_op.co_signatures, op.co_signatures);
+        assert_eq!(res_op.verify_integrity().unwrap(), id);
+    }
+
+    #[test]
+    #[serial]
+    fn test_bs58_check_encoding() {
+        let sender_priv = generate_random_private_key();
+        let content = Operation {
+            fee: Amount::from_str("20").unwrap(),
+            sender_public_key: derive_public_key(&sender_priv),
+            op: OperationType::RollBuy { roll_count: 3 },
+            expire_period: 50,
+            multisig: None,
+            replaces: None,
+        };
+
+        let encoded = content.to_bs58_check().unwrap();
+        let decoded = Operation::from_bs58_check(&encoded).unwrap();
+        assert_eq!(decoded.compute_id().unwrap(), content.compute_id().unwrap());
+
+        let op = Signed::new_signed(decoded, &sender_priv).unwrap().1;
+        let encoded = op.to_bs58_check().unwrap();
+        let decoded = SignedOperation::from_bs58_check(&encoded).unwrap();
+        assert_eq!(
+            decoded.verify_integrity().unwrap(),
+            op.verify_integrity().unwrap()
+        );
+
+        // a corrupted string is rejected by the checksum
+        let mut corrupted = encoded.into_bytes();
+        corrupted[10] = if corrupted[10] == b'2' { b'3' } else { b'2' };
+        assert!(SignedOperation::from_bs58_check(&String::from_utf8(corrupted).unwrap()).is_err());
+    }
 }
@@ -0,0 +1,837 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Typed arguments of smart contract calls.
+//!
+//! The parameter of a smart contract call is a raw byte string.
+//! Typed arguments are encoded as `TYPED_ARGS_PREFIX | arg_1 | ... | arg_n`, each argument according to its type:
+//! * `bool`: 1 byte, 0 or 1
+//! * `u64`, `i64`: 8 bytes, little endian
+//! * `Amount`: raw fixed-point value on 8 bytes, little endian
+//! * `Address`: 32 bytes
+//! * `String`, `Bytes`: length on 4 bytes little endian, followed by the UTF-8 or raw bytes
+//! * `Vec<T>`: element count on 4 bytes little endian, followed by the elements
+//!
+//! The first byte of the prefix never appears in UTF-8, so typed arguments cannot be mistaken for a text parameter.
+//!
+//! The runtime receives the parameter as a string: see `param_to_runtime_string`.
+//! In JSON, parameters are strings when they are UTF-8 and byte arrays otherwise: see `serde_param`.
+//!
+//! The ABI of a contract is the list of the signatures of its functions, like `transfer(to: Address, amount: Amount)`.
+//! A contract publishes it as a JSON array of signatures in the `ABI_DATASTORE_KEY` entry of its datastore.
+
+use crate::{constants::ADDRESS_SIZE_BYTES, Address, Amount, ModelsError};
+use massa_serialization::{Deserializer, SerializeError, Serializer};
+use nom::{
+    bytes::complete::{tag, take},
+    error::{context, ContextError, ErrorKind, ParseError},
+    multi::length_data,
+    number::complete::{le_i64, le_u32, le_u64, le_u8},
+    IResult,
+};
+use serde::{Deserialize, Serialize};
+use std::{borrow::Cow, fmt::Display, str::FromStr};
+
+/// Prefix of typed arguments: an invalid UTF-8 byte followed by the version of the encoding
+pub const TYPED_ARGS_PREFIX: [u8; 2] = [0xff, 0x01];
+
+/// Datastore key under which a contract publishes its ABI
+pub const ABI_DATASTORE_KEY: &[u8] = b"massa_abi";
+
+/// Marker of the parameters passed base64-encoded to the runtime
+pub const RUNTIME_BASE64_PARAM_MARKER: &str = "\u{0}base64:";
+
+/// Type of a smart contract call argument
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArgType {
+    /// boolean
+    Bool,
+    /// unsigned 64-bit integer
+    U64,
+    /// signed 64-bit integer
+    I64,
+    /// amount of coins
+    Amount,
+    /// address
+    Address,
+    /// UTF-8 string
+    String,
+    /// byte string
+    Bytes,
+    /// list of arguments of the same type
+    List(Box<ArgType>),
+}
+
+impl Display for ArgType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArgType::Bool => write!(f, "bool"),
+            ArgType::U64 => write!(f, "u64"),
+            ArgType::I64 => write!(f, "i64"),
+            ArgType::Amount => write!(f, "Amount"),
+            ArgType::Address => write!(f, "Address"),
+            ArgType::String => write!(f, "String"),
+            ArgType::Bytes => write!(f, "Bytes"),
+            ArgType::List(elem) => write!(f, "Vec<{}>", elem),
+        }
+    }
+}
+
+impl FromStr for ArgType {
+    type Err = ModelsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let lowercase = s.to_lowercase();
+        if lowercase.starts_with("vec<") && lowercase.ends_with('>') {
+            return Ok(ArgType::List(Box::new(s[4..s.len() - 1].parse()?)));
+        }
+        match lowercase.as_str() {
+            "bool" => Ok(ArgType::Bool),
+            "u64" => Ok(ArgType::U64),
+            "i64" => Ok(ArgType::I64),
+            "amount" => Ok(ArgType::Amount),
+            "address" => Ok(ArgType::Address),
+            "string" => Ok(ArgType::String),
+            "bytes" => Ok(ArgType::Bytes),
+            _ => Err(ModelsError::ArgsError(format!(
+                "unknown argument type `{}`",
+                s
+            ))),
+        }
+    }
+}
+
+/// Value of a smart contract call argument
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArgValue {
+    /// boolean
+    Bool(bool),
+    /// unsigned 64-bit integer
+    U64(u64),
+    /// signed 64-bit integer
+    I64(i64),
+    /// amount of coins
+    Amount(Amount),
+    /// address
+    Address(Address),
+    /// UTF-8 string
+    String(String),
+    /// byte string
+    Bytes(Vec<u8>),
+    /// list of values of the given type
+    List(ArgType, Vec<ArgValue>),
+}
+
+impl ArgValue {
+    /// Gets the type of the value
+    pub fn get_type(&self) -> ArgType {
+        match self {
+            ArgValue::Bool(_) => ArgType::Bool,
+            ArgValue::U64(_) => ArgType::U64,
+            ArgValue::I64(_) => ArgType::I64,
+            ArgValue::Amount(_) => ArgType::Amount,
+            ArgValue::Address(_) => ArgType::Address,
+            ArgValue::String(_) => ArgType::String,
+            ArgValue::Bytes(_) => ArgType::Bytes,
+            ArgValue::List(elem, _) => ArgType::List(Box::new(elem.clone())),
+        }
+    }
+
+    /// Parses a value of the given type.
+    /// Strings may be quoted, and must be if they contain commas or brackets.
+    /// Bytes are written in hexadecimal with an optional `0x` prefix, lists like `[1, 2, 3]`.
+    ///
+    /// # Arguments
+    /// * `text`: text of the value
+    /// * `arg_type`: expected type
+    pub fn parse(text: &str, arg_type: &ArgType) -> Result<ArgValue, ModelsError> {
+        let text = text.trim();
+        let invalid =
+            || ModelsError::ArgsError(format!("`{}` is not a valid {} value", text, arg_type));
+        match arg_type {
+            ArgType::Bool => text.parse().map(ArgValue::Bool).map_err(|_| invalid()),
+            ArgType::U64 => text.parse().map(ArgValue::U64).map_err(|_| invalid()),
+            ArgType::I64 => text.parse().map(ArgValue::I64).map_err(|_| invalid()),
+            ArgType::Amount => text.parse().map(ArgValue::Amount).map_err(|_| invalid()),
+            ArgType::Address => text.parse().map(ArgValue::Address).map_err(|_| invalid()),
+            ArgType::String => Ok(ArgValue::String(unquote(text).ok_or_else(invalid)?)),
+            ArgType::Bytes => Ok(ArgValue::Bytes(
+                decode_hex(text.strip_prefix("0x").unwrap_or(text)).ok_or_else(invalid)?,
+            )),
+            ArgType::List(elem) => {
+                let inner = text
+                    .strip_prefix('[')
+                    .and_then(|inner| inner.strip_suffix(']'))
+                    .ok_or_else(invalid)?;
+                let values = split_top_level(inner, ',')?
+                    .into_iter()
+                    .map(|item| ArgValue::parse(item, elem))
+                    .collect::<Result<_, _>>()?;
+                Ok(ArgValue::List((**elem).clone(), values))
+            }
+        }
+    }
+}
+
+impl Display for ArgValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArgValue::Bool(value) => write!(f, "{}", value),
+            ArgValue::U64(value) => write!(f, "{}", value),
+            ArgValue::I64(value) => write!(f, "{}", value),
+            ArgValue::Amount(value) => write!(f, "{}", value),
+            ArgValue::Address(value) => write!(f, "{}", value),
+            ArgValue::String(value) => write!(
+                f,
+                "\"{}\"",
+                value.replace('\\', "\\\\").replace('"', "\\\"")
+            ),
+            ArgValue::Bytes(value) => {
+                write!(f, "0x")?;
+                for byte in value {
+                    write!(f, "{:02x}", byte)?;
+                }
+                Ok(())
+            }
+            ArgValue::List(_, values) => {
+                write!(f, "[")?;
+                for (index, value) in values.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                write!(f, "]")
+            }
+        }
+    }
+}
+
+/// Removes the quotes around a string and unescapes it, unquoted strings are taken as is
+fn unquote(text: &str) -> Option<String> {
+    let inner = match text.strip_prefix('"') {
+        Some(inner) => inner.strip_suffix('"')?,
+        None => return Some(text.to_string()),
+    };
+    let mut result = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => result.push(chars.next()?),
+            '"' => return None,
+            c => result.push(c),
+        }
+    }
+    Some(result)
+}
+
+/// Decodes an hexadecimal string
+fn decode_hex(text: &str) -> Option<Vec<u8>> {
+    if text.len() % 2 != 0 || !text.is_ascii() {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|index| u8::from_str_radix(&text[index..index + 2], 16).ok())
+        .collect()
+}
+
+/// Splits a text on a separator, ignoring the separators inside quotes and brackets.
+/// The parts are trimmed, and an empty text gives no parts.
+fn split_top_level(text: &str, separator: char) -> Result<Vec<&str>, ModelsError> {
+    if text.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let unbalanced =
+        || ModelsError::ArgsError(format!("unbalanced quotes or brackets in `{}`", text));
+    let mut parts = Vec::new();
+    let mut depth: usize = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut start = 0;
+    for (index, c) in text.char_indices() {
+        if in_string {
+            match (escaped, c) {
+                (false, '\\') => escaped = true,
+                (false, '"') => in_string = false,
+                _ => escaped = false,
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '[' | '(' | '<' => depth += 1,
+            ']' | ')' | '>' => depth = depth.checked_sub(1).ok_or_else(unbalanced)?,
+            c if c == separator && depth == 0 => {
+                parts.push(text[start..index].trim());
+                start = index + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    if in_string || depth != 0 {
+        return Err(unbalanced());
+    }
+    parts.push(text[start..].trim());
+    Ok(parts)
+}
+
+/// Splits a call or a signature like `name(a, b)` into its name and its arguments
+fn split_call(text: &str) -> Result<(&str, Vec<&str>), ModelsError> {
+    let text = text.trim();
+    let invalid = || {
+        ModelsError::ArgsError(format!(
+            "`{}` is not written like `function(arg, ...)`",
+            text
+        ))
+    };
+    let (name, args) = text
+        .strip_suffix(')')
+        .and_then(|text| text.split_once('('))
+        .ok_or_else(invalid)?;
+    let name = name.trim();
+    if name.is_empty() || !name.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        return Err(invalid());
+    }
+    Ok((name, split_top_level(args, ',')?))
+}
+
+/// Splits an argument like `value: type` into the value and the optional type annotation
+fn split_annotation(text: &str) -> Result<(&str, Option<ArgType>), ModelsError> {
+    match split_top_level(text, ':')?.as_slice() {
+        [value] => Ok((*value, None)),
+        [value, arg_type] => Ok((*value, Some(arg_type.parse()?))),
+        _ => Err(ModelsError::ArgsError(format!(
+            "`{}` is not written like `value` or `value: type`",
+            text
+        ))),
+    }
+}
+
+/// Serializer for typed arguments
+#[derive(Default)]
+pub struct ArgsSerializer;
+
+impl ArgsSerializer {
+    /// Creates a typed arguments serializer
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn serialize_length(&self, length: usize, buffer: &mut Vec<u8>) -> Result<(), SerializeError> {
+        let length = u32::try_from(length)
+            .map_err(|_| SerializeError::NumberTooBig(format!("argument length {}", length)))?;
+        buffer.extend(length.to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_arg(&self, value: &ArgValue, buffer: &mut Vec<u8>) -> Result<(), SerializeError> {
+        match value {
+            ArgValue::Bool(value) => buffer.push(u8::from(*value)),
+            ArgValue::U64(value) => buffer.extend(value.to_le_bytes()),
+            ArgValue::I64(value) => buffer.extend(value.to_le_bytes()),
+            ArgValue::Amount(value) => buffer.extend(value.to_raw().to_le_bytes()),
+            ArgValue::Address(value) => buffer.extend(value.to_bytes()),
+            ArgValue::String(value) => {
+                self.serialize_length(value.len(), buffer)?;
+                buffer.extend(value.as_bytes());
+            }
+            ArgValue::Bytes(value) => {
+                self.serialize_length(value.len(), buffer)?;
+                buffer.extend(value);
+            }
+            ArgValue::List(elem, values) => {
+                self.serialize_length(values.len(), buffer)?;
+                for value in values {
+                    if &value.get_type() != elem {
+                        return Err(SerializeError::GeneralError(format!(
+                            "{} value in a list of {}",
+                            value.get_type(),
+                            elem
+                        )));
+                    }
+                    self.serialize_arg(value, buffer)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Serializer<Vec<ArgValue>> for ArgsSerializer {
+    /// ```
+    /// use massa_models::sc_args::{ArgValue, ArgsSerializer, TYPED_ARGS_PREFIX};
+    /// use massa_serialization::Serializer;
+    ///
+    /// let mut buffer = Vec::new();
+    /// ArgsSerializer::new().serialize(&vec![ArgValue::Bool(true), ArgValue::U64(2)], &mut buffer).unwrap();
+    /// assert_eq!(buffer, [0xff, 0x01, 1, 2, 0, 0, 0, 0, 0, 0, 0]);
+    /// ```
+    fn serialize(&self, value: &Vec<ArgValue>, buffer: &mut Vec<u8>) -> Result<(), SerializeError> {
+        buffer.extend(TYPED_ARGS_PREFIX);
+        for arg in value {
+            self.serialize_arg(arg, buffer)?;
+        }
+        Ok(())
+    }
+}
+
+/// Deserializer for typed arguments of known types
+pub struct ArgsDeserializer {
+    types: Vec<ArgType>,
+}
+
+impl ArgsDeserializer {
+    /// Creates a deserializer for arguments of the given types
+    pub fn new(types: Vec<ArgType>) -> Self {
+        Self { types }
+    }
+
+    fn deserialize_arg<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+        &self,
+        buffer: &'a [u8],
+        arg_type: &ArgType,
+    ) -> IResult<&'a [u8], ArgValue, E> {
+        match arg_type {
+            ArgType::Bool => match le_u8(buffer)? {
+                (rest, 0) => Ok((rest, ArgValue::Bool(false))),
+                (rest, 1) => Ok((rest, ArgValue::Bool(true))),
+                _ => Err(nom::Err::Error(E::from_error_kind(
+                    buffer,
+                    ErrorKind::Verify,
+                ))),
+            },
+            ArgType::U64 => {
+                let (rest, value) = le_u64(buffer)?;
+                Ok((rest, ArgValue::U64(value)))
+            }
+            ArgType::I64 => {
+                let (rest, value) = le_i64(buffer)?;
+                Ok((rest, ArgValue::I64(value)))
+            }
+            ArgType::Amount => {
+                let (rest, raw) = le_u64(buffer)?;
+                Ok((rest, ArgValue::Amount(Amount::from_raw(raw))))
+            }
+            ArgType::Address => {
+                let (rest, bytes) = take(ADDRESS_SIZE_BYTES)(buffer)?;
+                let bytes: [u8; ADDRESS_SIZE_BYTES] =
+                    bytes.try_into().expect("address size checked by take");
+                Ok((rest, ArgValue::Address(Address::from_bytes(&bytes))))
+            }
+            ArgType::String => {
+                let (rest, bytes) = length_data(le_u32)(buffer)?;
+                let value = String::from_utf8(bytes.to_vec())
+                    .map_err(|_| nom::Err::Error(E::from_error_kind(buffer, ErrorKind::Verify)))?;
+                Ok((rest, ArgValue::String(value)))
+            }
+            ArgType::Bytes => {
+                let (rest, bytes) = length_data(le_u32)(buffer)?;
+                Ok((rest, ArgValue::Bytes(bytes.to_vec())))
+            }
+            ArgType::List(elem) => {
+                let (mut rest, count) = le_u32(buffer)?;
+                let mut values = Vec::new();
+                for _ in 0..count {
+                    let (new_rest, value) = self.deserialize_arg(rest, elem)?;
+                    rest = new_rest;
+                    values.push(value);
+                }
+                Ok((rest, ArgValue::List((**elem).clone(), values)))
+            }
+        }
+    }
+}
+
+impl Deserializer<Vec<ArgValue>> for ArgsDeserializer {
+    /// ```
+    /// use massa_models::sc_args::{ArgType, ArgValue, ArgsDeserializer, ArgsSerializer};
+    /// use massa_serialization::{DeserializeError, Deserializer, Serializer};
+    ///
+    /// let args = vec![ArgValue::String("hello".to_string()), ArgValue::I64(-3)];
+    /// let mut buffer = Vec::new();
+    /// ArgsSerializer::new().serialize(&args, &mut buffer).unwrap();
+    /// let (rest, deserialized) = ArgsDeserializer::new(vec![ArgType::String, ArgType::I64])
+    ///     .deserialize::<DeserializeError>(&buffer)
+    ///     .unwrap();
+    /// assert!(rest.is_empty());
+    /// assert_eq!(deserialized, args);
+    /// ```
+    fn deserialize<'a, E: ParseError<&'a [u8]> + ContextError<&'a [u8]>>(
+        &self,
+        buffer: &'a [u8],
+    ) -> IResult<&'a [u8], Vec<ArgValue>, E> {
+        context("Failed typed arguments deserialization", |input| {
+            let (mut rest, _) = tag(&TYPED_ARGS_PREFIX[..])(input)?;
+            let mut values = Vec::with_capacity(self.types.len());
+            for arg_type in &self.types {
+                let (new_rest, value) = self.deserialize_arg(rest, arg_type)?;
+                rest = new_rest;
+                values.push(value);
+            }
+            Ok((rest, values))
+        })(buffer)
+    }
+}
+
+/// Signature of a smart contract function, written like `transfer(to: Address, amount: Amount)`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct FunctionAbi {
+    /// name of the function
+    pub name: String,
+    /// names and types of the arguments
+    pub args: Vec<(String, ArgType)>,
+}
+
+impl FunctionAbi {
+    /// Decodes the typed arguments of a call to the function
+    pub fn decode_args(&self, param: &[u8]) -> Result<Vec<ArgValue>, ModelsError> {
+        let types = self
+            .args
+            .iter()
+            .map(|(_, arg_type)| arg_type.clone())
+            .collect();
+        let (rest, values) =
+            ArgsDeserializer::new(types).deserialize::<nom::error::Error<&[u8]>>(param)?;
+        if !rest.is_empty() {
+            return Err(ModelsError::ArgsError(format!(
+                "{} unexpected bytes after the arguments of {}",
+                rest.len(),
+                self.name
+            )));
+        }
+        Ok(values)
+    }
+}
+
+impl Display for FunctionAbi {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}(", self.name)?;
+        for (index, (name, arg_type)) in self.args.iter().enumerate() {
+            if index > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}: {}", name, arg_type)?;
+        }
+        write!(f, ")")
+    }
+}
+
+impl FromStr for FunctionAbi {
+    type Err = ModelsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, args) = split_call(s)?;
+        let args = args
+            .into_iter()
+            .map(|arg| match split_annotation(arg)? {
+                (arg_name, Some(arg_type)) if !arg_name.is_empty() => {
+                    Ok((arg_name.to_string(), arg_type))
+                }
+                _ => Err(ModelsError::ArgsError(format!(
+                    "argument `{}` of {} is not written like `name: type`",
+                    arg, name
+                ))),
+            })
+            .collect::<Result<_, ModelsError>>()?;
+        Ok(FunctionAbi {
+            name: name.to_string(),
+            args,
+        })
+    }
+}
+
+impl TryFrom<String> for FunctionAbi {
+    type Error = ModelsError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<FunctionAbi> for String {
+    fn from(value: FunctionAbi) -> Self {
+        value.to_string()
+    }
+}
+
+/// ABI of a smart contract, serialized as a JSON array of function signatures
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ContractAbi(pub Vec<FunctionAbi>);
+
+impl ContractAbi {
+    /// Gets the signature of a function
+    pub fn get_function(&self, name: &str) -> Option<&FunctionAbi> {
+        self.0.iter().find(|function| function.name == name)
+    }
+}
+
+/// Function call with typed arguments, written like `transfer(A12..., 1.5)`.
+/// Arguments may be annotated with their type, like `transfer(A12...: Address, 1.5: Amount)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypedCall {
+    /// name of the called function
+    pub function: String,
+    /// text of the arguments, with their optional type annotation
+    pub args: Vec<(String, Option<ArgType>)>,
+}
+
+impl TypedCall {
+    /// Parses and encodes the arguments, checking them against the ABI of the contract if there is one.
+    /// Without ABI, the type of every argument must be annotated.
+    pub fn encode_args(&self, abi: Option<&ContractAbi>) -> Result<Vec<u8>, ModelsError> {
+        let types: Vec<ArgType> = match abi {
+            Some(abi) => {
+                let function = abi.get_function(&self.function).ok_or_else(|| {
+                    ModelsError::ArgsError(format!(
+                        "function {} is not in the ABI of the contract",
+                        self.function
+                    ))
+                })?;
+                if function.args.len() != self.args.len() {
+                    return Err(ModelsError::ArgsError(format!(
+                        "{} arguments given to {}",
+                        self.args.len(),
+                        function
+                    )));
+                }
+                for ((text, annotation), (name, arg_type)) in self.args.iter().zip(&function.args) {
+                    match annotation {
+                        Some(annotation) if annotation != arg_type => {
+                            return Err(ModelsError::ArgsError(format!(
+                                "argument `{}` is annotated as {} but {} expects {}: {}",
+                                text, annotation, function, name, arg_type
+                            )));
+                        }
+                        _ => {}
+                    }
+                }
+                function.args.iter().map(|(_, arg_type)| arg_type.clone()).collect()
+            }
+            None => self
+                .args
+                .iter()
+                .map(|(text, annotation)| {
+                    annotation.clone().ok_or_else(|| {
+                        ModelsError::ArgsError(format!(
+                            "the contract has no ABI, the type of argument `{}` must be given like `{}: u64`",
+                            text, text
+                        ))
+                    })
+                })
+                .collect::<Result<_, _>>()?,
+        };
+        let values = self
+            .args
+            .iter()
+            .zip(&types)
+            .map(|((text, _), arg_type)| ArgValue::parse(text, arg_type))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut param = Vec::new();
+        ArgsSerializer::new().serialize(&values, &mut param)?;
+        Ok(param)
+    }
+}
+
+impl FromStr for TypedCall {
+    type Err = ModelsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (function, args) = split_call(s)?;
+        let args = args
+            .into_iter()
+            .map(|arg| {
+                let (value, annotation) = split_annotation(arg)?;
+                Ok((value.to_string(), annotation))
+            })
+            .collect::<Result<_, ModelsError>>()?;
+        Ok(TypedCall {
+            function: function.to_string(),
+            args,
+        })
+    }
+}
+
+/// Converts a call parameter to the string passed to the runtime.
+/// UTF-8 parameters are passed as they are, unless they start with `RUNTIME_BASE64_PARAM_MARKER`.
+/// The other parameters, typed arguments included, are passed as the marker followed by their base64 encoding.
+pub fn param_to_runtime_string(param: &[u8]) -> Cow<'_, str> {
+    match std::str::from_utf8(param) {
+        Ok(text) if !text.starts_with(RUNTIME_BASE64_PARAM_MARKER) => Cow::Borrowed(text),
+        _ => Cow::Owned(format!(
+            "{}{}",
+            RUNTIME_BASE64_PARAM_MARKER,
+            base64::encode(param)
+        )),
+    }
+}
+
+/// Converts a string passed to the runtime back into the call parameter
+pub fn runtime_string_to_param(value: &str) -> Result<Vec<u8>, ModelsError> {
+    match value.strip_prefix(RUNTIME_BASE64_PARAM_MARKER) {
+        Some(encoded) => base64::decode(encoded)
+            .map_err(|err| ModelsError::DeserializeError(format!("invalid parameter: {}", err))),
+        None => Ok(value.as_bytes().to_vec()),
+    }
+}
+
+/// Serde representation of call parameters, compatible with the former text parameters:
+/// UTF-8 parameters are serialized as strings and the other ones as byte arrays.
+/// Both representations are accepted on deserialization.
+pub mod serde_param {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    /// Serializes a call parameter
+    pub fn serialize<S: Serializer>(param: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        match std::str::from_utf8(param) {
+            Ok(text) => serializer.serialize_str(text),
+            Err(_) => serializer.collect_seq(param),
+        }
+    }
+
+    /// Deserializes a call parameter
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Param {
+            Text(String),
+            Bytes(Vec<u8>),
+        }
+        Ok(match Param::deserialize(deserializer)? {
+            Param::Text(text) => text.into_bytes(),
+            Param::Bytes(bytes) => bytes,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use massa_signature::{derive_public_key, generate_random_private_key};
+
+    #[test]
+    fn test_typed_call_with_abi() {
+        let address = Address::from_public_key(&derive_public_key(&generate_random_private_key()));
+        let abi: ContractAbi = ContractAbi(vec![
+            "transfer(to: Address, amount: Amount)".parse().unwrap(),
+            "set_tags(tags: Vec<String>, raw: Bytes, enabled: bool)"
+                .parse()
+                .unwrap(),
+        ]);
+        let transfer = abi.get_function("transfer").unwrap();
+        assert_eq!(
+            transfer.to_string(),
+            "transfer(to: Address, amount: Amount)"
+        );
+
+        let call: TypedCall = format!("transfer({}, 1.5)", address).parse().unwrap();
+        let param = call.encode_args(Some(&abi)).unwrap();
+        assert_eq!(
+            transfer.decode_args(&param).unwrap(),
+            vec![
+                ArgValue::Address(address),
+                ArgValue::Amount(Amount::from_str("1.5").unwrap())
+            ]
+        );
+
+        let call: TypedCall = r#"set_tags(["a, b", "c\"d"], 0x00ff, true)"#.parse().unwrap();
+        let param = call.encode_args(Some(&abi)).unwrap();
+        let values = abi
+            .get_function("set_tags")
+            .unwrap()
+            .decode_args(&param)
+            .unwrap();
+        assert_eq!(
+            values,
+            vec![
+                ArgValue::List(
+                    ArgType::String,
+                    vec![
+                        ArgValue::String("a, b".to_string()),
+                        ArgValue::String("c\"d".to_string())
+                    ]
+                ),
+                ArgValue::Bytes(vec![0x00, 0xff]),
+                ArgValue::Bool(true),
+            ]
+        );
+        assert_eq!(values[0].to_string(), r#"["a, b", "c\"d"]"#);
+
+        // wrong count, wrong annotation, wrong value, unknown function
+        for call in [
+            "transfer(1.5)".to_string(),
+            format!("transfer({}: Address, 1.5: u64)", address),
+            format!("transfer({}, -1)", address),
+            "burn(1)".to_string(),
+        ] {
+            let call: TypedCall = call.parse().unwrap();
+            assert!(call.encode_args(Some(&abi)).is_err());
+        }
+    }
+
+    #[test]
+    fn test_typed_call_without_abi() {
+        let call: TypedCall = "f(1: u64, -2: i64, [1, 2]: Vec<u64>)".parse().unwrap();
+        let param = call.encode_args(None).unwrap();
+        assert!(param.starts_with(&TYPED_ARGS_PREFIX));
+        let function: FunctionAbi = "f(a: u64, b: i64, c: vec<u64>)".parse().unwrap();
+        assert_eq!(
+            function.decode_args(&param).unwrap(),
+            vec![
+                ArgValue::U64(1),
+                ArgValue::I64(-2),
+                ArgValue::List(ArgType::U64, vec![ArgValue::U64(1), ArgValue::U64(2)])
+            ]
+        );
+
+        let call: TypedCall = "f(1)".parse().unwrap();
+        assert!(call.encode_args(None).is_err());
+        assert!("f(\"unterminated)".parse::<TypedCall>().is_err());
+        assert!("f(a: u128)".parse::<FunctionAbi>().is_err());
+    }
+
+    #[test]
+    fn test_param_encodings() {
+        let typed = TypedCall::from_str("f(1: u64)")
+            .unwrap()
+            .encode_args(None)
+            .unwrap();
+        let marked_text = format!("{}AAAA", RUNTIME_BASE64_PARAM_MARKER).into_bytes();
+        for param in [b"text".to_vec(), typed.clone(), marked_text.clone()] {
+            assert_eq!(
+                runtime_string_to_param(&param_to_runtime_string(&param)).unwrap(),
+                param
+            );
+        }
+        assert_eq!(param_to_runtime_string(b"text"), "text");
+        assert!(param_to_runtime_string(&typed).starts_with(RUNTIME_BASE64_PARAM_MARKER));
+        assert_ne!(
+            param_to_runtime_string(&marked_text).as_bytes(),
+            &marked_text[..]
+        );
+
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Call {
+            #[serde(with = "serde_param")]
+            param: Vec<u8>,
+        }
+        let text = Call {
+            param: b"text".to_vec(),
+        };
+        assert_eq!(serde_json::to_string(&text).unwrap(), r#"{"param":"text"}"#);
+        assert_eq!(
+            serde_json::from_str::<Call>(r#"{"param":"text"}"#).unwrap(),
+            text
+        );
+        assert_eq!(
+            serde_json::from_str::<Call>(r#"{"param":[116,101,120,116]}"#).unwrap(),
+            text
+        );
+        let typed = Call { param: typed };
+        let json = serde_json::to_string(&typed).unwrap();
+        assert!(json.starts_with(r#"{"param":[255,1,"#));
+        assert_eq!(serde_json::from_str::<Call>(&json).unwrap(), typed);
+    }
+}
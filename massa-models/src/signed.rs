@@ -1,6 +1,9 @@
 use std::{fmt::Display, marker::PhantomData};
 
-use crate::{array_from_slice, DeserializeCompact, ModelsError, SerializeCompact};
+use crate::{
+    array_from_slice, u8_from_slice, DeserializeCompact, DeserializeVarInt, ModelsError,
+    SerializeCompact, SerializeVarInt,
+};
 use massa_hash::Hash;
 use massa_signature::{
     sign, verify_signature, PrivateKey, PublicKey, Signature, SIGNATURE_SIZE_BYTES,
@@ -18,6 +21,9 @@ where
     pub content: T,
     /// signature
     pub signature: Signature,
+    /// additional signatures, along with the index of the signer's key, if the content accepts them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub co_signatures: Vec<(u8, Signature)>,
     #[serde(skip)]
     phantom: PhantomData<U>,
 }
@@ -35,6 +41,9 @@ where
 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "Signature: {}", self.signature)?;
+        for (index, signature) in self.co_signatures.iter() {
+            writeln!(f, "Co-signature of key {}: {}", index, signature)?;
+        }
         writeln!(f, "{}", self.content)?;
         Ok(())
     }
@@ -55,6 +64,11 @@ where
     fn compute_id(&self) -> Result<U, ModelsError> {
         Ok(U::new(Hash::compute_from(&self.to_bytes_compact()?)))
     }
+
+    /// Whether the signed structure carries co-signatures
+    fn accepts_co_signatures(&self) -> bool {
+        false
+    }
 }
 
 impl<T, U> Signed<T, U>
@@ -68,6 +82,7 @@ where
             content.compute_id()?,
             Self {
                 signature: sign(&content.get_signature_message()?, private_key)?,
+                co_signatures: Vec::new(),
                 content,
                 phantom: PhantomData,
             },
@@ -82,6 +97,44 @@ where
             public_key,
         )?)
     }
+
+    /// Adds a co-signature, replacing the previous one of the same key
+    ///
+    /// # Arguments
+    /// * `key_index`: index of the signer's key, meaningful to the content
+    /// * `private_key`: private key of the signer
+    pub fn add_co_signature(
+        &mut self,
+        key_index: u8,
+        private_key: &PrivateKey,
+    ) -> Result<(), ModelsError> {
+        if !self.content.accepts_co_signatures() {
+            return Err(ModelsError::CheckedOperationError(
+                "the signed content does not accept co-signatures".into(),
+            ));
+        }
+        let signature = sign(&self.content.get_signature_message()?, private_key)?;
+        self.co_signatures.retain(|(index, _)| *index != key_index);
+        self.co_signatures.push((key_index, signature));
+        self.co_signatures.sort_unstable_by_key(|(index, _)| *index);
+        Ok(())
+    }
+
+    /// check if the co-signature at position `position` has been made by public key
+    pub fn verify_co_signature(
+        &self,
+        position: usize,
+        public_key: &PublicKey,
+    ) -> Result<(), ModelsError> {
+        let (_, signature) = self.co_signatures.get(position).ok_or_else(|| {
+            ModelsError::CheckedOperationError(format!("no co-signature at position {}", position))
+        })?;
+        Ok(verify_signature(
+            &self.content.get_signature_message()?,
+            signature,
+            public_key,
+        )?)
+    }
 }
 
 impl<T, U> SerializeCompact for Signed<T, U>
@@ -98,6 +151,22 @@ where
         // signature
         res.extend(self.signature.to_bytes());
 
+        // co-signatures
+        if self.content.accepts_co_signatures() {
+            let co_signature_count: u32 = self.co_signatures.len().try_into().map_err(|err| {
+                ModelsError::SerializeError(format!("too many co-signatures: {}", err))
+            })?;
+            res.extend(co_signature_count.to_varint_bytes());
+            for (index, signature) in self.co_signatures.iter() {
+                res.push(*index);
+                res.extend(signature.to_bytes());
+            }
+        } else if !self.co_signatures.is_empty() {
+            return Err(ModelsError::SerializeError(
+                "the signed content does not accept co-signatures".into(),
+            ));
+        }
+
         Ok(res)
     }
 }
@@ -118,10 +187,31 @@ where
         let signature = Signature::from_bytes(&array_from_slice(&buffer[cursor..])?)?;
         cursor += SIGNATURE_SIZE_BYTES;
 
+        // co-signatures
+        let mut co_signatures = Vec::new();
+        if content.accepts_co_signatures() {
+            let (co_signature_count, delta) = u32::from_varint_bytes(&buffer[cursor..])?;
+            cursor += delta;
+            if co_signature_count > u8::MAX as u32 {
+                return Err(ModelsError::DeserializeError(format!(
+                    "too many co-signatures: {}",
+                    co_signature_count
+                )));
+            }
+            for _ in 0..co_signature_count {
+                let index = u8_from_slice(&buffer[cursor..])?;
+                cursor += 1;
+                let signature = Signature::from_bytes(&array_from_slice(&buffer[cursor..])?)?;
+                cursor += SIGNATURE_SIZE_BYTES;
+                co_signatures.push((index, signature));
+            }
+        }
+
         Ok((
             Self {
                 content,
                 signature,
+                co_signatures,
                 phantom: PhantomData,
             },
             cursor,
@@ -1,7 +1,9 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use crate::Amount;
 use massa_time::MassaTime;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fmt::Formatter;
 
 /// stats produced by network module
@@ -89,3 +91,34 @@ impl std::fmt::Display for PoolStats {
         Ok(())
     }
 }
+
+/// fee statistics of the operations of a thread of the pool
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PoolThreadFeeStats {
+    /// thread
+    pub thread: u8,
+    /// number of operations of the thread in the pool
+    pub operation_count: u64,
+    /// fee of the pending operations of the thread, by percentile
+    pub fee_percentiles: BTreeMap<u8, Amount>,
+    /// lowest fee still kept in the pool if the thread is full, `None` if any fee is accepted
+    pub eviction_threshold: Option<Amount>,
+    /// fee an operation of usual size needs to be included within the requested number of slots of the thread
+    pub suggested_fee: Amount,
+}
+
+impl std::fmt::Display for PoolThreadFeeStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Thread {} fee stats:", self.thread)?;
+        writeln!(f, "\tOperations: {}", self.operation_count)?;
+        for (percentile, fee) in self.fee_percentiles.iter() {
+            writeln!(f, "\tFee percentile {}: {}", percentile, fee)?;
+        }
+        match self.eviction_threshold {
+            Some(fee) => writeln!(f, "\tEviction threshold: {}", fee)?,
+            None => writeln!(f, "\tEviction threshold: none")?,
+        }
+        writeln!(f, "\tSuggested fee: {}", self.suggested_fee)?;
+        Ok(())
+    }
+}
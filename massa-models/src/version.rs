@@ -12,7 +12,7 @@ use nom::{
 };
 use serde::de::Unexpected;
 use std::ops::Bound::Included;
-use std::{convert::TryInto, fmt, str::FromStr};
+use std::{cmp::Ordering, convert::TryInto, fmt, str::FromStr};
 
 const INSTANCE_LEN: usize = 4;
 
@@ -191,6 +191,38 @@ impl Version {
     pub fn is_compatible(&self, other: &Version) -> bool {
         self.instance == other.instance && self.major == other.major
     }
+
+    /// instance and major, shared by compatible versions
+    ///
+    /// ```rust
+    /// # use massa_models::*;
+    /// # use std::str::FromStr;
+    /// let v: Version = Version::from_str("TEST.1.2").unwrap();
+    /// assert_eq!(v.network(), "TEST.1");
+    /// ```
+    pub fn network(&self) -> String {
+        let network_str: String = self.instance.iter().cloned().collect();
+        format!("{}.{}", network_str, self.major)
+    }
+}
+
+impl PartialOrd for Version {
+    /// Versions are ordered by major then minor, only within the same instance
+    ///
+    /// ```rust
+    /// # use massa_models::*;
+    /// # use std::str::FromStr;
+    /// let v: Version = Version::from_str("TEST.1.2").unwrap();
+    /// assert!(v < Version::from_str("TEST.1.10").unwrap());
+    /// assert!(v > Version::from_str("TEST.0.5").unwrap());
+    /// assert_eq!(v.partial_cmp(&Version::from_str("SAND.1.2").unwrap()), None);
+    /// ```
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        if self.instance != other.instance {
+            return None;
+        }
+        Some((self.major, self.minor).cmp(&(other.major, other.minor)))
+    }
 }
 
 impl fmt::Display for Version {
@@ -2,7 +2,7 @@
 
 use crate::{peers::PeerType, ConnectionId};
 use displaydoc::Display;
-use massa_models::ModelsError;
+use massa_models::{ModelsError, Version};
 use std::net::IpAddr;
 use thiserror::Error;
 
@@ -52,6 +52,8 @@ pub enum NetworkError {
     ModelsError(#[from] ModelsError),
     /// container inconsistency error: {0}
     ContainerInconsistencyError(String),
+    /// encrypted transport required, but node version {0} does not encrypt connections: the first version doing so is {1}
+    EncryptedTransportUnavailable(Version, Version),
 }
 
 /// Handshake error type
@@ -71,6 +73,8 @@ pub enum HandshakeErrorType {
     HandshakeInvalidSignature,
     /// Incompatible version
     IncompatibleVersion,
+    /// Unencrypted connection while encryption is required
+    EncryptionRequired,
     /// Outgoing connection returned a bootstrapable peer list: {0:?}
     PeerListReceived(Vec<IpAddr>),
 }
@@ -15,9 +15,12 @@ mod types {
 #[cfg(not(feature = "testing"))]
 mod types {
     use massa_time::MassaTime;
-    use std::{io, net::SocketAddr};
+    use std::{
+        io,
+        net::{IpAddr, SocketAddr},
+    };
     use tokio::{
-        net::{TcpListener, TcpStream},
+        net::{TcpListener, TcpSocket, TcpStream},
         time::timeout,
     };
 
@@ -47,9 +50,10 @@ mod types {
         }
     }
 
-    /// Initiates a connection with given timeout in milliseconds
+    /// Initiates a connection with given timeout in milliseconds,
+    /// from the given local IP if any
     #[derive(Debug)]
-    pub struct DefaultConnector(MassaTime);
+    pub struct DefaultConnector(MassaTime, Option<IpAddr>);
 
     impl DefaultConnector {
         /// Tries to connect to a address
@@ -57,7 +61,22 @@ mod types {
         /// # Argument
         /// * `addr`: `SocketAddr` we are trying to connect to.
         pub async fn connect(&mut self, addr: SocketAddr) -> io::Result<(ReadHalf, WriteHalf)> {
-            match timeout(self.0.to_duration(), TcpStream::connect(addr)).await {
+            let local_ip = self.1;
+            let connection = async move {
+                match local_ip {
+                    Some(local_ip) => {
+                        let socket = if local_ip.is_ipv4() {
+                            TcpSocket::new_v4()?
+                        } else {
+                            TcpSocket::new_v6()?
+                        };
+                        socket.bind(SocketAddr::new(local_ip, 0))?;
+                        socket.connect(addr).await
+                    }
+                    None => TcpStream::connect(addr).await,
+                }
+            };
+            match timeout(self.0.to_duration(), connection).await {
                 Ok(Ok(sock)) => {
                     let (reader, writer) = sock.into_split();
                     Ok((reader, writer))
@@ -70,12 +89,26 @@ mod types {
 
     /// Establishes a connection
     #[derive(Debug)]
-    pub struct DefaultEstablisher;
+    pub struct DefaultEstablisher {
+        /// local IP the outgoing connections are bound to, chosen by the OS if `None`
+        local_ip: Option<IpAddr>,
+    }
 
     impl DefaultEstablisher {
         /// Creates an Establisher.
         pub fn new() -> Self {
-            DefaultEstablisher {}
+            DefaultEstablisher { local_ip: None }
+        }
+
+        /// Creates an Establisher binding the outgoing connections to a local IP,
+        /// so that several nodes running on the loopback interface can tell each other apart.
+        ///
+        /// # Argument
+        /// * `local_ip`: IP the outgoing connections are made from
+        pub fn with_local_ip(local_ip: IpAddr) -> Self {
+            DefaultEstablisher {
+                local_ip: Some(local_ip),
+            }
         }
 
         /// Gets the associated listener
@@ -94,7 +127,7 @@ mod types {
             &mut self,
             timeout_duration: MassaTime,
         ) -> io::Result<DefaultConnector> {
-            Ok(DefaultConnector(timeout_duration))
+            Ok(DefaultConnector(timeout_duration, self.local_ip))
         }
     }
 
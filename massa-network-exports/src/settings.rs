@@ -56,6 +56,11 @@ pub struct NetworkSettings {
     pub max_bytes_read: f64,
     /// Write limitation for a connection in bytes per seconds
     pub max_bytes_write: f64,
+    /// Refuse the connections that are not encrypted.
+    /// Otherwise, connections with peers running a version older than the first one encrypting connections stay in plaintext,
+    /// and so does a connection whose announced versions were altered by a relay.
+    /// The network controller does not start with this option if the node version does not encrypt connections yet.
+    pub require_encrypted_transport: bool,
 }
 
 /// Connection configuration for a peer type
@@ -124,6 +129,7 @@ pub mod tests {
                 max_operations_per_message: MAX_OPERATIONS_PER_MESSAGE,
                 max_bytes_read: std::f64::INFINITY,
                 max_bytes_write: std::f64::INFINITY,
+                require_encrypted_transport: false,
             }
         }
     }
@@ -183,6 +189,7 @@ pub mod tests {
                 max_operations_per_message: MAX_OPERATIONS_PER_MESSAGE,
                 max_bytes_read: std::f64::INFINITY,
                 max_bytes_write: std::f64::INFINITY,
+                require_encrypted_transport: false,
             }
         }
     }
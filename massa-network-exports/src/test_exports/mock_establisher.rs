@@ -130,6 +130,11 @@ impl MockEstablisher {
         unreachable!("place holder")
     }
 
+    /// place holder
+    pub fn with_local_ip(_local_ip: std::net::IpAddr) -> Self {
+        unreachable!("place holder")
+    }
+
     /// get connection listener
     pub async fn get_listener(&mut self, _addr: SocketAddr) -> io::Result<MockListener> {
         Ok(MockListener {
@@ -1,19 +1,33 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 //! `Flexbuffer` layer between raw data and our objects.
-use super::messages::{
-    deserialize_message_with_optional_serialized_object, Message, SerializedForm,
+use super::{
+    cipher::{FrameCipher, TAG_SIZE_BYTES},
+    messages::{deserialize_message_with_optional_serialized_object, Message, SerializedForm},
 };
 use async_speed_limit::{clock::StandardClock, Limiter, Resource};
+use massa_metrics::{NETWORK_RECEIVED_BYTES, NETWORK_SENT_BYTES};
 use massa_models::{with_serialization_context, DeserializeMinBEInt, SerializeMinBEInt};
 use massa_network_exports::{NetworkError, ReadHalf, WriteHalf};
 use std::convert::TryInto;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 
+/// Maximum size of a frame: a message, followed by an authentication tag if the connection is encrypted
+fn max_frame_size(encrypted: bool) -> u32 {
+    let max_message_size = with_serialization_context(|context| context.max_message_size);
+    if encrypted {
+        max_message_size.saturating_add(TAG_SIZE_BYTES as u32)
+    } else {
+        max_message_size
+    }
+}
+
 /// Used to serialize and send data.
 pub struct WriteBinder {
     write_half: Resource<WriteHalf, StandardClock>,
     message_index: u64,
+    /// encrypts the sent messages once the handshake established an encrypted session
+    cipher: Option<FrameCipher>,
 }
 
 impl WriteBinder {
@@ -26,29 +40,43 @@ impl WriteBinder {
         WriteBinder {
             write_half: <Limiter>::new(limit).limit(write_half),
             message_index: 0,
+            cipher: None,
         }
     }
 
+    /// Encrypts all the messages sent from now on.
+    pub fn set_cipher(&mut self, cipher: FrameCipher) {
+        self.cipher = Some(cipher);
+    }
+
     /// Sends a serialized message.
     ///
     /// # Argument
     /// * `buf`: data to transmit.
     pub async fn send(&mut self, buf: &[u8]) -> Result<u64, NetworkError> {
         //        massa_trace!("binder.send", { "msg": msg });
+        let encrypted;
+        let buf = match self.cipher.as_mut() {
+            Some(cipher) => {
+                encrypted = cipher.encrypt(buf)?;
+                &encrypted[..]
+            }
+            None => buf,
+        };
         let msg_size: u32 = buf
             .len()
             .try_into()
             .map_err(|_| NetworkError::GeneralProtocolError("message too long".into()))?;
 
         // send length
-        let max_message_size = with_serialization_context(|context| context.max_message_size);
+        let max_frame_size = max_frame_size(self.cipher.is_some());
 
-        self.write_half
-            .write_all(&msg_size.to_be_bytes_min(max_message_size)?[..])
-            .await?;
+        let size_field = msg_size.to_be_bytes_min(max_frame_size)?;
+        self.write_half.write_all(&size_field[..]).await?;
 
         // send message
         self.write_half.write_all(buf).await?;
+        NETWORK_SENT_BYTES.inc_by((size_field.len() + buf.len()) as u64);
 
         let res_index = self.message_index;
         self.message_index += 1;
@@ -64,6 +92,8 @@ pub struct ReadBinder {
     buf: Vec<u8>,
     cursor: usize,
     msg_size: Option<u32>,
+    /// decrypts the received messages once the handshake established an encrypted session
+    cipher: Option<FrameCipher>,
 }
 
 impl ReadBinder {
@@ -79,9 +109,15 @@ impl ReadBinder {
             buf: Vec::new(),
             cursor: 0,
             msg_size: None,
+            cipher: None,
         }
     }
 
+    /// Decrypts all the messages received from now on.
+    pub fn set_cipher(&mut self, cipher: FrameCipher) {
+        self.cipher = Some(cipher);
+    }
+
     /// Awaits the next incoming message and deserializes it. Asynchronous cancel-safe.
     /// Returns the message, as well as the serialized object in the case of a block.
     ///
@@ -96,12 +132,12 @@ impl ReadBinder {
     pub async fn next(
         &mut self,
     ) -> Result<Option<(u64, Message, Option<SerializedForm>)>, NetworkError> {
-        let max_message_size = with_serialization_context(|context| context.max_message_size);
+        let max_frame_size = max_frame_size(self.cipher.is_some());
 
         // check if we are in the process of reading the message length
         if self.msg_size.is_none() {
             // pre-allocate the buffer to fit the encoded message size if the buffer is not already allocated
-            let size_field_len = u32::be_bytes_min_length(max_message_size);
+            let size_field_len = u32::be_bytes_min_length(max_frame_size);
             if self.buf.len() != size_field_len {
                 self.buf = vec![0u8; size_field_len];
             }
@@ -116,6 +152,7 @@ impl ReadBinder {
                         if nr == 0 {
                             return Ok(None);
                         }
+                        NETWORK_RECEIVED_BYTES.inc_by(nr as u64);
                         self.cursor += nr;
                     }
                     Err(err) => {
@@ -129,7 +166,7 @@ impl ReadBinder {
             }
 
             // once we have all the message size bytes, deserialize it
-            let res_size = u32::from_be_bytes_min(&self.buf, max_message_size)?.0;
+            let res_size = u32::from_be_bytes_min(&self.buf, max_frame_size)?.0;
             // set self.msg_size to indicate that we are now in the process of reading the message contents (and not the size anymore).
             self.msg_size = Some(res_size);
             // allocate the buffer to match the message length
@@ -148,6 +185,7 @@ impl ReadBinder {
                     if nr == 0 {
                         return Ok(None);
                     }
+                    NETWORK_RECEIVED_BYTES.inc_by(nr as u64);
                     self.cursor += nr;
                 }
                 Err(err) => {
@@ -159,8 +197,13 @@ impl ReadBinder {
                 }
             }
         }
-        // deserialize the message
-        let (res_msg, serialized) = deserialize_message_with_optional_serialized_object(&self.buf)?;
+        // decrypt and deserialize the message
+        let (res_msg, serialized) = match self.cipher.as_mut() {
+            Some(cipher) => {
+                deserialize_message_with_optional_serialized_object(&cipher.decrypt(&self.buf)?)?
+            }
+            None => deserialize_message_with_optional_serialized_object(&self.buf)?,
+        };
 
         // now the message readout is over, we reset the state to start reading the next message's size field again at the next run
         self.cursor = 0;
@@ -0,0 +1,152 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Encryption of the frames exchanged with a peer once the handshake is over.
+//!
+//! During the handshake, each side generates an ephemeral secp256k1 key pair and sends its public key.
+//! The ephemeral public keys are covered by the handshake signatures, which authenticates them with the node keys.
+//! A Diffie-Hellman exchange between the ephemeral keys gives a shared secret from which a key is derived
+//! for each direction of the connection. Frames are then encrypted and authenticated with ChaCha20-Poly1305,
+//! using the index of the frame in its direction as nonce.
+
+use chacha20poly1305::aead::{Aead, NewAead};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use massa_hash::Hash;
+use massa_network_exports::NetworkError;
+use secp256k1::ecdh::SharedSecret;
+use secp256k1::{PublicKey, SecretKey, SECP256K1};
+
+/// Size of a serialized ephemeral public key
+pub const EPHEMERAL_PUBLIC_KEY_SIZE_BYTES: usize = secp256k1::constants::PUBLIC_KEY_SIZE;
+
+/// Size of the authentication tag appended to every encrypted frame
+pub const TAG_SIZE_BYTES: usize = 16;
+
+/// Domain separation of the derived session keys
+const SESSION_KEY_CONTEXT: &[u8] = b"massa_p2p_session_key";
+
+/// Ephemeral key pair used to establish a session with a peer
+pub struct EphemeralKeyPair {
+    secret_key: SecretKey,
+    /// serialized public key, sent to the peer
+    pub public_key: [u8; EPHEMERAL_PUBLIC_KEY_SIZE_BYTES],
+}
+
+impl EphemeralKeyPair {
+    /// Generates a new random key pair
+    pub fn generate() -> Self {
+        let (secret_key, public_key) =
+            SECP256K1.generate_keypair(&mut secp256k1::rand::thread_rng());
+        EphemeralKeyPair {
+            secret_key,
+            public_key: public_key.serialize(),
+        }
+    }
+
+    /// Derives the ciphers of both directions of the connection
+    ///
+    /// # Arguments
+    /// * `other_public_key`: ephemeral public key of the peer
+    /// * `self_random_bytes`: random bytes we sent during the handshake
+    /// * `other_random_bytes`: random bytes the peer sent during the handshake
+    ///
+    /// Returns the cipher of the frames we send and the cipher of the frames we receive.
+    pub fn derive_ciphers(
+        &self,
+        other_public_key: &[u8],
+        self_random_bytes: &[u8],
+        other_random_bytes: &[u8],
+    ) -> Result<(FrameCipher, FrameCipher), NetworkError> {
+        let other_public_key = PublicKey::from_slice(other_public_key).map_err(|err| {
+            NetworkError::GeneralProtocolError(format!("invalid ephemeral public key: {}", err))
+        })?;
+        let shared_secret = SharedSecret::new(&other_public_key, &self.secret_key);
+        let derive_key = |sender_random_bytes: &[u8], receiver_random_bytes: &[u8]| {
+            Hash::compute_from(
+                &[
+                    SESSION_KEY_CONTEXT,
+                    &shared_secret.secret_bytes(),
+                    sender_random_bytes,
+                    receiver_random_bytes,
+                ]
+                .concat(),
+            )
+        };
+        Ok((
+            FrameCipher::new(derive_key(self_random_bytes, other_random_bytes)),
+            FrameCipher::new(derive_key(other_random_bytes, self_random_bytes)),
+        ))
+    }
+}
+
+/// Encrypts or decrypts the frames of one direction of a connection
+pub struct FrameCipher {
+    cipher: ChaCha20Poly1305,
+    /// index of the next frame, used as nonce
+    frame_index: u64,
+}
+
+impl FrameCipher {
+    fn new(key: Hash) -> Self {
+        FrameCipher {
+            cipher: ChaCha20Poly1305::new(Key::from_slice(&key.to_bytes()[..])),
+            frame_index: 0,
+        }
+    }
+
+    /// Nonce of the next frame
+    fn next_nonce(&mut self) -> [u8; 12] {
+        let mut nonce = [0u8; 12];
+        nonce[4..].copy_from_slice(&self.frame_index.to_be_bytes());
+        self.frame_index += 1;
+        nonce
+    }
+
+    /// Encrypts the next frame
+    pub fn encrypt(&mut self, plaintext: &[u8]) -> Result<Vec<u8>, NetworkError> {
+        let nonce = self.next_nonce();
+        self.cipher
+            .encrypt(Nonce::from_slice(&nonce), plaintext)
+            .map_err(|_| NetworkError::GeneralProtocolError("frame encryption failed".into()))
+    }
+
+    /// Decrypts the next frame, checking its authenticity
+    pub fn decrypt(&mut self, ciphertext: &[u8]) -> Result<Vec<u8>, NetworkError> {
+        let nonce = self.next_nonce();
+        self.cipher
+            .decrypt(Nonce::from_slice(&nonce), ciphertext)
+            .map_err(|_| NetworkError::GeneralProtocolError("frame decryption failed".into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_ciphers() {
+        let (alice, bob) = (EphemeralKeyPair::generate(), EphemeralKeyPair::generate());
+        let (alice_random_bytes, bob_random_bytes) = ([1u8; 32], [2u8; 32]);
+        let (mut alice_send, mut alice_recv) = alice
+            .derive_ciphers(&bob.public_key, &alice_random_bytes, &bob_random_bytes)
+            .unwrap();
+        let (mut bob_send, mut bob_recv) = bob
+            .derive_ciphers(&alice.public_key, &bob_random_bytes, &alice_random_bytes)
+            .unwrap();
+
+        for msg in [&b"first"[..], &b"second"[..]] {
+            let frame = alice_send.encrypt(msg).unwrap();
+            assert_eq!(frame.len(), msg.len() + TAG_SIZE_BYTES);
+            assert_eq!(bob_recv.decrypt(&frame).unwrap(), msg);
+        }
+        let frame = bob_send.encrypt(b"reply").unwrap();
+        assert_eq!(alice_recv.decrypt(&frame).unwrap(), b"reply");
+
+        // tampered or replayed frames are rejected
+        let mut frame = alice_send.encrypt(b"third").unwrap();
+        frame[0] ^= 1;
+        assert!(bob_recv.decrypt(&frame).is_err());
+        let frame = bob_send.encrypt(b"again").unwrap();
+        assert!(alice_recv.decrypt(&frame).is_ok());
+        assert!(alice_recv.decrypt(&frame).is_err());
+    }
+}
@@ -1,26 +1,31 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 //! Here are happening handshakes.
+//!
+//! When both nodes run a version supporting it, they also exchange ephemeral keys
+//! and the rest of the connection is encrypted (see the `cipher` module).
+//! Nodes requiring encryption refuse the other connections.
 
 use super::{
     binders::{ReadBinder, WriteBinder},
+    cipher::EphemeralKeyPair,
     messages::Message,
 };
 use futures::future::try_join;
 use massa_hash::Hash;
 use massa_logging::massa_trace;
 use massa_models::node::NodeId;
-use massa_models::SerializeCompact;
-use massa_models::Version;
+use massa_models::{ModelsError, SerializeCompact, Version, VersionSerializer};
 use massa_network_exports::{
     throw_handshake_error as throw, ConnectionId, HandshakeErrorType, NetworkError, ReadHalf,
     WriteHalf,
 };
+use massa_serialization::Serializer;
 use massa_signature::{sign, verify_signature, PrivateKey};
 use massa_time::MassaTime;
 use rand::{rngs::StdRng, RngCore, SeedableRng};
 use tokio::{task::JoinHandle, time::timeout};
-use tracing::debug;
+use tracing::{debug, warn};
 
 /// Type alias for more readability
 pub type HandshakeReturnType = Result<(NodeId, ReadBinder, WriteBinder), NetworkError>;
@@ -38,6 +43,10 @@ pub struct HandshakeWorker {
     /// After `timeout_duration` milliseconds, the handshake attempt is dropped.
     timeout_duration: MassaTime,
     version: Version,
+    /// First version encrypting connections: the connection is encrypted if both nodes run it or a later one.
+    encrypted_transport_min_version: Version,
+    /// Refuse the connection if it is not encrypted.
+    require_encryption: bool,
 }
 
 impl HandshakeWorker {
@@ -57,6 +66,8 @@ impl HandshakeWorker {
     /// * `timeout_duration`: after `timeout_duration` milliseconds, the handshake attempt is dropped.
     /// * `connection_id`: Node we are trying to connect for debugging
     /// * `version`: Node version used in handshake initialization (check peers compatibility)
+    /// * `encrypted_transport_min_version`: first version encrypting connections
+    /// * `require_encryption`: refuse the connection if it is not encrypted
     #[allow(clippy::too_many_arguments)]
     pub fn spawn(
         socket_reader: ReadHalf,
@@ -65,9 +76,11 @@ impl HandshakeWorker {
         private_key: PrivateKey,
         timeout_duration: MassaTime,
         version: Version,
+        encrypted_transport_min_version: Version,
         connection_id: ConnectionId,
         max_bytes_read: f64,
         max_bytes_write: f64,
+        require_encryption: bool,
     ) -> JoinHandle<(ConnectionId, HandshakeReturnType)> {
         debug!("starting handshake with connection_id={}", connection_id);
         massa_trace!("network_worker.new_connection", {
@@ -85,6 +98,8 @@ impl HandshakeWorker {
                     private_key,
                     timeout_duration,
                     version,
+                    encrypted_transport_min_version,
+                    require_encryption,
                 }
                 .run()
                 .await,
@@ -144,8 +159,71 @@ impl HandshakeWorker {
             throw!(IncompatibleVersion)
         }
 
-        // sign their random bytes
-        let other_random_hash = Hash::compute_from(&other_random_bytes);
+        // exchange ephemeral keys if both versions support encrypted connections.
+        // The versions are only authenticated once the connection is encrypted:
+        // a relay altering them can keep the connection in plaintext unless encryption is required.
+        let encrypted = self.version >= self.encrypted_transport_min_version
+            && other_version >= self.encrypted_transport_min_version;
+        if !encrypted {
+            if self.require_encryption {
+                throw!(EncryptionRequired)
+            }
+            if other_version >= self.encrypted_transport_min_version {
+                warn!(
+                    "peer {} announced version {} supporting encrypted connections, but the connection is not encrypted",
+                    other_node_id, other_version
+                );
+            } else {
+                debug!(
+                    "connection with peer {} running version {} is not encrypted",
+                    other_node_id, other_version
+                );
+            }
+        }
+        let ephemeral_keys = if encrypted {
+            let self_ephemeral_key = EphemeralKeyPair::generate();
+            let send_key_msg = Message::HandshakeKeyExchange {
+                ephemeral_public_key: self_ephemeral_key.public_key.to_vec(),
+            };
+            let bytes_vec: Vec<u8> = send_key_msg.to_bytes_compact().unwrap();
+            let send_key_fut = self.writer.send(&bytes_vec);
+
+            // receive handshake key exchange future
+            let recv_key_fut = self.reader.next();
+
+            // join send_key_fut and recv_key_fut with a timeout, and match result
+            let other_ephemeral_public_key = match timeout(
+                self.timeout_duration.to_duration(),
+                try_join(send_key_fut, recv_key_fut),
+            )
+            .await
+            {
+                Err(_) => throw!(HandshakeTimeout),
+                Ok(Err(e)) => return Err(e),
+                Ok(Ok((_, None))) => throw!(HandshakeInterruption, "key".into()),
+                Ok(Ok((_, Some((_, msg, _))))) => match msg {
+                    Message::HandshakeKeyExchange {
+                        ephemeral_public_key,
+                    } => ephemeral_public_key,
+                    _ => throw!(HandshakeWrongMessage),
+                },
+            };
+            Some((self_ephemeral_key, other_ephemeral_public_key))
+        } else {
+            None
+        };
+
+        // sign their random bytes. In encrypted handshakes, also sign their version and the ephemeral keys to authenticate them:
+        // the plaintext handshake of older versions only signs the random bytes
+        let other_random_hash = match &ephemeral_keys {
+            Some((self_ephemeral_key, other_ephemeral_public_key)) => encrypted_handshake_hash(
+                &other_random_bytes,
+                &other_version,
+                other_ephemeral_public_key,
+                &self_ephemeral_key.public_key,
+            )?,
+            None => Hash::compute_from(&other_random_bytes),
+        };
         let self_signature = sign(&other_random_hash, &self.private_key)?;
 
         // send handshake reply future
@@ -175,10 +253,56 @@ impl HandshakeWorker {
         };
 
         // check their signature
+        let self_random_hash = match &ephemeral_keys {
+            Some((self_ephemeral_key, other_ephemeral_public_key)) => encrypted_handshake_hash(
+                &self_random_bytes,
+                &self.version,
+                &self_ephemeral_key.public_key,
+                other_ephemeral_public_key,
+            )?,
+            None => self_random_hash,
+        };
         verify_signature(&self_random_hash, &other_signature, &other_node_id.0).map_err(
             |_err| NetworkError::HandshakeError(HandshakeErrorType::HandshakeInvalidSignature),
         )?;
 
+        // encrypt the rest of the connection
+        if let Some((self_ephemeral_key, other_ephemeral_public_key)) = ephemeral_keys {
+            let (send_cipher, receive_cipher) = self_ephemeral_key.derive_ciphers(
+                &other_ephemeral_public_key,
+                &self_random_bytes,
+                &other_random_bytes,
+            )?;
+            self.writer.set_cipher(send_cipher);
+            self.reader.set_cipher(receive_cipher);
+        }
+
         Ok((other_node_id, self.reader, self.writer))
     }
 }
+
+/// Hash signed by a node to authenticate itself in an encrypted handshake.
+/// Besides the random bytes of the peer, it covers the version announced by the peer
+/// and the ephemeral public keys of both nodes, so that a relay cannot alter them in an encrypted handshake.
+/// Plaintext handshakes only sign the random bytes: a relay can alter the announced versions
+/// so that the connection stays in plaintext, which nodes requiring encryption refuse.
+///
+/// # Arguments
+/// * `random_bytes`: random bytes sent by the peer in its handshake initiation
+/// * `version`: version sent by the peer in its handshake initiation
+/// * `peer_ephemeral_public_key`: ephemeral public key of the peer
+/// * `signer_ephemeral_public_key`: ephemeral public key of the signing node
+fn encrypted_handshake_hash(
+    random_bytes: &[u8],
+    version: &Version,
+    peer_ephemeral_public_key: &[u8],
+    signer_ephemeral_public_key: &[u8],
+) -> Result<Hash, NetworkError> {
+    let mut bytes = random_bytes.to_vec();
+    VersionSerializer::new()
+        .serialize(version, &mut bytes)
+        .map_err(ModelsError::from)?;
+    bytes.extend(peer_ephemeral_public_key);
+    bytes.extend(signer_ephemeral_public_key);
+    Ok(Hash::compute_from(&bytes))
+}
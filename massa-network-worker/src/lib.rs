@@ -12,7 +12,11 @@ use crate::{
     peer_info_database::PeerInfoDatabase,
 };
 use massa_logging::massa_trace;
-use massa_models::{constants::CHANNEL_SIZE, node::NodeId, Version};
+use massa_models::{
+    constants::{CHANNEL_SIZE, ENCRYPTED_TRANSPORT_MIN_VERSION},
+    node::NodeId,
+    Version,
+};
 use massa_network_exports::{
     BootstrapPeers, Establisher, NetworkCommand, NetworkCommandSender, NetworkError, NetworkEvent,
     NetworkEventReceiver, NetworkManagementCommand, NetworkManager, NetworkSettings,
@@ -24,6 +28,7 @@ use tracing::{debug, error, info, warn};
 
 //pub use establisher::Establisher;
 mod binders;
+mod cipher;
 mod handshake_worker;
 mod messages;
 mod network_cmd_impl;
@@ -35,6 +40,28 @@ mod peer_info_database;
 #[cfg(test)]
 pub mod tests;
 
+/// Checks that a node requiring encrypted connections runs a version encrypting them:
+/// it would refuse every peer otherwise.
+///
+/// # Arguments
+/// * `require_encryption`: whether the connections that are not encrypted are refused
+/// * `version`: node version
+/// * `encrypted_transport_min_version`: first version encrypting connections
+fn check_encrypted_transport(
+    require_encryption: bool,
+    version: Version,
+    encrypted_transport_min_version: Version,
+) -> Result<(), NetworkError> {
+    let encrypting = version >= encrypted_transport_min_version;
+    if require_encryption && !encrypting {
+        return Err(NetworkError::EncryptedTransportUnavailable(
+            version,
+            encrypted_transport_min_version,
+        ));
+    }
+    Ok(())
+}
+
 /// Starts a new `NetworkWorker` in a spawned task
 ///
 /// # Arguments
@@ -58,9 +85,16 @@ pub async fn start_network_controller(
 > {
     debug!("starting network controller");
 
+    // check that the connections can be encrypted if they have to be
+    check_encrypted_transport(
+        network_settings.require_encrypted_transport,
+        version,
+        *ENCRYPTED_TRANSPORT_MIN_VERSION,
+    )?;
+
     // check that local IP is routable
     if let Some(self_ip) = network_settings.routable_ip {
-        if !self_ip.is_global() {
+        if !peer_info_database::is_allowed_peer_ip(&self_ip) {
             return Err(NetworkError::InvalidIpError(self_ip));
         }
     }
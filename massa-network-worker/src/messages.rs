@@ -1,5 +1,6 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
+use crate::cipher::EPHEMERAL_PUBLIC_KEY_SIZE_BYTES;
 use massa_models::{
     array_from_slice,
     constants::{BLOCK_ID_SIZE_BYTES, HANDSHAKE_RANDOMNESS_SIZE_BYTES},
@@ -29,6 +30,12 @@ pub enum Message {
         random_bytes: [u8; HANDSHAKE_RANDOMNESS_SIZE_BYTES],
         version: Version,
     },
+    /// Ephemeral key used to establish an encrypted session,
+    /// sent after the handshake initiation when both versions support it.
+    HandshakeKeyExchange {
+        /// Serialized ephemeral public key.
+        ephemeral_public_key: Vec<u8>,
+    },
     /// Reply to a handshake initiation message.
     HandshakeReply {
         /// Signature of the received random bytes with our `private_key`.
@@ -134,6 +141,7 @@ pub(crate) enum MessageTypeId {
     Endorsements = 9,
     AskForOperations = 10,
     OperationsAnnouncement = 11,
+    HandshakeKeyExchange = 12,
 }
 
 /// For more details on how incoming objects are checked for validity at this stage,
@@ -153,6 +161,17 @@ impl SerializeCompact for Message {
                 res.extend(random_bytes);
                 version_serializer.serialize(version, &mut res)?;
             }
+            Message::HandshakeKeyExchange {
+                ephemeral_public_key,
+            } => {
+                if ephemeral_public_key.len() != EPHEMERAL_PUBLIC_KEY_SIZE_BYTES {
+                    return Err(ModelsError::SerializeError(
+                        "invalid ephemeral public key length".into(),
+                    ));
+                }
+                res.extend(u32::from(MessageTypeId::HandshakeKeyExchange).to_varint_bytes());
+                res.extend(ephemeral_public_key);
+            }
             Message::HandshakeReply { signature } => {
                 res.extend(u32::from(MessageTypeId::HandshakeReply).to_varint_bytes());
                 res.extend(signature.to_bytes());
@@ -260,6 +279,14 @@ impl DeserializeCompact for Message {
                     version,
                 }
             }
+            MessageTypeId::HandshakeKeyExchange => {
+                let ephemeral_public_key: [u8; EPHEMERAL_PUBLIC_KEY_SIZE_BYTES] =
+                    array_from_slice(&buffer[cursor..])?;
+                cursor += EPHEMERAL_PUBLIC_KEY_SIZE_BYTES;
+                Message::HandshakeKeyExchange {
+                    ephemeral_public_key: ephemeral_public_key.to_vec(),
+                }
+            }
             MessageTypeId::HandshakeReply => {
                 let signature = Signature::from_bytes(&array_from_slice(&buffer[cursor..])?)?;
                 cursor += SIGNATURE_SIZE_BYTES;
@@ -12,7 +12,11 @@ use crate::{
 };
 use futures::{stream::FuturesUnordered, StreamExt};
 use massa_logging::massa_trace;
-use massa_models::{constants::CHANNEL_SIZE, node::NodeId, SerializeCompact, Version};
+use massa_models::{
+    constants::{CHANNEL_SIZE, ENCRYPTED_TRANSPORT_MIN_VERSION},
+    node::NodeId,
+    SerializeCompact, Version,
+};
 use massa_network_exports::{
     ConnectionClosureReason, ConnectionId, Establisher, HandshakeErrorType, Listener,
     NetworkCommand, NetworkConnectionErrorType, NetworkError, NetworkEvent,
@@ -198,6 +202,7 @@ impl NetworkWorker {
                 // wake up interval
                 _ = wakeup_interval.tick() => {
                     self.peer_info_db.update()?; // notify tick to peer db
+                    self.peer_info_db.update_metrics();
 
                     need_connect_retry = true; // retry out connections
                 }
@@ -753,9 +758,11 @@ impl NetworkWorker {
             self.private_key,
             self.cfg.connect_timeout,
             self.version,
+            *ENCRYPTED_TRANSPORT_MIN_VERSION,
             connection_id,
             self.cfg.max_bytes_read,
             self.cfg.max_bytes_write,
+            self.cfg.require_encrypted_transport,
         ));
         Ok(())
     }
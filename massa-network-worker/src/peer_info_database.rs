@@ -3,6 +3,7 @@
 use enum_map::EnumMap;
 use itertools::Itertools;
 use massa_logging::massa_trace;
+use massa_metrics::{NETWORK_BANNED_PEERS, NETWORK_CONNECTIONS, NETWORK_KNOWN_PEERS};
 use massa_models::constants::MAX_ADVERTISE_LENGTH;
 use massa_network_exports::settings::PeerTypeConnectionConfig;
 use massa_network_exports::ConnectionCount;
@@ -21,6 +22,13 @@ use tokio::sync::watch;
 use tokio::task::JoinHandle;
 use tokio::time::{sleep, Duration};
 use tracing::{trace, warn};
+
+/// Checks that peers can be reached at an IP.
+/// Only global IPs are allowed, except in sandbox mode where local networks run on loopback IPs.
+pub(crate) fn is_allowed_peer_ip(ip: &IpAddr) -> bool {
+    ip.is_global() || (cfg!(feature = "sandbox") && ip.is_loopback())
+}
+
 /// Contains all information about every peers we know about.
 pub struct PeerInfoDatabase {
     /// Network configuration.
@@ -99,7 +107,7 @@ pub(crate) fn cleanup_peers(
                     p.advertised = true;
                     return false;
                 }
-                if !ip.is_global() {
+                if !is_allowed_peer_ip(&ip) {
                     // avoid non-global IPs
                     return false;
                 }
@@ -126,7 +134,7 @@ pub(crate) fn cleanup_peers(
     let mut banned_peers: Vec<PeerInfo> = Vec::new();
     let mut idle_peers: Vec<PeerInfo> = Vec::new();
     for (ip, p) in peers.drain() {
-        if !ip.is_global() {
+        if !is_allowed_peer_ip(&ip) {
             // avoid non-global IPs
             continue;
         }
@@ -287,6 +295,21 @@ impl PeerInfoDatabase {
             })
     }
 
+    /// exports the connection counts per peer type and the number of known and banned peers as metrics
+    pub fn update_metrics(&self) {
+        for (peer_type, connection_count) in self.peer_types_connection_count.iter() {
+            let peer_type = format!("{:?}", peer_type).to_lowercase();
+            NETWORK_CONNECTIONS
+                .with_label_values(&[&peer_type, "in"])
+                .set(connection_count.active_in_connections as i64);
+            NETWORK_CONNECTIONS
+                .with_label_values(&[&peer_type, "out"])
+                .set(connection_count.active_out_connections as i64);
+        }
+        NETWORK_KNOWN_PEERS.set(self.peers.len() as i64);
+        NETWORK_BANNED_PEERS.set(self.peers.values().filter(|peer| peer.banned).count() as i64);
+    }
+
     ///////////////////////
     // hard disk storage //
     ///////////////////////
@@ -433,7 +456,7 @@ impl PeerInfoDatabase {
     /// `ip`: `IpAddr` we are now connected to
     pub fn new_out_connection_attempt(&mut self, ip: &IpAddr) -> Result<(), NetworkError> {
         let ip = ip.to_canonical();
-        if !ip.is_global() {
+        if !is_allowed_peer_ip(&ip) {
             return Err(NetworkError::InvalidIpError(ip));
         }
         let peer_type = if let Some(peer) = self.peers.get(&ip) {
@@ -710,7 +733,7 @@ impl PeerInfoDatabase {
     pub fn try_new_in_connection(&mut self, ip: &IpAddr) -> Result<(), NetworkError> {
         let ip = ip.to_canonical();
         // try to create a new input connection, return false if no slots
-        if !ip.is_global() || self.network_settings.max_in_connections_per_ip == 0 {
+        if !is_allowed_peer_ip(&ip) || self.network_settings.max_in_connections_per_ip == 0 {
             return Err(NetworkError::PeerConnectionError(
                 NetworkConnectionErrorType::MaxPeersConnectionReached(ip),
             ));
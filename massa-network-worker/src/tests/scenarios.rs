@@ -20,7 +20,7 @@ use massa_models::{
     node::NodeId,
     signed::{Signable, Signed},
 };
-use massa_models::{BlockId, Endorsement, SignedOperation, Slot};
+use massa_models::{BlockId, Endorsement, SignedOperation, Slot, Version};
 use massa_network_exports::{settings::PeerTypeConnectionConfig, NodeCommand, NodeEvent};
 use massa_network_exports::{
     ConnectionClosureReason, ConnectionId, HandshakeErrorType, PeerInfo, PeerType,
@@ -1289,3 +1289,185 @@ async fn test_endorsements_messages() {
     )
     .await;
 }
+
+/// Test that two nodes supporting encrypted connections only exchange ciphered frames after the handshake.
+#[tokio::test]
+#[serial]
+async fn test_encrypted_handshake() {
+    use crate::handshake_worker::HandshakeWorker;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    massa_models::init_serialization_context(massa_models::SerializationContext::default());
+    let (a_stream, a_relay) = tokio::io::duplex(1024);
+    let (b_stream, b_relay) = tokio::io::duplex(1024);
+
+    // relay the bytes between the two nodes, recording the ones sent from a to b
+    let (mut a_relay_read, mut a_relay_write) = tokio::io::split(a_relay);
+    let (mut b_relay_read, mut b_relay_write) = tokio::io::split(b_relay);
+    let recorded = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+    let relay_recorded = recorded.clone();
+    tokio::spawn(async move {
+        let mut buf = [0u8; 1024];
+        loop {
+            let read = a_relay_read.read(&mut buf).await.unwrap();
+            if read == 0 {
+                break;
+            }
+            relay_recorded
+                .lock()
+                .unwrap()
+                .extend_from_slice(&buf[..read]);
+            b_relay_write.write_all(&buf[..read]).await.unwrap();
+        }
+    });
+    tokio::spawn(async move { tokio::io::copy(&mut b_relay_read, &mut a_relay_write).await });
+
+    // both nodes run the testing version, which supports encrypted connections
+    let handshake = |stream, connection_id| {
+        let (read_half, write_half) = tokio::io::split(stream);
+        let private_key = massa_signature::generate_random_private_key();
+        HandshakeWorker::spawn(
+            read_half,
+            write_half,
+            NodeId(massa_signature::derive_public_key(&private_key)),
+            private_key,
+            1000.into(),
+            *massa_models::constants::VERSION,
+            *massa_models::constants::ENCRYPTED_TRANSPORT_MIN_VERSION,
+            ConnectionId(connection_id),
+            f64::INFINITY,
+            f64::INFINITY,
+            false,
+        )
+    };
+    let (a_result, b_result) = tokio::join!(handshake(a_stream, 0), handshake(b_stream, 1));
+    let (_, _, mut a_writer) = a_result.unwrap().1.expect("handshake of a failed");
+    let (_, mut b_reader, _) = b_result.unwrap().1.expect("handshake of b failed");
+    let handshake_len = recorded.lock().unwrap().len();
+
+    let peers: Vec<IpAddr> = (1..=8)
+        .map(|i| IpAddr::V4(Ipv4Addr::new(i, i, i, i)))
+        .collect();
+    let serialized = Message::PeerList(peers.clone()).to_bytes_compact().unwrap();
+    a_writer.send(&serialized).await.unwrap();
+    match b_reader.next().await.unwrap() {
+        Some((_, Message::PeerList(received), _)) => assert_eq!(received, peers),
+        _ => panic!("peer list not received"),
+    }
+
+    // the relay only saw the ciphered frame
+    let frame = recorded.lock().unwrap()[handshake_len..].to_vec();
+    assert!(frame.len() > serialized.len());
+    assert!(!frame
+        .windows(serialized.len())
+        .any(|window| window == &serialized[..]));
+}
+
+/// Test that a node requiring encryption refuses a peer running a version older than the first one encrypting connections,
+/// with which the connection would otherwise stay in plaintext.
+#[tokio::test]
+#[serial]
+async fn test_encryption_required() {
+    use crate::handshake_worker::HandshakeWorker;
+
+    massa_models::init_serialization_context(massa_models::SerializationContext::default());
+    let handshake = |stream, version, require_encryption, connection_id| {
+        let (read_half, write_half) = tokio::io::split(stream);
+        let private_key = massa_signature::generate_random_private_key();
+        HandshakeWorker::spawn(
+            read_half,
+            write_half,
+            NodeId(massa_signature::derive_public_key(&private_key)),
+            private_key,
+            1000.into(),
+            version,
+            *massa_models::constants::ENCRYPTED_TRANSPORT_MIN_VERSION,
+            ConnectionId(connection_id),
+            f64::INFINITY,
+            f64::INFINITY,
+            require_encryption,
+        )
+    };
+    let version = *massa_models::constants::VERSION;
+    let old_version: Version = "DEVE.0.0".parse().unwrap();
+    assert!(old_version < *massa_models::constants::ENCRYPTED_TRANSPORT_MIN_VERSION);
+
+    // the connection with the older peer stays in plaintext
+    let (a_stream, b_stream) = tokio::io::duplex(1024);
+    let (a_result, b_result) = tokio::join!(
+        handshake(a_stream, version, false, 0),
+        handshake(b_stream, old_version, false, 1)
+    );
+    a_result.unwrap().1.expect("handshake of a failed");
+    b_result.unwrap().1.expect("handshake of b failed");
+
+    // unless encryption is required
+    let (a_stream, b_stream) = tokio::io::duplex(1024);
+    let (a_result, _) = tokio::join!(
+        handshake(a_stream, version, true, 0),
+        handshake(b_stream, old_version, false, 1)
+    );
+    assert!(matches!(
+        a_result.unwrap().1,
+        Err(NetworkError::HandshakeError(
+            HandshakeErrorType::EncryptionRequired
+        ))
+    ));
+}
+
+/// Test the handshake between two nodes running the nominal version (not the testing one):
+/// encryption can only be required once this version encrypts connections,
+/// and the node refuses to start requiring it before.
+#[tokio::test]
+#[serial]
+async fn test_nominal_version_encryption() {
+    use crate::handshake_worker::HandshakeWorker;
+    use massa_models::constants::default::{ENCRYPTED_TRANSPORT_MIN_VERSION, VERSION};
+
+    massa_models::init_serialization_context(massa_models::SerializationContext::default());
+    let handshake = |stream, require_encryption, connection_id| {
+        let (read_half, write_half) = tokio::io::split(stream);
+        let private_key = massa_signature::generate_random_private_key();
+        HandshakeWorker::spawn(
+            read_half,
+            write_half,
+            NodeId(massa_signature::derive_public_key(&private_key)),
+            private_key,
+            1000.into(),
+            *VERSION,
+            *ENCRYPTED_TRANSPORT_MIN_VERSION,
+            ConnectionId(connection_id),
+            f64::INFINITY,
+            f64::INFINITY,
+            require_encryption,
+        )
+    };
+    let encrypting = *VERSION >= *ENCRYPTED_TRANSPORT_MIN_VERSION;
+
+    // nodes that do not require encryption connect
+    let (a_stream, b_stream) = tokio::io::duplex(1024);
+    let (a_result, b_result) =
+        tokio::join!(handshake(a_stream, false, 0), handshake(b_stream, false, 1));
+    a_result.unwrap().1.expect("handshake of a failed");
+    b_result.unwrap().1.expect("handshake of b failed");
+
+    // nodes requiring encryption only connect if the version encrypts connections
+    let (a_stream, b_stream) = tokio::io::duplex(1024);
+    let (a_result, _) = tokio::join!(handshake(a_stream, true, 0), handshake(b_stream, true, 1));
+    match a_result.unwrap().1 {
+        Ok(_) => assert!(encrypting),
+        Err(NetworkError::HandshakeError(HandshakeErrorType::EncryptionRequired)) => {
+            assert!(!encrypting)
+        }
+        Err(err) => panic!("unexpected handshake error: {}", err),
+    }
+
+    // so the node only starts requiring encryption if the version encrypts connections
+    assert_eq!(
+        crate::check_encrypted_transport(true, *VERSION, *ENCRYPTED_TRANSPORT_MIN_VERSION).is_ok(),
+        encrypting
+    );
+    assert!(
+        crate::check_encrypted_transport(false, *VERSION, *ENCRYPTED_TRANSPORT_MIN_VERSION).is_ok()
+    );
+}
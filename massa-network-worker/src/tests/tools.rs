@@ -89,9 +89,11 @@ pub async fn full_connection_to_controller(
         private_key,
         rw_timeout_ms.into(),
         Version::from_str("TEST.1.2").unwrap(),
+        *massa_models::constants::ENCRYPTED_TRANSPORT_MIN_VERSION,
         connection_id,
         f64::INFINITY,
         f64::INFINITY,
+        false,
     )
     .await
     .expect("handshake creation failed")
@@ -149,9 +151,11 @@ pub async fn rejected_connection_to_controller(
         private_key,
         rw_timeout_ms.into(),
         Version::from_str("TEST.1.2").unwrap(),
+        *massa_models::constants::ENCRYPTED_TRANSPORT_MIN_VERSION,
         connection_id,
         f64::INFINITY,
         f64::INFINITY,
+        false,
     )
     .await
     .expect("handshake creation failed")
@@ -235,9 +239,11 @@ pub async fn full_connection_from_controller(
         private_key,
         rw_timeout_ms.into(),
         Version::from_str("TEST.1.2").unwrap(),
+        *massa_models::constants::ENCRYPTED_TRANSPORT_MIN_VERSION,
         connection_id,
         f64::INFINITY,
         f64::INFINITY,
+        false,
     )
     .await
     .expect("handshake creation failed")
@@ -344,6 +350,8 @@ pub fn get_transaction(expire_period: u64, fee: u64) -> (SignedOperation, u8) {
         op,
         sender_public_key: sender_pub,
         expire_period,
+        multisig: None,
+        replaces: None,
     };
 
     (
@@ -0,0 +1,422 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Local network launcher.
+//!
+//! From a single specification, generates the keys of every node and staker,
+//! consistent initial ledger, rolls and peers files, then launches the nodes as child processes.
+//! Node `i` runs on the loopback IP `127.0.0.<i + 1>` with the default ports of the configuration.
+
+use crate::STAKING_KEYS_PASSWORD_ENV;
+use massa_models::{ledger_models::LedgerData, Address, Amount};
+use massa_network_exports::PeerInfo;
+use massa_signature::{derive_public_key, generate_random_private_key, PrivateKey, PublicKey};
+use massa_time::MassaTime;
+use serde::Deserialize;
+use std::{
+    collections::BTreeMap,
+    net::{IpAddr, Ipv4Addr},
+    path::{Path, PathBuf},
+    process::Stdio,
+};
+use tokio::{process::Child, signal};
+use tracing::{error, info};
+
+/// Password of the staking keys of the devnet nodes
+const DEVNET_STAKING_KEYS_PASSWORD: &str = "devnet";
+
+/// File listing the staking addresses and their private keys, also marks a devnet directory
+const STAKERS_FILE: &str = "stakers.json";
+
+/// Specification of a local network
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DevnetSpec {
+    /// number of nodes
+    pub node_count: u8,
+    /// number of staking addresses of each node
+    #[serde(default = "default_stakers_per_node")]
+    pub stakers_per_node: usize,
+    /// initial rolls of each staking address
+    pub initial_rolls: u64,
+    /// initial consensus balance of each staking address
+    pub initial_balance: Amount,
+    /// initial smart contract balance of each staking address
+    pub initial_sce_balance: Amount,
+    /// genesis timestamp in milliseconds, `genesis_delay` after the launch if not set
+    #[serde(default)]
+    pub genesis_timestamp: Option<MassaTime>,
+    /// delay in milliseconds between the launch and the genesis if no genesis timestamp is set
+    #[serde(default = "default_genesis_delay")]
+    pub genesis_delay: MassaTime,
+    /// number of threads, the default one if not set
+    #[serde(default)]
+    pub thread_count: Option<u8>,
+    /// time in milliseconds between two periods of a thread, the default one if not set
+    #[serde(default)]
+    pub t0: Option<MassaTime>,
+}
+
+fn default_stakers_per_node() -> usize {
+    1
+}
+
+fn default_genesis_delay() -> MassaTime {
+    MassaTime::from(10000)
+}
+
+impl DevnetSpec {
+    /// Checks that the specification describes a network that can run
+    fn check(&self) -> Result<(), String> {
+        if self.node_count == 0 || self.node_count == u8::MAX {
+            return Err(format!("node_count must be between 1 and {}", u8::MAX - 1));
+        }
+        if self.stakers_per_node == 0 || self.initial_rolls == 0 {
+            return Err("every node needs staking addresses with rolls".to_string());
+        }
+        if let Some(thread_count) = self.thread_count {
+            if thread_count < 2 {
+                return Err("thread_count must be at least 2".to_string());
+            }
+            if let Some(t0) = self.t0 {
+                if t0.to_millis() == 0 || t0.to_millis() % thread_count as u64 != 0 {
+                    return Err("t0 must be a non-zero multiple of thread_count".to_string());
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Generated files and keys of a devnet node
+struct DevnetNode {
+    /// loopback IP of the node
+    ip: IpAddr,
+    /// node directory
+    dir: PathBuf,
+    /// configuration file overriding the base one
+    config_path: PathBuf,
+}
+
+/// Gets the loopback IP of a devnet node
+fn node_ip(index: u8) -> IpAddr {
+    IpAddr::V4(Ipv4Addr::new(127, 0, 0, index + 1))
+}
+
+/// Writes a path as a TOML string
+fn toml_path(path: &Path) -> String {
+    format!("{:?}", path.display().to_string())
+}
+
+/// Writes a JSON file
+fn write_json<T: serde::Serialize>(path: &Path, value: &T) -> std::io::Result<()> {
+    std::fs::write(path, serde_json::to_string_pretty(value)?)
+}
+
+/// Generates the keys, the initial files and the configuration of every node of a devnet
+///
+/// # Arguments
+/// * `spec`: devnet specification
+/// * `dir`: directory where the files are written, it must not exist or hold a previous devnet
+fn generate_devnet(spec: &DevnetSpec, dir: &Path) -> std::io::Result<Vec<DevnetNode>> {
+    if dir.exists() {
+        if !dir.join(STAKERS_FILE).is_file() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                format!("{} exists and does not hold a devnet", dir.display()),
+            ));
+        }
+        std::fs::remove_dir_all(dir)?;
+    }
+    std::fs::create_dir_all(dir)?;
+    let dir = dir.canonicalize()?;
+
+    // keys
+    let node_keys: Vec<(PrivateKey, PublicKey)> = (0..spec.node_count)
+        .map(|_| {
+            let private_key = generate_random_private_key();
+            (private_key, derive_public_key(&private_key))
+        })
+        .collect();
+    let staking_keys: Vec<Vec<PrivateKey>> = (0..spec.node_count)
+        .map(|_| {
+            (0..spec.stakers_per_node)
+                .map(|_| generate_random_private_key())
+                .collect()
+        })
+        .collect();
+    let stakers: BTreeMap<Address, PrivateKey> = staking_keys
+        .iter()
+        .flatten()
+        .map(|private_key| {
+            (
+                Address::from_public_key(&derive_public_key(private_key)),
+                *private_key,
+            )
+        })
+        .collect();
+
+    // initial files shared by every node
+    let initial_ledger_path = dir.join("initial_ledger.json");
+    let initial_rolls_path = dir.join("initial_rolls.json");
+    let initial_sce_ledger_path = dir.join("initial_sce_ledger.json");
+    let initial_peers_path = dir.join("initial_peers.json");
+    write_json(
+        &initial_ledger_path,
+        &stakers
+            .keys()
+            .map(|address| {
+                (
+                    *address,
+                    LedgerData {
+                        balance: spec.initial_balance,
+                    },
+                )
+            })
+            .collect::<BTreeMap<_, _>>(),
+    )?;
+    write_json(
+        &initial_rolls_path,
+        &stakers
+            .keys()
+            .map(|address| (*address, spec.initial_rolls))
+            .collect::<BTreeMap<_, _>>(),
+    )?;
+    write_json(
+        &initial_sce_ledger_path,
+        &stakers
+            .keys()
+            .map(|address| (*address, spec.initial_sce_balance))
+            .collect::<BTreeMap<_, _>>(),
+    )?;
+    write_json(
+        &initial_peers_path,
+        &(0..spec.node_count)
+            .map(|index| PeerInfo::new(node_ip(index), true))
+            .collect::<Vec<_>>(),
+    )?;
+    write_json(&dir.join(STAKERS_FILE), &stakers)?;
+
+    // node files and configuration
+    let mut nodes = Vec::with_capacity(spec.node_count as usize);
+    for index in 0..spec.node_count {
+        let ip = node_ip(index);
+        let node_dir = dir.join(format!("node_{}", index));
+        std::fs::create_dir_all(node_dir.join("storage/ledger"))?;
+        std::fs::create_dir_all(node_dir.join("storage/blocks"))?;
+        let private_key_path = node_dir.join("node_privkey.key");
+        std::fs::write(
+            &private_key_path,
+            node_keys[index as usize].0.to_bs58_check(),
+        )?;
+        let staking_keys_path = node_dir.join("staking_keys.json");
+        write_json(&staking_keys_path, &staking_keys[index as usize])?;
+
+        // every other node is a bootstrap server, so that a node can bootstrap again after genesis
+        let bootstrap_list = (0..spec.node_count)
+            .filter(|other| *other != index)
+            .map(|other| {
+                format!(
+                    "[\"{}:31245\", \"{}\"]",
+                    node_ip(other),
+                    node_keys[other as usize].1.to_bs58_check()
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        let config = format!(
+            r#"[api]
+    bind_private = "{ip}:33034"
+    bind_public = "{ip}:33035"
+    bind_pubsub = "{ip}:33036"
+
+[ledger]
+    initial_sce_ledger_path = {initial_sce_ledger_path}
+    disk_ledger_path = {disk_ledger_path}
+
+[storage]
+    block_archive_path = {block_archive_path}
+
+[consensus]
+    ledger_path = {ledger_path}
+    initial_ledger_path = {initial_ledger_path}
+    initial_rolls_path = {initial_rolls_path}
+    staking_keys_path = {staking_keys_path}
+
+[network]
+    bind = "{ip}:31244"
+    routable_ip = "{ip}"
+    peers_file = {peers_file}
+    initial_peers_file = {initial_peers_path}
+    private_key_file = {private_key_path}
+
+[bootstrap]
+    bootstrap_list = [{bootstrap_list}]
+    bind = "{ip}:31245"
+    per_ip_min_interval = 0
+"#,
+            ip = ip,
+            initial_sce_ledger_path = toml_path(&initial_sce_ledger_path),
+            disk_ledger_path = toml_path(&node_dir.join("storage/ledger/rocks_db")),
+            block_archive_path = toml_path(&node_dir.join("storage/blocks/rocks_db")),
+            ledger_path = toml_path(&node_dir.join("storage/ledger")),
+            initial_ledger_path = toml_path(&initial_ledger_path),
+            initial_rolls_path = toml_path(&initial_rolls_path),
+            staking_keys_path = toml_path(&staking_keys_path),
+            peers_file = toml_path(&node_dir.join("storage/peers.json")),
+            initial_peers_path = toml_path(&initial_peers_path),
+            private_key_path = toml_path(&private_key_path),
+            bootstrap_list = bootstrap_list,
+        );
+        let config_path = node_dir.join("config.toml");
+        std::fs::write(&config_path, config)?;
+        nodes.push(DevnetNode {
+            ip,
+            dir: node_dir,
+            config_path,
+        });
+    }
+    Ok(nodes)
+}
+
+/// Launches a devnet node as a child process of the current executable, logging to `node.log` in its directory
+fn spawn_node(
+    node: &DevnetNode,
+    genesis_timestamp: MassaTime,
+    spec: &DevnetSpec,
+) -> std::io::Result<Child> {
+    let log_file = std::fs::File::create(node.dir.join("node.log"))?;
+    let mut command = tokio::process::Command::new(std::env::current_exe()?);
+    command
+        .env("MASSA_CONFIG_OVERRIDE_PATH", &node.config_path)
+        .env(
+            "GENESIS_TIMESTAMP",
+            genesis_timestamp.to_millis().to_string(),
+        )
+        .env(STAKING_KEYS_PASSWORD_ENV, DEVNET_STAKING_KEYS_PASSWORD)
+        .stdin(Stdio::null())
+        .stdout(log_file.try_clone()?)
+        .stderr(log_file)
+        .kill_on_drop(true);
+    if let Some(thread_count) = spec.thread_count {
+        command.env("THREAD_COUNT", thread_count.to_string());
+    }
+    if let Some(t0) = spec.t0 {
+        command.env("T0", t0.to_millis().to_string());
+    }
+    command.spawn()
+}
+
+/// Generates a devnet from a specification file and runs its nodes until an interruption or a node exit
+///
+/// # Arguments
+/// * `spec_path`: JSON file holding a `DevnetSpec`
+/// * `dir`: directory where the devnet files are written
+pub async fn run_devnet(spec_path: &Path, dir: &Path) {
+    let spec: DevnetSpec = serde_json::from_str(
+        &std::fs::read_to_string(spec_path).expect("could not read devnet specification file"),
+    )
+    .expect("could not parse devnet specification file");
+    if let Err(err) = spec.check() {
+        panic!("invalid devnet specification: {}", err);
+    }
+    let nodes = generate_devnet(&spec, dir).expect("could not generate the devnet files");
+    let genesis_timestamp = spec.genesis_timestamp.unwrap_or_else(|| {
+        MassaTime::now()
+            .expect("could not get now time")
+            .saturating_add(spec.genesis_delay)
+    });
+
+    let mut children = Vec::with_capacity(nodes.len());
+    for (index, node) in nodes.iter().enumerate() {
+        children.push(
+            spawn_node(node, genesis_timestamp, &spec).expect("could not launch devnet node"),
+        );
+        info!(
+            "devnet node {} launched on {} (API ports 33034, 33035 and 33036), logs in {}",
+            index,
+            node.ip,
+            node.dir.join("node.log").display()
+        );
+    }
+    info!(
+        "devnet of {} nodes with genesis at {}, staking addresses and keys in {}",
+        nodes.len(),
+        genesis_timestamp.to_millis(),
+        dir.join(STAKERS_FILE).display()
+    );
+
+    // wait for an interruption or a node exit
+    let stop_signal = signal::ctrl_c();
+    tokio::pin!(stop_signal);
+    let mut check_interval = tokio::time::interval(std::time::Duration::from_secs(1));
+    loop {
+        tokio::select! {
+            _ = &mut stop_signal => {
+                info!("interrupt signal received, stopping the devnet");
+                break;
+            }
+            _ = check_interval.tick() => {
+                let exited = children.iter_mut().enumerate().find_map(|(index, child)| {
+                    child.try_wait().ok().flatten().map(|status| (index, status))
+                });
+                if let Some((index, status)) = exited {
+                    error!("devnet node {} exited ({}), stopping the devnet", index, status);
+                    break;
+                }
+            }
+        }
+    }
+    for child in children.iter_mut() {
+        // the node may have already stopped
+        let _ = child.kill().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_devnet() {
+        let spec: DevnetSpec = serde_json::from_str(
+            r#"{
+                "node_count": 3,
+                "stakers_per_node": 2,
+                "initial_rolls": 10,
+                "initial_balance": "1000",
+                "initial_sce_balance": "500",
+                "thread_count": 4,
+                "t0": 4000
+            }"#,
+        )
+        .unwrap();
+        spec.check().unwrap();
+        let dir = std::env::temp_dir().join(format!(
+            "massa_devnet_{}_{}",
+            std::process::id(),
+            MassaTime::now().unwrap().to_millis()
+        ));
+        let nodes = generate_devnet(&spec, &dir).unwrap();
+        assert_eq!(nodes.len(), 3);
+
+        let stakers: BTreeMap<Address, PrivateKey> =
+            serde_json::from_str(&std::fs::read_to_string(dir.join(STAKERS_FILE)).unwrap())
+                .unwrap();
+        assert_eq!(stakers.len(), 6);
+        let rolls: BTreeMap<Address, u64> =
+            serde_json::from_str(&std::fs::read_to_string(dir.join("initial_rolls.json")).unwrap())
+                .unwrap();
+        assert!(stakers.keys().all(|address| rolls[address] == 10));
+        let peers: Vec<PeerInfo> =
+            serde_json::from_str(&std::fs::read_to_string(dir.join("initial_peers.json")).unwrap())
+                .unwrap();
+        assert_eq!(
+            peers.iter().map(|peer| peer.ip).collect::<Vec<_>>(),
+            nodes.iter().map(|node| node.ip).collect::<Vec<_>>()
+        );
+
+        // the directory of a previous devnet is replaced
+        generate_devnet(&spec, &dir).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -6,12 +6,19 @@
 #![warn(unused_crate_dependencies)]
 extern crate massa_logging;
 use crate::settings::{POOL_CONFIG, SETTINGS};
-use massa_api::{Private, Public, RpcServer, StopHandle, API};
+use atty::Stream;
+use dialoguer::Password;
+use massa_api::{
+    start_web_gateway, Private, PubSub, Public, RpcServer, StopHandle, WebGatewayStopHandle, API,
+};
 use massa_async_pool::AsyncPoolConfig;
-use massa_bootstrap::{get_state, start_bootstrap_server, BootstrapManager};
+use massa_bootstrap::{
+    get_state, read_snapshot, start_bootstrap_server, start_clock_sync, write_snapshot,
+    BootstrapManager, ClockSyncManager,
+};
 use massa_consensus_exports::{
     events::ConsensusEvent, settings::ConsensusChannels, ConsensusCommandSender, ConsensusConfig,
-    ConsensusEventReceiver, ConsensusManager,
+    ConsensusEventReceiver, ConsensusManager, StakingKeysPassword,
 };
 use massa_consensus_worker::start_consensus_controller;
 use massa_execution_exports::{ExecutionConfig, ExecutionManager};
@@ -20,6 +27,7 @@ use massa_final_state::{FinalState, FinalStateConfig};
 use massa_ledger_exports::LedgerConfig;
 use massa_ledger_worker::FinalLedger;
 use massa_logging::massa_trace;
+use massa_metrics::start_metrics_server;
 use massa_models::{
     constants::{
         END_TIMESTAMP, GENESIS_TIMESTAMP, MAX_ASYNC_GAS, MAX_ASYNC_POOL_LENGTH, MAX_GAS_PER_BLOCK,
@@ -33,23 +41,189 @@ use massa_pool::{start_pool_controller, PoolCommandSender, PoolManager};
 use massa_protocol_exports::ProtocolManager;
 use massa_protocol_worker::start_protocol_controller;
 use massa_storage::Storage;
-use massa_time::MassaTime;
+use massa_time::{ClockCompensation, MassaTime};
 use parking_lot::RwLock;
-use std::{process, sync::Arc};
+use std::{
+    path::{Path, PathBuf},
+    process,
+    sync::Arc,
+};
+use structopt::StructOpt;
 use tokio::signal;
 use tokio::sync::mpsc;
 use tracing::{error, info, warn};
 #[cfg(not(feature = "instrument"))]
 use tracing_subscriber::filter::{filter_fn, LevelFilter};
 
+#[cfg(feature = "sandbox")]
+mod devnet;
 mod settings;
 
-async fn launch() -> (
+#[derive(StructOpt)]
+struct Args {
+    /// Boot from a snapshot file written by `export-snapshot`.
+    /// Only the changes that happened after the snapshot slot are then streamed from the bootstrap servers,
+    /// unless the snapshot is older than their final state history.
+    /// Without bootstrap servers, the node boots from the snapshot alone.
+    #[structopt(long, parse(from_os_str))]
+    bootstrap_snapshot: Option<PathBuf>,
+    #[structopt(subcommand)]
+    command: Option<NodeCommand>,
+}
+
+#[derive(StructOpt)]
+enum NodeCommand {
+    /// Bootstrap from the configured bootstrap servers, write the final ledger, async pool,
+    /// proof of stake and block graph at the final slot to a checksummed snapshot file, and exit
+    ExportSnapshot {
+        /// snapshot file to write
+        #[structopt(parse(from_os_str))]
+        path: PathBuf,
+    },
+    /// Generate the keys, initial ledger, rolls and peers files of a local network from a JSON specification,
+    /// then launch its nodes on loopback IPs until interrupted (Linux only)
+    #[cfg(feature = "sandbox")]
+    Devnet {
+        /// JSON specification of the network
+        #[structopt(parse(from_os_str))]
+        spec: PathBuf,
+        /// directory where the network files are written, a previous network there is replaced
+        #[structopt(long, parse(from_os_str), default_value = "devnet")]
+        dir: PathBuf,
+    },
+}
+
+/// Environment variable that can hold the staking keys password
+const STAKING_KEYS_PASSWORD_ENV: &str = "MASSA_STAKING_KEYS_PASSWORD";
+
+/// Get the staking keys password from, in order of priority:
+/// the environment, the configured password file or a prompt if the node runs in a terminal.
+/// Returns `None` if no password was supplied or it could not be read: the staking keys will stay locked.
+fn get_staking_keys_password() -> Option<String> {
+    if let Ok(password) = std::env::var(STAKING_KEYS_PASSWORD_ENV) {
+        return Some(password);
+    }
+    if let Some(path) = &SETTINGS.consensus.staking_keys_password_path {
+        return match std::fs::read_to_string(path) {
+            Ok(content) => Some(content.trim_end_matches(&['\r', '\n'][..]).to_string()),
+            Err(err) => {
+                warn!(
+                    "could not read staking keys password file {:?}, the staking keys are locked: {}",
+                    path, err
+                );
+                None
+            }
+        };
+    }
+    if !atty::is(Stream::Stdin) {
+        warn!("no staking keys password supplied, the staking keys are locked");
+        return None;
+    }
+    let password = if SETTINGS.consensus.staking_keys_path.is_file() {
+        Password::new()
+            .with_prompt("Enter staking keys password")
+            .allow_empty_password(true)
+            .interact()
+    } else {
+        Password::new()
+            .with_prompt("Enter new password for staking keys")
+            .with_confirmation("Confirm password", "Passwords mismatching")
+            .interact()
+    };
+    let password = match password {
+        Ok(password) => password,
+        Err(err) => {
+            warn!(
+                "staking keys password reading failed, the staking keys are locked: {}",
+                err
+            );
+            return None;
+        }
+    };
+    if password.is_empty() {
+        warn!("no staking keys password supplied, the staking keys are locked");
+        return None;
+    }
+    Some(password)
+}
+
+/// Creates the final state persisted at the given path.
+/// A final state previously persisted there for the same network and genesis is resumed at its slot.
+fn create_final_state(disk_ledger_path: PathBuf) -> Arc<RwLock<FinalState>> {
+    #[cfg(not(feature = "sandbox"))]
+    let thread_count = THREAD_COUNT;
+    #[cfg(feature = "sandbox")]
+    let thread_count = *THREAD_COUNT;
+
+    let ledger_config = LedgerConfig {
+        initial_sce_ledger_path: SETTINGS.ledger.initial_sce_ledger_path.clone(),
+        disk_ledger_path,
+        archive_mode: SETTINGS.ledger.archive_mode,
+        network_id: format!("{}.{}", VERSION.network(), GENESIS_TIMESTAMP.to_millis()),
+    };
+    let async_pool_config = AsyncPoolConfig {
+        max_length: MAX_ASYNC_POOL_LENGTH,
+    };
+    let final_state_config = FinalStateConfig {
+        final_history_length: SETTINGS.ledger.final_history_length,
+        thread_count,
+        ledger_config: ledger_config.clone(),
+        async_pool_config,
+    };
+
+    // Create final ledger, resuming the persisted one if it belongs to the same network and genesis
+    let ledger = FinalLedger::new(ledger_config).expect("could not init final ledger");
+
+    // Create final state
+    Arc::new(RwLock::new(
+        FinalState::new(final_state_config, Box::new(ledger)).expect("could not init final state"),
+    ))
+}
+
+/// Bootstraps from the configured bootstrap servers and writes the obtained state to a snapshot file
+async fn export_snapshot(path: &Path) {
+    // Init the global serialization context
+    init_serialization_context(SerializationContext::default());
+
+    // the state is bootstrapped in its own ledger, so that a node running with the same settings is not disturbed
+    let disk_ledger_path = path.with_extension("ledger");
+    let slot = {
+        let final_state = create_final_state(disk_ledger_path.clone());
+        let bootstrap_state = get_state(
+            &SETTINGS.bootstrap,
+            final_state.clone(),
+            massa_bootstrap::types::Establisher::new(),
+            *VERSION,
+            *GENESIS_TIMESTAMP,
+            *END_TIMESTAMP,
+        )
+        .await
+        .expect("could not bootstrap the state to export");
+        let (pos, graph) = match (&bootstrap_state.pos, &bootstrap_state.graph) {
+            (Some(pos), Some(graph)) => (pos, graph),
+            _ => panic!("there is no state to export before genesis"),
+        };
+        let final_state = final_state.read();
+        write_snapshot(path, &final_state, pos, graph, *VERSION).expect("could not write snapshot")
+    };
+    std::fs::remove_dir_all(disk_ledger_path).expect("disk ledger delete failed");
+    info!(
+        "Snapshot at final slot {} written to {}",
+        slot,
+        path.display()
+    );
+}
+
+async fn launch(
+    staking_keys_password: StakingKeysPassword,
+    bootstrap_snapshot: Option<PathBuf>,
+) -> (
     PoolCommandSender,
     ConsensusEventReceiver,
     ConsensusCommandSender,
     NetworkCommandSender,
     Option<BootstrapManager>,
+    Option<ClockSyncManager>,
     ConsensusManager,
     Box<dyn ExecutionManager>,
     PoolManager,
@@ -58,6 +232,8 @@ async fn launch() -> (
     mpsc::Receiver<()>,
     StopHandle,
     StopHandle,
+    Option<StopHandle>,
+    Option<WebGatewayStopHandle>,
 ) {
     info!("Node version : {}", *VERSION);
     if let Some(end) = *END_TIMESTAMP {
@@ -67,7 +243,11 @@ async fn launch() -> (
     }
 
     // Storage shared by multiple components.
-    let shared_storage: Storage = Default::default();
+    let shared_storage: Storage = if SETTINGS.storage.block_archive {
+        Storage::with_block_archive(SETTINGS.storage.block_archive_path.clone())
+    } else {
+        Default::default()
+    };
 
     #[cfg(not(feature = "sandbox"))]
     let thread_count = THREAD_COUNT;
@@ -78,68 +258,60 @@ async fn launch() -> (
     #[cfg(feature = "sandbox")]
     let t0 = *T0;
 
-    // init final state
-    let ledger_config = LedgerConfig {
-        initial_sce_ledger_path: SETTINGS.ledger.initial_sce_ledger_path.clone(),
-        disk_ledger_path: SETTINGS.ledger.disk_ledger_path.clone(),
-    };
-    let async_pool_config = AsyncPoolConfig {
-        max_length: MAX_ASYNC_POOL_LENGTH,
-    };
-    let final_state_config = FinalStateConfig {
-        final_history_length: SETTINGS.ledger.final_history_length,
-        thread_count,
-        ledger_config: ledger_config.clone(),
-        async_pool_config,
-    };
-
     // Init the global serialization context
     init_serialization_context(SerializationContext::default());
 
-    // Remove current disk ledger if there is one
-    // NOTE: this is temporary, since we cannot currently handle bootstrap from remaining ledger
-    if SETTINGS.ledger.disk_ledger_path.exists() {
-        std::fs::remove_dir_all(SETTINGS.ledger.disk_ledger_path.clone())
-            .expect("disk ledger delete failed");
-    }
-
-    // Create final ledger
-    let ledger = FinalLedger::new(ledger_config.clone()).expect("could not init final ledger");
+    // init final state
+    let final_state = create_final_state(SETTINGS.ledger.disk_ledger_path.clone());
 
-    // Create final state
-    let final_state = Arc::new(RwLock::new(
-        FinalState::new(final_state_config, Box::new(ledger)).expect("could not init final state"),
-    ));
+    // load the snapshot if any: the bootstrap resumes its persisted final state
+    let snapshot_state = bootstrap_snapshot.map(|path| {
+        read_snapshot(&path, final_state.clone(), *VERSION)
+            .expect("could not load bootstrap snapshot")
+    });
 
     // interrupt signal listener
     let stop_signal = signal::ctrl_c();
     tokio::pin!(stop_signal);
 
     // bootstrap
-    let bootstrap_state = tokio::select! {
-        _ = &mut stop_signal => {
-            info!("interrupt signal received in bootstrap loop");
-            process::exit(0);
+    let bootstrap_state = match snapshot_state {
+        // without bootstrap servers, the node boots from the snapshot alone
+        Some(snapshot_state) if SETTINGS.bootstrap.bootstrap_list.is_empty() => snapshot_state,
+        _ => tokio::select! {
+            _ = &mut stop_signal => {
+                info!("interrupt signal received in bootstrap loop");
+                process::exit(0);
+            },
+            res = get_state(
+                &SETTINGS.bootstrap,
+                final_state.clone(),
+                massa_bootstrap::types::Establisher::new(),
+                *VERSION,
+                *GENESIS_TIMESTAMP,
+                *END_TIMESTAMP,
+            ) => match res {
+                Ok(vals) => vals,
+                Err(err) => panic!("critical error detected in the bootstrap process: {}", err)
+            }
         },
-        res = get_state(
-            &SETTINGS.bootstrap,
-            final_state.clone(),
-            massa_bootstrap::types::Establisher::new(),
-            *VERSION,
-            *GENESIS_TIMESTAMP,
-            *END_TIMESTAMP,
-        ) => match res {
-            Ok(vals) => vals,
-            Err(err) => panic!("critical error detected in the bootstrap process: {}", err)
-        }
+    };
+
+    // clock compensation shared by the components, then kept up to date by the clock synchronization
+    let clock_compensation = ClockCompensation::new(bootstrap_state.compensation_millis);
+
+    // nodes sharing the loopback interface make their outgoing connections from their own IP
+    let network_establisher = match SETTINGS.network.routable_ip {
+        Some(ip) if ip.is_loopback() => Establisher::with_local_ip(ip),
+        _ => Establisher::new(),
     };
 
     // launch network controller
     let (network_command_sender, network_event_receiver, network_manager, private_key, node_id) =
         start_network_controller(
             SETTINGS.network.clone(), // TODO: get rid of this clone() ... see #1277
-            Establisher::new(),
-            bootstrap_state.compensation_millis,
+            network_establisher,
+            clock_compensation.get(),
             bootstrap_state.peers,
             shared_storage.clone(),
             *VERSION,
@@ -178,11 +350,14 @@ async fn launch() -> (
         max_final_events: SETTINGS.execution.max_final_events,
         readonly_queue_length: SETTINGS.execution.readonly_queue_length,
         cursor_delay: SETTINGS.execution.cursor_delay,
-        clock_compensation: bootstrap_state.compensation_millis,
+        clock_compensation: clock_compensation.clone(),
         max_async_gas: MAX_ASYNC_GAS,
         thread_count,
         t0,
         genesis_timestamp: *GENESIS_TIMESTAMP,
+        event_db_path: SETTINGS.execution.event_db_path.clone(),
+        event_db_retention_periods: SETTINGS.execution.event_db_retention_periods,
+        event_db_max_bytes: SETTINGS.execution.event_db_max_bytes,
     };
     let (execution_manager, execution_controller) = start_execution_worker(
         execution_config,
@@ -204,7 +379,8 @@ async fn launch() -> (
             bootstrap_state.pos,
             bootstrap_state.graph,
             shared_storage.clone(),
-            bootstrap_state.compensation_millis,
+            clock_compensation.clone(),
+            staking_keys_password,
         )
         .await
         .expect("could not start consensus controller");
@@ -217,12 +393,20 @@ async fn launch() -> (
         &SETTINGS.bootstrap,
         massa_bootstrap::Establisher::new(),
         private_key,
-        bootstrap_state.compensation_millis,
+        clock_compensation.clone(),
         *VERSION,
     )
     .await
     .unwrap();
 
+    // launch clock synchronization
+    let clock_sync_manager = start_clock_sync(
+        &SETTINGS.bootstrap,
+        massa_bootstrap::Establisher::new(),
+        clock_compensation.clone(),
+        *VERSION,
+    );
+
     // spawn private API
     let (api_private, api_private_stop_rx) = API::<Private>::new(
         consensus_command_sender.clone(),
@@ -243,17 +427,38 @@ async fn launch() -> (
         &SETTINGS.network,
         *VERSION,
         network_command_sender.clone(),
-        bootstrap_state.compensation_millis,
+        clock_compensation,
         node_id,
+        shared_storage.clone(),
     );
     let api_public_handle = api_public.serve(&SETTINGS.api.bind_public);
 
+    // spawn the web gateway
+    let web_gateway_stop_handle = SETTINGS.api.bind_web.map(|bind| {
+        start_web_gateway(
+            &bind,
+            execution_controller.clone(),
+            SETTINGS.api.web_dns_address,
+        )
+    });
+
+    // spawn publish/subscribe API
+    let api_pubsub_handle = SETTINGS.api.bind_pubsub.map(|bind| {
+        API::<PubSub>::new(
+            consensus_command_sender.clone(),
+            execution_controller,
+            SETTINGS.api.max_subscriptions_per_session,
+        )
+        .serve(&bind)
+    });
+
     (
         pool_command_sender,
         consensus_event_receiver,
         consensus_command_sender,
         network_command_sender,
         bootstrap_manager,
+        clock_sync_manager,
         consensus_manager,
         execution_manager,
         pool_manager,
@@ -262,11 +467,14 @@ async fn launch() -> (
         api_private_stop_rx,
         api_private_handle,
         api_public_handle,
+        api_pubsub_handle,
+        web_gateway_stop_handle,
     )
 }
 
 struct Managers {
     bootstrap_manager: Option<BootstrapManager>,
+    clock_sync_manager: Option<ClockSyncManager>,
     consensus_manager: ConsensusManager,
     execution_manager: Box<dyn ExecutionManager>,
     pool_manager: PoolManager,
@@ -278,6 +486,7 @@ async fn stop(
     consensus_event_receiver: ConsensusEventReceiver,
     Managers {
         bootstrap_manager,
+        clock_sync_manager,
         consensus_manager,
         mut execution_manager,
         pool_manager,
@@ -286,6 +495,8 @@ async fn stop(
     }: Managers,
     api_private_handle: StopHandle,
     api_public_handle: StopHandle,
+    api_pubsub_handle: Option<StopHandle>,
+    web_gateway_stop_handle: Option<WebGatewayStopHandle>,
 ) {
     // stop bootstrap
     if let Some(bootstrap_manager) = bootstrap_manager {
@@ -295,6 +506,21 @@ async fn stop(
             .expect("bootstrap server shutdown failed")
     }
 
+    // stop clock synchronization
+    if let Some(clock_sync_manager) = clock_sync_manager {
+        clock_sync_manager.stop().await;
+    }
+
+    // stop web gateway
+    if let Some(web_gateway_stop_handle) = web_gateway_stop_handle {
+        web_gateway_stop_handle.stop().await;
+    }
+
+    // stop publish/subscribe API
+    if let Some(api_pubsub_handle) = api_pubsub_handle {
+        api_pubsub_handle.stop();
+    }
+
     // stop public API
     api_public_handle.stop();
 
@@ -356,6 +582,33 @@ async fn main() {
         .with(tracing_layer)
         .init();
 
+    let args = Args::from_args();
+    match args.command {
+        Some(NodeCommand::ExportSnapshot { path }) => {
+            export_snapshot(&path).await;
+            return;
+        }
+        #[cfg(feature = "sandbox")]
+        Some(NodeCommand::Devnet { spec, dir }) => {
+            devnet::run_devnet(&spec, &dir).await;
+            return;
+        }
+        None => {}
+    }
+    // the snapshot is only used by the first launch, a restart bootstraps as usual
+    let mut bootstrap_snapshot = args.bootstrap_snapshot;
+
+    // updated by consensus when the staking keys are locked or unlocked,
+    // so that a restart keeps the staking keys in their current state
+    let staking_keys_password = StakingKeysPassword::new(get_staking_keys_password());
+
+    // the metrics are global, so their server is kept across restarts
+    let metrics_stop_handle = if SETTINGS.metrics.enabled {
+        Some(start_metrics_server(&SETTINGS.metrics.bind))
+    } else {
+        None
+    };
+
     // run
     loop {
         let (
@@ -364,6 +617,7 @@ async fn main() {
             _consensus_command_sender,
             _network_command_sender,
             bootstrap_manager,
+            clock_sync_manager,
             consensus_manager,
             execution_manager,
             pool_manager,
@@ -372,7 +626,9 @@ async fn main() {
             mut api_private_stop_rx,
             api_private_handle,
             api_public_handle,
-        ) = launch().await;
+            api_pubsub_handle,
+            web_gateway_stop_handle,
+        ) = launch(staking_keys_password.clone(), bootstrap_snapshot.take()).await;
 
         // interrupt signal listener
         let stop_signal = signal::ctrl_c();
@@ -411,6 +667,7 @@ async fn main() {
             consensus_event_receiver,
             Managers {
                 bootstrap_manager,
+                clock_sync_manager,
                 consensus_manager,
                 execution_manager,
                 pool_manager,
@@ -419,6 +676,8 @@ async fn main() {
             },
             api_private_handle,
             api_public_handle,
+            api_pubsub_handle,
+            web_gateway_stop_handle,
         )
         .await;
 
@@ -426,4 +685,9 @@ async fn main() {
             break;
         }
     }
+
+    // stop metrics server
+    if let Some(metrics_stop_handle) = metrics_stop_handle {
+        metrics_stop_handle.stop().await;
+    }
 }
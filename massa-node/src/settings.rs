@@ -1,7 +1,7 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 //! Build here the default node settings from the configuration file toml
-use std::path::PathBuf;
+use std::{net::SocketAddr, path::PathBuf};
 
 use massa_api::APISettings;
 use massa_bootstrap::BootstrapSettings;
@@ -43,6 +43,12 @@ pub struct ExecutionSettings {
     pub max_final_events: usize,
     pub readonly_queue_length: usize,
     pub cursor_delay: MassaTime,
+    #[serde(default)]
+    pub event_db_path: Option<PathBuf>,
+    #[serde(default)]
+    pub event_db_retention_periods: Option<u64>,
+    #[serde(default)]
+    pub event_db_max_bytes: Option<u64>,
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -50,6 +56,19 @@ pub struct LedgerSettings {
     pub initial_sce_ledger_path: PathBuf,
     pub disk_ledger_path: PathBuf,
     pub final_history_length: usize,
+    pub archive_mode: bool,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct StorageSettings {
+    pub block_archive: bool,
+    pub block_archive_path: PathBuf,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct MetricsSettings {
+    pub enabled: bool,
+    pub bind: SocketAddr,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -63,6 +82,8 @@ pub struct Settings {
     pub pool: PoolSettings,
     pub execution: ExecutionSettings,
     pub ledger: LedgerSettings,
+    pub storage: StorageSettings,
+    pub metrics: MetricsSettings,
 }
 
 #[cfg(test)]
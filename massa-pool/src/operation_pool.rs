@@ -1,15 +1,21 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 
 use crate::{settings::PoolConfig, PoolError};
+use massa_models::api::PendingOperationInfo;
 use massa_models::prehash::{Map, Set};
+use massa_models::stats::PoolThreadFeeStats;
 use massa_models::{
-    Address, OperationId, OperationSearchResult, OperationSearchResultStatus, SignedOperation, Slot,
+    Address, Amount, OperationId, OperationSearchResult, OperationSearchResultStatus,
+    SignedOperation, Slot,
 };
 use massa_storage::Storage;
 use num::rational::Ratio;
 use std::ops::RangeInclusive;
 use std::{collections::BTreeSet, usize};
 
+/// Percentiles of the fee statistics
+const FEE_PERCENTILES: [u8; 5] = [10, 25, 50, 75, 90];
+
 struct OperationIndex(Map<Address, Set<OperationId>>);
 
 impl OperationIndex {
@@ -49,6 +55,19 @@ struct OperationMetadata {
     fee_density: Ratio<u64>,
     /// The range of periods during which an operation is valid.
     validity_range: RangeInclusive<u64>,
+    /// The fee paid to the block producer
+    fee: Amount,
+    /// The maximum amount of gas the operation can use in a block
+    max_gas: u64,
+    /// The address of the sender
+    sender_address: Address,
+    /// The operation the sender declared this operation replaces
+    replaces: Option<OperationId>,
+}
+
+/// A pending operation can only be replaced by an operation of the same sender paying a strictly higher fee
+fn can_replace(pending_op: &OperationMetadata, replacing_op: &OperationMetadata) -> bool {
+    replacing_op.sender_address == pending_op.sender_address && replacing_op.fee > pending_op.fee
 }
 
 impl OperationMetadata {
@@ -57,7 +76,7 @@ impl OperationMetadata {
         byte_count: u64,
         thread_count: u8,
         operation_validity_periods: u64,
-    ) -> Self {
+    ) -> Result<Self, PoolError> {
         // Fee density
         // add inclusion fee and gas fees
         let total_return = operation
@@ -66,20 +85,24 @@ impl OperationMetadata {
             .saturating_add(operation.content.get_gas_coins());
         // return ratio with size
         let fee_density = Ratio::new(total_return.to_raw(), byte_count);
-        let thread =
-            Address::from_public_key(&operation.content.sender_public_key).get_thread(thread_count);
+        let sender_address = operation.content.get_sender_address();
+        let thread = sender_address.get_thread(thread_count);
         let ledger_involved_addresses = operation.content.get_ledger_involved_addresses();
         let validity_range = operation
             .content
             .get_validity_range(operation_validity_periods);
-        OperationMetadata {
+        Ok(OperationMetadata {
             byte_count,
             thread,
             expire_period: operation.content.expire_period,
             ledger_involved_addresses,
             fee_density,
             validity_range,
-        }
+            fee: operation.content.fee,
+            max_gas: operation.content.get_gas_usage(),
+            sender_address,
+            replaces: operation.content.replaces,
+        })
     }
 }
 
@@ -90,6 +113,8 @@ pub struct OperationPool {
         Vec<BTreeSet<(std::cmp::Reverse<num::rational::Ratio<u64>>, OperationId)>>, // [thread][order by: (rev rentability, OperationId)]
     /// Maps Address -> Op id
     ops_by_address: OperationIndex,
+    /// Maps replaced op id -> id of the pending op declaring it replaces it
+    ops_by_replaced: Map<OperationId, OperationId>,
     /// latest final blocks periods
     last_final_periods: Vec<u64>,
     /// current slot
@@ -98,6 +123,8 @@ pub struct OperationPool {
     cfg: &'static PoolConfig,
     /// ids of operations that are final with expire period and thread
     final_operations: Map<OperationId, (u64, u8)>,
+    /// ids of operations that were replaced by fee with expire period and thread
+    replaced_operations: Map<OperationId, (u64, u8)>,
     /// Shared storage.
     storage: Storage,
 }
@@ -111,24 +138,46 @@ impl OperationPool {
             last_final_periods: vec![0; cfg.thread_count as usize],
             cfg,
             final_operations: Default::default(),
+            replaced_operations: Default::default(),
             ops_by_address: OperationIndex::new(),
+            ops_by_replaced: Default::default(),
             storage,
         }
     }
 
     /// Process incoming operations.
-    /// Returns newly added.
+    ///
+    /// An incoming operation declaring it replaces a pending operation of the same sender
+    /// replaces it if it pays a strictly higher fee (replace-by-fee), and is ignored otherwise.
+    /// The replaced operation is not accepted back, and neither is a replacement of a final operation.
+    /// A valid copy of a pending operation replaces the stored one if its co-signatures are invalid.
+    ///
+    /// Returns newly added and replaced operations.
     pub fn process_operations(
         &mut self,
         mut operations: Map<OperationId, (SignedOperation, Vec<u8>)>,
-    ) -> Result<Set<OperationId>, PoolError> {
+    ) -> Result<(Set<OperationId>, Set<OperationId>), PoolError> {
         let mut removed = Set::<OperationId>::default();
+        let mut replaced = Set::<OperationId>::default();
         for (op_id, (op, serialized)) in operations.iter() {
             massa_trace!("pool add_operations op", { "op_id": op_id });
 
             // Already present
             if self.ops.contains_key(op_id) {
                 massa_trace!("pool add_operations op already present", {});
+                // The id does not cover the co-signatures of a multisig sender:
+                // a valid copy replaces a stored copy whose co-signatures are invalid.
+                if op.content.multisig.is_some()
+                    && self.storage.with_operation(op_id, |stored| {
+                        stored.map_or(false, |stored| stored.operation.verify_integrity().is_err())
+                    })
+                    && op.verify_integrity().is_ok()
+                {
+                    massa_trace!("pool add_operations op replaces an invalid stored copy", {});
+                    self.storage.remove_operations(&[*op_id]);
+                    self.storage
+                        .store_operation(*op_id, op.clone(), serialized.clone());
+                }
                 removed.insert(*op_id);
                 continue;
             }
@@ -150,7 +199,7 @@ impl OperationPool {
                     byte_count,
                     thread_count,
                     operation_validity_periods,
-                );
+                )?;
                 let validity_range = op.content.get_validity_range(operation_validity_periods);
                 let validity_start_period = validity_range.start();
                 (wrapped, *validity_start_period)
@@ -189,6 +238,69 @@ impl OperationPool {
                 continue;
             }
 
+            // already replaced
+            if self.replaced_operations.contains_key(op_id) {
+                massa_trace!("pool add_operations op already replaced", {});
+                removed.insert(*op_id);
+                continue;
+            }
+
+            // replaced by a pending op received first
+            if let Some(replacing_id) = self.ops_by_replaced.get(op_id).copied() {
+                if self
+                    .ops
+                    .get(&replacing_id)
+                    .map_or(false, |replacing_op| can_replace(&wrapped_op, replacing_op))
+                {
+                    massa_trace!("pool add_operations op is replaced by a pending op", {
+                        "replacing_op_id": replacing_id
+                    });
+                    self.replaced_operations
+                        .insert(*op_id, (wrapped_op.expire_period, wrapped_op.thread));
+                    replaced.insert(*op_id);
+                    continue;
+                }
+            }
+
+            // replace by fee the declared op, or the pending op that already replaced it
+            if let Some(replaced_id) = wrapped_op.replaces {
+                let pending_id = if self.ops.contains_key(&replaced_id) {
+                    Some(replaced_id)
+                } else {
+                    self.ops_by_replaced.get(&replaced_id).copied()
+                };
+                let pending_op = pending_id.and_then(|id| self.ops.get(&id).map(|op| (id, op)));
+                match pending_op {
+                    Some((pending_id, pending_op)) => {
+                        if !can_replace(pending_op, &wrapped_op) {
+                            massa_trace!("pool add_operations op can not replace the pending op", {
+                                "pending_op_id": pending_id
+                            });
+                            removed.insert(*op_id);
+                            continue;
+                        }
+                        massa_trace!("pool add_operations op replaces a pending op", {
+                            "replaced_op_id": pending_id
+                        });
+                        self.replaced_operations
+                            .insert(pending_id, (pending_op.expire_period, pending_op.thread));
+                        self.remove_ops(vec![pending_id])?;
+                        replaced.insert(pending_id);
+                    }
+                    None if self.final_operations.contains_key(&replaced_id)
+                        || self.replaced_operations.contains_key(&replaced_id) =>
+                    {
+                        massa_trace!("pool add_operations op replaces an op that is final or already replaced", {
+                            "replaced_op_id": replaced_id
+                        });
+                        removed.insert(*op_id);
+                        continue;
+                    }
+                    // unknown replaced op: it will not be accepted if it is received later
+                    None => {}
+                }
+            }
+
             // insert
             let interest = (std::cmp::Reverse(wrapped_op.fee_density), *op_id);
 
@@ -199,6 +311,9 @@ impl OperationPool {
                 .for_each(|addr| {
                     self.ops_by_address.insert_op(*addr, *op_id);
                 });
+            if let Some(replaced_id) = wrapped_op.replaces {
+                self.ops_by_replaced.insert(replaced_id, *op_id);
+            }
             self.ops.insert(*op_id, wrapped_op);
         }
 
@@ -213,6 +328,7 @@ impl OperationPool {
                     .unwrap(); // will not panic because of the while condition. complexity = log or better
                 if let Some(removed_op) = self.ops.remove(&removed_id) {
                     // complexity: const
+                    self.forget_replaced(&removed_op, &removed_id);
                     for addr in removed_op.ledger_involved_addresses {
                         self.ops_by_address
                             .remove_op_for_address(&addr, &removed_id);
@@ -222,6 +338,8 @@ impl OperationPool {
             }
         }
 
+        // operations replaced by others of the same batch were never added
+        operations.retain(|id, _| !replaced.contains(id));
         let newly_added_ids = operations
             .keys()
             .filter(|id| !removed.contains(id))
@@ -233,24 +351,46 @@ impl OperationPool {
             self.storage.store_operation(op_id, op, serialized);
         }
 
-        Ok(newly_added_ids)
+        Ok((newly_added_ids, replaced))
     }
 
+    /// Forgets the op replaced by a removed operation, unless another operation replaced it since
+    fn forget_replaced(&mut self, wrapped_op: &OperationMetadata, op_id: &OperationId) {
+        if let Some(replaced_id) = &wrapped_op.replaces {
+            if self.ops_by_replaced.get(replaced_id) == Some(op_id) {
+                self.ops_by_replaced.remove(replaced_id);
+            }
+        }
+    }
+
+    /// Removes the final operations from the pool.
+    /// Their replacements and the operations they replaced are removed too:
+    /// including them would execute the same operation a second time.
     pub fn new_final_operations(
         &mut self,
         ops: Map<OperationId, (u64, u8)>,
     ) -> Result<(), PoolError> {
-        for (id, _) in ops.iter() {
+        let mut superseded = Vec::new();
+        for (id, (expire_period, thread)) in ops.iter() {
+            if let Some(replacing_id) = self.ops_by_replaced.get(id) {
+                superseded.push(*replacing_id);
+            }
             if let Some(wrapped) = self.ops.remove(id) {
                 self.ops_by_thread_and_interest[wrapped.thread as usize]
                     .remove(&(std::cmp::Reverse(wrapped.fee_density), *id));
+                self.forget_replaced(&wrapped, id);
+                if let Some(replaced_id) = wrapped.replaces {
+                    self.replaced_operations
+                        .insert(replaced_id, (*expire_period, *thread));
+                    superseded.push(replaced_id);
+                }
                 for addr in wrapped.ledger_involved_addresses {
                     self.ops_by_address.remove_op_for_address(&addr, id);
                 }
             } // else final op wasn't in pool.
         }
         self.final_operations.extend(ops);
-        Ok(())
+        self.remove_ops(superseded)
     }
 
     pub fn update_current_slot(&mut self, slot: Slot) {
@@ -261,6 +401,14 @@ impl OperationPool {
         self.ops.len()
     }
 
+    /// number of operations of each thread
+    pub fn len_by_thread(&self) -> Vec<usize> {
+        self.ops_by_thread_and_interest
+            .iter()
+            .map(|ops| ops.len())
+            .collect()
+    }
+
     fn prune(&mut self) -> Result<(), PoolError> {
         let ids = self
             .ops
@@ -283,6 +431,10 @@ impl OperationPool {
             self.final_operations.remove(&id);
         }
 
+        let last_final_periods = &self.last_final_periods;
+        self.replaced_operations
+            .retain(|_, (exp, thread)| *exp > last_final_periods[*thread as usize]);
+
         Ok(())
     }
 
@@ -303,6 +455,7 @@ impl OperationPool {
                 let interest = (std::cmp::Reverse(wrapped_op.fee_density), op_id);
                 self.ops_by_thread_and_interest[wrapped_op.thread as usize].remove(&interest);
                 // complexity: log
+                self.forget_replaced(&wrapped_op, &op_id);
 
                 for addr in wrapped_op.ledger_involved_addresses {
                     self.ops_by_address.remove_op_for_address(&addr, &op_id);
@@ -375,6 +528,142 @@ impl OperationPool {
             .collect()
     }
 
+    /// Get the pending operations involving an address
+    pub fn get_pending_operations(
+        &self,
+        address: &Address,
+    ) -> Result<Vec<PendingOperationInfo>, PoolError> {
+        let ids = match self.ops_by_address.get_ops_for_address(address) {
+            Some(ids) => ids,
+            None => return Ok(Vec::new()),
+        };
+        ids.iter()
+            .take(self.cfg.settings.max_item_return_count)
+            .map(|op_id| {
+                let w_op = self.ops.get(op_id).ok_or_else(|| {
+                    PoolError::ContainerInconsistency(
+                        "op in ops by address is not in ops".to_string(),
+                    )
+                })?;
+                let stored = self.storage.retrieve_operation(op_id).ok_or_else(|| {
+                    PoolError::ContainerInconsistency(
+                        "op in ops by address is not in storage".to_string(),
+                    )
+                })?;
+                Ok(PendingOperationInfo {
+                    id: *op_id,
+                    thread: w_op.thread,
+                    validity_start_period: *w_op.validity_range.start(),
+                    expire_period: w_op.expire_period,
+                    operation: stored.operation,
+                })
+            })
+            .collect()
+    }
+
+    /// Get the fee statistics of each thread
+    ///
+    /// # Arguments
+    /// * `slot_count`: number of slots of a thread within which an operation should be included for the suggested fee
+    /// * `max_block_size`: maximum size of a block in bytes
+    /// * `max_operations_per_block`: maximum number of operations in a block
+    /// * `max_gas_per_block`: maximum amount of gas used by the operations of a block
+    ///
+    /// The suggested fee is an estimation: blocks are filled by decreasing fee density
+    /// and the operations suggested with the fee are assumed to be the size of the operation they need to outbid.
+    /// Only the operations that can be included in one of the next `slot_count` slots of the thread are outbid:
+    /// the ones that are not valid yet or already expired at those slots do not compete for block space.
+    pub fn get_fee_stats(
+        &self,
+        slot_count: u64,
+        max_block_size: u64,
+        max_operations_per_block: u64,
+        max_gas_per_block: u64,
+    ) -> Vec<PoolThreadFeeStats> {
+        let smallest_fee_increment = Amount::from_raw(1);
+        self.ops_by_thread_and_interest
+            .iter()
+            .enumerate()
+            .map(|(thread, ops_by_interest)| {
+                let ops: Vec<&OperationMetadata> = ops_by_interest
+                    .iter()
+                    .filter_map(|(_, id)| self.ops.get(id))
+                    .collect();
+
+                // periods of the next `slot_count` slots of the thread
+                let next_periods = self.current_slot.map(|cur_slot| {
+                    let cur_period_in_thread = if cur_slot.thread >= thread as u8 {
+                        cur_slot.period
+                    } else {
+                        cur_slot.period.saturating_sub(1)
+                    };
+                    let first = cur_period_in_thread.saturating_add(1);
+                    (first, first.saturating_add(slot_count.saturating_sub(1)))
+                });
+
+                let mut fees: Vec<Amount> = ops.iter().map(|w_op| w_op.fee).collect();
+                fees.sort_unstable();
+                let fee_percentiles = if fees.is_empty() {
+                    Default::default()
+                } else {
+                    FEE_PERCENTILES
+                        .iter()
+                        .map(|p| (*p, fees[(fees.len() - 1) * (*p as usize) / 100]))
+                        .collect()
+                };
+
+                // the lowest interest op is the first evicted when the thread is full
+                let eviction_threshold =
+                    if ops.len() as u64 >= self.cfg.settings.max_pool_size_per_thread {
+                        ops.last().map(|w_op| w_op.fee)
+                    } else {
+                        None
+                    };
+
+                // find the first op that does not fit in the next `slot_count` blocks
+                let mut remaining_size = slot_count.saturating_mul(max_block_size);
+                let mut remaining_count = slot_count.saturating_mul(max_operations_per_block);
+                let mut remaining_gas = slot_count.saturating_mul(max_gas_per_block);
+                let first_excluded = ops
+                    .iter()
+                    .filter(|w_op| {
+                        next_periods.map_or(true, |(first, last)| {
+                            *w_op.validity_range.start() <= last
+                                && *w_op.validity_range.end() >= first
+                        })
+                    })
+                    .find(|w_op| {
+                        if remaining_count == 0
+                            || w_op.byte_count > remaining_size
+                            || w_op.max_gas > remaining_gas
+                        {
+                            return true;
+                        }
+                        remaining_size -= w_op.byte_count;
+                        remaining_count -= 1;
+                        remaining_gas -= w_op.max_gas;
+                        false
+                    });
+                let suggested_fee = first_excluded
+                    .map(|w_op| w_op.fee)
+                    .into_iter()
+                    .chain(eviction_threshold)
+                    .max()
+                    .map_or_else(Amount::default, |fee| {
+                        fee.saturating_add(smallest_fee_increment)
+                    });
+
+                PoolThreadFeeStats {
+                    thread: thread as u8,
+                    operation_count: ops.len() as u64,
+                    fee_percentiles,
+                    eviction_threshold,
+                    suggested_fee,
+                }
+            })
+            .collect()
+    }
+
     pub fn get_operations_involving_address(
         &self,
         address: &Address,
@@ -8,9 +8,10 @@ use super::{
 };
 use massa_logging::massa_trace;
 use massa_models::{
+    api::PendingOperationInfo,
     constants::CHANNEL_SIZE,
     prehash::{Map, Set},
-    stats::PoolStats,
+    stats::{PoolStats, PoolThreadFeeStats},
     Address, BlockId, EndorsementId, OperationId, OperationSearchResult, SignedEndorsement,
     SignedOperation, Slot,
 };
@@ -263,6 +264,72 @@ impl PoolCommandSender {
         })
     }
 
+    /// get the pending operations involving an address
+    pub async fn get_pending_operations(
+        &mut self,
+        address: Address,
+    ) -> Result<Vec<PendingOperationInfo>, PoolError> {
+        massa_trace!("pool.command_sender.get_pending_operations", {
+            "address": address
+        });
+
+        let (response_tx, response_rx) = oneshot::channel();
+        self.0
+            .send(PoolCommand::GetPendingOperations {
+                address,
+                response_tx,
+            })
+            .await
+            .map_err(|_| {
+                PoolError::ChannelError("get_pending_operations command send error".into())
+            })?;
+
+        response_rx.await.map_err(|e| {
+            PoolError::ChannelError(format!(
+                "pool command response read error in get_pending_operations {}",
+                e
+            ))
+        })
+    }
+
+    /// get the fee statistics of each thread
+    ///
+    /// # Arguments
+    /// * `slot_count`: number of slots of a thread within which an operation should be included for the suggested fee
+    /// * `max_block_size`: maximum size of a block in bytes
+    /// * `max_operations_per_block`: maximum number of operations in a block
+    /// * `max_gas_per_block`: maximum amount of gas used by the operations of a block
+    pub async fn get_fee_stats(
+        &mut self,
+        slot_count: u64,
+        max_block_size: u64,
+        max_operations_per_block: u64,
+        max_gas_per_block: u64,
+    ) -> Result<Vec<PoolThreadFeeStats>, PoolError> {
+        massa_trace!("pool.command_sender.get_fee_stats", {
+            "slot_count": slot_count
+        });
+
+        let (response_tx, response_rx) = oneshot::channel();
+        self.0
+            .send(PoolCommand::GetFeeStats {
+                slot_count,
+                max_block_size,
+                max_operations_per_block,
+                max_gas_per_block,
+                response_tx,
+            })
+            .await
+            .map_err(|_| PoolError::ChannelError("get_fee_stats command send error".into()))?;
+
+        response_rx.await.map_err(|e| {
+            PoolError::ChannelError(format!(
+                "pool command response read error in get_fee_stats {}",
+                e
+            ))
+        })
+    }
+
     /// add endorsements to pool
     pub async fn add_endorsements(
         &mut self,
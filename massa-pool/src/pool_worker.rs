@@ -3,8 +3,10 @@
 use super::error::PoolError;
 use crate::operation_pool::OperationPool;
 use crate::{endorsement_pool::EndorsementPool, settings::PoolConfig};
+use massa_metrics::{POOL_ENDORSEMENTS, POOL_OPERATIONS};
+use massa_models::api::PendingOperationInfo;
 use massa_models::prehash::{Map, Set};
-use massa_models::stats::PoolStats;
+use massa_models::stats::{PoolStats, PoolThreadFeeStats};
 use massa_models::SerializeCompact;
 use massa_models::{
     Address, BlockId, EndorsementId, OperationId, OperationSearchResult, SignedEndorsement,
@@ -51,6 +53,26 @@ pub enum PoolCommand {
         /// response channel
         response_tx: oneshot::Sender<Map<OperationId, OperationSearchResult>>,
     },
+    /// Get the pending operations involving an address
+    GetPendingOperations {
+        /// address
+        address: Address,
+        /// response channel
+        response_tx: oneshot::Sender<Vec<PendingOperationInfo>>,
+    },
+    /// Get the fee statistics of each thread
+    GetFeeStats {
+        /// number of slots of a thread within which an operation should be included for the suggested fee
+        slot_count: u64,
+        /// maximum size of a block in bytes
+        max_block_size: u64,
+        /// maximum number of operations in a block
+        max_operations_per_block: u64,
+        /// maximum amount of gas used by the operations of a block
+        max_gas_per_block: u64,
+        /// response channel
+        response_tx: oneshot::Sender<Vec<PoolThreadFeeStats>>,
+    },
     /// mark operations as final
     /// by end of validity period, thread
     FinalOperations(Map<OperationId, (u64, u8)>),
@@ -179,6 +201,29 @@ impl PoolWorker {
         Ok(self.protocol_pool_event_receiver)
     }
 
+    /// Exports the sizes of the pool as metrics
+    fn update_metrics(&self) {
+        for (thread, count) in self.operation_pool.len_by_thread().into_iter().enumerate() {
+            POOL_OPERATIONS
+                .with_label_values(&[&thread.to_string()])
+                .set(count as i64);
+        }
+        POOL_ENDORSEMENTS.set(self.endorsement_pool.len() as i64);
+    }
+
+    /// Asks protocol to stop propagating operations that were replaced by fee
+    async fn forget_replaced_operations(
+        &mut self,
+        replaced: Set<OperationId>,
+    ) -> Result<(), PoolError> {
+        if !replaced.is_empty() {
+            self.protocol_command_sender
+                .remove_operations(replaced)
+                .await?;
+        }
+        Ok(())
+    }
+
     /// Manages given pool command.
     ///
     /// # Argument
@@ -194,7 +239,8 @@ impl PoolWorker {
                             .ok()
                     })
                     .collect();
-                let newly_added = self.operation_pool.process_operations(operations)?;
+                let (newly_added, replaced) = self.operation_pool.process_operations(operations)?;
+                self.forget_replaced_operations(replaced).await?;
                 if !newly_added.is_empty() {
                     self.protocol_command_sender
                         .propagate_operations(newly_added)
@@ -203,7 +249,8 @@ impl PoolWorker {
             }
             PoolCommand::UpdateCurrentSlot(slot) => {
                 self.operation_pool.update_current_slot(slot);
-                self.endorsement_pool.update_current_slot(slot)
+                self.endorsement_pool.update_current_slot(slot);
+                self.update_metrics()
             }
             PoolCommand::UpdateLatestFinalPeriods(periods) => {
                 self.operation_pool
@@ -254,6 +301,36 @@ impl PoolWorker {
                     warn!("pool: could not send get_operations_involving_address response");
                 }
             }
+            PoolCommand::GetPendingOperations {
+                address,
+                response_tx,
+            } => {
+                if response_tx
+                    .send(self.operation_pool.get_pending_operations(&address)?)
+                    .is_err()
+                {
+                    warn!("pool: could not send get_pending_operations response");
+                }
+            }
+            PoolCommand::GetFeeStats {
+                slot_count,
+                max_block_size,
+                max_operations_per_block,
+                max_gas_per_block,
+                response_tx,
+            } => {
+                if response_tx
+                    .send(self.operation_pool.get_fee_stats(
+                        slot_count,
+                        max_block_size,
+                        max_operations_per_block,
+                        max_gas_per_block,
+                    ))
+                    .is_err()
+                {
+                    warn!("pool: could not send get_fee_stats response");
+                }
+            }
             PoolCommand::FinalOperations(ops) => self.operation_pool.new_final_operations(ops)?,
             PoolCommand::GetEndorsements {
                 target_slot,
@@ -332,15 +409,12 @@ impl PoolWorker {
                 operations,
                 propagate,
             } => {
-                if propagate {
-                    let newly_added = self.operation_pool.process_operations(operations)?;
-                    if !newly_added.is_empty() {
-                        self.protocol_command_sender
-                            .propagate_operations(newly_added)
-                            .await?;
-                    }
-                } else {
-                    self.operation_pool.process_operations(operations)?;
+                let (newly_added, replaced) = self.operation_pool.process_operations(operations)?;
+                self.forget_replaced_operations(replaced).await?;
+                if propagate && !newly_added.is_empty() {
+                    self.protocol_command_sender
+                        .propagate_operations(newly_added)
+                        .await?;
                 }
             }
             ProtocolPoolEvent::ReceivedEndorsements {
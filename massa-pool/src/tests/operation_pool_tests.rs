@@ -1,10 +1,11 @@
 use massa_models::{
+    multisig::MultisigPolicy,
     prehash::{Map, Set},
     signed::Signed,
     Address, Amount, Operation, OperationId, OperationType, SerializeCompact, SignedOperation,
     Slot,
 };
-use massa_signature::{derive_public_key, generate_random_private_key};
+use massa_signature::{derive_public_key, generate_random_private_key, PrivateKey};
 use serial_test::serial;
 use std::str::FromStr;
 
@@ -28,6 +29,8 @@ fn get_transaction(expire_period: u64, fee: u64) -> (SignedOperation, u8) {
         op,
         sender_public_key: sender_pub,
         expire_period,
+        multisig: None,
+        replaces: None,
     };
     (
         Signed::new_signed(content, &sender_priv).unwrap().1,
@@ -52,11 +55,11 @@ fn test_pool() {
         let mut ops = Map::default();
         ops.insert(id, (op.clone(), op.to_bytes_compact().unwrap()));
 
-        let newly_added = pool.process_operations(ops.clone()).unwrap();
+        let (newly_added, _) = pool.process_operations(ops.clone()).unwrap();
         assert_eq!(newly_added, ops.keys().copied().collect());
 
         // duplicate
-        let newly_added = pool.process_operations(ops).unwrap();
+        let (newly_added, _) = pool.process_operations(ops).unwrap();
         assert_eq!(newly_added, Set::<OperationId>::default());
 
         thread_tx_lists[thread as usize].push((id, op, start_period..=expire_period));
@@ -124,7 +127,7 @@ fn test_pool() {
         let id = op.verify_integrity().unwrap();
         let mut ops = Map::default();
         ops.insert(id, (op.clone(), op.to_bytes_compact().unwrap()));
-        let newly_added = pool.process_operations(ops).unwrap();
+        let (newly_added, _) = pool.process_operations(ops).unwrap();
         assert_eq!(newly_added, Set::<OperationId>::default());
         let res = pool
             .get_operation_batch(
@@ -137,3 +140,302 @@ fn test_pool() {
         assert!(res.is_empty());
     }
 }
+
+#[test]
+#[serial]
+fn test_replace_by_fee() {
+    let mut pool = OperationPool::new(&POOL_CONFIG, Default::default());
+    let sender_priv = generate_random_private_key();
+    let sender_pub = derive_public_key(&sender_priv);
+    let thread = Address::from_public_key(&sender_pub).get_thread(POOL_CONFIG.thread_count);
+    let signed = |sender_priv: &PrivateKey, fee: u64, replaces: Option<OperationId>| {
+        let content = Operation {
+            fee: Amount::from_str(&fee.to_string()).unwrap(),
+            op: OperationType::RollBuy { roll_count: 1 },
+            sender_public_key: derive_public_key(sender_priv),
+            expire_period: 10,
+            multisig: None,
+            replaces,
+        };
+        let (id, op) = Signed::new_signed(content, sender_priv).unwrap();
+        let mut ops = Map::default();
+        ops.insert(id, (op.clone(), op.to_bytes_compact().unwrap()));
+        (id, ops)
+    };
+    let pending_ids = |pool: &mut OperationPool| {
+        pool.get_operation_batch(Slot::new(5, thread), Set::default(), 10, 10000)
+            .unwrap()
+            .into_iter()
+            .map(|(id, _, _)| id)
+            .collect::<Set<_>>()
+    };
+
+    let (low_id, low_ops) = signed(&sender_priv, 10, None);
+    pool.process_operations(low_ops).unwrap();
+
+    // the same operation at another fee without declaring a replacement is another operation
+    let (other_id, other_ops) = signed(&sender_priv, 20, None);
+    let (newly_added, replaced) = pool.process_operations(other_ops).unwrap();
+    assert_eq!(newly_added, vec![other_id].into_iter().collect());
+    assert!(replaced.is_empty());
+    assert_eq!(
+        pending_ids(&mut pool),
+        vec![low_id, other_id].into_iter().collect()
+    );
+
+    // same fee or lower, or another sender: ignored
+    for (sender_priv, fee) in [(sender_priv, 10), (generate_random_private_key(), 30)] {
+        let (_, ops) = signed(&sender_priv, fee, Some(low_id));
+        let (newly_added, replaced) = pool.process_operations(ops).unwrap();
+        assert!(newly_added.is_empty() && replaced.is_empty());
+    }
+
+    // strictly higher fee: replaces the pending op
+    let (high_id, high_ops) = signed(&sender_priv, 30, Some(low_id));
+    let (newly_added, replaced) = pool.process_operations(high_ops).unwrap();
+    assert_eq!(newly_added, vec![high_id].into_iter().collect());
+    assert_eq!(replaced, vec![low_id].into_iter().collect());
+    assert_eq!(
+        pending_ids(&mut pool),
+        vec![other_id, high_id].into_iter().collect()
+    );
+    assert!(pool
+        .find_operations(vec![low_id].into_iter().collect())
+        .is_empty());
+    let by_address = pool
+        .get_operations_involving_address(&Address::from_public_key(&sender_pub))
+        .unwrap();
+    assert_eq!(
+        by_address.keys().copied().collect::<Set<_>>(),
+        vec![other_id, high_id].into_iter().collect()
+    );
+
+    // the replaced op is not accepted back
+    let (_, low_ops) = signed(&sender_priv, 10, None);
+    let (newly_added, _) = pool.process_operations(low_ops).unwrap();
+    assert!(newly_added.is_empty());
+
+    // bumping the replaced op again replaces its pending replacement
+    let (higher_id, higher_ops) = signed(&sender_priv, 40, Some(low_id));
+    let (newly_added, replaced) = pool.process_operations(higher_ops).unwrap();
+    assert_eq!(newly_added, vec![higher_id].into_iter().collect());
+    assert_eq!(replaced, vec![high_id].into_iter().collect());
+
+    // a replacement of a final op is ignored
+    pool.new_final_operations(vec![(other_id, (10, thread))].into_iter().collect())
+        .unwrap();
+    let (_, ops) = signed(&sender_priv, 50, Some(other_id));
+    let (newly_added, _) = pool.process_operations(ops).unwrap();
+    assert!(newly_added.is_empty());
+    assert_eq!(
+        pending_ids(&mut pool),
+        vec![higher_id].into_iter().collect()
+    );
+}
+
+#[test]
+#[serial]
+fn test_replacement_received_first() {
+    let mut pool = OperationPool::new(&POOL_CONFIG, Default::default());
+    let sender_priv = generate_random_private_key();
+    let sender_pub = derive_public_key(&sender_priv);
+    let thread = Address::from_public_key(&sender_pub).get_thread(POOL_CONFIG.thread_count);
+    let signed = |fee: u64, replaces: Option<OperationId>| {
+        let content = Operation {
+            fee: Amount::from_str(&fee.to_string()).unwrap(),
+            op: OperationType::RollBuy { roll_count: 1 },
+            sender_public_key: sender_pub,
+            expire_period: 10,
+            multisig: None,
+            replaces,
+        };
+        let (id, op) = Signed::new_signed(content, &sender_priv).unwrap();
+        let mut ops = Map::default();
+        ops.insert(id, (op.clone(), op.to_bytes_compact().unwrap()));
+        (id, ops)
+    };
+
+    // the replacement of an unknown op is accepted
+    let (low_id, low_ops) = signed(10, None);
+    let (high_id, high_ops) = signed(20, Some(low_id));
+    let (newly_added, _) = pool.process_operations(high_ops).unwrap();
+    assert_eq!(newly_added, vec![high_id].into_iter().collect());
+
+    // the replaced op is refused when it is received later
+    let (newly_added, replaced) = pool.process_operations(low_ops).unwrap();
+    assert!(newly_added.is_empty());
+    assert_eq!(replaced, vec![low_id].into_iter().collect());
+
+    // once the replacement is final, the replaced op is still refused
+    pool.new_final_operations(vec![(high_id, (10, thread))].into_iter().collect())
+        .unwrap();
+    let (_, low_ops) = signed(10, None);
+    let (newly_added, _) = pool.process_operations(low_ops).unwrap();
+    assert!(newly_added.is_empty());
+    assert!(pool
+        .find_operations(vec![low_id, high_id].into_iter().collect())
+        .is_empty());
+}
+
+#[test]
+#[serial]
+fn test_valid_copy_replaces_stripped_copy() {
+    let mut pool = OperationPool::new(&POOL_CONFIG, Default::default());
+
+    // operation of a 2 of 2 multisig sender, with its co-signature
+    let private_keys: Vec<PrivateKey> = (0..2).map(|_| generate_random_private_key()).collect();
+    let policy =
+        MultisigPolicy::new(2, private_keys.iter().map(derive_public_key).collect()).unwrap();
+    let thread = policy.get_address().get_thread(POOL_CONFIG.thread_count);
+    let content = Operation {
+        fee: Amount::default(),
+        op: OperationType::RollBuy { roll_count: 1 },
+        sender_public_key: derive_public_key(&private_keys[0]),
+        expire_period: 10,
+        multisig: Some(policy.clone()),
+        replaces: None,
+    };
+    let (id, mut op) = Signed::new_signed(content, &private_keys[0]).unwrap();
+    let co_signer_index = policy
+        .get_key_index(&derive_public_key(&private_keys[1]))
+        .unwrap();
+    op.add_co_signature(co_signer_index, &private_keys[1])
+        .unwrap();
+    let mut stripped_op = op.clone();
+    stripped_op.co_signatures.clear();
+    let stored_co_signatures = |pool: &mut OperationPool| {
+        let batch = pool
+            .get_operation_batch(Slot::new(5, thread), Set::default(), 10, 10000)
+            .unwrap();
+        assert_eq!(batch.len(), 1);
+        batch[0].1.co_signatures.clone()
+    };
+
+    // the copy stripped of its co-signatures arrives first
+    let mut ops = Map::default();
+    ops.insert(
+        id,
+        (stripped_op.clone(), stripped_op.to_bytes_compact().unwrap()),
+    );
+    pool.process_operations(ops.clone()).unwrap();
+    assert!(stored_co_signatures(&mut pool).is_empty());
+
+    // the valid copy replaces it, without being added again
+    let mut valid_ops = Map::default();
+    valid_ops.insert(id, (op.clone(), op.to_bytes_compact().unwrap()));
+    let (newly_added, replaced) = pool.process_operations(valid_ops).unwrap();
+    assert!(newly_added.is_empty() && replaced.is_empty());
+    assert_eq!(stored_co_signatures(&mut pool), op.co_signatures);
+
+    // the stripped copy does not replace the valid one
+    pool.process_operations(ops).unwrap();
+    assert_eq!(stored_co_signatures(&mut pool), op.co_signatures);
+}
+
+#[test]
+#[serial]
+fn test_pending_operations_and_fee_stats() {
+    let mut pool = OperationPool::new(&POOL_CONFIG, Default::default());
+    let sender_priv = generate_random_private_key();
+    let sender_pub = derive_public_key(&sender_priv);
+    let sender_address = Address::from_public_key(&sender_pub);
+    let thread = sender_address.get_thread(POOL_CONFIG.thread_count);
+    let amount = |fee: u64| Amount::from_str(&fee.to_string()).unwrap();
+
+    // more operations than the pool keeps per thread: the ones with the lowest fees are evicted
+    for fee in 1..=12 {
+        let content = Operation {
+            fee: amount(fee),
+            op: OperationType::RollBuy { roll_count: fee },
+            sender_public_key: sender_pub,
+            expire_period: 10,
+            multisig: None,
+            replaces: None,
+        };
+        let (id, op) = Signed::new_signed(content, &sender_priv).unwrap();
+        let mut ops = Map::default();
+        ops.insert(id, (op.clone(), op.to_bytes_compact().unwrap()));
+        pool.process_operations(ops).unwrap();
+    }
+
+    let pending = pool.get_pending_operations(&sender_address).unwrap();
+    assert_eq!(
+        pending.len() as u64,
+        POOL_CONFIG.settings.max_pool_size_per_thread
+    );
+    assert!(pending.iter().all(|op| op.thread == thread
+        && op.validity_start_period == 0
+        && op.expire_period == 10
+        && op.operation.content.fee >= amount(3)));
+
+    // blocks of 4 operations: the 5th best operation is the first one left out after one slot
+    let stats = pool.get_fee_stats(1, u64::MAX, 4, u64::MAX);
+    assert_eq!(stats.len(), POOL_CONFIG.thread_count as usize);
+    let thread_stats = &stats[thread as usize];
+    assert_eq!(thread_stats.operation_count, 10);
+    assert_eq!(thread_stats.fee_percentiles.get(&50), Some(&amount(7)));
+    assert_eq!(thread_stats.eviction_threshold, Some(amount(3)));
+    assert_eq!(
+        thread_stats.suggested_fee,
+        amount(8).saturating_add(Amount::from_raw(1))
+    );
+    let other_stats = &stats[1 - thread as usize];
+    assert_eq!(other_stats.operation_count, 0);
+    assert!(other_stats.fee_percentiles.is_empty());
+    assert_eq!(other_stats.eviction_threshold, None);
+    assert_eq!(other_stats.suggested_fee, Amount::default());
+
+    // every operation fits in 3 slots: only the eviction threshold has to be outbid
+    let stats = pool.get_fee_stats(3, u64::MAX, 4, u64::MAX);
+    assert_eq!(
+        stats[thread as usize].suggested_fee,
+        amount(3).saturating_add(Amount::from_raw(1))
+    );
+}
+
+#[test]
+#[serial]
+fn test_fee_stats_validity_range() {
+    let mut pool = OperationPool::new(&POOL_CONFIG, Default::default());
+    let sender_priv = generate_random_private_key();
+    let sender_pub = derive_public_key(&sender_priv);
+    let thread = Address::from_public_key(&sender_pub).get_thread(POOL_CONFIG.thread_count);
+    let amount = |fee: u64| Amount::from_str(&fee.to_string()).unwrap();
+
+    // operations valid now, and operations with higher fees that are only valid much later
+    for (fee, expire_period) in (1..=4)
+        .map(|fee| (fee, 10))
+        .chain((10..=13).map(|fee| (fee, 200)))
+    {
+        let content = Operation {
+            fee: amount(fee),
+            op: OperationType::RollBuy { roll_count: fee },
+            sender_public_key: sender_pub,
+            expire_period,
+            multisig: None,
+            replaces: None,
+        };
+        let (id, op) = Signed::new_signed(content, &sender_priv).unwrap();
+        let mut ops = Map::default();
+        ops.insert(id, (op.clone(), op.to_bytes_compact().unwrap()));
+        pool.process_operations(ops).unwrap();
+    }
+    pool.update_current_slot(Slot::new(5, thread));
+
+    // blocks of 2 operations: the operations not valid yet do not compete for the next slot
+    let stats = pool.get_fee_stats(1, u64::MAX, 2, u64::MAX);
+    let thread_stats = &stats[thread as usize];
+    assert_eq!(thread_stats.operation_count, 8);
+    assert_eq!(
+        thread_stats.suggested_fee,
+        amount(2).saturating_add(Amount::from_raw(1))
+    );
+
+    // once the early operations are expired, only the late ones are left to outbid
+    pool.update_current_slot(Slot::new(160, thread));
+    let stats = pool.get_fee_stats(1, u64::MAX, 2, u64::MAX);
+    assert_eq!(
+        stats[thread as usize].suggested_fee,
+        amount(11).saturating_add(Amount::from_raw(1))
+    );
+}
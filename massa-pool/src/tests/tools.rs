@@ -53,6 +53,8 @@ pub fn get_transaction(expire_period: u64, fee: u64) -> (SignedOperation, u8) {
         op,
         sender_public_key: sender_pub,
         expire_period,
+        multisig: None,
+        replaces: None,
     };
     (
         Signed::new_signed(content, &sender_priv).unwrap().1,
@@ -90,6 +92,8 @@ pub fn get_transaction_with_addresses(
         op,
         sender_public_key: sender_pub,
         expire_period,
+        multisig: None,
+        replaces: None,
     };
     (
         Signed::new_signed(content, &sender_priv).unwrap().1,
@@ -121,6 +125,8 @@ pub fn create_executesc(
         fee: Amount::from_str(&fee.to_string()).unwrap(),
         expire_period,
         op,
+        multisig: None,
+        replaces: None,
     };
 
     (
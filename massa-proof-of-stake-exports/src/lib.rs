@@ -11,7 +11,7 @@ mod settings;
 
 use massa_models::{
     rolls::{RollUpdate, RollUpdates},
-    Address, Operation, OperationType,
+    Operation, OperationType,
 };
 
 mod proof_of_stake;
@@ -39,7 +39,7 @@ impl OperationRollInterface for Operation {
             OperationType::Transaction { .. } => {}
             OperationType::RollBuy { roll_count } => {
                 res.apply(
-                    &Address::from_public_key(&self.sender_public_key),
+                    &self.get_sender_address(),
                     &RollUpdate {
                         roll_purchases: roll_count,
                         roll_sales: 0,
@@ -48,7 +48,7 @@ impl OperationRollInterface for Operation {
             }
             OperationType::RollSell { roll_count } => {
                 res.apply(
-                    &Address::from_public_key(&self.sender_public_key),
+                    &self.get_sender_address(),
                     &RollUpdate {
                         roll_purchases: 0,
                         roll_sales: roll_count,
@@ -57,6 +57,7 @@ impl OperationRollInterface for Operation {
             }
             OperationType::ExecuteSC { .. } => {}
             OperationType::CallSC { .. } => {}
+            OperationType::BatchTransaction { .. } => {}
         }
         Ok(res)
     }
@@ -84,6 +84,7 @@ impl ProofOfStake {
                     cycle_updates: RollUpdates::default(),
                     rng_seed,
                     production_stats: Default::default(),
+                    endorsement_stats: Default::default(),
                 };
                 history.push_front(thread_cycle_state);
                 cycle_states.push(history);
@@ -384,6 +385,7 @@ impl ProofOfStake {
                         roll_count,
                         rng_seed: BitVec::<Lsb0, u8>::new(),
                         production_stats: Default::default(),
+                        endorsement_stats: Default::default(),
                     });
                     // If cycle_states becomes longer than pos_lookback_cycles+pos_lock_cycles+1, truncate it by removing the back elements
                     self.cycle_states[thread as usize].truncate(
@@ -431,6 +433,40 @@ impl ProofOfStake {
                             }
                         }
                     }
+
+                    // update endorsement_stats: the block includes the endorsements of its same-thread parent slot,
+                    // endorsers drawn for that slot whose endorsement is not included missed it
+                    if let Some((_, parent_period)) = a_block.parents.get(thread as usize) {
+                        let endorsed_slot = Slot::new(*parent_period, thread);
+                        let endorsed_cycle = endorsed_slot.get_cycle(self.cfg.periods_per_cycle);
+                        if *parent_period > 0 {
+                            if let Ok(endorsers) = self.draw_endorsement_producers(endorsed_slot) {
+                                if let Some(entry) = last_final_block_cycle
+                                    .checked_sub(endorsed_cycle)
+                                    .and_then(|neg_relative_cycle| {
+                                        self.cycle_states[thread as usize]
+                                            .get_mut(neg_relative_cycle as usize)
+                                    })
+                                {
+                                    for (index, endorser) in endorsers.into_iter().enumerate() {
+                                        let counts = entry
+                                            .endorsement_stats
+                                            .entry(endorser)
+                                            .or_insert((0, 0));
+                                        if a_block
+                                            .endorsement_ids
+                                            .values()
+                                            .any(|included_index| *included_index as usize == index)
+                                        {
+                                            counts.0 += 1;
+                                        } else {
+                                            counts.1 += 1;
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
                 }
 
                 // apply the miss/block to the latest cycle_states
@@ -486,6 +522,7 @@ impl ProofOfStake {
                         cycle,
                         is_final: false,
                         ok_nok_counts: Map::default(),
+                        endorsement_ok_nok_counts: Map::default(),
                     });
 
                 cycle_entry.is_final = if thread_cycle_complete {
@@ -511,6 +548,18 @@ impl ProofOfStake {
                             *p_nok += n_nok;
                         })
                         .or_insert_with(|| (*n_ok, *n_nok));
+                    let (n_ok, n_nok) = thread_cycle_info
+                        .endorsement_stats
+                        .get(addr)
+                        .unwrap_or(&(0, 0));
+                    cycle_entry
+                        .endorsement_ok_nok_counts
+                        .entry(*addr)
+                        .and_modify(|(p_ok, p_nok)| {
+                            *p_ok += n_ok;
+                            *p_nok += n_nok;
+                        })
+                        .or_insert_with(|| (*n_ok, *n_nok));
                 }
             }
         }
@@ -565,6 +614,64 @@ impl ProofOfStake {
         Ok(res)
     }
 
+    /// returns the coins of the roll sales of an address that are not credited yet by final blocks,
+    /// as `(credit cycle, amount)` pairs computed like `get_roll_sell_credit` does once the sale cycle is final
+    pub fn get_deferred_roll_sell_credits(
+        &self,
+        address: &Address,
+    ) -> POSResult<Vec<(u64, Amount)>> {
+        let thread = address.get_thread(self.cfg.thread_count);
+        let last_final_block_cycle = self.get_last_final_block_cycle(thread);
+        let mut res = Vec::new();
+        for roll_data in self.cycle_states[thread as usize].iter().rev() {
+            let credit_cycle =
+                roll_data.cycle + self.cfg.pos_lookback_cycles + self.cfg.pos_lock_cycles + 1;
+            if credit_cycle <= last_final_block_cycle {
+                continue;
+            }
+            if let Some(update) = roll_data.cycle_updates.0.get(address) {
+                let sale_delta = update.roll_sales.saturating_sub(update.roll_purchases);
+                if sale_delta > 0 {
+                    res.push((
+                        credit_cycle,
+                        self.cfg
+                            .roll_price
+                            .checked_mul_u64(sale_delta)
+                            .ok_or(ProofOfStakeError::RollOverflowError)?,
+                    ));
+                }
+            }
+        }
+        Ok(res)
+    }
+
+    /// returns the cycles at which the rolls of an address are going to be deactivated
+    /// because of the blocks it missed during the cycles in memory, complete or not
+    pub fn get_roll_deactivation_risk(&self, address: &Address) -> Vec<u64> {
+        let thread = address.get_thread(self.cfg.thread_count);
+        let last_final_block_cycle = self.get_last_final_block_cycle(thread);
+        let mut addrs = Set::<Address>::default();
+        addrs.insert(*address);
+        let mut res: Vec<u64> = self
+            .get_stakers_production_stats(&addrs)
+            .into_iter()
+            .filter_map(|cycle_stats| {
+                let (ok_count, nok_count) = *cycle_stats.ok_nok_counts.get(address)?;
+                let deactivation_cycle = cycle_stats.cycle + self.cfg.pos_lookback_cycles + 1;
+                if ok_count + nok_count == 0 || deactivation_cycle <= last_final_block_cycle {
+                    return None;
+                }
+                let miss_ratio = Ratio::new(nok_count, ok_count + nok_count);
+                if miss_ratio > self.cfg.pos_miss_rate_deactivation_threshold {
+                    return Some(deactivation_cycle);
+                }
+                None
+            })
+            .collect();
+        res.sort_unstable();
+        res
+    }
+
     /// returns the list of addresses whose rolls need to be deactivated
     pub fn get_roll_deactivations(
         &self,
@@ -33,6 +33,10 @@ pub struct ThreadCycleState {
     pub rng_seed: BitVec<Lsb0, u8>,
     /// Per-address production statistics `(ok_count, nok_count)`
     pub production_stats: Map<Address, (u64, u64)>,
+    /// Per-address endorsement statistics `(ok_count, nok_count)`, by endorsed slot.
+    /// Only informative: they are not part of the bootstrap state and restart empty on bootstrap.
+    #[serde(default)]
+    pub endorsement_stats: Map<Address, (u64, u64)>,
 }
 
 impl ThreadCycleState {
@@ -267,9 +271,207 @@ impl Deserializer<ThreadCycleState> for ThreadCycleStateDeserializer {
                     cycle_updates,
                     rng_seed,
                     production_stats,
+                    endorsement_stats: Default::default(),
                 }
             },
         )
         .parse(buffer)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use massa_models::{
+        prehash::{Map, Set},
+        rolls::{RollCounts, RollUpdate, RollUpdates},
+        Address, Amount, Slot,
+    };
+    use massa_serialization::{DeserializeError, Deserializer, Serializer};
+    use massa_signature::{derive_public_key, generate_random_private_key};
+    use num::rational::Ratio;
+    use std::{collections::VecDeque, str::FromStr};
+
+    use crate::{
+        export_pos::ExportProofOfStake,
+        proof_of_stake::ProofOfStake,
+        settings::ProofOfStakeConfig,
+        thread_cycle_state::{
+            ThreadCycleState, ThreadCycleStateDeserializer, ThreadCycleStateSerializer,
+        },
+    };
+
+    const PERIODS_PER_CYCLE: u64 = 10;
+    const LAST_FINAL_CYCLE: u64 = 10;
+
+    fn random_address() -> Address {
+        Address::from_public_key(&derive_public_key(&generate_random_private_key()))
+    }
+
+    /// Complete cycle state without rolls nor stats
+    fn cycle_state(cycle: u64) -> ThreadCycleState {
+        ThreadCycleState {
+            cycle,
+            last_final_slot: Slot::new((cycle + 1) * PERIODS_PER_CYCLE - 1, 0),
+            roll_count: RollCounts::default(),
+            cycle_updates: RollUpdates::default(),
+            rng_seed: Default::default(),
+            production_stats: Map::default(),
+            endorsement_stats: Map::default(),
+        }
+    }
+
+    /// Single thread proof of stake, bootstrapped with the states of the cycles 5 to `LAST_FINAL_CYCLE`
+    /// updated by `update`. Rolls are credited back 4 cycles after their sale,
+    /// and deactivated 3 cycles after a cycle missing more than 70% of the blocks.
+    async fn get_pos(update: impl Fn(&mut ThreadCycleState)) -> ProofOfStake {
+        let cfg = ProofOfStakeConfig {
+            thread_count: 1,
+            genesis_key: generate_random_private_key(),
+            periods_per_cycle: PERIODS_PER_CYCLE,
+            pos_lookback_cycles: 2,
+            pos_lock_cycles: 1,
+            pos_draw_cached_cycles: 2,
+            pos_miss_rate_deactivation_threshold: Ratio::new(7, 10),
+            initial_rolls_path: Default::default(),
+            initial_draw_seed: "seed".into(),
+            roll_price: Amount::from_str("100").unwrap(),
+            endorsement_count: 1,
+        };
+        let cycle_states: VecDeque<ThreadCycleState> = (5..=LAST_FINAL_CYCLE)
+            .rev()
+            .map(|cycle| {
+                let mut state = cycle_state(cycle);
+                update(&mut state);
+                state
+            })
+            .collect();
+        ProofOfStake::new(
+            cfg,
+            &[],
+            Some(ExportProofOfStake {
+                cycle_states: vec![cycle_states],
+            }),
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_deferred_roll_sell_credits() {
+        let address = random_address();
+        let other_address = random_address();
+        let pos = get_pos(|state| {
+            let update = match state.cycle {
+                // credited at cycle 10, already final
+                6 => RollUpdate {
+                    roll_purchases: 0,
+                    roll_sales: 1,
+                },
+                // credited at cycle 11
+                7 => RollUpdate {
+                    roll_purchases: 1,
+                    roll_sales: 3,
+                },
+                // more purchases than sales
+                8 => RollUpdate {
+                    roll_purchases: 2,
+                    roll_sales: 1,
+                },
+                // credited at cycle 13
+                9 => RollUpdate {
+                    roll_purchases: 0,
+                    roll_sales: 1,
+                },
+                _ => return,
+            };
+            state.cycle_updates.0.insert(address, update);
+            state.cycle_updates.0.insert(
+                other_address,
+                RollUpdate {
+                    roll_purchases: 0,
+                    roll_sales: 5,
+                },
+            );
+        })
+        .await;
+
+        // credits not final yet, by increasing cycle
+        assert_eq!(
+            pos.get_deferred_roll_sell_credits(&address).unwrap(),
+            vec![
+                (11, Amount::from_str("200").unwrap()),
+                (13, Amount::from_str("100").unwrap())
+            ]
+        );
+        assert!(pos
+            .get_deferred_roll_sell_credits(&random_address())
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_roll_deactivation_risk() {
+        let address = random_address();
+        let pos = get_pos(|state| {
+            let stats = match state.cycle {
+                // deactivation at cycle 10, already final
+                7 => (0, 10),
+                // miss ratio at the threshold
+                8 => (3, 7),
+                // deactivation at cycle 12
+                9 => (2, 8),
+                // deactivation at cycle 13, even though the cycle is not complete
+                10 => {
+                    state.last_final_slot = Slot::new(LAST_FINAL_CYCLE * PERIODS_PER_CYCLE, 0);
+                    (0, 1)
+                }
+                _ => return,
+            };
+            state.production_stats.insert(address, stats);
+        })
+        .await;
+
+        assert_eq!(pos.get_roll_deactivation_risk(&address), vec![12, 13]);
+        assert!(pos.get_roll_deactivation_risk(&random_address()).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_endorsement_stats() {
+        let address = random_address();
+        let pos = get_pos(|state| {
+            state
+                .endorsement_stats
+                .insert(address, (state.cycle, state.cycle % 2));
+        })
+        .await;
+
+        // endorsement stats are reported by cycle along with the production stats
+        let mut addrs = Set::<Address>::default();
+        addrs.insert(address);
+        let mut stats = pos.get_stakers_production_stats(&addrs);
+        stats.sort_unstable_by_key(|cycle_stats| cycle_stats.cycle);
+        assert_eq!(stats.len(), 6);
+        for cycle_stats in stats {
+            assert!(cycle_stats.is_final);
+            assert_eq!(
+                cycle_stats.endorsement_ok_nok_counts[&address],
+                (cycle_stats.cycle, cycle_stats.cycle % 2)
+            );
+            assert_eq!(cycle_stats.ok_nok_counts[&address], (0, 0));
+        }
+
+        // they are not part of the bootstrap state and restart empty
+        let mut state = cycle_state(LAST_FINAL_CYCLE);
+        state.endorsement_stats.insert(address, (1, 1));
+        let mut serialized = Vec::new();
+        ThreadCycleStateSerializer::new()
+            .serialize(&state, &mut serialized)
+            .unwrap();
+        let (rest, deserialized) = ThreadCycleStateDeserializer::new()
+            .deserialize::<DeserializeError>(&serialized)
+            .unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(deserialized.cycle, LAST_FINAL_CYCLE);
+        assert!(deserialized.endorsement_stats.is_empty());
+    }
+}
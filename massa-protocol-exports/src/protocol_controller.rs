@@ -104,6 +104,8 @@ pub enum ProtocolCommand {
     GetOperationsResults((NodeId, OperationIds)),
     /// Propagate operations ids (send batches)
     PropagateOperations(OperationIds),
+    /// Stop asking for and propagating operations that were replaced in the pool
+    RemoveOperations(OperationIds),
     /// Propagate endorsements
     PropagateEndorsements(Map<EndorsementId, SignedEndorsement>),
 }
@@ -217,6 +219,20 @@ impl ProtocolCommandSender {
             })
     }
 
+    /// Stop asking for and propagating operations that were replaced in the pool.
+    pub async fn remove_operations(
+        &mut self,
+        operation_ids: OperationIds,
+    ) -> Result<(), ProtocolError> {
+        massa_trace!("protocol.command_sender.remove_operations", {
+            "operations": operation_ids
+        });
+        self.0
+            .send(ProtocolCommand::RemoveOperations(operation_ids))
+            .await
+            .map_err(|_| ProtocolError::ChannelError("remove_operations command send error".into()))
+    }
+
     /// propagate endorsements to connected node
     pub async fn propagate_endorsements(
         &mut self,
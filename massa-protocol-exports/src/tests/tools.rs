@@ -6,11 +6,13 @@ use crate::{
     ProtocolSettings,
 };
 use massa_hash::Hash;
+use massa_models::multisig::MultisigPolicy;
 use massa_models::node::NodeId;
 use massa_models::signed::{Signable, Signed};
 use massa_models::SerializeCompact;
 use massa_models::{
-    Address, Amount, Block, BlockHeader, BlockId, SignedEndorsement, SignedOperation, Slot,
+    get_serialization_context, Address, Amount, Block, BlockHeader, BlockId, SignedEndorsement,
+    SignedOperation, Slot,
 };
 use massa_models::{Endorsement, Operation, OperationType};
 use massa_network_exports::NetworkCommand;
@@ -90,7 +92,7 @@ pub fn create_block_with_operations(
 ) -> Block {
     let operation_merkle_root = Hash::compute_from(
         &operations.iter().fold(Vec::new(), |acc, v| {
-            [acc, v.content.compute_id().unwrap().to_bytes().to_vec()].concat()
+            [acc, v.get_merkle_leaf(&v.content.compute_id().unwrap())].concat()
         })[..],
     );
     let (_, header) = Signed::new_signed(
@@ -212,10 +214,45 @@ pub fn create_operation_with_expire_period(
         op,
         sender_public_key: sender_pub,
         expire_period,
+        multisig: None,
+        replaces: None,
     };
     Signed::new_signed(content, sender_priv).unwrap().1
 }
 
+/// Create an operation of a 2 of 2 multisig sender in a given thread, with its co-signature.
+pub fn create_co_signed_operation(thread: u8, expire_period: u64) -> SignedOperation {
+    let thread_count = get_serialization_context().thread_count;
+    let (private_keys, policy) = loop {
+        let private_keys: Vec<PrivateKey> = (0..2).map(|_| generate_random_private_key()).collect();
+        let policy =
+            MultisigPolicy::new(2, private_keys.iter().map(derive_public_key).collect()).unwrap();
+        if policy.get_address().get_thread(thread_count) == thread {
+            break (private_keys, policy);
+        }
+    };
+    let content = Operation {
+        fee: Amount::default(),
+        op: OperationType::Transaction {
+            recipient_address: Address::from_public_key(&derive_public_key(
+                &generate_random_private_key(),
+            )),
+            amount: Amount::default(),
+        },
+        sender_public_key: derive_public_key(&private_keys[0]),
+        expire_period,
+        multisig: Some(policy.clone()),
+        replaces: None,
+    };
+    let mut op = Signed::new_signed(content, &private_keys[0]).unwrap().1;
+    let co_signer_index = policy
+        .get_key_index(&derive_public_key(&private_keys[1]))
+        .unwrap();
+    op.add_co_signature(co_signer_index, &private_keys[1])
+        .unwrap();
+    op
+}
+
 lazy_static::lazy_static! {
     /// protocol settings
     pub static ref PROTOCOL_SETTINGS: ProtocolSettings = create_protocol_settings();
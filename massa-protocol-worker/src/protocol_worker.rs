@@ -11,7 +11,7 @@ use massa_models::{
     operation::{OperationIds, Operations},
     prehash::{BuildMap, Map, Set},
     signed::Signable,
-    Address, Block, BlockId, EndorsementId, OperationId, SignedEndorsement, SignedHeader,
+    Block, BlockId, EndorsementId, OperationId, SignedEndorsement, SignedHeader,
 };
 use massa_network_exports::{NetworkCommandSender, NetworkEvent, NetworkEventReceiver};
 use massa_protocol_exports::{
@@ -530,6 +530,22 @@ impl ProtocolWorker {
                     }
                 }
             }
+            ProtocolCommand::RemoveOperations(operation_ids) => {
+                massa_trace!(
+                    "protocol.protocol_worker.process_command.remove_operations.begin",
+                    { "operation_ids": operation_ids }
+                );
+                // keep them as checked so that they are not asked again when announced
+                self.checked_operations
+                    .extend(operation_ids.iter().cloned());
+                for op_id in operation_ids.iter() {
+                    self.asked_operations.remove(op_id);
+                }
+                for item in self.op_batch_buffer.iter_mut() {
+                    item.operations_ids
+                        .retain(|op_id| !operation_ids.contains(op_id));
+                }
+            }
             ProtocolCommand::PropagateEndorsements(endorsements) => {
                 massa_trace!(
                     "protocol.protocol_worker.process_command.propagate_endorsements.begin",
@@ -1006,10 +1022,16 @@ impl ProtocolWorker {
             massa_models::with_serialization_context(|context| context.clone());
 
         // Perform general checks on the operations, note them into caches and send them to pool
-        // but do not propagate as they are already propagating within a block
-        let (seen_ops, received_operations_ids, has_duplicate_operations, total_gas) = self
+        // but do not propagate as they are already propagating within a block.
+        // Invalid operations make the block invalid without invalidating its header:
+        // the same header may be received again with valid operations from another node.
+        let (seen_ops, received_operations_ids, has_duplicate_operations, total_gas) = match self
             .note_operations_from_node(operations.clone(), source_node_id, false, None)
-            .await?;
+            .await
+        {
+            Ok(v) => v,
+            Err(_) => return Ok(None),
+        };
         if total_gas > self.max_block_gas {
             // Gas usage over limit => block invalid
             // TODO remove this check in the single-ledger version,
@@ -1036,7 +1058,7 @@ impl ProtocolWorker {
             }
 
             // check address and thread
-            let addr = Address::from_public_key(&op.content.sender_public_key);
+            let addr = op.content.get_sender_address();
             if addr.get_thread(serialization_context.thread_count) != slot.thread {
                 massa_trace!("protocol.protocol_worker.note_block_from_node.err_op_thread",
                     { "node": source_node_id,"block_id":block_id, "op": op});
@@ -1044,11 +1066,12 @@ impl ProtocolWorker {
             }
         }
 
-        // check root hash
+        // check root hash, which also commits the co-signatures of the operations
         {
             let concat_bytes = seen_ops
                 .iter()
-                .map(|op_id| op_id.to_bytes().to_vec())
+                .zip(operations.iter())
+                .map(|(op_id, op)| op.get_merkle_leaf(op_id))
                 .concat();
             if operation_merkle_root != Hash::compute_from(&concat_bytes) {
                 massa_trace!("protocol.protocol_worker.note_block_from_node.err_op_root_hash",
@@ -1127,10 +1150,17 @@ impl ProtocolWorker {
             // Accumulate gas
             total_gas = total_gas.saturating_add(operation.content.get_gas_usage());
 
-            // Check operation signature only if not already checked.
-            if self.checked_operations.insert(operation_id) {
-                // check signature
-                operation.verify_signature(&operation.content.sender_public_key)?;
+            // Check operation signatures only if not already checked.
+            // The id does not cover the signatures, so an operation is only marked as checked once they are valid.
+            // The co-signatures of a multisig sender may differ between copies with the same id:
+            // those copies are always checked, and the valid ones are passed to pool
+            // which prefers them over a stored copy with invalid co-signatures.
+            if !self.checked_operations.contains(&operation_id)
+                || operation.content.multisig.is_some()
+            {
+                // check signatures, including the co-signatures of a multisig sender
+                operation.verify_integrity()?;
+                self.checked_operations.insert(operation_id);
 
                 new_operations.insert(operation_id, (operation, serialized));
             };
@@ -199,3 +199,129 @@ async fn test_protocol_sends_blocks_with_operations_to_consensus() {
     )
     .await;
 }
+
+#[tokio::test]
+#[serial]
+async fn test_protocol_rejects_blocks_with_stripped_co_signatures() {
+    let protocol_settings = &tools::PROTOCOL_SETTINGS;
+    protocol_test(
+        protocol_settings,
+        async move |mut network_controller,
+                    mut protocol_event_receiver,
+                    protocol_command_sender,
+                    protocol_manager,
+                    protocol_pool_event_receiver| {
+            // Create 2 nodes: a relay stripping the co-signatures and an honest one.
+            let mut nodes = create_and_connect_nodes(2, &mut network_controller).await;
+            let relay_node = nodes.pop().expect("Failed to get node info.");
+            let honest_node = nodes.pop().expect("Failed to get node info.");
+
+            // multisig sender in thread 0
+            let op = tools::create_co_signed_operation(0, 5);
+
+            let block = create_block_with_operations(
+                &honest_node.private_key,
+                &honest_node.id.0,
+                Slot::new(1, 0),
+                vec![op],
+            );
+
+            // the block relayed without the co-signatures is rejected
+            let mut stripped_block = block.clone();
+            stripped_block.operations[0].co_signatures.clear();
+            send_and_propagate_block(
+                &mut network_controller,
+                stripped_block,
+                false,
+                relay_node.id,
+                &mut protocol_event_receiver,
+            )
+            .await;
+
+            // the original block, with the same id, is still accepted
+            send_and_propagate_block(
+                &mut network_controller,
+                block,
+                true,
+                honest_node.id,
+                &mut protocol_event_receiver,
+            )
+            .await;
+
+            (
+                network_controller,
+                protocol_event_receiver,
+                protocol_command_sender,
+                protocol_manager,
+                protocol_pool_event_receiver,
+            )
+        },
+    )
+    .await;
+}
+
+#[tokio::test]
+#[serial]
+async fn test_protocol_checks_co_signatures_of_known_operations_in_blocks() {
+    let protocol_settings = &tools::PROTOCOL_SETTINGS;
+    protocol_test(
+        protocol_settings,
+        async move |mut network_controller,
+                    mut protocol_event_receiver,
+                    protocol_command_sender,
+                    protocol_manager,
+                    protocol_pool_event_receiver| {
+            let mut nodes = create_and_connect_nodes(1, &mut network_controller).await;
+            let creator_node = nodes.pop().expect("Failed to get node info.");
+
+            // the valid copy of a multisig operation is received and checked first
+            let op = tools::create_co_signed_operation(0, 5);
+            network_controller
+                .send_operations(creator_node.id, vec![op.clone()])
+                .await;
+
+            // a block committing a stripped copy with the same id is still rejected
+            let mut stripped_op = op.clone();
+            stripped_op.co_signatures.clear();
+            let block = create_block_with_operations(
+                &creator_node.private_key,
+                &creator_node.id.0,
+                Slot::new(1, 0),
+                vec![stripped_op],
+            );
+            send_and_propagate_block(
+                &mut network_controller,
+                block,
+                false,
+                creator_node.id,
+                &mut protocol_event_receiver,
+            )
+            .await;
+
+            // a block with the valid copy is accepted
+            let block = create_block_with_operations(
+                &creator_node.private_key,
+                &creator_node.id.0,
+                Slot::new(1, 0),
+                vec![op],
+            );
+            send_and_propagate_block(
+                &mut network_controller,
+                block,
+                true,
+                creator_node.id,
+                &mut protocol_event_receiver,
+            )
+            .await;
+
+            (
+                network_controller,
+                protocol_event_receiver,
+                protocol_command_sender,
+                protocol_manager,
+                protocol_pool_event_receiver,
+            )
+        },
+    )
+    .await;
+}
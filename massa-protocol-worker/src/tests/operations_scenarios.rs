@@ -156,6 +156,79 @@ async fn test_protocol_does_not_send_invalid_operations_it_receives_to_consensus
     .await;
 }
 
+#[tokio::test]
+#[serial]
+async fn test_protocol_sends_valid_copy_of_co_signed_operation_received_after_stripped_copy() {
+    let protocol_settings = &tools::PROTOCOL_SETTINGS;
+    protocol_test(
+        protocol_settings,
+        async move |mut network_controller,
+                    protocol_event_receiver,
+                    protocol_command_sender,
+                    protocol_manager,
+                    mut protocol_pool_event_receiver| {
+            // Create 2 nodes: a relay stripping the co-signatures and an honest one.
+            let mut nodes = tools::create_and_connect_nodes(2, &mut network_controller).await;
+            let relay_node = nodes.pop().expect("Failed to get node info.");
+            let honest_node = nodes.pop().expect("Failed to get node info.");
+
+            let operation = tools::create_co_signed_operation(0, 1);
+            let expected_operation_id = operation.verify_integrity().unwrap();
+            let mut stripped_operation = operation.clone();
+            stripped_operation.co_signatures.clear();
+
+            // The stripped copy arrives first: it is not sent to pool.
+            network_controller
+                .send_operations(relay_node.id, vec![stripped_operation])
+                .await;
+            if let Some(ProtocolPoolEvent::ReceivedOperations { .. }) =
+                tools::wait_protocol_pool_event(
+                    &mut protocol_pool_event_receiver,
+                    1000.into(),
+                    |evt| match evt {
+                        evt @ ProtocolPoolEvent::ReceivedOperations { .. } => Some(evt),
+                        _ => None,
+                    },
+                )
+                .await
+            {
+                panic!("Protocol sent the stripped copy.")
+            };
+
+            // The valid copy, with the same id, is sent to pool with its co-signatures.
+            network_controller
+                .send_operations(honest_node.id, vec![operation.clone()])
+                .await;
+            let received_operations = match tools::wait_protocol_pool_event(
+                &mut protocol_pool_event_receiver,
+                1000.into(),
+                |evt| match evt {
+                    evt @ ProtocolPoolEvent::ReceivedOperations { .. } => Some(evt),
+                    _ => None,
+                },
+            )
+            .await
+            {
+                Some(ProtocolPoolEvent::ReceivedOperations { operations, .. }) => operations,
+                _ => panic!("Unexpected or no protocol pool event."),
+            };
+            let (received_operation, _) = received_operations
+                .get(&expected_operation_id)
+                .expect("valid copy not sent to pool");
+            assert_eq!(received_operation.co_signatures, operation.co_signatures);
+
+            (
+                network_controller,
+                protocol_event_receiver,
+                protocol_command_sender,
+                protocol_manager,
+                protocol_pool_event_receiver,
+            )
+        },
+    )
+    .await;
+}
+
 #[tokio::test]
 #[serial]
 async fn test_protocol_propagates_operations_to_active_nodes() {
@@ -7,8 +7,10 @@
 use jsonrpc_core_client::transports::http;
 use jsonrpc_core_client::{RpcChannel, RpcError, RpcResult, TypedClient};
 use massa_models::api::{
-    AddressInfo, BlockInfo, BlockSummary, EndorsementInfo, EventFilter, NodeStatus, OperationInfo,
-    ReadOnlyBytecodeExecution, ReadOnlyCall, TimeInterval,
+    AddressInfo, BlockInfo, BlockSummary, DatastoreEntryInput, DatastoreEntryOutput,
+    EndorsementInfo, EventFilter, GasEstimation, GasEstimationInput, HistoricalLedgerInput,
+    HistoricalLedgerOutput, NodeStatus, OperationInfo, PendingOperationInfo,
+    ReadOnlyBytecodeExecution, ReadOnlyCall, StakerReport, TimeInterval,
 };
 use massa_models::clique::Clique;
 use massa_models::composite::PubkeySig;
@@ -16,7 +18,8 @@ use massa_models::execution::ExecuteReadOnlyResponse;
 use massa_models::node::NodeId;
 use massa_models::output_event::SCOutputEvent;
 use massa_models::prehash::{Map, Set};
-use massa_models::{Address, BlockId, EndorsementId, OperationId, SignedOperation};
+use massa_models::stats::PoolThreadFeeStats;
+use massa_models::{Address, BlockId, EndorsementId, OperationId, SignedOperation, Slot};
 use massa_signature::PrivateKey;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
@@ -97,14 +100,14 @@ impl RpcClient {
     }
 
     /// Add a vector of new private keys for the node to use to stake.
-    /// No confirmation to expect.
+    /// Fails if the staking keys are locked.
     pub async fn add_staking_private_keys(&self, private_keys: Vec<PrivateKey>) -> RpcResult<()> {
         self.call_method("add_staking_private_keys", "()", vec![private_keys])
             .await
     }
 
     /// Remove a vector of addresses used to stake.
-    /// No confirmation to expect.
+    /// Fails if the staking keys are locked.
     pub async fn remove_staking_addresses(&self, addresses: Vec<Address>) -> RpcResult<()> {
         self.call_method("remove_staking_addresses", "()", vec![addresses])
             .await
@@ -116,6 +119,18 @@ impl RpcClient {
             .await
     }
 
+    /// Unlock the staking keys with their password.
+    /// Returns the unlocked staking addresses.
+    pub async fn unlock_staking_keys(&self, password: String) -> RpcResult<Set<Address>> {
+        self.call_method("unlock_staking_keys", "Set<Address>", vec![password])
+            .await
+    }
+
+    /// Lock the staking keys, the node stops staking until they are unlocked again.
+    pub async fn lock_staking_keys(&self) -> RpcResult<()> {
+        self.call_method("lock_staking_keys", "()", ()).await
+    }
+
     /// Bans given ip address(es)
     /// No confirmation to expect.
     pub async fn node_ban_by_ip(&self, ips: Vec<IpAddr>) -> RpcResult<()> {
@@ -175,6 +190,15 @@ impl RpcClient {
             .await
     }
 
+    /// Returns draws, production stats, roll deactivation risk and deferred credits of staking addresses
+    pub async fn get_staker_reports(
+        &self,
+        addresses: Vec<Address>,
+    ) -> RpcResult<Vec<StakerReport>> {
+        self.call_method("get_staker_reports", "Vec<StakerReport>", vec![addresses])
+            .await
+    }
+
     /// Returns operations information associated to a given list of operations' IDs.
     pub async fn get_operations(
         &self,
@@ -184,6 +208,30 @@ impl RpcClient {
             .await
     }
 
+    /// Returns the operations involving an address that are waiting in the pool.
+    pub async fn get_pending_operations(
+        &self,
+        address: Address,
+    ) -> RpcResult<Vec<PendingOperationInfo>> {
+        self.call_method(
+            "get_pending_operations",
+            "Vec<PendingOperationInfo>",
+            vec![address],
+        )
+        .await
+    }
+
+    /// Returns the fee statistics of the pool for each thread,
+    /// with the fee suggested to be included within `slot_count` slots.
+    pub async fn get_pool_fee_stats(&self, slot_count: u64) -> RpcResult<Vec<PoolThreadFeeStats>> {
+        self.call_method(
+            "get_pool_fee_stats",
+            "Vec<PoolThreadFeeStats>",
+            vec![slot_count],
+        )
+        .await
+    }
+
     /// get info on endorsements by ids
     pub async fn get_endorsements(
         &self,
@@ -203,6 +251,12 @@ impl RpcClient {
             .await
     }
 
+    /// Get the final block of a slot, if any
+    pub async fn get_block_by_slot(&self, slot: Slot) -> RpcResult<Option<BlockInfo>> {
+        self.call_method("get_block_by_slot", "Option<BlockInfo>", vec![slot])
+            .await
+    }
+
     /// Get events emitted by smart contracts with various filters
     pub async fn get_filtered_sc_output_event(
         &self,
@@ -232,6 +286,28 @@ impl RpcClient {
             .await
     }
 
+    /// Get the final and active values of a datastore entry
+    pub async fn get_datastore_entry(
+        &self,
+        input: DatastoreEntryInput,
+    ) -> RpcResult<DatastoreEntryOutput> {
+        self.call_method("get_datastore_entry", "DatastoreEntryOutput", vec![input])
+            .await
+    }
+
+    /// Get the balance, bytecode and some datastore entries of an address at the output of a final slot
+    pub async fn get_historical_ledger_info(
+        &self,
+        input: HistoricalLedgerInput,
+    ) -> RpcResult<HistoricalLedgerOutput> {
+        self.call_method(
+            "get_historical_ledger_info",
+            "HistoricalLedgerOutput",
+            vec![input],
+        )
+        .await
+    }
+
     // User (interaction with the node)
 
     /// Adds operations to pool. Returns operations that were ok and sent to pool.
@@ -274,4 +350,16 @@ impl RpcClient {
         .pop()
         .ok_or_else(|| RpcError::Client("missing return value on execute_read_only_call".into()))
     }
+
+    /// estimate the gas needed by an `ExecuteSC` or `CallSC` operation
+    pub async fn estimate_gas(&self, input: GasEstimationInput) -> RpcResult<GasEstimation> {
+        self.call_method::<Vec<Vec<GasEstimationInput>>, Vec<GasEstimation>>(
+            "estimate_gas",
+            "Vec<GasEstimation>",
+            vec![vec![input]],
+        )
+        .await?
+        .pop()
+        .ok_or_else(|| RpcError::Client("missing return value on estimate_gas".into()))
+    }
 }
@@ -0,0 +1,208 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! This module provides an optional on-disk archive of final blocks.
+//! Unlike the blocks kept in RAM by the `Storage`, archived blocks are never pruned.
+//!
+//! Blocks are stored in a RocksDB instance with the following column families:
+//! * `blocks`: block id -> serialized block
+//! * `blocks_by_slot`: slot period (big endian) and thread -> block id
+//! * `blocks_by_operation`: operation id -> id of the final block including it
+
+use massa_models::signed::Signable;
+use massa_models::{Block, BlockId, DeserializeCompact, OperationId, SignedOperation, Slot};
+use rocksdb::{ColumnFamilyDescriptor, Options, WriteBatch, DB};
+use std::path::PathBuf;
+use tracing::warn;
+
+const BLOCKS_CF: &str = "blocks";
+const BLOCKS_BY_SLOT_CF: &str = "blocks_by_slot";
+const BLOCKS_BY_OPERATION_CF: &str = "blocks_by_operation";
+const OPEN_ERROR: &str = "critical: rocksdb open operation failed";
+const CRUD_ERROR: &str = "critical: rocksdb crud operation failed";
+const CF_ERROR: &str = "critical: rocksdb column family operation failed";
+
+/// On-disk archive of final blocks
+pub struct BlockArchive {
+    /// RocksDB instance
+    db: DB,
+}
+
+impl BlockArchive {
+    /// Open or create the block archive
+    ///
+    /// # Arguments
+    /// * `path`: path to the RocksDB directory
+    pub fn new(path: PathBuf) -> Self {
+        let mut db_opts = Options::default();
+        db_opts.create_if_missing(true);
+        db_opts.create_missing_column_families(true);
+
+        let db = DB::open_cf_descriptors(
+            &db_opts,
+            path,
+            vec![
+                ColumnFamilyDescriptor::new(BLOCKS_CF, Options::default()),
+                ColumnFamilyDescriptor::new(BLOCKS_BY_SLOT_CF, Options::default()),
+                ColumnFamilyDescriptor::new(BLOCKS_BY_OPERATION_CF, Options::default()),
+            ],
+        )
+        .expect(OPEN_ERROR);
+
+        BlockArchive { db }
+    }
+
+    /// Archive a final block and index it by slot and by included operation
+    ///
+    /// # Arguments
+    /// * `block_id`: id of the block
+    /// * `block`: the block
+    /// * `serialized`: serialized representation of the block
+    pub fn insert_block(&self, block_id: &BlockId, block: &Block, serialized: &[u8]) {
+        let mut batch = WriteBatch::default();
+        batch.put_cf(
+            self.db.cf_handle(BLOCKS_CF).expect(CF_ERROR),
+            block_id.to_bytes(),
+            serialized,
+        );
+        batch.put_cf(
+            self.db.cf_handle(BLOCKS_BY_SLOT_CF).expect(CF_ERROR),
+            block.header.content.slot.to_bytes_key(),
+            block_id.to_bytes(),
+        );
+        let operations_handle = self.db.cf_handle(BLOCKS_BY_OPERATION_CF).expect(CF_ERROR);
+        for operation in block.operations.iter() {
+            match operation.content.compute_id() {
+                Ok(operation_id) => batch.put_cf(
+                    operations_handle,
+                    operation_id.to_bytes(),
+                    block_id.to_bytes(),
+                ),
+                Err(err) => warn!(
+                    "could not compute the id of an operation of archived block {}: {}",
+                    block_id, err
+                ),
+            }
+        }
+        self.db.write(batch).expect(CRUD_ERROR);
+    }
+
+    /// Get an archived block
+    pub fn get_block(&self, block_id: &BlockId) -> Option<Block> {
+        let handle = self.db.cf_handle(BLOCKS_CF).expect(CF_ERROR);
+        self.db
+            .get_cf(handle, block_id.to_bytes())
+            .expect(CRUD_ERROR)
+            .map(|bytes| Block::from_bytes_compact(&bytes).expect(CRUD_ERROR).0)
+    }
+
+    /// Get the id of the archived final block of a slot
+    pub fn get_block_id_by_slot(&self, slot: &Slot) -> Option<BlockId> {
+        let handle = self.db.cf_handle(BLOCKS_BY_SLOT_CF).expect(CF_ERROR);
+        self.db
+            .get_cf(handle, slot.to_bytes_key())
+            .expect(CRUD_ERROR)
+            .map(|bytes| BlockId::from_bytes(&bytes[..].try_into().expect(CRUD_ERROR)))
+    }
+
+    /// Get an archived operation along with the id of the final block including it
+    pub fn get_operation(&self, operation_id: &OperationId) -> Option<(SignedOperation, BlockId)> {
+        let handle = self.db.cf_handle(BLOCKS_BY_OPERATION_CF).expect(CF_ERROR);
+        let block_id = self
+            .db
+            .get_cf(handle, operation_id.to_bytes())
+            .expect(CRUD_ERROR)
+            .map(|bytes| BlockId::from_bytes(&bytes[..].try_into().expect(CRUD_ERROR)))?;
+        let operation = self
+            .get_block(&block_id)?
+            .operations
+            .into_iter()
+            .find(|op| {
+                op.content
+                    .compute_id()
+                    .map_or(false, |id| id == *operation_id)
+            })?;
+        Some((operation, block_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BlockArchive;
+    use massa_hash::Hash;
+    use massa_models::signed::{Signable, Signed};
+    use massa_models::{
+        Address, Amount, Block, BlockHeader, BlockId, Operation, OperationType, SerializeCompact,
+        Slot,
+    };
+    use massa_signature::{derive_public_key, generate_random_private_key};
+    use tempfile::TempDir;
+
+    /// Archived blocks can be read back by id, by slot and by included operation
+    #[test]
+    fn test_block_archive() {
+        massa_models::init_serialization_context(massa_models::SerializationContext::default());
+        let private_key = generate_random_private_key();
+        let public_key = derive_public_key(&private_key);
+        let operation = Signed::new_signed(
+            Operation {
+                sender_public_key: public_key,
+                fee: Amount::default(),
+                expire_period: 10,
+                op: OperationType::Transaction {
+                    recipient_address: Address::from_public_key(&public_key),
+                    amount: Amount::from_raw(1),
+                },
+                multisig: None,
+                replaces: None,
+            },
+            &private_key,
+        )
+        .unwrap()
+        .1;
+        let operation_id = operation.content.compute_id().unwrap();
+        let slot = Slot::new(1, 0);
+        let (block_id, header) = Signed::new_signed(
+            BlockHeader {
+                creator: public_key,
+                slot,
+                parents: Vec::new(),
+                operation_merkle_root: Hash::compute_from(operation_id.to_bytes()),
+                endorsements: Vec::new(),
+            },
+            &private_key,
+        )
+        .unwrap();
+        let block = Block {
+            header,
+            operations: vec![operation],
+        };
+
+        let temp_dir = TempDir::new().unwrap();
+        let archive = BlockArchive::new(temp_dir.path().to_path_buf());
+        archive.insert_block(&block_id, &block, &block.to_bytes_compact().unwrap());
+
+        // blocks
+        let archived_block = archive.get_block(&block_id).unwrap();
+        assert_eq!(
+            archived_block.header.content.compute_id().unwrap(),
+            block_id
+        );
+        assert_eq!(archived_block.operations.len(), 1);
+        assert!(archive
+            .get_block(&BlockId(Hash::compute_from(b"unknown")))
+            .is_none());
+
+        // blocks_by_slot
+        assert_eq!(archive.get_block_id_by_slot(&slot), Some(block_id));
+        assert!(archive.get_block_id_by_slot(&Slot::new(1, 1)).is_none());
+
+        // blocks_by_operation
+        let (archived_operation, including_block_id) =
+            archive.get_operation(&operation_id).unwrap();
+        assert_eq!(including_block_id, block_id);
+        assert_eq!(
+            archived_operation.content.compute_id().unwrap(),
+            operation_id
+        );
+    }
+}
@@ -2,12 +2,19 @@
 
 #![warn(missing_docs)]
 
+mod block_archive;
+
+pub use block_archive::BlockArchive;
+
 use massa_logging::massa_trace;
 use massa_models::prehash::Map;
-use massa_models::{Block, BlockId, OperationId, SignedOperation};
-use parking_lot::RwLock;
+use massa_models::{Block, BlockId, OperationId, SignedOperation, Slot};
+use parking_lot::{Mutex, RwLock};
 use std::collections::hash_map::Entry;
+use std::path::PathBuf;
+use std::sync::mpsc::{channel, Sender};
 use std::sync::Arc;
+use tracing::warn;
 
 /// Stored block: block + serialized block + (serialized header)
 #[derive(Debug)]
@@ -35,9 +42,81 @@ pub struct StoredOperation {
 pub struct Storage {
     blocks: Arc<RwLock<Map<BlockId, Arc<RwLock<StoredBlock>>>>>,
     operations: Arc<RwLock<Map<OperationId, StoredOperation>>>,
+    /// optional on-disk archive of final blocks
+    archive: Option<Arc<BlockArchive>>,
+    /// channel to the thread writing final blocks to the archive,
+    /// so that finalization never waits for the disk
+    archive_sender: Option<Arc<Mutex<Sender<(BlockId, Arc<RwLock<StoredBlock>>)>>>>,
 }
 
 impl Storage {
+    /// Create a storage that also archives final blocks on disk.
+    ///
+    /// # Arguments
+    /// * `archive_path`: path to the block archive directory
+    /// The archive is written by a dedicated thread, which stops once every clone of the storage is dropped.
+    pub fn with_block_archive(archive_path: PathBuf) -> Self {
+        let archive = Arc::new(BlockArchive::new(archive_path));
+        let (archive_sender, archive_receiver) = channel::<(BlockId, Arc<RwLock<StoredBlock>>)>();
+        let writer_archive = archive.clone();
+        std::thread::spawn(move || {
+            for (block_id, stored_block) in archive_receiver {
+                let stored_block = stored_block.read();
+                writer_archive.insert_block(
+                    &block_id,
+                    &stored_block.block,
+                    &stored_block.serialized,
+                );
+            }
+        });
+        Storage {
+            archive: Some(archive),
+            archive_sender: Some(Arc::new(Mutex::new(archive_sender))),
+            ..Default::default()
+        }
+    }
+
+    /// Queue a stored block that became final for archiving, if the block archive is enabled.
+    /// The block is kept alive until it is written, even if it is removed from the storage in the meantime.
+    pub fn archive_final_block(&self, block_id: &BlockId) {
+        if let Some(archive_sender) = &self.archive_sender {
+            massa_trace!("storage.storage.archive_final_block", { "block_id": block_id });
+            if let Some(stored_block) = self.retrieve_block(block_id) {
+                if archive_sender
+                    .lock()
+                    .send((*block_id, stored_block))
+                    .is_err()
+                {
+                    warn!(
+                        "block archive writer stopped: block {} was not archived",
+                        block_id
+                    );
+                }
+            }
+        }
+    }
+
+    /// Get a final block from the block archive.
+    /// Returns `None` if the block archive is disabled or if the block was not archived.
+    pub fn get_archived_block(&self, block_id: &BlockId) -> Option<Block> {
+        self.archive.as_ref()?.get_block(block_id)
+    }
+
+    /// Get the id of the final block of a slot from the block archive.
+    /// Returns `None` if the block archive is disabled or if there is no archived block at that slot.
+    pub fn get_archived_block_id_by_slot(&self, slot: &Slot) -> Option<BlockId> {
+        self.archive.as_ref()?.get_block_id_by_slot(slot)
+    }
+
+    /// Get an operation and the id of the final block including it from the block archive.
+    /// Returns `None` if the block archive is disabled or if the operation is not in an archived block.
+    pub fn get_archived_operation(
+        &self,
+        operation_id: &OperationId,
+    ) -> Option<(SignedOperation, BlockId)> {
+        self.archive.as_ref()?.get_operation(operation_id)
+    }
+
     /// Store a block, along with it's serialized representation.
     pub fn store_block(&self, block_id: BlockId, block: Block, serialized: Vec<u8>) {
         massa_trace!("storage.storage.store_block", { "block_id": block_id });
@@ -143,3 +222,52 @@ impl Storage {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Storage;
+    use massa_hash::Hash;
+    use massa_models::signed::Signed;
+    use massa_models::{Block, BlockHeader, SerializeCompact, Slot};
+    use massa_signature::{derive_public_key, generate_random_private_key};
+    use std::time::{Duration, Instant};
+    use tempfile::TempDir;
+
+    /// Final blocks are archived by the writer thread, even once removed from the storage
+    #[test]
+    fn test_archive_final_block() {
+        massa_models::init_serialization_context(massa_models::SerializationContext::default());
+        let private_key = generate_random_private_key();
+        let slot = Slot::new(2, 1);
+        let (block_id, header) = Signed::new_signed(
+            BlockHeader {
+                creator: derive_public_key(&private_key),
+                slot,
+                parents: Vec::new(),
+                operation_merkle_root: Hash::compute_from(&[]),
+                endorsements: Vec::new(),
+            },
+            &private_key,
+        )
+        .unwrap();
+        let block = Block {
+            header,
+            operations: Vec::new(),
+        };
+        let serialized = block.to_bytes_compact().unwrap();
+
+        let temp_dir = TempDir::new().unwrap();
+        let storage = Storage::with_block_archive(temp_dir.path().to_path_buf());
+        storage.store_block(block_id, block, serialized);
+        storage.archive_final_block(&block_id);
+        storage.remove_blocks(&[block_id]);
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while storage.get_archived_block_id_by_slot(&slot).is_none() {
+            assert!(Instant::now() < deadline, "block was not archived");
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(storage.get_archived_block_id_by_slot(&slot), Some(block_id));
+        assert!(storage.get_archived_block(&block_id).is_some());
+    }
+}
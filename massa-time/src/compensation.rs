@@ -0,0 +1,85 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+use crate::{MassaTime, TimeError};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+
+/// Value of `measured_drift` before the first measurement
+const DRIFT_NOT_MEASURED: i64 = i64::MIN;
+
+/// Clock compensation shared between the components of the node.
+/// It is updated live by the clock synchronization, and every clone sees the updates.
+#[derive(Debug, Clone)]
+pub struct ClockCompensation {
+    /// signed number of milliseconds added to the local clock
+    compensation_millis: Arc<AtomicI64>,
+    /// drift of the compensated clock measured by the latest clock synchronization, in milliseconds
+    measured_drift: Arc<AtomicI64>,
+}
+
+impl ClockCompensation {
+    /// Creates a shared clock compensation
+    ///
+    /// # Arguments
+    /// * `compensation_millis`: initial compensation, usually measured during bootstrap
+    pub fn new(compensation_millis: i64) -> Self {
+        ClockCompensation {
+            compensation_millis: Arc::new(AtomicI64::new(compensation_millis)),
+            measured_drift: Arc::new(AtomicI64::new(DRIFT_NOT_MEASURED)),
+        }
+    }
+
+    /// Gets the current compensation in milliseconds
+    pub fn get(&self) -> i64 {
+        self.compensation_millis.load(Ordering::Relaxed)
+    }
+
+    /// Sets the compensation in milliseconds, effective immediately for all the clones
+    pub fn set(&self, compensation_millis: i64) {
+        self.compensation_millis
+            .store(compensation_millis, Ordering::Relaxed);
+    }
+
+    /// Gets the drift of the compensated clock measured by the latest clock synchronization,
+    /// none if it was never measured
+    pub fn get_measured_drift(&self) -> Option<i64> {
+        match self.measured_drift.load(Ordering::Relaxed) {
+            DRIFT_NOT_MEASURED => None,
+            drift => Some(drift),
+        }
+    }
+
+    /// Records the drift of the compensated clock measured by a clock synchronization
+    pub fn set_measured_drift(&self, drift_millis: i64) {
+        self.measured_drift
+            .store(drift_millis.max(DRIFT_NOT_MEASURED + 1), Ordering::Relaxed);
+    }
+
+    /// Gets the current compensated UNIX timestamp, see `MassaTime::compensated_now`
+    pub fn now(&self) -> Result<MassaTime, TimeError> {
+        MassaTime::compensated_now(self.get())
+    }
+}
+
+impl Default for ClockCompensation {
+    fn default() -> Self {
+        ClockCompensation::new(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shared_compensation() {
+        let compensation = ClockCompensation::new(10);
+        let clone = compensation.clone();
+        assert_eq!(clone.get(), 10);
+        assert_eq!(clone.get_measured_drift(), None);
+        compensation.set(-25);
+        compensation.set_measured_drift(-35);
+        assert_eq!(clone.get(), -25);
+        assert_eq!(clone.get_measured_drift(), Some(-35));
+    }
+}
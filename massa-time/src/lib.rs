@@ -4,7 +4,9 @@
 #![warn(unused_crate_dependencies)]
 #![feature(bound_map)]
 
+mod compensation;
 mod error;
+pub use compensation::ClockCompensation;
 pub use error::TimeError;
 use massa_serialization::{Deserializer, Serializer, U64VarIntDeserializer, U64VarIntSerializer};
 use nom::error::{context, ContextError, ParseError};
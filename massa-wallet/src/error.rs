@@ -22,4 +22,12 @@ pub enum WalletError {
     MissingKeyError(Address),
     /// `MassaCipher` error: {0}
     MassaCipherError(#[from] massa_cipher::CipherError),
+    /// Mnemonic error: {0}
+    MnemonicError(String),
+    /// Key derivation error: {0}
+    DerivationError(String),
+    /// The wallet has no mnemonic
+    MissingMnemonicError,
+    /// The wallet already has a mnemonic
+    MnemonicAlreadySetError,
 }
@@ -0,0 +1,137 @@
+// Copyright (c) 2022 MASSA LABS <info@massa.net>
+
+//! Hierarchical deterministic key derivation.
+//!
+//! Seeds are BIP-39 mnemonics (with an empty passphrase) and keys are derived from them following BIP-32.
+//! Only hardened derivation is used, the private key of account `i` is derived along the path
+//! `m/44'/632'/i'/0'/0'` (632 being the SLIP-44 coin type of Massa).
+
+use crate::WalletError;
+use bip39::Mnemonic;
+use hmac::{Hmac, Mac};
+use massa_signature::PrivateKey;
+use secp256k1::SecretKey;
+use sha2::Sha512;
+
+/// SLIP-44 coin type of Massa
+pub const MASSA_COIN_TYPE: u32 = 632;
+
+/// Number of words of the generated mnemonics
+pub const MNEMONIC_WORD_COUNT: usize = 24;
+
+/// Offset of the hardened child indices
+const HARDENED_OFFSET: u32 = 1 << 31;
+
+/// Generates a new random mnemonic
+pub fn generate_mnemonic() -> Mnemonic {
+    let entropy: [u8; MNEMONIC_WORD_COUNT * 4 / 3] = secp256k1::rand::random();
+    // the entropy length matches a valid word count
+    Mnemonic::from_entropy(&entropy).unwrap()
+}
+
+/// Parses a mnemonic, checking its words and checksum
+pub fn parse_mnemonic(phrase: &str) -> Result<Mnemonic, WalletError> {
+    Mnemonic::parse(phrase).map_err(|err| WalletError::MnemonicError(err.to_string()))
+}
+
+/// Derives the private key of an account from a mnemonic
+///
+/// # Arguments
+/// * `mnemonic`: seed of the wallet
+/// * `account`: index of the account
+pub fn derive_account_private_key(
+    mnemonic: &Mnemonic,
+    account: u32,
+) -> Result<PrivateKey, WalletError> {
+    derive_private_key(&mnemonic.to_seed(""), &[44, MASSA_COIN_TYPE, account, 0, 0])
+}
+
+/// Derives a private key from a seed along a path of hardened child indices
+fn derive_private_key(seed: &[u8], path: &[u32]) -> Result<PrivateKey, WalletError> {
+    let (mut key, mut chain_code) = split_extended_key(hmac_sha512(b"Bitcoin seed", &[seed]))?;
+    for index in path {
+        if *index >= HARDENED_OFFSET {
+            return Err(WalletError::DerivationError(format!(
+                "child index {} is too big",
+                index
+            )));
+        }
+        let (tweak, child_chain_code) = split_extended_key(hmac_sha512(
+            &chain_code,
+            &[
+                &[0],
+                &key.secret_bytes(),
+                &(index + HARDENED_OFFSET).to_be_bytes(),
+            ],
+        ))?;
+        key.add_assign(&tweak.secret_bytes())
+            .map_err(|err| WalletError::DerivationError(err.to_string()))?;
+        chain_code = child_chain_code;
+    }
+    PrivateKey::from_bytes(&key.secret_bytes())
+        .map_err(|err| WalletError::DerivationError(err.to_string()))
+}
+
+/// Computes the HMAC-SHA512 of the concatenation of `data` parts
+fn hmac_sha512(key: &[u8], data: &[&[u8]]) -> [u8; 64] {
+    // HMAC accepts keys of any length
+    let mut mac = Hmac::<Sha512>::new_from_slice(key).unwrap();
+    for part in data {
+        mac.update(part);
+    }
+    let mut bytes = [0u8; 64];
+    bytes.copy_from_slice(&mac.finalize().into_bytes());
+    bytes
+}
+
+/// Splits a 64 bytes HMAC output into a key and a chain code.
+/// Fails in the (negligible) case where the key is not a valid secret key.
+fn split_extended_key(bytes: [u8; 64]) -> Result<(SecretKey, [u8; 32]), WalletError> {
+    let key = SecretKey::from_slice(&bytes[..32])
+        .map_err(|err| WalletError::DerivationError(err.to_string()))?;
+    let mut chain_code = [0u8; 32];
+    chain_code.copy_from_slice(&bytes[32..]);
+    Ok((key, chain_code))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// BIP-32 test vector 1
+    #[test]
+    fn test_derive_private_key() {
+        let seed = (0u8..16).collect::<Vec<_>>();
+        assert_eq!(
+            derive_private_key(&seed, &[]).unwrap().to_bytes().to_vec(),
+            hex_to_bytes("e8f32e723decf4051aefac8e2c93c9c5b214313817cdb01a1494b917c8436b35")
+        );
+        assert_eq!(
+            derive_private_key(&seed, &[0]).unwrap().to_bytes().to_vec(),
+            hex_to_bytes("edb2e14f9ee77d26dd93b4ecede8d16ed408ce149b6cd80b0715a2d911a0afea")
+        );
+    }
+
+    #[test]
+    fn test_mnemonic_round_trip() {
+        let mnemonic = generate_mnemonic();
+        assert_eq!(mnemonic.word_count(), MNEMONIC_WORD_COUNT);
+        let parsed = parse_mnemonic(&mnemonic.to_string()).unwrap();
+        assert_eq!(
+            derive_account_private_key(&mnemonic, 1).unwrap().to_bytes(),
+            derive_account_private_key(&parsed, 1).unwrap().to_bytes()
+        );
+        assert_ne!(
+            derive_account_private_key(&mnemonic, 0).unwrap().to_bytes(),
+            derive_account_private_key(&mnemonic, 1).unwrap().to_bytes()
+        );
+        assert!(parse_mnemonic("not a valid mnemonic").is_err());
+    }
+
+    fn hex_to_bytes(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+}
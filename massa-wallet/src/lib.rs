@@ -1,57 +1,95 @@
 // Copyright (c) 2022 MASSA LABS <info@massa.net>
 //! Standalone massa wallet
-//! private key management
+//! private key management, with hierarchical deterministic derivation from a mnemonic seed phrase
 #![warn(missing_docs)]
 #![warn(unused_crate_dependencies)]
 
 pub use error::WalletError;
 
-use massa_cipher::{decrypt, encrypt};
+use massa_cipher::{decrypt, encrypt, needs_upgrade};
 use massa_hash::Hash;
 use massa_models::address::Address;
 use massa_models::composite::PubkeySig;
 use massa_models::prehash::{Map, Set};
 use massa_models::signed::Signed;
-use massa_models::{Operation, SignedOperation};
+use massa_models::{ModelsError, Operation, SignedOperation};
 use massa_signature::{derive_public_key, sign, PrivateKey, PublicKey};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
 mod error;
+pub mod hd;
 
 /// Contains the private keys created in the wallet.
 #[derive(Clone, Debug, Deserialize, Serialize)]
 pub struct Wallet {
     /// Private keys and derived public keys and addresses
     pub keys: Map<Address, (PublicKey, PrivateKey)>,
-    /// Path to the file containing the private keys (not encrypted)
+    /// Mnemonic seed phrase from which account keys are derived, if any
+    pub mnemonic: Option<String>,
+    /// Index of the next account to derive from the mnemonic
+    pub next_account_index: u32,
+    /// Path to the file containing the encrypted private keys
     pub wallet_path: PathBuf,
     /// Password
     pub password: String,
 }
 
+/// Content of the wallet file, before encryption
+#[derive(Deserialize, Serialize)]
+struct WalletFileContent {
+    /// mnemonic seed phrase
+    mnemonic: Option<String>,
+    /// index of the next account to derive
+    next_account_index: u32,
+    /// every private key of the wallet, derived or not
+    private_keys: Vec<PrivateKey>,
+}
+
 impl Wallet {
     /// Generates a new wallet initialized with the provided file content
+    ///
+    /// A wallet file written with an outdated encryption format is re-encrypted with the current one.
     pub fn new(path: PathBuf, password: String) -> Result<Wallet, WalletError> {
         if path.is_file() {
             let content = &std::fs::read(&path)?[..];
             let decrypted_content = decrypt(&password, content)?;
-            let priv_keys = serde_json::from_slice::<Vec<PrivateKey>>(&decrypted_content[..])?;
-            let keys: Result<Map<Address, (PublicKey, PrivateKey)>, WalletError> = priv_keys
+            // wallets without mnemonic used to only store a list of private keys
+            let file_content =
+                match serde_json::from_slice::<WalletFileContent>(&decrypted_content[..]) {
+                    Ok(file_content) => file_content,
+                    Err(_) => WalletFileContent {
+                        mnemonic: None,
+                        next_account_index: 0,
+                        private_keys: serde_json::from_slice::<Vec<PrivateKey>>(
+                            &decrypted_content[..],
+                        )?,
+                    },
+                };
+            let keys: Result<Map<Address, (PublicKey, PrivateKey)>, WalletError> = file_content
+                .private_keys
                 .iter()
                 .map(|priv_key| {
                     let pub_key = derive_public_key(priv_key);
                     Ok((Address::from_public_key(&pub_key), (pub_key, *priv_key)))
                 })
                 .collect();
-            Ok(Wallet {
+            let wallet = Wallet {
                 keys: keys?,
+                mnemonic: file_content.mnemonic,
+                next_account_index: file_content.next_account_index,
                 wallet_path: path,
                 password,
-            })
+            };
+            if needs_upgrade(content) {
+                wallet.save()?;
+            }
+            Ok(wallet)
         } else {
             let wallet = Wallet {
                 keys: Map::default(),
+                mnemonic: None,
+                next_account_index: 0,
                 wallet_path: path,
                 password,
             };
@@ -98,6 +136,62 @@ impl Wallet {
         }
     }
 
+    /// Generates a new mnemonic seed phrase and uses it as the seed of the wallet
+    /// returns the generated mnemonic, to be backed up by the user
+    pub fn generate_mnemonic(&mut self) -> Result<String, WalletError> {
+        let mnemonic = hd::generate_mnemonic().to_string();
+        self.set_mnemonic(&mnemonic)?;
+        Ok(mnemonic)
+    }
+
+    /// Uses the given mnemonic seed phrase as the seed of the wallet.
+    /// Fails if the wallet already has one.
+    /// No key is derived: use `derive_next_address` or `restore_accounts` to do so.
+    pub fn set_mnemonic(&mut self, mnemonic: &str) -> Result<(), WalletError> {
+        if self.mnemonic.is_some() {
+            return Err(WalletError::MnemonicAlreadySetError);
+        }
+        self.mnemonic = Some(hd::parse_mnemonic(mnemonic)?.to_string());
+        self.next_account_index = 0;
+        self.save()
+    }
+
+    /// Derives the private key and address of an account from the mnemonic of the wallet,
+    /// without adding them to the wallet
+    pub fn derive_account(&self, account: u32) -> Result<(Address, PrivateKey), WalletError> {
+        let mnemonic = hd::parse_mnemonic(
+            self.mnemonic
+                .as_ref()
+                .ok_or(WalletError::MissingMnemonicError)?,
+        )?;
+        let key = hd::derive_account_private_key(&mnemonic, account)?;
+        Ok((Address::from_public_key(&derive_public_key(&key)), key))
+    }
+
+    /// Derives the next account from the mnemonic of the wallet and adds its key to the wallet
+    /// returns corresponding address
+    pub fn derive_next_address(&mut self) -> Result<Address, WalletError> {
+        let (address, key) = self.derive_account(self.next_account_index)?;
+        self.keys.insert(address, (derive_public_key(&key), key));
+        self.next_account_index += 1;
+        self.save()?;
+        Ok(address)
+    }
+
+    /// Adds the keys of the first `account_count` accounts derived from the mnemonic of the wallet
+    /// returns the corresponding addresses
+    pub fn restore_accounts(&mut self, account_count: u32) -> Result<Vec<Address>, WalletError> {
+        let mut addresses = Vec::new();
+        for account in 0..account_count {
+            let (address, key) = self.derive_account(account)?;
+            self.keys.insert(address, (derive_public_key(&key), key));
+            addresses.push(address);
+        }
+        self.next_account_index = self.next_account_index.max(account_count);
+        self.save()?;
+        Ok(addresses)
+    }
+
     /// Remove a wallet entry (keys and address) given the address
     /// The file is overwritten
     pub fn remove_address(&mut self, address: Address) -> Result<(), WalletError> {
@@ -123,16 +217,18 @@ impl Wallet {
     }
 
     /// Save the wallet in json format in a file
-    /// Only the private keys are dumped
+    /// Only the private keys and the derivation state are dumped
     fn save(&self) -> Result<(), WalletError> {
-        let ser_keys = serde_json::to_string(
-            &self
+        let ser_content = serde_json::to_string(&WalletFileContent {
+            mnemonic: self.mnemonic.clone(),
+            next_account_index: self.next_account_index,
+            private_keys: self
                 .keys
                 .iter()
                 .map(|(_, (_, private_key))| *private_key)
-                .collect::<Vec<_>>(),
-        )?;
-        let encrypted_content = encrypt(&self.password, ser_keys.as_bytes())?;
+                .collect(),
+        })?;
+        let encrypted_content = encrypt(&self.password, ser_content.as_bytes())?;
         std::fs::write(&self.wallet_path, encrypted_content)?;
         Ok(())
     }
@@ -153,6 +249,30 @@ impl Wallet {
             .ok_or(WalletError::MissingKeyError(address))?;
         Ok(Signed::new_signed(content, sender_priv).unwrap().1)
     }
+
+    /// Adds to a multisig operation the co-signatures of the wallet keys that are part of its policy
+    /// returns the number of co-signatures added
+    pub fn co_sign_operation(&self, operation: &mut SignedOperation) -> Result<usize, WalletError> {
+        let policy = operation.content.multisig.clone().ok_or_else(|| {
+            ModelsError::MultisigError("the operation is not sent from a multisig address".into())
+        })?;
+        let mut added = 0;
+        for (index, public_key) in policy.public_keys.iter().enumerate() {
+            let index = index as u8;
+            if public_key == &operation.content.sender_public_key
+                || operation.co_signatures.iter().any(|(i, _)| *i == index)
+            {
+                continue;
+            }
+            if let Some(private_key) =
+                self.find_associated_private_key(Address::from_public_key(public_key))
+            {
+                operation.add_co_signature(index, private_key)?;
+                added += 1;
+            }
+        }
+        Ok(added)
+    }
 }
 
 impl std::fmt::Display for Wallet {